@@ -1,3 +1,122 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
 fn main() {
+    check_command_registration();
     tauri_build::build()
 }
+
+/// Fail the build if any `#[tauri::command]` under src/ is missing from the
+/// `generate_handler!` list in lib.rs. An unregistered command only shows up
+/// at runtime as "command not found".
+fn check_command_registration() {
+    println!("cargo:rerun-if-changed=src");
+
+    let lib = fs::read_to_string("src/lib.rs").expect("failed to read src/lib.rs");
+    let registered = registered_commands(&lib);
+
+    let mut sources = Vec::new();
+    collect_sources(Path::new("src"), &mut sources);
+    sources.sort();
+
+    let mut missing = Vec::new();
+    for path in &sources {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let Some(module) = module_path(path) else {
+            continue;
+        };
+
+        let source = fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("failed to read {}", path.display()));
+        for name in defined_commands(&source) {
+            let command = format!("{}::{}", module, name);
+            if !registered.contains(&command) {
+                missing.push(command);
+            }
+        }
+    }
+
+    if !missing.is_empty() {
+        panic!(
+            "commands missing from generate_handler! in src/lib.rs:\n  {}",
+            missing.join("\n  ")
+        );
+    }
+}
+
+/// Every `.rs` file under `dir`
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    let entries = fs::read_dir(dir).unwrap_or_else(|_| panic!("failed to read {}", dir.display()));
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, sources);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            sources.push(path);
+        }
+    }
+}
+
+/// Module path of a source file as written in lib.rs, e.g. `commands::notes`
+/// for src/commands/notes.rs and `git` for src/git/mod.rs. None for the crate
+/// root, whose commands would be registered by bare name.
+fn module_path(path: &Path) -> Option<String> {
+    let relative = path.strip_prefix("src").ok()?.with_extension("");
+    let mut parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.last().is_some_and(|last| last == "mod") {
+        parts.pop();
+    }
+    match parts.as_slice() {
+        [] => None,
+        [root] if root == "lib" || root == "main" => None,
+        _ => Some(parts.join("::")),
+    }
+}
+
+/// Paths listed in the `generate_handler![...]` invocation
+fn registered_commands(lib: &str) -> BTreeSet<String> {
+    let Some(start) = lib.find("generate_handler![") else {
+        return BTreeSet::new();
+    };
+    let list = &lib[start + "generate_handler![".len()..];
+    let list = &list[..list.find(']').unwrap_or(list.len())];
+
+    list.lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .flat_map(|line| line.split(','))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Names of functions annotated with `#[tauri::command]`
+fn defined_commands(source: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut pending = false;
+
+    for line in source.lines() {
+        let line = line.trim();
+        if line.starts_with("#[tauri::command") {
+            pending = true;
+            continue;
+        }
+        if !pending || line.starts_with("#[") || line.starts_with("//") {
+            continue;
+        }
+
+        if let Some(rest) = line.split("fn ").nth(1) {
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            commands.push(name);
+        }
+        pending = false;
+    }
+
+    commands
+}
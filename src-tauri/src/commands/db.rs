@@ -10,27 +10,127 @@ pub struct Backlink {
     pub source_title: String,
     pub context: String, // The text surrounding the link
     pub archived: bool,
+    /// Relevance score, present when backlinks were ranked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
 }
 
-/// Reindex the entire vault
+/// Outcome of a manual reindex
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReindexResult {
+    pub indexed: usize,
+    /// Notes that couldn't be read or parsed; the rest of the vault is still indexed
+    pub failed: Vec<db::FailedFile>,
+}
+
+/// Reindex the entire vault. Progress is reported through `index-progress` events.
+#[tauri::command]
+pub async fn reindex_vault(app: AppHandle) -> Result<ReindexResult, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    let result = db::index_vault(&app, &vault_path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if result.cancelled {
+        return Err("Indexing cancelled".to_string());
+    }
+    Ok(ReindexResult {
+        indexed: result.indexed,
+        failed: result.failed_files,
+    })
+}
+
+/// Stop an in-flight reindex after the file it is currently indexing
+#[tauri::command]
+pub fn cancel_indexing(app: AppHandle) -> Result<(), String> {
+    db::cancel_indexing(&app).map_err(|e| e.to_string())
+}
+
+/// Drop all derived index data and rebuild it from the note files on disk
+#[tauri::command]
+pub async fn rebuild_index(app: AppHandle) -> Result<db::IndexRebuildResult, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    db::rebuild_index(&app, &vault_path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Check the index for FTS drift, dangling links and database corruption
+#[tauri::command]
+pub fn check_index_integrity(app: AppHandle) -> Result<db::IndexIntegrityReport, String> {
+    db::check_index_integrity(&app).map_err(|e| e.to_string())
+}
+
+/// Write each note's indexed id into its frontmatter, for switching a vault to
+/// the frontmatter id scheme without changing any existing ids
 #[tauri::command]
-pub async fn reindex_vault(app: AppHandle) -> Result<usize, String> {
+pub async fn backfill_note_ids(app: AppHandle) -> Result<usize, String> {
     let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
-    db::index_vault(&app, &vault_path)
+    db::backfill_note_ids(&app, &vault_path)
         .await
         .map_err(|e| e.to_string())
 }
 
 /// Get all backlinks to a specific note
 #[tauri::command]
-pub fn get_backlinks(app: AppHandle, note_path: String) -> Result<Vec<Backlink>, String> {
-    db::get_backlinks(&app, &note_path).map_err(|e| e.to_string())
+pub fn get_backlinks(
+    app: AppHandle,
+    note_path: String,
+    ranked: Option<bool>,
+) -> Result<Vec<Backlink>, String> {
+    if ranked.unwrap_or(false) {
+        db::get_ranked_backlinks(&app, &note_path).map_err(|e| e.to_string())
+    } else {
+        db::get_backlinks(&app, &note_path).map_err(|e| e.to_string())
+    }
+}
+
+/// Notes that link to themselves, for cleanup
+#[tauri::command]
+pub fn get_self_referencing_notes(app: AppHandle) -> Result<Vec<db::SelfReference>, String> {
+    db::get_self_referencing_notes(&app).map_err(|e| e.to_string())
 }
 
 /// Get graph data for visualization
 #[tauri::command]
-pub fn get_graph_data(app: AppHandle) -> Result<db::GraphData, String> {
-    db::get_graph_data(&app).map_err(|e| e.to_string())
+pub async fn get_graph_data(app: AppHandle) -> Result<db::GraphData, String> {
+    tauri::async_runtime::spawn_blocking(move || db::get_graph_data(&app))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Get saved graph node positions
+#[tauri::command]
+pub fn get_graph_positions(app: AppHandle) -> Result<Vec<db::GraphPosition>, String> {
+    db::get_graph_positions(&app).map_err(|e| e.to_string())
+}
+
+/// Save manually arranged graph node positions
+#[tauri::command]
+pub fn save_graph_positions(
+    app: AppHandle,
+    positions: Vec<db::GraphPosition>,
+) -> Result<(), String> {
+    db::save_graph_positions(&app, &positions).map_err(|e| e.to_string())
+}
+
+/// Export the link graph as GraphML, DOT or JSON
+#[tauri::command]
+pub fn export_graph(app: AppHandle, format: db::GraphExportFormat) -> Result<String, String> {
+    db::export_graph(&app, format).map_err(|e| e.to_string())
+}
+
+/// Export a note's IOCs (IPs, domains, URLs, emails, CVEs, hashes) as a STIX
+/// bundle or CSV, optionally defanged
+#[tauri::command]
+pub fn export_note_iocs(
+    app: AppHandle,
+    path: String,
+    format: db::IocExportFormat,
+    defang: Option<bool>,
+) -> Result<String, String> {
+    db::export_note_iocs(&app, &path, format, defang.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
 /// Get all unique tags in the vault
@@ -39,6 +139,12 @@ pub fn get_all_tags(app: AppHandle) -> Result<Vec<String>, String> {
     db::get_all_tags(&app).map_err(|e| e.to_string())
 }
 
+/// Get code-block languages with usage counts
+#[tauri::command]
+pub fn get_code_languages(app: AppHandle) -> Result<Vec<db::CodeLanguageCount>, String> {
+    db::get_code_languages(&app).map_err(|e| e.to_string())
+}
+
 /// Get tags with their associated note paths
 #[tauri::command]
 pub fn get_tag_notes(
@@ -53,10 +159,22 @@ pub fn get_all_mentions(app: AppHandle) -> Result<Vec<String>, String> {
     db::get_all_mentions(&app).map_err(|e| e.to_string())
 }
 
-/// Get orphan notes (notes with no incoming or outgoing links)
+/// Get notes that @mention a kanban board member
 #[tauri::command]
-pub fn get_orphan_notes(app: AppHandle) -> Result<Vec<db::OrphanNote>, String> {
-    db::get_orphan_notes(&app).map_err(|e| e.to_string())
+pub fn get_notes_mentioning_member(
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<db::MemberMention>, String> {
+    db::get_notes_mentioning_member(&app, &name).map_err(|e| e.to_string())
+}
+
+/// Get orphan notes; `orphan_mode` defaults to notes with no incoming or outgoing links
+#[tauri::command]
+pub fn get_orphan_notes(
+    app: AppHandle,
+    orphan_mode: Option<db::OrphanMode>,
+) -> Result<Vec<db::OrphanNote>, String> {
+    db::get_orphan_notes(&app, orphan_mode.unwrap_or_default()).map_err(|e| e.to_string())
 }
 
 /// Get broken links (links pointing to non-existent notes)
@@ -67,14 +185,24 @@ pub fn get_broken_links(app: AppHandle) -> Result<Vec<db::BrokenLink>, String> {
 
 /// Get vault health statistics
 #[tauri::command]
-pub fn get_vault_health(app: AppHandle) -> Result<db::VaultHealth, String> {
-    db::get_vault_health(&app).map_err(|e| e.to_string())
+pub async fn get_vault_health(
+    app: AppHandle,
+    orphan_mode: Option<db::OrphanMode>,
+) -> Result<db::VaultHealth, String> {
+    let orphan_mode = orphan_mode.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || db::get_vault_health(&app, orphan_mode))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
 /// Get unlinked mentions (note titles that appear in content but aren't wiki-linked)
 #[tauri::command]
-pub fn get_unlinked_mentions(app: AppHandle) -> Result<Vec<db::UnlinkedMention>, String> {
-    db::get_unlinked_mentions(&app).map_err(|e| e.to_string())
+pub async fn get_unlinked_mentions(app: AppHandle) -> Result<Vec<db::UnlinkedMention>, String> {
+    tauri::async_runtime::spawn_blocking(move || db::get_unlinked_mentions(&app))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
 /// Get a random note for Zettelkasten-style review
@@ -1,9 +1,12 @@
+use once_cell::sync::Lazy;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tauri::AppHandle;
 use uuid::Uuid;
 
-use crate::db::with_db;
+use crate::db::{self, with_db, with_db_tx};
 
 // ============= Data Structures =============
 
@@ -47,6 +50,16 @@ pub struct DiagramBoard {
     pub archived: bool,
 }
 
+/// A board that links to a note, for the note's related diagrams
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteDiagramBoard {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "modifiedAt")]
+    pub modified_at: i64,
+}
+
 /// Data stored in a node's JSON data field
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct NodeData {
@@ -169,12 +182,26 @@ pub struct DiagramEdge {
     pub updated_at: i64,
 }
 
+/// A named layer that nodes are assigned to via `NodeData.layer_id`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiagramLayer {
+    pub id: String,
+    #[serde(rename = "boardId")]
+    pub board_id: String,
+    pub name: String,
+    pub visible: bool,
+    pub order: i32,
+    pub locked: bool,
+}
+
 /// Full board data including nodes and edges
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiagramBoardFull {
     pub board: DiagramBoard,
     pub nodes: Vec<DiagramNode>,
     pub edges: Vec<DiagramEdge>,
+    #[serde(default)]
+    pub layers: Vec<DiagramLayer>,
 }
 
 /// Bulk update request for node positions
@@ -187,6 +214,19 @@ pub struct NodePositionUpdate {
     pub position_y: f64,
 }
 
+/// Bulk update request for an edge; unset fields keep their current value
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EdgeUpdate {
+    pub id: String,
+    #[serde(rename = "sourceHandle")]
+    pub source_handle: Option<String>,
+    #[serde(rename = "targetHandle")]
+    pub target_handle: Option<String>,
+    pub data: Option<EdgeData>,
+    /// Replaces the waypoints, including any in `data`
+    pub waypoints: Option<Vec<Waypoint>>,
+}
+
 // ============= Validation =============
 
 /// Valid node types
@@ -242,54 +282,133 @@ fn fetch_linked_notes(conn: &rusqlite::Connection, board_id: &str) -> Vec<Linked
     .unwrap_or_default()
 }
 
-/// List all diagram boards
-#[tauri::command]
-pub fn diagram_list_boards(app: AppHandle) -> Result<Vec<DiagramBoard>, String> {
-    with_db(&app, |conn| {
-        let mut stmt = conn
-            .prepare(
-                "SELECT b.id, b.name, b.description, b.note_id, n.path, b.viewport, b.created_at, b.modified_at, COALESCE(b.archived, 0)
-                 FROM diagram_boards b
-                 LEFT JOIN notes n ON b.note_id = n.id
-                 ORDER BY b.modified_at DESC"
-            )
-            .map_err(|e| e.to_string())?;
+/// Load a board's nodes in z-order
+fn fetch_nodes(conn: &rusqlite::Connection, board_id: &str) -> Result<Vec<DiagramNode>, String> {
+    let mut node_stmt = conn
+        .prepare(
+            "SELECT id, board_id, node_type, position_x, position_y, width, height, data, z_index, created_at, updated_at
+             FROM diagram_nodes WHERE board_id = ?1 ORDER BY z_index"
+        )
+        .map_err(|e| e.to_string())?;
 
-        let boards: Vec<DiagramBoard> = stmt
-            .query_map([], |row| {
-                let viewport_json: String = row.get(5)?;
-                let viewport: Viewport = serde_json::from_str(&viewport_json).unwrap_or_default();
-                let archived_int: i32 = row.get(8)?;
+    let nodes = node_stmt
+        .query_map(params![board_id], |row| {
+            let data_json: String = row.get(7)?;
+            let data: NodeData = serde_json::from_str(&data_json).unwrap_or_default();
+
+            Ok(DiagramNode {
+                id: row.get(0)?,
+                board_id: row.get(1)?,
+                node_type: row.get(2)?,
+                position_x: row.get(3)?,
+                position_y: row.get(4)?,
+                width: row.get(5)?,
+                height: row.get(6)?,
+                data,
+                z_index: row.get(8)?,
+                created_at: row.get(9)?,
+                updated_at: row.get(10)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
 
-                Ok(DiagramBoard {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    note_id: row.get(3)?,
-                    note_path: row.get(4)?,
-                    linked_notes: Vec::new(), // Will be populated below
-                    viewport,
-                    created_at: row.get(6)?,
-                    modified_at: row.get(7)?,
-                    archived: archived_int != 0,
-                })
+    Ok(nodes)
+}
+
+/// Load a board's edges
+fn fetch_edges(conn: &rusqlite::Connection, board_id: &str) -> Result<Vec<DiagramEdge>, String> {
+    let mut edge_stmt = conn
+        .prepare(
+            "SELECT id, board_id, source_node_id, target_node_id, source_handle, target_handle, edge_type, data, created_at, updated_at
+             FROM diagram_edges WHERE board_id = ?1"
+        )
+        .map_err(|e| e.to_string())?;
+
+    let edges = edge_stmt
+        .query_map(params![board_id], |row| {
+            let data_json: Option<String> = row.get(7)?;
+            let data: Option<EdgeData> = data_json.and_then(|s| serde_json::from_str(&s).ok());
+
+            Ok(DiagramEdge {
+                id: row.get(0)?,
+                board_id: row.get(1)?,
+                source_node_id: row.get(2)?,
+                target_node_id: row.get(3)?,
+                source_handle: row.get(4)?,
+                target_handle: row.get(5)?,
+                edge_type: row.get(6)?,
+                data,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
             })
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
 
-        // Fetch linked notes for each board
-        let boards_with_links: Vec<DiagramBoard> = boards
-            .into_iter()
-            .map(|mut board| {
-                board.linked_notes = fetch_linked_notes(conn, &board.id);
-                board
+    Ok(edges)
+}
+
+/// Boards, most recently modified first; archived boards only with `include_archived`
+fn list_boards(
+    conn: &rusqlite::Connection,
+    include_archived: bool,
+) -> Result<Vec<DiagramBoard>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT b.id, b.name, b.description, b.note_id, n.path, b.viewport, b.created_at, b.modified_at, COALESCE(b.archived, 0)
+             FROM diagram_boards b
+             LEFT JOIN notes n ON b.note_id = n.id
+             WHERE COALESCE(b.archived, 0) = 0 OR ?1 = 1
+             ORDER BY b.modified_at DESC"
+        )
+        .map_err(|e| e.to_string())?;
+
+    let boards: Vec<DiagramBoard> = stmt
+        .query_map(params![include_archived as i32], |row| {
+            let viewport_json: String = row.get(5)?;
+            let viewport: Viewport = serde_json::from_str(&viewport_json).unwrap_or_default();
+            let archived_int: i32 = row.get(8)?;
+
+            Ok(DiagramBoard {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                note_id: row.get(3)?,
+                note_path: row.get(4)?,
+                linked_notes: Vec::new(), // Will be populated below
+                viewport,
+                created_at: row.get(6)?,
+                modified_at: row.get(7)?,
+                archived: archived_int != 0,
             })
-            .collect();
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Fetch linked notes for each board
+    let boards_with_links: Vec<DiagramBoard> = boards
+        .into_iter()
+        .map(|mut board| {
+            board.linked_notes = fetch_linked_notes(conn, &board.id);
+            board
+        })
+        .collect();
 
-        Ok(boards_with_links)
-    })
-    .map_err(|e| e.to_string())
+    Ok(boards_with_links)
+}
+
+/// List diagram boards; archived boards are left out unless `include_archived`
+#[tauri::command]
+pub fn diagram_list_boards(
+    app: AppHandle,
+    include_archived: Option<bool>,
+) -> Result<Vec<DiagramBoard>, String> {
+    let include_archived = include_archived.unwrap_or(false);
+    with_db(&app, |conn| Ok(list_boards(conn, include_archived)?)).map_err(|e| e.to_string())
 }
 
 /// Get a board with all its nodes and edges
@@ -328,68 +447,16 @@ pub fn diagram_get_board(app: AppHandle, board_id: String) -> Result<DiagramBoar
         // Fetch linked notes
         board.linked_notes = fetch_linked_notes(conn, &board_id);
 
-        // Get nodes
-        let mut node_stmt = conn
-            .prepare(
-                "SELECT id, board_id, node_type, position_x, position_y, width, height, data, z_index, created_at, updated_at
-                 FROM diagram_nodes WHERE board_id = ?1 ORDER BY z_index"
-            )
-            .map_err(|e| e.to_string())?;
-
-        let nodes: Vec<DiagramNode> = node_stmt
-            .query_map(params![board_id], |row| {
-                let data_json: String = row.get(7)?;
-                let data: NodeData = serde_json::from_str(&data_json).unwrap_or_default();
-
-                Ok(DiagramNode {
-                    id: row.get(0)?,
-                    board_id: row.get(1)?,
-                    node_type: row.get(2)?,
-                    position_x: row.get(3)?,
-                    position_y: row.get(4)?,
-                    width: row.get(5)?,
-                    height: row.get(6)?,
-                    data,
-                    z_index: row.get(8)?,
-                    created_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                })
-            })
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        // Get edges
-        let mut edge_stmt = conn
-            .prepare(
-                "SELECT id, board_id, source_node_id, target_node_id, source_handle, target_handle, edge_type, data, created_at, updated_at
-                 FROM diagram_edges WHERE board_id = ?1"
-            )
-            .map_err(|e| e.to_string())?;
-
-        let edges: Vec<DiagramEdge> = edge_stmt
-            .query_map(params![board_id], |row| {
-                let data_json: Option<String> = row.get(7)?;
-                let data: Option<EdgeData> = data_json.and_then(|s| serde_json::from_str(&s).ok());
+        let nodes = fetch_nodes(conn, &board_id)?;
+        let edges = fetch_edges(conn, &board_id)?;
+        let layers = fetch_layers(conn, &board_id)?;
 
-                Ok(DiagramEdge {
-                    id: row.get(0)?,
-                    board_id: row.get(1)?,
-                    source_node_id: row.get(2)?,
-                    target_node_id: row.get(3)?,
-                    source_handle: row.get(4)?,
-                    target_handle: row.get(5)?,
-                    edge_type: row.get(6)?,
-                    data,
-                    created_at: row.get(8)?,
-                    updated_at: row.get(9)?,
-                })
-            })
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        Ok(DiagramBoardFull { board, nodes, edges })
+        Ok(DiagramBoardFull {
+            board,
+            nodes,
+            edges,
+            layers,
+        })
     })
     .map_err(|e| e.to_string())
 }
@@ -564,7 +631,7 @@ pub fn diagram_add_note_link(
 ) -> Result<DiagramBoard, String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Verify the note exists
         let _note_path: String = conn
             .query_row(
@@ -638,7 +705,7 @@ pub fn diagram_remove_note_link(
 ) -> Result<DiagramBoard, String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Remove from junction table
         conn.execute(
             "DELETE FROM diagram_board_notes WHERE board_id = ?1 AND note_id = ?2",
@@ -702,7 +769,7 @@ pub fn diagram_remove_all_note_links(
 ) -> Result<DiagramBoard, String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Remove all from junction table
         conn.execute(
             "DELETE FROM diagram_board_notes WHERE board_id = ?1",
@@ -744,6 +811,44 @@ pub fn diagram_remove_all_note_links(
     .map_err(|e| e.to_string())
 }
 
+/// Get the non-archived boards linked to a note, through either the legacy
+/// `note_id` column or the junction table, most recently modified first
+#[tauri::command]
+pub fn diagram_get_boards_for_note(
+    app: AppHandle,
+    note_id: String,
+) -> Result<Vec<NoteDiagramBoard>, String> {
+    with_db(&app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT b.id, b.name, b.description, b.modified_at
+                 FROM diagram_boards b
+                 WHERE COALESCE(b.archived, 0) = 0
+                   AND (b.note_id = ?1
+                        OR EXISTS (SELECT 1 FROM diagram_board_notes dbn
+                                   WHERE dbn.board_id = b.id AND dbn.note_id = ?1))
+                 ORDER BY b.modified_at DESC",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let boards = stmt
+            .query_map(params![note_id], |row| {
+                Ok(NoteDiagramBoard {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    modified_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(boards)
+    })
+    .map_err(|e| e.to_string())
+}
+
 /// Delete a diagram board (cascades to nodes and edges)
 #[tauri::command]
 pub fn diagram_delete_board(app: AppHandle, board_id: String) -> Result<(), String> {
@@ -766,17 +871,25 @@ pub fn diagram_archive_board(
     archived: bool,
 ) -> Result<(), String> {
     with_db(&app, |conn| {
-        let now = chrono::Utc::now().timestamp();
-        conn.execute(
-            "UPDATE diagram_boards SET archived = ?1, modified_at = ?2 WHERE id = ?3",
-            params![archived as i32, now, board_id],
-        )
-        .map_err(|e| e.to_string())?;
-        Ok(())
+        Ok(set_board_archived(conn, &board_id, archived)?)
     })
     .map_err(|e| e.to_string())
 }
 
+fn set_board_archived(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+    archived: bool,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "UPDATE diagram_boards SET archived = ?1, modified_at = ?2 WHERE id = ?3",
+        params![archived as i32, now, board_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 // ============= Node Commands =============
 
 /// Add a node to a diagram
@@ -799,7 +912,7 @@ pub fn diagram_add_node(
     let now = chrono::Utc::now().timestamp();
     let data_json = serde_json::to_string(&data).map_err(|e| e.to_string())?;
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Get max z_index
         let max_z: i32 = conn
             .query_row(
@@ -857,7 +970,7 @@ pub fn diagram_update_node(
 ) -> Result<DiagramNode, String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Get current node
         let (board_id, node_type, curr_x, curr_y, curr_w, curr_h, curr_data_json, curr_z, created_at):
             (String, String, f64, f64, Option<f64>, Option<f64>, String, i32, i64) = conn
@@ -913,7 +1026,7 @@ pub fn diagram_update_node(
 pub fn diagram_delete_node(app: AppHandle, node_id: String) -> Result<(), String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Get board_id before delete
         let board_id: String = conn
             .query_row(
@@ -947,7 +1060,7 @@ pub fn diagram_bulk_update_nodes(
 ) -> Result<(), String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         for update in updates {
             conn.execute(
                 "UPDATE diagram_nodes SET position_x = ?1, position_y = ?2, updated_at = ?3 WHERE id = ?4 AND board_id = ?5",
@@ -990,7 +1103,7 @@ pub fn diagram_add_edge(
     let now = chrono::Utc::now().timestamp();
     let data_json = data.as_ref().and_then(|d| serde_json::to_string(d).ok());
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Verify both nodes exist and belong to this board
         let source_board: String = conn
             .query_row(
@@ -1059,29 +1172,371 @@ pub fn diagram_update_edge(
 
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
-        // Get current edge
-        #[allow(clippy::type_complexity)]
-        let (board_id, source_node_id, target_node_id, curr_source_handle, curr_target_handle, curr_edge_type, curr_data_json, created_at):
-            (String, String, String, Option<String>, Option<String>, String, Option<String>, i64) = conn
+    with_db_tx(&app, |conn| {
+        let edge = apply_edge_update(
+            conn,
+            edge_id,
+            source_handle,
+            target_handle,
+            edge_type,
+            data,
+            None,
+            now,
+        )?;
+
+        // Update board modified_at
+        conn.execute(
+            "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+            params![now, edge.board_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(edge)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Update several edges in one transaction (e.g. rerouting after a layout),
+/// bumping the board's modified time once. Returns the updated edges.
+#[tauri::command]
+pub fn diagram_bulk_update_edges(
+    app: AppHandle,
+    board_id: String,
+    updates: Vec<EdgeUpdate>,
+) -> Result<Vec<DiagramEdge>, String> {
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        let mut edges = Vec::with_capacity(updates.len());
+        for update in updates {
+            let edge = apply_edge_update(
+                conn,
+                update.id,
+                update.source_handle,
+                update.target_handle,
+                None,
+                update.data,
+                update.waypoints,
+                now,
+            )?;
+            if edge.board_id != board_id {
+                return Err(format!("Edge {} is not on board {}", edge.id, board_id).into());
+            }
+            edges.push(edge);
+        }
+
+        // Update board modified_at
+        conn.execute(
+            "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+            params![now, board_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(edges)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Write an edge's changed fields, keeping the current value for any left unset.
+/// `waypoints` replaces the routing in `data` (or in the stored data).
+#[allow(clippy::too_many_arguments)]
+fn apply_edge_update(
+    conn: &rusqlite::Connection,
+    edge_id: String,
+    source_handle: Option<String>,
+    target_handle: Option<String>,
+    edge_type: Option<String>,
+    data: Option<EdgeData>,
+    waypoints: Option<Vec<Waypoint>>,
+    now: i64,
+) -> Result<DiagramEdge, String> {
+    // Get current edge
+    #[allow(clippy::type_complexity)]
+    let (board_id, source_node_id, target_node_id, curr_source_handle, curr_target_handle, curr_edge_type, curr_data_json, created_at):
+        (String, String, String, Option<String>, Option<String>, String, Option<String>, i64) = conn
+        .query_row(
+            "SELECT board_id, source_node_id, target_node_id, source_handle, target_handle, edge_type, data, created_at FROM diagram_edges WHERE id = ?1",
+            params![edge_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let new_source_handle = source_handle.or(curr_source_handle);
+    let new_target_handle = target_handle.or(curr_target_handle);
+    let new_edge_type = edge_type.unwrap_or(curr_edge_type);
+    let mut new_data = data.or_else(|| curr_data_json.and_then(|s| serde_json::from_str(&s).ok()));
+    if let Some(waypoints) = waypoints {
+        new_data.get_or_insert_with(EdgeData::default).waypoints = Some(waypoints);
+    }
+    let new_data_json = new_data
+        .as_ref()
+        .and_then(|d| serde_json::to_string(d).ok());
+
+    conn.execute(
+        "UPDATE diagram_edges SET source_handle = ?1, target_handle = ?2, edge_type = ?3, data = ?4, updated_at = ?5 WHERE id = ?6",
+        params![new_source_handle, new_target_handle, new_edge_type, new_data_json, now, edge_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(DiagramEdge {
+        id: edge_id,
+        board_id,
+        source_node_id,
+        target_node_id,
+        source_handle: new_source_handle,
+        target_handle: new_target_handle,
+        edge_type: new_edge_type,
+        data: new_data,
+        created_at,
+        updated_at: now,
+    })
+}
+
+/// Delete an edge
+#[tauri::command]
+pub fn diagram_delete_edge(app: AppHandle, edge_id: String) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        // Get board_id before delete
+        let board_id: String = conn
             .query_row(
-                "SELECT board_id, source_node_id, target_node_id, source_handle, target_handle, edge_type, data, created_at FROM diagram_edges WHERE id = ?1",
+                "SELECT board_id FROM diagram_edges WHERE id = ?1",
                 params![edge_id],
-                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?)),
+                |row| row.get(0),
             )
             .map_err(|e| e.to_string())?;
 
-        let new_source_handle = source_handle.or(curr_source_handle);
-        let new_target_handle = target_handle.or(curr_target_handle);
-        let new_edge_type = edge_type.unwrap_or(curr_edge_type);
-        let new_data = data.or_else(|| {
-            curr_data_json.and_then(|s| serde_json::from_str(&s).ok())
-        });
-        let new_data_json = new_data.as_ref().and_then(|d| serde_json::to_string(d).ok());
+        conn.execute("DELETE FROM diagram_edges WHERE id = ?1", params![edge_id])
+            .map_err(|e| e.to_string())?;
 
+        // Update board modified_at
         conn.execute(
-            "UPDATE diagram_edges SET source_handle = ?1, target_handle = ?2, edge_type = ?3, data = ?4, updated_at = ?5 WHERE id = ?6",
-            params![new_source_handle, new_target_handle, new_edge_type, new_data_json, now, edge_id],
+            "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+            params![now, board_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    })
+    .map_err(|e| e.to_string())
+}
+
+// ============= Edge Simplification =============
+
+/// Default tolerance (in canvas units) for dropping near-collinear waypoints
+const DEFAULT_SIMPLIFY_EPSILON: f64 = 2.0;
+
+/// Distance from `p` to the line through `a` and `b`
+fn perpendicular_distance(p: &Waypoint, a: &Waypoint, b: &Waypoint) -> f64 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len = (dx * dx + dy * dy).sqrt();
+    if len == 0.0 {
+        return ((p.x - a.x).powi(2) + (p.y - a.y).powi(2)).sqrt();
+    }
+    (dy * p.x - dx * p.y + b.x * a.y - b.y * a.x).abs() / len
+}
+
+/// Ramer-Douglas-Peucker over a waypoint polyline. The first and last
+/// waypoints are always kept.
+fn simplify_waypoints(points: &[Waypoint], epsilon: f64) -> Vec<Waypoint> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let first = &points[0];
+    let last = &points[points.len() - 1];
+    let (index, max_dist) = points[1..points.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i + 1, perpendicular_distance(p, first, last)))
+        .fold(
+            (0, 0.0),
+            |best, cur| if cur.1 > best.1 { cur } else { best },
+        );
+
+    if max_dist > epsilon {
+        let mut left = simplify_waypoints(&points[..=index], epsilon);
+        let right = simplify_waypoints(&points[index..], epsilon);
+        left.pop();
+        left.extend(right);
+        left
+    } else {
+        vec![first.clone(), last.clone()]
+    }
+}
+
+/// Simplify an edge's waypoints in place and persist them.
+/// Returns whether any waypoints were removed.
+fn simplify_edge_in_place(
+    conn: &rusqlite::Connection,
+    edge: &mut DiagramEdge,
+    epsilon: f64,
+    now: i64,
+) -> Result<bool, String> {
+    let Some(data) = edge.data.as_mut() else {
+        return Ok(false);
+    };
+    let Some(waypoints) = data.waypoints.as_ref() else {
+        return Ok(false);
+    };
+
+    let simplified = simplify_waypoints(waypoints, epsilon);
+    if simplified.len() == waypoints.len() {
+        return Ok(false);
+    }
+    data.waypoints = Some(simplified);
+
+    let data_json = serde_json::to_string(data).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE diagram_edges SET data = ?1, updated_at = ?2 WHERE id = ?3",
+        params![data_json, now, edge.id],
+    )
+    .map_err(|e| e.to_string())?;
+    edge.updated_at = now;
+
+    Ok(true)
+}
+
+fn simplify_epsilon(epsilon: Option<f64>) -> Result<f64, String> {
+    let epsilon = epsilon.unwrap_or(DEFAULT_SIMPLIFY_EPSILON);
+    if !epsilon.is_finite() || epsilon < 0.0 {
+        return Err("Epsilon must be a non-negative number".to_string());
+    }
+    Ok(epsilon)
+}
+
+/// Remove redundant (near-collinear) waypoints from an edge
+#[tauri::command]
+pub fn diagram_simplify_edge(
+    app: AppHandle,
+    edge_id: String,
+    epsilon: Option<f64>,
+) -> Result<DiagramEdge, String> {
+    let epsilon = simplify_epsilon(epsilon)?;
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        let board_id: String = conn
+            .query_row(
+                "SELECT board_id FROM diagram_edges WHERE id = ?1",
+                params![edge_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let mut edge = fetch_edges(conn, &board_id)?
+            .into_iter()
+            .find(|e| e.id == edge_id)
+            .ok_or_else(|| format!("Edge not found: {}", edge_id))?;
+
+        if simplify_edge_in_place(conn, &mut edge, epsilon, now)? {
+            conn.execute(
+                "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+                params![now, board_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(edge)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Simplify every edge on a board, returning the edges that changed
+#[tauri::command]
+pub fn diagram_simplify_all_edges(
+    app: AppHandle,
+    board_id: String,
+    epsilon: Option<f64>,
+) -> Result<Vec<DiagramEdge>, String> {
+    let epsilon = simplify_epsilon(epsilon)?;
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        let mut changed = Vec::new();
+        for mut edge in fetch_edges(conn, &board_id)? {
+            if simplify_edge_in_place(conn, &mut edge, epsilon, now)? {
+                changed.push(edge);
+            }
+        }
+
+        if !changed.is_empty() {
+            conn.execute(
+                "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+                params![now, board_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(changed)
+    })
+    .map_err(|e| e.to_string())
+}
+
+// ============= Layer Commands =============
+
+/// Load a board's layers in display order
+fn fetch_layers(conn: &rusqlite::Connection, board_id: &str) -> Result<Vec<DiagramLayer>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, board_id, name, visible, sort_order, locked
+             FROM diagram_layers WHERE board_id = ?1 ORDER BY sort_order, name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let layers = stmt
+        .query_map(params![board_id], |row| {
+            Ok(DiagramLayer {
+                id: row.get(0)?,
+                board_id: row.get(1)?,
+                name: row.get(2)?,
+                visible: row.get::<_, i32>(3)? != 0,
+                order: row.get(4)?,
+                locked: row.get::<_, i32>(5)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(layers)
+}
+
+/// List a board's layers
+#[tauri::command]
+pub fn diagram_get_layers(app: AppHandle, board_id: String) -> Result<Vec<DiagramLayer>, String> {
+    with_db(&app, |conn| Ok(fetch_layers(conn, &board_id)?)).map_err(|e| e.to_string())
+}
+
+/// Create a layer on top of the board's existing layers
+#[tauri::command]
+pub fn diagram_create_layer(
+    app: AppHandle,
+    board_id: String,
+    name: String,
+) -> Result<DiagramLayer, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Layer name cannot be empty".to_string());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        let order: i32 = conn
+            .query_row(
+                "SELECT COALESCE(MAX(sort_order), -1) + 1 FROM diagram_layers WHERE board_id = ?1",
+                params![board_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        conn.execute(
+            "INSERT INTO diagram_layers (id, board_id, name, visible, sort_order, locked) VALUES (?1, ?2, ?3, 1, ?4, 0)",
+            params![id, board_id, name, order],
         )
         .map_err(|e| e.to_string())?;
 
@@ -1092,38 +1547,154 @@ pub fn diagram_update_edge(
         )
         .map_err(|e| e.to_string())?;
 
-        Ok(DiagramEdge {
-            id: edge_id,
+        Ok(DiagramLayer {
+            id,
             board_id,
-            source_node_id,
-            target_node_id,
-            source_handle: new_source_handle,
-            target_handle: new_target_handle,
-            edge_type: new_edge_type,
-            data: new_data,
-            created_at,
-            updated_at: now,
+            name,
+            visible: true,
+            order,
+            locked: false,
         })
     })
     .map_err(|e| e.to_string())
 }
 
-/// Delete an edge
+/// Rename a layer or toggle its visibility/lock
 #[tauri::command]
-pub fn diagram_delete_edge(app: AppHandle, edge_id: String) -> Result<(), String> {
+pub fn diagram_update_layer(
+    app: AppHandle,
+    layer_id: String,
+    name: Option<String>,
+    visible: Option<bool>,
+    locked: Option<bool>,
+) -> Result<DiagramLayer, String> {
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
-        // Get board_id before delete
+    with_db_tx(&app, |conn| {
+        let mut layer = conn
+            .query_row(
+                "SELECT id, board_id, name, visible, sort_order, locked FROM diagram_layers WHERE id = ?1",
+                params![layer_id],
+                |row| {
+                    Ok(DiagramLayer {
+                        id: row.get(0)?,
+                        board_id: row.get(1)?,
+                        name: row.get(2)?,
+                        visible: row.get::<_, i32>(3)? != 0,
+                        order: row.get(4)?,
+                        locked: row.get::<_, i32>(5)? != 0,
+                    })
+                },
+            )
+            .map_err(|_| "Layer not found")?;
+
+        if let Some(name) = name.map(|n| n.trim().to_string()) {
+            if name.is_empty() {
+                return Err("Layer name cannot be empty".into());
+            }
+            layer.name = name;
+        }
+        layer.visible = visible.unwrap_or(layer.visible);
+        layer.locked = locked.unwrap_or(layer.locked);
+
+        conn.execute(
+            "UPDATE diagram_layers SET name = ?1, visible = ?2, locked = ?3 WHERE id = ?4",
+            params![layer.name, layer.visible as i32, layer.locked as i32, layer_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        // Update board modified_at
+        conn.execute(
+            "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+            params![now, layer.board_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(layer)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Reorder a board's layers; `layer_ids` lists them bottom to top
+#[tauri::command]
+pub fn diagram_reorder_layers(
+    app: AppHandle,
+    board_id: String,
+    layer_ids: Vec<String>,
+) -> Result<Vec<DiagramLayer>, String> {
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
+        for (order, layer_id) in layer_ids.iter().enumerate() {
+            conn.execute(
+                "UPDATE diagram_layers SET sort_order = ?1 WHERE id = ?2 AND board_id = ?3",
+                params![order as i32, layer_id, board_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // Update board modified_at
+        conn.execute(
+            "UPDATE diagram_boards SET modified_at = ?1 WHERE id = ?2",
+            params![now, board_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(fetch_layers(conn, &board_id)?)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a layer. Its nodes are deleted when `delete_nodes` is set, otherwise
+/// they move to `reassign_to` (or to no layer when that is `None`).
+#[tauri::command]
+pub fn diagram_delete_layer(
+    app: AppHandle,
+    layer_id: String,
+    delete_nodes: bool,
+    reassign_to: Option<String>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+
+    with_db_tx(&app, |conn| {
         let board_id: String = conn
             .query_row(
-                "SELECT board_id FROM diagram_edges WHERE id = ?1",
-                params![edge_id],
+                "SELECT board_id FROM diagram_layers WHERE id = ?1",
+                params![layer_id],
                 |row| row.get(0),
             )
+            .map_err(|_| "Layer not found")?;
+
+        if delete_nodes {
+            // Edges attached to these nodes cascade
+            conn.execute(
+                "DELETE FROM diagram_nodes WHERE board_id = ?1 AND json_extract(data, '$.layerId') = ?2",
+                params![board_id, layer_id],
+            )
             .map_err(|e| e.to_string())?;
+        } else {
+            if let Some(ref target) = reassign_to {
+                let target_board: Option<String> = conn
+                    .query_row(
+                        "SELECT board_id FROM diagram_layers WHERE id = ?1",
+                        params![target],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                if target_board.as_deref() != Some(board_id.as_str()) || *target == layer_id {
+                    return Err("Target layer must be another layer on the same board".into());
+                }
+            }
 
-        conn.execute("DELETE FROM diagram_edges WHERE id = ?1", params![edge_id])
+            conn.execute(
+                "UPDATE diagram_nodes SET data = json_set(data, '$.layerId', ?1), updated_at = ?2
+                 WHERE board_id = ?3 AND json_extract(data, '$.layerId') = ?4",
+                params![reassign_to, now, board_id, layer_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        conn.execute("DELETE FROM diagram_layers WHERE id = ?1", params![layer_id])
             .map_err(|e| e.to_string())?;
 
         // Update board modified_at
@@ -1137,3 +1708,361 @@ pub fn diagram_delete_edge(app: AppHandle, edge_id: String) -> Result<(), String
     })
     .map_err(|e| e.to_string())
 }
+
+// ============= Node Search =============
+
+/// Most results returned by a cross-board node search
+const GLOBAL_NODE_SEARCH_LIMIT: usize = 50;
+
+/// A node whose label matched a search, with enough context to pan to it
+#[derive(Debug, Serialize)]
+pub struct NodeMatch {
+    #[serde(flatten)]
+    pub node: DiagramNode,
+    #[serde(rename = "boardName")]
+    pub board_name: String,
+    pub score: f64,
+}
+
+/// Score nodes by how well their label matches `query`, best first
+fn rank_node_matches(query: &str, nodes: Vec<(DiagramNode, String)>) -> Vec<NodeMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<NodeMatch> = nodes
+        .into_iter()
+        .filter_map(|(node, board_name)| {
+            let label = node.data.label.as_deref()?;
+            let score = db::fuzzy_score(query, label)?;
+            Some(NodeMatch {
+                node,
+                board_name,
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches
+}
+
+/// Find nodes on a board whose label matches the query (substring or fuzzy)
+#[tauri::command]
+pub fn diagram_find_nodes(
+    app: AppHandle,
+    board_id: String,
+    query: String,
+) -> Result<Vec<NodeMatch>, String> {
+    with_db(&app, |conn| {
+        let board_name: String = conn
+            .query_row(
+                "SELECT name FROM diagram_boards WHERE id = ?1",
+                params![board_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let nodes = fetch_nodes(conn, &board_id)?
+            .into_iter()
+            .map(|node| (node, board_name.clone()))
+            .collect();
+
+        Ok(rank_node_matches(&query, nodes))
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Find matching nodes across all non-archived boards
+#[tauri::command]
+pub fn diagram_find_nodes_global(app: AppHandle, query: String) -> Result<Vec<NodeMatch>, String> {
+    with_db(&app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT n.id, n.board_id, n.node_type, n.position_x, n.position_y, n.width, n.height, n.data, n.z_index, n.created_at, n.updated_at, b.name
+                 FROM diagram_nodes n
+                 JOIN diagram_boards b ON n.board_id = b.id
+                 WHERE COALESCE(b.archived, 0) = 0",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let nodes: Vec<(DiagramNode, String)> = stmt
+            .query_map([], |row| {
+                let data_json: String = row.get(7)?;
+                let data: NodeData = serde_json::from_str(&data_json).unwrap_or_default();
+
+                Ok((
+                    DiagramNode {
+                        id: row.get(0)?,
+                        board_id: row.get(1)?,
+                        node_type: row.get(2)?,
+                        position_x: row.get(3)?,
+                        position_y: row.get(4)?,
+                        width: row.get(5)?,
+                        height: row.get(6)?,
+                        data,
+                        z_index: row.get(8)?,
+                        created_at: row.get(9)?,
+                        updated_at: row.get(10)?,
+                    },
+                    row.get(11)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut matches = rank_node_matches(&query, nodes);
+        matches.truncate(GLOBAL_NODE_SEARCH_LIMIT);
+        Ok(matches)
+    })
+    .map_err(|e| e.to_string())
+}
+
+// ============= Thumbnails =============
+
+/// Size used for nodes saved without explicit dimensions
+const DEFAULT_NODE_WIDTH: f64 = 150.0;
+const DEFAULT_NODE_HEIGHT: f64 = 60.0;
+
+/// Rendered thumbnails keyed by (board id, max_dim), tagged with the board's
+/// `modified_at` so any edit invalidates them
+type ThumbnailCache = HashMap<(String, u32), (i64, String)>;
+
+static THUMBNAIL_CACHE: Lazy<Mutex<ThumbnailCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Render a board as a small SVG that fits within `max_dim` pixels
+#[tauri::command]
+pub fn diagram_render_thumbnail(
+    app: AppHandle,
+    board_id: String,
+    max_dim: u32,
+) -> Result<String, String> {
+    let max_dim = max_dim.clamp(16, 2048);
+    let cache_key = (board_id.clone(), max_dim);
+
+    let modified_at: i64 = with_db(&app, |conn| {
+        conn.query_row(
+            "SELECT modified_at FROM diagram_boards WHERE id = ?1",
+            params![board_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string().into())
+    })
+    .map_err(|e| e.to_string())?;
+
+    if let Ok(cache) = THUMBNAIL_CACHE.lock() {
+        if let Some((cached_at, svg)) = cache.get(&cache_key) {
+            if *cached_at == modified_at {
+                return Ok(svg.clone());
+            }
+        }
+    }
+
+    let (nodes, edges) = with_db(&app, |conn| {
+        Ok((fetch_nodes(conn, &board_id)?, fetch_edges(conn, &board_id)?))
+    })
+    .map_err(|e| e.to_string())?;
+
+    let svg = render_board_svg(&nodes, &edges, max_dim);
+
+    if let Ok(mut cache) = THUMBNAIL_CACHE.lock() {
+        cache.insert(cache_key, (modified_at, svg.clone()));
+    }
+
+    Ok(svg)
+}
+
+fn node_size(node: &DiagramNode) -> (f64, f64) {
+    (
+        node.width.unwrap_or(DEFAULT_NODE_WIDTH),
+        node.height.unwrap_or(DEFAULT_NODE_HEIGHT),
+    )
+}
+
+fn node_center(node: &DiagramNode) -> (f64, f64) {
+    let (w, h) = node_size(node);
+    (node.position_x + w / 2.0, node.position_y + h / 2.0)
+}
+
+/// Draw nodes and edges as plain SVG shapes, scaled to fit `max_dim`.
+/// Labels are left out; they are unreadable at thumbnail size.
+fn render_board_svg(nodes: &[DiagramNode], edges: &[DiagramEdge], max_dim: u32) -> String {
+    if nodes.is_empty() {
+        return format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 100 100"><rect x="4" y="4" width="92" height="92" rx="8" fill="#f3f4f6" stroke="#d1d5db" stroke-dasharray="6 4"/></svg>"##,
+            max_dim
+        );
+    }
+
+    // Bounding box over nodes and edge waypoints
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+    let mut include = |x: f64, y: f64| {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    };
+    for node in nodes {
+        let (w, h) = node_size(node);
+        include(node.position_x, node.position_y);
+        include(node.position_x + w, node.position_y + h);
+    }
+    for waypoint in edges
+        .iter()
+        .filter_map(|e| e.data.as_ref()?.waypoints.as_ref())
+        .flatten()
+    {
+        include(waypoint.x, waypoint.y);
+    }
+
+    let padding = ((max_x - min_x).max(max_y - min_y) * 0.05).max(10.0);
+    let (view_x, view_y) = (min_x - padding, min_y - padding);
+    let view_w = (max_x - min_x) + padding * 2.0;
+    let view_h = (max_y - min_y) + padding * 2.0;
+
+    let scale = max_dim as f64 / view_w.max(view_h);
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="{:.1} {:.1} {:.1} {:.1}">"#,
+        (view_w * scale).max(1.0),
+        (view_h * scale).max(1.0),
+        view_x,
+        view_y,
+        view_w,
+        view_h
+    );
+
+    let centers: HashMap<&str, (f64, f64)> = nodes
+        .iter()
+        .map(|n| (n.id.as_str(), node_center(n)))
+        .collect();
+
+    for edge in edges {
+        let (Some(&source), Some(&target)) = (
+            centers.get(edge.source_node_id.as_str()),
+            centers.get(edge.target_node_id.as_str()),
+        ) else {
+            continue;
+        };
+
+        let mut points = vec![source];
+        if let Some(waypoints) = edge.data.as_ref().and_then(|d| d.waypoints.as_ref()) {
+            points.extend(waypoints.iter().map(|w| (w.x, w.y)));
+        }
+        points.push(target);
+
+        let color = edge
+            .data
+            .as_ref()
+            .and_then(|d| d.color.as_deref())
+            .unwrap_or("#94a3b8");
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect();
+
+        svg.push_str(&format!(
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5" vector-effect="non-scaling-stroke"/>"#,
+            points.join(" "),
+            db::escape_xml(color)
+        ));
+    }
+
+    for node in nodes {
+        let (w, h) = node_size(node);
+        let (x, y) = (node.position_x, node.position_y);
+        let fill = db::escape_xml(node.data.color.as_deref().unwrap_or("#e5e7eb"));
+        let stroke = db::escape_xml(node.data.border_color.as_deref().unwrap_or("#6b7280"));
+        let style = format!(
+            r#"fill="{}" stroke="{}" stroke-width="1" vector-effect="non-scaling-stroke""#,
+            fill, stroke
+        );
+
+        let shape = match node.node_type.as_str() {
+            // Containers are drawn as translucent outlines so their children show through
+            "group" | "swimlane" => format!(
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}" fill-opacity="0.15" stroke="{}" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+                x, y, w, h, fill, stroke
+            ),
+            // Text nodes become a single bar standing in for the line of text
+            "text" => format!(
+                r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#9ca3af"/>"##,
+                x,
+                y + h * 0.4,
+                w,
+                h * 0.2
+            ),
+            _ => match node.data.shape_type.as_deref() {
+                Some("circle") => format!(
+                    r#"<ellipse cx="{:.1}" cy="{:.1}" rx="{:.1}" ry="{:.1}" {}/>"#,
+                    x + w / 2.0,
+                    y + h / 2.0,
+                    w / 2.0,
+                    h / 2.0,
+                    style
+                ),
+                Some("diamond") => format!(
+                    r#"<polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" {}/>"#,
+                    x + w / 2.0,
+                    y,
+                    x + w,
+                    y + h / 2.0,
+                    x + w / 2.0,
+                    y + h,
+                    x,
+                    y + h / 2.0,
+                    style
+                ),
+                _ => format!(
+                    r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="{:.1}" {}/>"#,
+                    x,
+                    y,
+                    w,
+                    h,
+                    node.data.border_radius.unwrap_or(4.0),
+                    style
+                ),
+            },
+        };
+        svg.push_str(&shape);
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn board_ids(conn: &rusqlite::Connection, include_archived: bool) -> Vec<String> {
+        list_boards(conn, include_archived)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.id)
+            .collect()
+    }
+
+    #[test]
+    fn archived_boards_are_listed_only_when_asked_for() {
+        let conn = open_test_db();
+        for (id, modified_at) in [("recon", 2), ("infra", 1)] {
+            conn.execute(
+                "INSERT INTO diagram_boards (id, name, created_at, modified_at) VALUES (?1, ?1, 0, ?2)",
+                params![id, modified_at],
+            )
+            .unwrap();
+        }
+
+        set_board_archived(&conn, "infra", true).unwrap();
+        assert_eq!(board_ids(&conn, false), ["recon"]);
+        assert_eq!(board_ids(&conn, true), ["infra", "recon"]);
+        assert!(list_boards(&conn, true).unwrap()[0].archived);
+
+        set_board_archived(&conn, "infra", false).unwrap();
+        assert_eq!(board_ids(&conn, false).len(), 2);
+    }
+}
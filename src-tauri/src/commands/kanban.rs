@@ -1,9 +1,15 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tauri::AppHandle;
 use uuid::Uuid;
+use yaml_rust2::yaml::{Hash, Yaml};
 
-use crate::db::with_db;
+use crate::commands::notes::{self, NoteMetadata};
+use crate::commands::settings::{read_vault_settings, LinkSettings};
+use crate::db::{self, with_db, with_db_tx};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KanbanColumn {
@@ -151,27 +157,11 @@ pub fn kanban_create_board(
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp();
 
-    let kanban_columns: Vec<KanbanColumn> = columns
-        .into_iter()
-        .map(|col_name| {
-            // Auto-mark completion columns
-            let is_done = col_name.eq_ignore_ascii_case("done")
-                || col_name.eq_ignore_ascii_case("complete")
-                || col_name.eq_ignore_ascii_case("completed")
-                || col_name.eq_ignore_ascii_case("finished")
-                || col_name.eq_ignore_ascii_case("closed");
-            KanbanColumn {
-                id: Uuid::new_v4().to_string(),
-                name: col_name,
-                color: None,
-                is_done,
-            }
-        })
-        .collect();
+    let kanban_columns: Vec<KanbanColumn> = columns.into_iter().map(new_column).collect();
 
     let columns_json = serde_json::to_string(&kanban_columns).map_err(|e| e.to_string())?;
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Check if a board with this name already exists (case-insensitive)
         let name_lower = name.to_lowercase();
         let existing: Result<String, _> = conn.query_row(
@@ -211,12 +201,27 @@ pub fn kanban_create_board(
     .map_err(|e| e.to_string())
 }
 
+/// A new column, auto-marked as done when its name says so
+fn new_column(name: String) -> KanbanColumn {
+    let is_done = ["done", "complete", "completed", "finished", "closed"]
+        .iter()
+        .any(|done| name.eq_ignore_ascii_case(done));
+    KanbanColumn {
+        id: Uuid::new_v4().to_string(),
+        name,
+        color: None,
+        is_done,
+    }
+}
+
 /// Delete a board
 #[tauri::command]
 pub fn kanban_delete_board(app: AppHandle, board_id: String) -> Result<(), String> {
     with_db(&app, |conn| {
         conn.execute("DELETE FROM kanban_boards WHERE id = ?1", params![board_id])
             .map_err(|e| e.to_string())?;
+        // The board's members go with it
+        db::resolve_member_mentions(conn, None).map_err(|e| e.to_string())?;
         Ok(())
     })
     .map_err(|e| e.to_string())
@@ -295,7 +300,7 @@ pub fn kanban_remove_column(
     board_id: String,
     column_id: String,
 ) -> Result<KanbanBoard, String> {
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Get current columns
         let columns_json: String = conn
             .query_row(
@@ -356,75 +361,149 @@ pub fn kanban_remove_column(
     .map_err(|e| e.to_string())
 }
 
-/// Get cards for a board (includes home board cards and linked cards)
+/// Get cards for a board (includes home board cards and linked cards).
+/// Optional filters: `label_ids` (any label, or all of them with `match_all_labels`)
+/// and `assignees` (any of them). Archived cards are left out unless `include_archived`.
 #[tauri::command]
-pub fn kanban_get_cards(app: AppHandle, board_id: String) -> Result<Vec<KanbanCard>, String> {
+pub fn kanban_get_cards(
+    app: AppHandle,
+    board_id: String,
+    label_ids: Option<Vec<String>>,
+    assignees: Option<Vec<String>>,
+    match_all_labels: Option<bool>,
+    include_archived: Option<bool>,
+) -> Result<Vec<KanbanCard>, String> {
+    let label_ids = label_ids.unwrap_or_default();
+    let assignees = assignees.unwrap_or_default();
+    let match_all_labels = match_all_labels.unwrap_or(false);
+    let include_archived = include_archived.unwrap_or(false);
+
     with_db(&app, |conn| {
-        // Get cards where this is the home board OR the board is in linked_board_ids
-        let mut stmt = conn
-            .prepare(
-                r#"
-                SELECT c.id, c.board_id, c.column_id, c.title, c.description, c.note_id,
-                       c.position, c.created_at, c.updated_at, c.closed_at, c.due_date,
-                       c.priority, c.metadata, n.path, c.linked_board_ids, c.board_columns,
-                       c.is_complete, COALESCE(c.archived, 0)
-                FROM kanban_cards c
-                LEFT JOIN notes n ON c.note_id = n.id
-                WHERE c.board_id = ?1
-                   OR (c.linked_board_ids IS NOT NULL AND c.linked_board_ids LIKE '%' || ?1 || '%')
-                ORDER BY c.position
-                "#,
-            )
-            .map_err(|e| e.to_string())?;
+        let cards = fetch_board_cards(conn, &board_id, include_archived)?
+            .into_iter()
+            .filter(|card| card_matches_filters(card, &label_ids, &assignees, match_all_labels))
+            .collect();
+        Ok(cards)
+    })
+    .map_err(|e| e.to_string())
+}
 
-        let now = chrono::Utc::now().timestamp();
-        let cards = stmt
-            .query_map(params![board_id], |row| {
-                let metadata_str: Option<String> = row.get(12)?;
-                let metadata: Option<CardMetadata> =
-                    metadata_str.and_then(|s| serde_json::from_str(&s).ok());
+/// Get a board's archived cards, for restoring them
+#[tauri::command]
+pub fn kanban_get_archived_cards(
+    app: AppHandle,
+    board_id: String,
+) -> Result<Vec<KanbanCard>, String> {
+    with_db(&app, |conn| Ok(fetch_archived_cards(conn, &board_id)?)).map_err(|e| e.to_string())
+}
 
-                let linked_board_ids_str: Option<String> = row.get(14)?;
-                let linked_board_ids: Option<Vec<String>> =
-                    linked_board_ids_str.and_then(|s| serde_json::from_str(&s).ok());
+fn fetch_archived_cards(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+) -> Result<Vec<KanbanCard>, String> {
+    let cards = fetch_board_cards(conn, board_id, true)?;
+    Ok(cards.into_iter().filter(|card| card.archived).collect())
+}
 
-                let board_columns_str: Option<String> = row.get(15)?;
-                let board_columns: Option<std::collections::HashMap<String, String>> =
-                    board_columns_str.and_then(|s| serde_json::from_str(&s).ok());
+/// A board's home and linked cards in position order; archived cards only with `include_archived`
+fn fetch_board_cards(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+    include_archived: bool,
+) -> Result<Vec<KanbanCard>, String> {
+    // Get cards where this is the home board OR the board is in linked_board_ids
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT c.id, c.board_id, c.column_id, c.title, c.description, c.note_id,
+                   c.position, c.created_at, c.updated_at, c.closed_at, c.due_date,
+                   c.priority, c.metadata, n.path, c.linked_board_ids, c.board_columns,
+                   c.is_complete, COALESCE(c.archived, 0)
+            FROM kanban_cards c
+            LEFT JOIN notes n ON c.note_id = n.id
+            WHERE (c.board_id = ?1
+               OR (c.linked_board_ids IS NOT NULL AND c.linked_board_ids LIKE '%' || ?1 || '%'))
+              AND (COALESCE(c.archived, 0) = 0 OR ?2 = 1)
+            ORDER BY c.position
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
 
-                let is_complete_int: Option<i32> = row.get(16)?;
-                let is_complete = is_complete_int.map(|v| v != 0);
+    let now = chrono::Utc::now().timestamp();
+    let cards = stmt
+        .query_map(params![board_id, include_archived as i32], |row| {
+            let metadata_str: Option<String> = row.get(12)?;
+            let metadata: Option<CardMetadata> =
+                metadata_str.and_then(|s| serde_json::from_str(&s).ok());
 
-                let archived_int: i32 = row.get(17)?;
+            let linked_board_ids_str: Option<String> = row.get(14)?;
+            let linked_board_ids: Option<Vec<String>> =
+                linked_board_ids_str.and_then(|s| serde_json::from_str(&s).ok());
 
-                Ok(KanbanCard {
-                    id: row.get(0)?,
-                    board_id: row.get(1)?,
-                    column_id: row.get(2)?,
-                    title: row.get(3)?,
-                    description: row.get(4)?,
-                    note_id: row.get(5)?,
-                    note_path: row.get(13)?,
-                    position: row.get(6)?,
-                    created_at: row.get::<_, Option<i64>>(7)?.unwrap_or(now),
-                    updated_at: row.get::<_, Option<i64>>(8)?.unwrap_or(now),
-                    closed_at: row.get(9)?,
-                    due_date: row.get(10)?,
-                    priority: row.get(11)?,
-                    metadata,
-                    linked_board_ids,
-                    board_columns,
-                    is_complete,
-                    archived: archived_int != 0,
-                })
+            let board_columns_str: Option<String> = row.get(15)?;
+            let board_columns: Option<std::collections::HashMap<String, String>> =
+                board_columns_str.and_then(|s| serde_json::from_str(&s).ok());
+
+            let is_complete_int: Option<i32> = row.get(16)?;
+            let is_complete = is_complete_int.map(|v| v != 0);
+
+            let archived_int: i32 = row.get(17)?;
+
+            Ok(KanbanCard {
+                id: row.get(0)?,
+                board_id: row.get(1)?,
+                column_id: row.get(2)?,
+                title: row.get(3)?,
+                description: row.get(4)?,
+                note_id: row.get(5)?,
+                note_path: row.get(13)?,
+                position: row.get(6)?,
+                created_at: row.get::<_, Option<i64>>(7)?.unwrap_or(now),
+                updated_at: row.get::<_, Option<i64>>(8)?.unwrap_or(now),
+                closed_at: row.get(9)?,
+                due_date: row.get(10)?,
+                priority: row.get(11)?,
+                metadata,
+                linked_board_ids,
+                board_columns,
+                is_complete,
+                archived: archived_int != 0,
             })
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect::<Vec<KanbanCard>>();
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect::<Vec<KanbanCard>>();
 
-        Ok(cards)
-    })
-    .map_err(|e| e.to_string())
+    Ok(cards)
+}
+
+/// Whether a card passes the label and assignee filters; empty filters match everything
+fn card_matches_filters(
+    card: &KanbanCard,
+    label_ids: &[String],
+    assignees: &[String],
+    match_all_labels: bool,
+) -> bool {
+    let empty = CardMetadata::default();
+    let metadata = card.metadata.as_ref().unwrap_or(&empty);
+
+    let labels_match = if label_ids.is_empty() {
+        true
+    } else if match_all_labels {
+        label_ids.iter().all(|id| metadata.labels.contains(id))
+    } else {
+        label_ids.iter().any(|id| metadata.labels.contains(id))
+    };
+
+    let assignees_match = assignees.is_empty()
+        || assignees.iter().any(|name| {
+            metadata
+                .assignees
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(name))
+        });
+
+    labels_match && assignees_match
 }
 
 /// Add a card to a board
@@ -436,49 +515,60 @@ pub fn kanban_add_card(
     title: String,
     note_id: Option<String>,
 ) -> Result<KanbanCard, String> {
-    let id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().timestamp();
-
     with_db(&app, |conn| {
-        // Get max position in column
-        let max_pos: i32 = conn
-            .query_row(
-                "SELECT COALESCE(MAX(position), -1) FROM kanban_cards WHERE column_id = ?1",
-                params![column_id],
-                |row| row.get(0),
-            )
-            .unwrap_or(-1);
+        Ok(insert_card(conn, board_id, column_id, title, note_id)?)
+    })
+    .map_err(|e| e.to_string())
+}
 
-        let position = max_pos + 1;
+/// Insert a card at the bottom of its column
+fn insert_card(
+    conn: &rusqlite::Connection,
+    board_id: String,
+    column_id: String,
+    title: String,
+    note_id: Option<String>,
+) -> Result<KanbanCard, String> {
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp();
 
-        conn.execute(
-            "INSERT INTO kanban_cards (id, board_id, column_id, title, note_id, position, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![id, board_id, column_id, title, note_id, position, now, now],
+    // Get max position in column
+    let max_pos: i32 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(position), -1) FROM kanban_cards WHERE column_id = ?1",
+            params![column_id],
+            |row| row.get(0),
         )
-        .map_err(|e| e.to_string())?;
-
-        Ok(KanbanCard {
-            id,
-            board_id,
-            column_id,
-            title,
-            description: None,
-            note_id,
-            note_path: None,
-            position,
-            created_at: now,
-            updated_at: now,
-            closed_at: None,
-            due_date: None,
-            priority: None,
-            metadata: None,
-            linked_board_ids: None,
-            board_columns: None,
-            is_complete: Some(false),
-            archived: false,
-        })
+        .unwrap_or(-1);
+
+    let position = max_pos + 1;
+
+    conn.execute(
+        "INSERT INTO kanban_cards (id, board_id, column_id, title, note_id, position, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![id, board_id, column_id, title, note_id, position, now, now],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(KanbanCard {
+        id,
+        board_id,
+        column_id,
+        title,
+        description: None,
+        note_id,
+        note_path: None,
+        position,
+        created_at: now,
+        updated_at: now,
+        closed_at: None,
+        due_date: None,
+        priority: None,
+        metadata: None,
+        linked_board_ids: None,
+        board_columns: None,
+        is_complete: Some(false),
+        archived: false,
     })
-    .map_err(|e| e.to_string())
 }
 
 /// Move a card to a different column/position
@@ -491,8 +581,9 @@ pub fn kanban_move_card(
     position: i32,
 ) -> Result<(), String> {
     let now = chrono::Utc::now().timestamp();
+    let actor = history_actor(&app);
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Get the board's columns to check if destination is a "done" column
         let columns_json: String = conn
             .query_row(
@@ -547,6 +638,29 @@ pub fn kanban_move_card(
             .map_err(|e| e.to_string())?;
         }
 
+        if current_column_id != to_column_id {
+            record_card_history(
+                conn,
+                &card_id,
+                "column",
+                Some(&current_column_id),
+                Some(&to_column_id),
+                actor.as_deref(),
+                now,
+            )?;
+        }
+        if is_done_column != was_in_done_column {
+            record_card_history(
+                conn,
+                &card_id,
+                "complete",
+                Some(&was_in_done_column.to_string()),
+                Some(&is_done_column.to_string()),
+                actor.as_deref(),
+                now,
+            )?;
+        }
+
         Ok(())
     })
     .map_err(|e| e.to_string())
@@ -567,17 +681,25 @@ pub fn kanban_delete_card(app: AppHandle, card_id: String) -> Result<(), String>
 #[tauri::command]
 pub fn kanban_archive_card(app: AppHandle, card_id: String, archived: bool) -> Result<(), String> {
     with_db(&app, |conn| {
-        let now = chrono::Utc::now().timestamp();
-        conn.execute(
-            "UPDATE kanban_cards SET archived = ?1, updated_at = ?2 WHERE id = ?3",
-            params![archived as i32, now, card_id],
-        )
-        .map_err(|e| e.to_string())?;
-        Ok(())
+        Ok(set_card_archived(conn, &card_id, archived)?)
     })
     .map_err(|e| e.to_string())
 }
 
+fn set_card_archived(
+    conn: &rusqlite::Connection,
+    card_id: &str,
+    archived: bool,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp();
+    conn.execute(
+        "UPDATE kanban_cards SET archived = ?1, updated_at = ?2 WHERE id = ?3",
+        params![archived as i32, now, card_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 /// Get a single card by ID
 #[tauri::command]
 pub fn kanban_get_card(app: AppHandle, card_id: String) -> Result<KanbanCard, String> {
@@ -639,10 +761,22 @@ pub fn kanban_get_card(app: AppHandle, card_id: String) -> Result<KanbanCard, St
     .map_err(|e| e.to_string())
 }
 
+/// Result of a card update: the card plus the number of note links rewritten
+/// to follow a title change, and the notes whose links couldn't be rewritten
+#[derive(Debug, Serialize)]
+pub struct KanbanCardUpdate {
+    #[serde(flatten)]
+    pub card: KanbanCard,
+    #[serde(rename = "updatedLinks")]
+    pub updated_links: usize,
+    #[serde(rename = "failedLinks")]
+    pub failed_links: Vec<db::FailedFile>,
+}
+
 /// Update card details
 #[tauri::command]
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
-pub fn kanban_update_card(
+pub async fn kanban_update_card(
     app: AppHandle,
     card_id: String,
     title: Option<String>,
@@ -655,10 +789,11 @@ pub fn kanban_update_card(
     board_columns: Option<std::collections::HashMap<String, String>>,
     assigned_by: Option<String>,
     new_board_id: Option<String>, // Transfer card ownership to a different board
-) -> Result<KanbanCard, String> {
+) -> Result<KanbanCardUpdate, String> {
     let now = chrono::Utc::now().timestamp();
+    let actor = history_actor(&app);
 
-    with_db(&app, |conn| {
+    let (card, old_title, board_name) = with_db_tx(&app, |conn| {
         // Get current card data
         let (current_title, current_desc, current_due, current_priority, current_metadata, current_linked, current_board_cols, current_board_id): (
             String,
@@ -682,8 +817,19 @@ pub fn kanban_update_card(
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
 
+        // Board name as written in `[[card:Board/Title]]` links, before any transfer
+        let board_name: String = conn
+            .query_row(
+                "SELECT name FROM kanban_boards WHERE id = ?1",
+                params![current_board_id],
+                |row| row.get(0),
+            )
+            .unwrap_or_default();
+
+        let old_assignees = metadata.assignees.clone();
+
         // Apply updates
-        let new_title = title.unwrap_or(current_title);
+        let new_title = title.unwrap_or_else(|| current_title.clone());
         let new_description = description.or(current_desc);
         let new_due_date = due_date.or(current_due);
         let new_priority = priority.or(current_priority);
@@ -736,6 +882,29 @@ pub fn kanban_update_card(
             None
         };
 
+        if metadata.assignees != old_assignees {
+            record_card_history(
+                conn,
+                &card_id,
+                "assignees",
+                Some(&serde_json::to_string(&old_assignees).map_err(|e| e.to_string())?),
+                Some(&serde_json::to_string(&metadata.assignees).map_err(|e| e.to_string())?),
+                actor.as_deref(),
+                now,
+            )?;
+        }
+        if final_board_id != current_board_id {
+            record_card_history(
+                conn,
+                &card_id,
+                "board",
+                Some(&current_board_id),
+                Some(&final_board_id),
+                actor.as_deref(),
+                now,
+            )?;
+        }
+
         // Build the update query based on whether we need to update column_id
         if let Some(new_column_id) = final_column_id {
             conn.execute(
@@ -762,7 +931,7 @@ pub fn kanban_update_card(
         }
 
         // Return updated card by querying it
-        conn.query_row(
+        let card = conn.query_row(
             r#"
             SELECT c.id, c.board_id, c.column_id, c.title, c.description, c.note_id,
                    c.position, c.created_at, c.updated_at, c.closed_at, c.due_date,
@@ -812,8 +981,119 @@ pub fn kanban_update_card(
                     archived: archived_int != 0,
                 })
             },
+        )?;
+
+        Ok((card, current_title, board_name))
+    })
+    .map_err(|e| e.to_string())?;
+
+    // Keep notes linking to the old title pointing at this card
+    let (updated_links, failed_links) = if card.title != old_title {
+        update_card_links(&app, &card_id, &board_name, &old_title, &card.title).await?
+    } else {
+        (0, Vec::new())
+    };
+
+    Ok(KanbanCardUpdate {
+        card,
+        updated_links,
+        failed_links,
+    })
+}
+
+// ============= Card History =============
+
+/// Most history entries kept per card; older ones are pruned as new ones arrive
+const CARD_HISTORY_LIMIT: i64 = 200;
+
+/// One recorded change to a card
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardHistoryEntry {
+    pub id: i64,
+    #[serde(rename = "cardId")]
+    pub card_id: String,
+    pub field: String,
+    #[serde(rename = "oldValue")]
+    pub old_value: Option<String>,
+    #[serde(rename = "newValue")]
+    pub new_value: Option<String>,
+    pub actor: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+/// The vault user recorded as the author of card changes
+fn history_actor(app: &AppHandle) -> Option<String> {
+    crate::commands::vault::get_vault_user(app.clone())
+        .ok()
+        .flatten()
+}
+
+/// Append a card history entry and drop the card's oldest entries past the limit
+fn record_card_history(
+    conn: &rusqlite::Connection,
+    card_id: &str,
+    field: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+    actor: Option<&str>,
+    now: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO kanban_card_history (card_id, field, old_value, new_value, actor, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![card_id, field, old_value, new_value, actor, now],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.execute(
+        r#"
+        DELETE FROM kanban_card_history
+        WHERE card_id = ?1 AND id NOT IN (
+            SELECT id FROM kanban_card_history WHERE card_id = ?1 ORDER BY id DESC LIMIT ?2
         )
-        .map_err(|e| e.to_string().into())
+        "#,
+        params![card_id, CARD_HISTORY_LIMIT],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Get the change log for a card, oldest first
+#[tauri::command]
+pub fn kanban_get_card_history(
+    app: AppHandle,
+    card_id: String,
+) -> Result<Vec<CardHistoryEntry>, String> {
+    with_db(&app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT id, card_id, field, old_value, new_value, actor, created_at
+                FROM kanban_card_history
+                WHERE card_id = ?1
+                ORDER BY created_at, id
+                "#,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let entries = stmt
+            .query_map(params![card_id], |row| {
+                Ok(CardHistoryEntry {
+                    id: row.get(0)?,
+                    card_id: row.get(1)?,
+                    field: row.get(2)?,
+                    old_value: row.get(3)?,
+                    new_value: row.get(4)?,
+                    actor: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
     })
     .map_err(|e| e.to_string())
 }
@@ -1059,7 +1339,7 @@ pub fn kanban_add_board_member(
     let member_id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp();
 
-    with_db(&app, |conn| {
+    with_db_tx(&app, |conn| {
         // Check if a member with this name already exists on this board (case-insensitive)
         let name_lower = name.to_lowercase();
         let existing: Result<String, _> = conn.query_row(
@@ -1077,6 +1357,7 @@ pub fn kanban_add_board_member(
             params![member_id, board_id, name, now],
         )
         .map_err(|e| e.to_string())?;
+        db::resolve_member_mentions(conn, None).map_err(|e| e.to_string())?;
 
         let member = BoardMember {
             id: member_id,
@@ -1155,11 +1436,108 @@ pub fn kanban_remove_board_member(app: AppHandle, member_id: String) -> Result<(
             params![member_id],
         )
         .map_err(|e| e.to_string())?;
+        db::resolve_member_mentions(conn, None).map_err(|e| e.to_string())?;
         Ok(())
     })
     .map_err(|e| e.to_string())
 }
 
+/// Rename a member everywhere: member rows on every board, card assignees and
+/// `assignedBy`, and the owner of their personal board. Renaming onto an
+/// existing member merges the two. Returns the number of cards rewritten.
+#[tauri::command]
+pub fn kanban_rename_member(
+    app: AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<usize, String> {
+    let old_name = old_name.trim().to_string();
+    let new_name = new_name.trim().to_string();
+    if old_name.is_empty() || new_name.is_empty() {
+        return Err("Member name cannot be empty".to_string());
+    }
+    if old_name == new_name {
+        return Ok(0);
+    }
+
+    with_db_tx(&app, |conn| {
+        // Where the new name is already a member of the board, drop the old row
+        conn.execute(
+            r#"
+            DELETE FROM kanban_board_members
+            WHERE LOWER(name) = LOWER(?1) AND name != ?2
+              AND board_id IN (SELECT board_id FROM kanban_board_members WHERE name = ?2)
+            "#,
+            params![old_name, new_name],
+        )?;
+        conn.execute(
+            "UPDATE kanban_board_members SET name = ?2 WHERE LOWER(name) = LOWER(?1)",
+            params![old_name, new_name],
+        )?;
+        conn.execute(
+            "UPDATE kanban_boards SET owner_name = ?2 WHERE LOWER(owner_name) = LOWER(?1)",
+            params![old_name, new_name],
+        )?;
+
+        // Edit the metadata as JSON so fields this version doesn't know survive
+        let cards: Vec<(String, String)> = conn
+            .prepare("SELECT id, metadata FROM kanban_cards WHERE metadata IS NOT NULL AND json_valid(metadata)")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let is_old = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(&old_name))
+        };
+
+        let mut updated = 0;
+        for (card_id, metadata_json) in cards {
+            let mut metadata: serde_json::Value = serde_json::from_str(&metadata_json)?;
+            let mut changed = false;
+
+            if let Some(assignees) = metadata.get_mut("assignees").and_then(|a| a.as_array_mut()) {
+                if assignees.iter().any(is_old) {
+                    let mut renamed: Vec<serde_json::Value> = Vec::new();
+                    for assignee in assignees.drain(..) {
+                        let assignee = if is_old(&assignee) {
+                            serde_json::Value::String(new_name.clone())
+                        } else {
+                            assignee
+                        };
+                        // Merging can leave the same person listed twice
+                        if !renamed.contains(&assignee) {
+                            renamed.push(assignee);
+                        }
+                    }
+                    *assignees = renamed;
+                    changed = true;
+                }
+            }
+            if let Some(assigned_by) = metadata.get_mut("assignedBy") {
+                if is_old(assigned_by) {
+                    *assigned_by = serde_json::Value::String(new_name.clone());
+                    changed = true;
+                }
+            }
+
+            if changed {
+                conn.execute(
+                    "UPDATE kanban_cards SET metadata = ?1 WHERE id = ?2",
+                    params![serde_json::to_string(&metadata)?, card_id],
+                )?;
+                updated += 1;
+            }
+        }
+
+        // Note @mentions resolve against the member names
+        db::resolve_member_mentions(conn, None)?;
+        Ok(updated)
+    })
+    .map_err(|e| e.to_string())
+}
+
 /// Get assignee suggestions - returns ALL unique members across all boards (global)
 #[tauri::command]
 pub fn kanban_get_assignee_suggestions(
@@ -1185,6 +1563,120 @@ pub fn kanban_get_assignee_suggestions(
 
 // ============= Card Backlinks and Lookup =============
 
+/// Rewrite `[[card:Old]]` links in every note that links to the card, then reindex
+/// those notes. Each note is written atomically; one that can't be read, written
+/// or reindexed is reported rather than stopping the rest. Returns the number of
+/// links rewritten and the notes that failed.
+async fn update_card_links(
+    app: &AppHandle,
+    card_id: &str,
+    board_name: &str,
+    old_title: &str,
+    new_title: &str,
+) -> Result<(usize, Vec<db::FailedFile>), String> {
+    let Some(vault_path) = db::get_current_vault_path(app) else {
+        return Ok((0, Vec::new()));
+    };
+
+    let paths: Vec<String> = with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT DISTINCT n.path
+            FROM card_backlinks cb
+            JOIN notes n ON cb.source_id = n.id
+            WHERE cb.card_id = ?1
+            "#,
+        )?;
+        let paths = stmt
+            .query_map(params![card_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(paths)
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mut updated = 0;
+    let mut failed = Vec::new();
+
+    for path in paths {
+        let full_path = vault_path.join(&path);
+        let content = match tokio::fs::read_to_string(&full_path).await {
+            Ok(content) => content,
+            Err(e) => {
+                failed.push(db::FailedFile {
+                    path,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let (rewritten, count) = rewrite_card_links(&content, board_name, old_title, new_title);
+        if count == 0 {
+            continue;
+        }
+
+        if let Err(e) = notes::write_atomic(&full_path, &rewritten) {
+            failed.push(db::FailedFile {
+                path,
+                error: e.to_string(),
+            });
+            continue;
+        }
+        updated += count;
+
+        if let Err(e) = db::index_single_note(app, &vault_path, Path::new(&path)).await {
+            failed.push(db::FailedFile {
+                path,
+                error: e.to_string(),
+            });
+        }
+    }
+
+    Ok((updated, failed))
+}
+
+/// `[[card:Title]]` or `[[card:Board/Title|alias]]`
+static CARD_LINK_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[\[card:([^\]|]+)(\|[^\]]+)?\]\]").unwrap());
+
+/// Replace card links that resolve to `old_title`, keeping any board prefix and
+/// display alias. Prefixed links only match when the board name matches.
+fn rewrite_card_links(
+    content: &str,
+    board_name: &str,
+    old_title: &str,
+    new_title: &str,
+) -> (String, usize) {
+    let mut count = 0;
+
+    let rewritten = CARD_LINK_REGEX.replace_all(content, |caps: &regex::Captures| {
+        let reference = caps[1].trim();
+        let alias = caps.get(2).map_or("", |m| m.as_str());
+
+        let (board, title) = match reference.split_once('/') {
+            Some((board, title)) => (Some(board), title.trim()),
+            None => (None, reference),
+        };
+        let board_matches = match board {
+            Some(board) => board.trim().eq_ignore_ascii_case(board_name),
+            None => true,
+        };
+
+        if !board_matches || !title.eq_ignore_ascii_case(old_title) {
+            return caps[0].to_string();
+        }
+
+        count += 1;
+        match board {
+            Some(board) => format!("[[card:{}/{}{}]]", board, new_title, alias),
+            None => format!("[[card:{}{}]]", new_title, alias),
+        }
+    });
+
+    (rewritten.into_owned(), count)
+}
+
 /// A note that references a kanban card
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CardBacklink {
@@ -1247,7 +1739,7 @@ pub struct KanbanCardSummary {
     pub column_name: Option<String>,
 }
 
-/// Get all cards across all boards (for autocomplete)
+/// Get all non-archived cards across all boards (for autocomplete)
 #[tauri::command]
 pub fn kanban_get_all_cards(app: AppHandle) -> Result<Vec<KanbanCardSummary>, String> {
     with_db(&app, |conn| {
@@ -1257,6 +1749,7 @@ pub fn kanban_get_all_cards(app: AppHandle) -> Result<Vec<KanbanCardSummary>, St
                 SELECT c.id, c.title, c.board_id, b.name, b.columns, c.column_id
                 FROM kanban_cards c
                 JOIN kanban_boards b ON c.board_id = b.id
+                WHERE COALESCE(c.archived, 0) = 0
                 ORDER BY c.updated_at DESC
                 "#,
             )
@@ -1290,6 +1783,92 @@ pub fn kanban_get_all_cards(app: AppHandle) -> Result<Vec<KanbanCardSummary>, St
     .map_err(|e| e.to_string())
 }
 
+/// A card assigned to someone, with its board and column for a cross-board task list
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignedCard {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "boardId")]
+    pub board_id: String,
+    #[serde(rename = "boardName")]
+    pub board_name: String,
+    #[serde(rename = "columnId")]
+    pub column_id: String,
+    #[serde(rename = "columnName")]
+    pub column_name: Option<String>,
+    #[serde(rename = "dueDate")]
+    pub due_date: Option<i64>,
+    pub priority: Option<String>,
+    #[serde(rename = "isComplete")]
+    pub is_complete: bool,
+}
+
+/// Get every non-archived card assigned to `name` across all boards,
+/// ordered by due date (undated last) then priority
+#[tauri::command]
+pub fn kanban_get_cards_for_assignee(
+    app: AppHandle,
+    name: String,
+) -> Result<Vec<AssignedCard>, String> {
+    with_db(&app, |conn| {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT c.id, c.title, c.board_id, b.name, b.columns, c.column_id,
+                       c.due_date, c.priority, COALESCE(c.is_complete, 0)
+                FROM kanban_cards c
+                JOIN kanban_boards b ON c.board_id = b.id
+                WHERE COALESCE(c.archived, 0) = 0
+                  AND c.metadata IS NOT NULL AND json_valid(c.metadata)
+                  AND EXISTS (
+                      SELECT 1 FROM json_each(c.metadata, '$.assignees')
+                      WHERE value = ?1 COLLATE NOCASE
+                  )
+                ORDER BY c.due_date IS NULL, c.due_date,
+                         CASE c.priority
+                             WHEN 'urgent' THEN 0
+                             WHEN 'high' THEN 1
+                             WHEN 'medium' THEN 2
+                             WHEN 'low' THEN 3
+                             ELSE 4
+                         END,
+                         c.updated_at DESC
+                "#,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let cards = stmt
+            .query_map(params![name.trim()], |row| {
+                let columns_json: String = row.get(4)?;
+                let column_id: String = row.get(5)?;
+                let columns: Vec<KanbanColumn> =
+                    serde_json::from_str(&columns_json).unwrap_or_default();
+                let column_name = columns
+                    .iter()
+                    .find(|c| c.id == column_id)
+                    .map(|c| c.name.clone());
+
+                Ok(AssignedCard {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    board_id: row.get(2)?,
+                    board_name: row.get(3)?,
+                    column_id,
+                    column_name,
+                    due_date: row.get(6)?,
+                    priority: row.get(7)?,
+                    is_complete: row.get::<_, i32>(8)? != 0,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(cards)
+    })
+    .map_err(|e| e.to_string())
+}
+
 /// Find a card by title (and optionally board name)
 #[tauri::command]
 pub fn kanban_find_card_by_title(
@@ -1405,3 +1984,635 @@ pub fn kanban_find_card_by_title(
     })
     .map_err(|e| e.to_string())
 }
+
+// ============= Card and Note Conversion =============
+
+/// Result of converting a card into a note
+#[derive(Debug, Serialize)]
+pub struct CardNoteConversion {
+    pub card: KanbanCard,
+    pub note: NoteMetadata,
+}
+
+/// Turn a card into a full note and link the card to it
+#[tauri::command]
+pub async fn kanban_convert_card_to_note(
+    app: AppHandle,
+    card_id: String,
+    target_path: String,
+) -> Result<CardNoteConversion, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    let card = kanban_get_card(app.clone(), card_id.clone())?;
+
+    let target_path = if target_path.ends_with(".md") {
+        target_path
+    } else {
+        format!("{}.md", target_path)
+    };
+    if vault_path.join(&target_path).exists() {
+        return Err(format!("A note already exists at {}", target_path));
+    }
+
+    // Cards store label ids; the note gets the readable names
+    let label_ids = card
+        .metadata
+        .as_ref()
+        .map(|m| m.labels.clone())
+        .unwrap_or_default();
+    let label_names: Vec<String> = with_db(&app, |conn| {
+        Ok(label_ids
+            .into_iter()
+            .map(|label_id| {
+                conn.query_row(
+                    "SELECT name FROM kanban_labels WHERE id = ?1",
+                    params![label_id],
+                    |row| row.get(0),
+                )
+                .unwrap_or(label_id)
+            })
+            .collect())
+    })
+    .map_err(|e| e.to_string())?;
+
+    let content = card_note_content(&card, label_names)?;
+    let note = notes::write_note(app.clone(), target_path, content, true)
+        .await
+        .map_err(|e| e.to_string())?
+        .metadata;
+
+    let now = chrono::Utc::now().timestamp();
+    with_db(&app, |conn| {
+        conn.execute(
+            "UPDATE kanban_cards SET note_id = ?1, updated_at = ?2 WHERE id = ?3",
+            params![note.id, now, card_id],
+        )?;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+
+    let card = kanban_get_card(app, card_id)?;
+    Ok(CardNoteConversion { card, note })
+}
+
+/// Build note content from a card: frontmatter with its tracking fields, then
+/// the title and description
+fn card_note_content(card: &KanbanCard, label_names: Vec<String>) -> Result<String, String> {
+    let mut frontmatter = Hash::new();
+    let key = |name: &str| Yaml::String(name.to_string());
+
+    if let Some(due) = card
+        .due_date
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+    {
+        frontmatter.insert(key("due"), Yaml::String(due.format("%Y-%m-%d").to_string()));
+    }
+    if let Some(priority) = &card.priority {
+        frontmatter.insert(key("priority"), Yaml::String(priority.clone()));
+    }
+
+    let assignees = card
+        .metadata
+        .as_ref()
+        .map(|m| m.assignees.clone())
+        .unwrap_or_default();
+    if !assignees.is_empty() {
+        frontmatter.insert(
+            key("assignees"),
+            Yaml::Array(assignees.into_iter().map(Yaml::String).collect()),
+        );
+    }
+    if !label_names.is_empty() {
+        frontmatter.insert(
+            key("labels"),
+            Yaml::Array(label_names.into_iter().map(Yaml::String).collect()),
+        );
+    }
+
+    let mut content = String::new();
+    if !frontmatter.is_empty() {
+        let yaml = notes::emit_yaml(&Yaml::Hash(frontmatter))?;
+        content.push_str(&format!("---\n{}---\n\n", yaml));
+    }
+
+    content.push_str(&format!("# {}\n", card.title));
+    if let Some(description) = card.description.as_deref().map(str::trim) {
+        if !description.is_empty() {
+            content.push_str(&format!("\n{}\n", description));
+        }
+    }
+
+    Ok(content)
+}
+
+/// Create a card that tracks an existing note, optionally carrying over the
+/// note's `due`, `priority` and `assignees` frontmatter
+#[tauri::command]
+pub fn kanban_create_card_from_note(
+    app: AppHandle,
+    note_id: String,
+    board_id: String,
+    column_id: String,
+    copy_frontmatter: Option<bool>,
+) -> Result<KanbanCard, String> {
+    // One transaction, so a failure never leaves a card half filled in
+    let card_id = with_db_tx(&app, |conn| {
+        let (title, frontmatter): (String, Option<String>) = conn
+            .query_row(
+                "SELECT title, frontmatter FROM notes WHERE id = ?1",
+                params![note_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| format!("Note not found: {}", note_id))?;
+
+        let card = insert_card(conn, board_id, column_id, title, Some(note_id.clone()))?;
+
+        let frontmatter: serde_json::Value = match frontmatter {
+            Some(json) if copy_frontmatter.unwrap_or(true) => {
+                serde_json::from_str(&json).unwrap_or_default()
+            }
+            _ => return Ok(card.id),
+        };
+
+        let due_date = frontmatter.get("due").and_then(parse_frontmatter_due);
+        let priority = frontmatter
+            .get("priority")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let assignees: Vec<String> = match frontmatter.get("assignees") {
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect(),
+            Some(serde_json::Value::String(name)) => vec![name.clone()],
+            _ => Vec::new(),
+        };
+
+        // Merge into whatever metadata the card already has rather than replacing it
+        let current: Option<String> = conn.query_row(
+            "SELECT metadata FROM kanban_cards WHERE id = ?1",
+            params![card.id],
+            |row| row.get(0),
+        )?;
+        let mut metadata: CardMetadata = current
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        for name in assignees {
+            if !metadata
+                .assignees
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(&name))
+            {
+                metadata.assignees.push(name);
+            }
+        }
+        let metadata_json = serde_json::to_string(&metadata).map_err(|e| e.to_string())?;
+
+        conn.execute(
+            "UPDATE kanban_cards
+             SET due_date = COALESCE(?1, due_date), priority = COALESCE(?2, priority), metadata = ?3
+             WHERE id = ?4",
+            params![due_date, priority, metadata_json, card.id],
+        )?;
+        Ok(card.id)
+    })
+    .map_err(|e| e.to_string())?;
+
+    kanban_get_card(app, card_id)
+}
+
+/// Parse a frontmatter `due` value (a date, an RFC 3339 timestamp or epoch
+/// seconds) into a card due date
+fn parse_frontmatter_due(value: &serde_json::Value) -> Option<i64> {
+    if let Some(ts) = value.as_i64() {
+        return Some(ts);
+    }
+
+    let s = value.as_str()?.trim();
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp());
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.timestamp())
+}
+
+// ============= Markdown Import/Export =============
+
+/// Render a board as markdown: a heading per column and a checklist item per
+/// card with its assignees, labels, due date and linked note. When
+/// `target_path` is given the markdown is also saved there as a note.
+#[tauri::command]
+pub async fn kanban_export_markdown(
+    app: AppHandle,
+    board_id: String,
+    target_path: Option<String>,
+) -> Result<String, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    let board = kanban_get_board(app.clone(), board_id.clone())?;
+    let cards = kanban_get_cards(app.clone(), board_id, None, None, None, None)?;
+
+    // Cards store label ids; the export shows the readable names
+    let label_names: std::collections::HashMap<String, String> = with_db(&app, |conn| {
+        let mut stmt = conn.prepare("SELECT id, name FROM kanban_labels")?;
+        let labels = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(labels)
+    })
+    .map_err(|e| e.to_string())?;
+
+    let links = read_vault_settings(&vault_path).unwrap_or_default().links;
+    let markdown = board_markdown(&board, &cards, &label_names, &links);
+
+    if let Some(target_path) = target_path {
+        let target_path = if target_path.ends_with(".md") {
+            target_path
+        } else {
+            format!("{}.md", target_path)
+        };
+        notes::write_note(app, target_path, markdown.clone(), true)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(markdown)
+}
+
+fn board_markdown(
+    board: &KanbanBoard,
+    cards: &[KanbanCard],
+    label_names: &std::collections::HashMap<String, String>,
+    links: &LinkSettings,
+) -> String {
+    let mut out = format!("# {}\n", board.name);
+
+    for column in &board.columns {
+        out.push_str(&format!("\n## {}\n\n", column.name));
+
+        // Linked cards sit in the column recorded for this board
+        let column_cards = cards.iter().filter(|card| {
+            let column_id = if card.board_id == board.id {
+                Some(&card.column_id)
+            } else {
+                card.board_columns
+                    .as_ref()
+                    .and_then(|cols| cols.get(&board.id))
+            };
+            !card.archived && column_id == Some(&column.id)
+        });
+
+        for card in column_cards {
+            let done = column.is_done || card.is_complete.unwrap_or(false);
+            let mut line = format!(
+                "- [{}] {}",
+                if done { 'x' } else { ' ' },
+                card.title.replace(['\r', '\n'], " ")
+            );
+
+            if let Some(metadata) = &card.metadata {
+                for assignee in &metadata.assignees {
+                    line.push_str(&format!(" @{}", inline_token(assignee)));
+                }
+                for label_id in &metadata.labels {
+                    let name = label_names.get(label_id).unwrap_or(label_id);
+                    line.push_str(&format!(" #{}", inline_token(name)));
+                }
+            }
+            if let Some(due) = card
+                .due_date
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+            {
+                line.push_str(&format!(" due:{}", due.format("%Y-%m-%d")));
+            }
+            if let Some(note_path) = &card.note_path {
+                line.push(' ');
+                line.push_str(&links.format_link(note_path, None));
+            }
+
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Names inside `@name` / `#label` annotations can't contain whitespace
+fn inline_token(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// A checklist item parsed from imported markdown
+struct ImportedCard {
+    title: String,
+    done: bool,
+    assignees: Vec<String>,
+    labels: Vec<String>,
+    due_date: Option<i64>,
+    note_link: Option<String>,
+}
+
+/// Create a board from a markdown checklist: `## Column` headings become
+/// columns and `- [ ]` / `- [x]` items become cards, with checked items placed
+/// in a done column. Inline `@name`, `#label` and `due:YYYY-MM-DD` annotations
+/// and a trailing note link are picked up as they are written by
+/// `kanban_export_markdown`; unknown members and labels are created.
+#[tauri::command]
+pub fn kanban_import_markdown(
+    app: AppHandle,
+    name: String,
+    markdown: String,
+) -> Result<KanbanBoard, String> {
+    let heading_re = Regex::new(r"^##\s+(.+?)\s*#*\s*$").unwrap();
+
+    // Items before the first heading go to a default column
+    let mut sections: Vec<(String, Vec<ImportedCard>)> = Vec::new();
+    for line in markdown.lines() {
+        if let Some(cap) = heading_re.captures(line) {
+            sections.push((cap[1].to_string(), Vec::new()));
+        } else if let Some(cap) = db::TASK_REGEX.captures(line) {
+            let text = cap.get(3).map_or("", |m| m.as_str());
+            if let Some(card) = parse_imported_card(text, &cap[2] != " ") {
+                if sections.is_empty() {
+                    sections.push(("To Do".to_string(), Vec::new()));
+                }
+                if let Some((_, cards)) = sections.last_mut() {
+                    cards.push(card);
+                }
+            }
+        }
+    }
+
+    if sections.is_empty() {
+        return Err("No columns or tasks found in markdown".to_string());
+    }
+
+    let mut columns: Vec<KanbanColumn> = sections
+        .iter()
+        .map(|(column, _)| new_column(column.clone()))
+        .collect();
+    let needs_done_column = !columns.iter().any(|c| c.is_done)
+        && sections
+            .iter()
+            .any(|(_, cards)| cards.iter().any(|c| c.done));
+    if needs_done_column {
+        columns.push(KanbanColumn {
+            is_done: true,
+            ..new_column("Done".to_string())
+        });
+    }
+    let done_column = columns.iter().position(|c| c.is_done);
+
+    let board_id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp();
+    let columns_json = serde_json::to_string(&columns).map_err(|e| e.to_string())?;
+
+    with_db_tx(&app, |conn| {
+        let existing: Result<String, _> = conn.query_row(
+            "SELECT id FROM kanban_boards WHERE LOWER(name) = ?1",
+            params![name.to_lowercase()],
+            |row| row.get(0),
+        );
+        if existing.is_ok() {
+            return Err(format!("A board named '{}' already exists", name).into());
+        }
+
+        conn.execute(
+            "INSERT INTO kanban_boards (id, name, columns, owner_name, created_at, modified_at) VALUES (?1, ?2, ?3, NULL, ?4, ?4)",
+            params![board_id, name, columns_json, now],
+        )?;
+
+        let mut positions = vec![0; columns.len()];
+        for (index, (_, cards)) in sections.into_iter().enumerate() {
+            for card in cards {
+                // Checked items land in a done column unless already in one
+                let column_index = match done_column {
+                    Some(done) if card.done && !columns[index].is_done => done,
+                    _ => index,
+                };
+                let column = &columns[column_index];
+                let is_done = column.is_done;
+
+                let assignees = card
+                    .assignees
+                    .iter()
+                    .map(|assignee| import_member(conn, &board_id, assignee, now))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let labels = card
+                    .labels
+                    .iter()
+                    .map(|label| import_label(conn, &board_id, label))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let metadata = CardMetadata {
+                    assignees,
+                    labels,
+                    ..Default::default()
+                };
+                let metadata_json = serde_json::to_string(&metadata)?;
+
+                let note_id = match &card.note_link {
+                    Some(target) => conn
+                        .query_row(
+                            r#"
+                            SELECT id FROM notes
+                            WHERE path IN (?1, ?1 || '.md', 'notes/' || ?1, 'notes/' || ?1 || '.md')
+                               OR substr(path, -length(?1) - 4) = '/' || ?1 || '.md'
+                               OR LOWER(title) = LOWER(?1)
+                            ORDER BY path IN (?1, ?1 || '.md', 'notes/' || ?1, 'notes/' || ?1 || '.md') DESC
+                            LIMIT 1
+                            "#,
+                            params![target],
+                            |row| row.get::<_, String>(0),
+                        )
+                        .ok(),
+                    None => None,
+                };
+
+                conn.execute(
+                    r#"
+                    INSERT INTO kanban_cards (id, board_id, column_id, title, note_id, position,
+                                              created_at, updated_at, closed_at, due_date,
+                                              metadata, is_complete)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    params![
+                        Uuid::new_v4().to_string(),
+                        board_id,
+                        column.id,
+                        card.title,
+                        note_id,
+                        positions[column_index],
+                        now,
+                        is_done.then_some(now),
+                        card.due_date,
+                        metadata_json,
+                        (is_done || card.done) as i32,
+                    ],
+                )?;
+                positions[column_index] += 1;
+            }
+        }
+
+        db::resolve_member_mentions(conn, None)?;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(KanbanBoard {
+        id: board_id,
+        name,
+        columns,
+        owner_name: None,
+        created_at: now,
+        modified_at: now,
+    })
+}
+
+/// Split a checklist item's text into the card title and its annotations
+fn parse_imported_card(text: &str, done: bool) -> Option<ImportedCard> {
+    let link_re =
+        Regex::new(r"\[\[([^\]|]+)(?:\|[^\]]*)?\]\]|\[[^\]]*\]\(([^)]+?)(?:\.md)?\)").unwrap();
+
+    let note_link = link_re.captures(text).and_then(|cap| {
+        cap.get(1)
+            .or_else(|| cap.get(2))
+            .map(|m| m.as_str().trim().trim_end_matches(".md").to_string())
+    });
+    let text = link_re.replace_all(text, " ");
+
+    let mut title_words = Vec::new();
+    let mut assignees = Vec::new();
+    let mut labels = Vec::new();
+    let mut due_date = None;
+
+    for word in text.split_whitespace() {
+        if let Some(name) = word.strip_prefix('@').filter(|n| !n.is_empty()) {
+            assignees.push(name.to_string());
+        } else if let Some(label) = word.strip_prefix('#').filter(|l| !l.is_empty()) {
+            labels.push(label.to_string());
+        } else if let Some(date) = word
+            .strip_prefix("due:")
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        {
+            due_date = date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp());
+        } else {
+            title_words.push(word);
+        }
+    }
+
+    // A bare link still makes a card, titled after the note
+    let title = if title_words.is_empty() {
+        let target = note_link.as_deref()?;
+        target.rsplit('/').next().unwrap_or(target).to_string()
+    } else {
+        title_words.join(" ")
+    };
+
+    Some(ImportedCard {
+        title,
+        done,
+        assignees,
+        labels,
+        due_date,
+        note_link,
+    })
+}
+
+/// Name of an existing member matching `name` (members are global), or a new
+/// member added to the board
+fn import_member(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+    name: &str,
+    now: i64,
+) -> Result<String, db::DbError> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT name FROM kanban_board_members WHERE LOWER(name) = LOWER(?1) LIMIT 1",
+            params![name],
+            |row| row.get(0),
+        )
+        .ok();
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    conn.execute(
+        "INSERT INTO kanban_board_members (id, board_id, name, added_at) VALUES (?1, ?2, ?3, ?4)",
+        params![Uuid::new_v4().to_string(), board_id, name, now],
+    )?;
+    Ok(name.to_string())
+}
+
+/// Id of the board's label named `name`, created with the default color if missing
+fn import_label(
+    conn: &rusqlite::Connection,
+    board_id: &str,
+    name: &str,
+) -> Result<String, db::DbError> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM kanban_labels WHERE board_id = ?1 AND LOWER(name) = LOWER(?2)",
+            params![board_id, name],
+            |row| row.get(0),
+        )
+        .ok();
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO kanban_labels (id, board_id, name) VALUES (?1, ?2, ?3)",
+        params![id, board_id, name],
+    )?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    fn card_ids(cards: Vec<KanbanCard>) -> Vec<String> {
+        cards.into_iter().map(|card| card.id).collect()
+    }
+
+    #[test]
+    fn archived_card_is_hidden_from_the_board_but_listed_as_archived() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO kanban_boards (id, name, columns, created_at, modified_at)
+             VALUES ('board', 'Board', '[]', 0, 0)",
+            [],
+        )
+        .unwrap();
+        for (position, id) in ["triage", "report"].iter().enumerate() {
+            conn.execute(
+                "INSERT INTO kanban_cards (id, board_id, column_id, title, position)
+                 VALUES (?1, 'board', 'todo', ?1, ?2)",
+                params![id, position as i32],
+            )
+            .unwrap();
+        }
+
+        set_card_archived(&conn, "triage", true).unwrap();
+
+        assert_eq!(
+            card_ids(fetch_board_cards(&conn, "board", false).unwrap()),
+            ["report"]
+        );
+        assert_eq!(
+            card_ids(fetch_archived_cards(&conn, "board").unwrap()),
+            ["triage"]
+        );
+
+        set_card_archived(&conn, "triage", false).unwrap();
+        assert_eq!(
+            card_ids(fetch_board_cards(&conn, "board", false).unwrap()),
+            ["triage", "report"]
+        );
+    }
+}
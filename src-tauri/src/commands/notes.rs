@@ -1,15 +1,30 @@
 use serde::{Deserialize, Serialize};
+use similar::{Algorithm, ChangeTag, TextDiff};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::AppHandle;
+use walkdir::WalkDir;
+use yaml_rust2::yaml::{Hash, Yaml};
+use yaml_rust2::{YamlEmitter, YamlLoader};
 
+use crate::commands::search::SearchFilters;
+use crate::commands::settings::{read_vault_settings, LinkSettings, LinkStyle, NoteIdScheme};
+use crate::crypto;
 use crate::db;
+use crate::error::CommandError;
+use crate::git::{DiffHunk, DiffLine};
 
 /// Validate that a relative path doesn't escape the vault directory
-fn validate_vault_path(vault_path: &Path, relative_path: &str) -> Result<PathBuf, String> {
+pub(crate) fn validate_vault_path(
+    vault_path: &Path,
+    relative_path: &str,
+) -> Result<PathBuf, CommandError> {
     // Reject obvious traversal attempts
     if relative_path.contains("..") || relative_path.contains("\0") {
-        return Err("Access denied: invalid path characters".to_string());
+        return Err(CommandError::PathTraversal {
+            path: relative_path.to_string(),
+        });
     }
 
     // Build the full path
@@ -19,16 +34,18 @@ fn validate_vault_path(vault_path: &Path, relative_path: &str) -> Result<PathBuf
     // Note: For new files, we canonicalize the parent directory
     let canonical_vault = vault_path
         .canonicalize()
-        .map_err(|_| "Invalid vault path".to_string())?;
+        .map_err(|_| CommandError::invalid("Invalid vault path"))?;
 
     // If the file exists, canonicalize it directly
     if full_path.exists() {
         let canonical_full = full_path
             .canonicalize()
-            .map_err(|_| "Invalid path".to_string())?;
+            .map_err(|_| CommandError::invalid("Invalid path"))?;
 
         if !canonical_full.starts_with(&canonical_vault) {
-            return Err("Access denied: path traversal detected".to_string());
+            return Err(CommandError::PathTraversal {
+                path: relative_path.to_string(),
+            });
         }
 
         return Ok(canonical_full);
@@ -40,10 +57,12 @@ fn validate_vault_path(vault_path: &Path, relative_path: &str) -> Result<PathBuf
         if parent.exists() {
             let canonical_parent = parent
                 .canonicalize()
-                .map_err(|_| "Invalid parent path".to_string())?;
+                .map_err(|_| CommandError::invalid("Invalid parent path"))?;
 
             if !canonical_parent.starts_with(&canonical_vault) {
-                return Err("Access denied: path traversal detected".to_string());
+                return Err(CommandError::PathTraversal {
+                    path: relative_path.to_string(),
+                });
             }
         }
     }
@@ -61,6 +80,34 @@ pub struct NoteMetadata {
     pub created_at: i64,
     pub archived: bool,
     pub starred: bool,
+    /// Cached count of links this note makes
+    #[serde(default)]
+    pub outgoing_count: usize,
+    /// Cached count of notes linking to this note
+    #[serde(default)]
+    pub incoming_count: usize,
+}
+
+/// Sort order for paginated note listings
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum NoteSort {
+    /// Most recently modified first
+    #[default]
+    Modified,
+    /// Most recently created first
+    Created,
+    /// Alphabetical by title
+    Title,
+    /// Alphabetical by path
+    Path,
+}
+
+/// One page of notes plus the total number of notes in the vault
+#[derive(Debug, Serialize)]
+pub struct NotePage {
+    pub notes: Vec<NoteMetadata>,
+    pub total: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,35 +118,167 @@ pub struct Note {
     pub content: String,
     pub modified_at: i64,
     pub created_at: i64,
+    /// True if the note is encrypted; `content` is then empty until decrypted
+    pub encrypted: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct FolderEntry {
     pub name: String,
     pub path: String,
     pub is_folder: bool,
     pub children: Option<Vec<FolderEntry>>,
+    /// Notes directly inside this folder
+    #[serde(default)]
+    pub note_count: usize,
 }
 
 /// List all notes in the vault
 #[tauri::command]
-pub fn list_notes(app: AppHandle) -> Result<Vec<NoteMetadata>, String> {
-    db::list_all_notes(&app).map_err(|e| e.to_string())
+pub fn list_notes(app: AppHandle) -> Result<Vec<NoteMetadata>, CommandError> {
+    db::list_all_notes(&app).map_err(CommandError::from)
+}
+
+/// List one page of notes, for windowed lists in large vaults
+#[tauri::command]
+pub fn list_notes_paginated(
+    app: AppHandle,
+    offset: u32,
+    limit: u32,
+    sort: Option<NoteSort>,
+) -> Result<NotePage, CommandError> {
+    let (notes, total) = db::list_notes_page(&app, offset, limit, sort.unwrap_or_default())?;
+    Ok(NotePage { notes, total })
+}
+
+/// Lightweight note summary for hover cards and link previews
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotePreview {
+    pub id: String,
+    pub path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    /// Start of the body, without frontmatter or title, whitespace collapsed
+    pub excerpt: String,
+    pub created_at: i64,
+    pub modified_at: i64,
+}
+
+/// Default excerpt length for `get_note_preview`, in characters
+const PREVIEW_EXCERPT_CHARS: usize = 200;
+
+/// Preview a note from the index alone, without reading the file. Encrypted
+/// and oversized notes have no stored content, so their excerpt is empty.
+#[tauri::command]
+pub fn get_note_preview(
+    app: AppHandle,
+    path: String,
+    max_chars: Option<usize>,
+) -> Result<NotePreview, CommandError> {
+    let row = db::get_note_preview_row(&app, &path)?;
+
+    let max_chars = max_chars.unwrap_or(PREVIEW_EXCERPT_CHARS);
+    let body = strip_frontmatter_and_title(&row.content);
+    let words = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut excerpt: String = words.chars().take(max_chars).collect();
+    if words.chars().nth(max_chars).is_some() {
+        excerpt.push('…');
+    }
+
+    Ok(NotePreview {
+        id: row.id,
+        path,
+        title: row.title,
+        tags: row.tags,
+        excerpt,
+        created_at: row.created_at,
+        modified_at: row.modified_at,
+    })
+}
+
+/// Per-note numbers for the editor status bar
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteDetailStats {
+    pub id: String,
+    pub path: String,
+    /// Words in the body, not counting frontmatter
+    pub word_count: usize,
+    /// At `READING_WORDS_PER_MINUTE`, rounded up
+    pub reading_time_minutes: usize,
+    pub outgoing_link_count: usize,
+    pub backlink_count: usize,
+    pub open_task_count: usize,
+    pub done_task_count: usize,
+    /// Headings outside code blocks
+    pub heading_count: usize,
+    /// Distinct attachments the note embeds or links to
+    pub attachment_count: usize,
+}
+
+/// Reading speed used for `reading_time_minutes`
+const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// Word, link, task, heading and attachment counts for a note in one call,
+/// from the index rather than the file. Encrypted and oversized notes have no
+/// stored content, so their text counts are zero.
+#[tauri::command]
+pub fn get_note_detail_stats(
+    app: AppHandle,
+    path: String,
+) -> Result<NoteDetailStats, CommandError> {
+    let row = db::get_note_stats_row(&app, &path)?;
+
+    let body = split_frontmatter(&row.content).map_or(row.content.as_str(), |(_, body)| body);
+    let word_count = body.split_whitespace().count();
+
+    let mut in_fence = false;
+    let heading_count = body
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return false;
+            }
+            let level = trimmed.bytes().take_while(|&b| b == b'#').count();
+            !in_fence
+                && (1..=6).contains(&level)
+                && !trimmed[level..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| !c.is_whitespace())
+        })
+        .count();
+
+    Ok(NoteDetailStats {
+        id: row.id,
+        path,
+        word_count,
+        reading_time_minutes: (word_count + READING_WORDS_PER_MINUTE - 1)
+            / READING_WORDS_PER_MINUTE,
+        outgoing_link_count: row.outgoing_count,
+        backlink_count: row.incoming_count,
+        open_task_count: row.open_tasks,
+        done_task_count: row.done_tasks,
+        heading_count,
+        attachment_count: row.attachment_count,
+    })
 }
 
 /// Read a note by its path (relative to vault)
 #[tauri::command]
-pub fn read_note(app: AppHandle, path: String) -> Result<Note, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub fn read_note(app: AppHandle, path: String) -> Result<Note, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
-    let content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
-    let metadata = fs::metadata(&note_path).map_err(|e| e.to_string())?;
+    let bytes = fs::read(&note_path)?;
+    let content =
+        db::decode_note_content(bytes).map_err(|e| format!("Note is not valid UTF-8: {}", e))?;
+    let metadata = fs::metadata(&note_path)?;
 
     let modified_at = metadata
         .modified()
@@ -110,20 +289,26 @@ pub fn read_note(app: AppHandle, path: String) -> Result<Note, String> {
         })
         .unwrap_or(0);
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+    let created_at = note_created_at(&app, &path, &content, &metadata);
+
+    // Don't hand ciphertext to the editor; it prompts for the passphrase instead
+    if let Some(title) = crypto::envelope_title(&content) {
+        return Ok(Note {
+            id: resolve_note_id(&app, &path),
+            path,
+            title,
+            content: String::new(),
+            modified_at,
+            created_at,
+            encrypted: true,
+        });
+    }
 
     // Extract title from first heading or filename
     let title = extract_title(&content, &path);
 
     // Generate ID from path
-    let id = generate_note_id(&path);
+    let id = resolve_note_id(&app, &path);
 
     Ok(Note {
         id,
@@ -132,9 +317,19 @@ pub fn read_note(app: AppHandle, path: String) -> Result<Note, String> {
         content,
         modified_at,
         created_at,
+        encrypted: false,
     })
 }
 
+/// A note as written by `write_note`
+#[derive(Debug, Serialize)]
+pub struct SavedNote {
+    #[serde(flatten)]
+    pub metadata: NoteMetadata,
+    /// Content as saved, which frontmatter normalization may have changed
+    pub content: String,
+}
+
 /// Write/update a note
 #[tauri::command]
 pub async fn write_note(
@@ -142,38 +337,75 @@ pub async fn write_note(
     path: String,
     content: String,
     create_if_missing: bool,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<SavedNote, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     // Check if note exists
-    if !note_path.exists() && !create_if_missing {
-        return Err(format!("Note not found: {}", path));
+    let is_new = !note_path.exists();
+    if is_new && !create_if_missing {
+        return Err(CommandError::not_found(&path));
     }
 
+    // A brand-new empty note gets its folder's template, if one is mapped; a
+    // broken mapping shouldn't stop the note being created
+    let content = if is_new && content.trim().is_empty() {
+        render_folder_template(&vault_path, &path)
+            .ok()
+            .flatten()
+            .unwrap_or(content)
+    } else {
+        content
+    };
+
     // Create a version of the current content before saving (if file exists)
     if note_path.exists() {
-        let note_id = generate_note_id(&path);
+        let note_id = resolve_note_id(&app, &path);
         if let Ok(current_content) = fs::read_to_string(&note_path) {
+            // The editor only ever holds an empty placeholder for an encrypted note, so
+            // a save would destroy the ciphertext; `decrypt_note` must run first
+            if crypto::is_encrypted(&current_content) {
+                return Err(CommandError::invalid(format!(
+                    "Note is encrypted; decrypt it before editing: {}",
+                    path
+                )));
+            }
             // Create version (auto-deduplicates, so no duplicates if content hasn't changed)
             let _ = db::create_note_version(&app, &note_id, &current_content, "save", None);
         }
     }
 
+    // Optionally canonicalize frontmatter; invalid YAML is saved as-is rather than rewritten
+    let settings = read_vault_settings(&vault_path).unwrap_or_default();
+    let content = if settings.normalize_frontmatter_on_save {
+        normalize_frontmatter_content(&content).unwrap_or(content)
+    } else {
+        content
+    };
+
     // Ensure parent directory exists
     if let Some(parent) = note_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        fs::create_dir_all(parent)?;
     }
 
     // Write the file
-    fs::write(&note_path, &content).map_err(|e| e.to_string())?;
+    write_atomic(&note_path, &content)?;
 
     // Update index
-    db::index_single_note(&app, &vault_path, &PathBuf::from(&path))
-        .await
-        .map_err(|e| e.to_string())?;
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&path)).await?;
 
-    let metadata = fs::metadata(&note_path).map_err(|e| e.to_string())?;
+    let metadata = note_metadata(&app, path, &content, &note_path)?;
+    Ok(SavedNote { metadata, content })
+}
+
+/// Metadata for a note file, given its content
+fn note_metadata(
+    app: &AppHandle,
+    path: String,
+    content: &str,
+    note_path: &Path,
+) -> Result<NoteMetadata, CommandError> {
+    let metadata = fs::metadata(note_path)?;
     let modified_at = metadata
         .modified()
         .map(|t| {
@@ -183,19 +415,13 @@ pub async fn write_note(
         })
         .unwrap_or(0);
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+    let created_at = note_created_at(app, &path, content, &metadata);
 
-    let title = extract_title(&content, &path);
-    let id = generate_note_id(&path);
-    let archived = extract_archived(&content);
-    let starred = db::get_note_starred(&app, &id).unwrap_or(false);
+    let title = extract_title(content, &path);
+    let id = resolve_note_id(app, &path);
+    let archived = extract_archived(content);
+    let starred = db::get_note_starred(app, &id).unwrap_or(false);
+    let (outgoing_count, incoming_count) = db::get_note_link_counts(app, &id).unwrap_or_default();
 
     Ok(NoteMetadata {
         id,
@@ -205,23 +431,25 @@ pub async fn write_note(
         created_at,
         archived,
         starred,
+        outgoing_count,
+        incoming_count,
     })
 }
 
 /// Delete a note
 #[tauri::command]
-pub async fn delete_note(app: AppHandle, path: String) -> Result<(), String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub async fn delete_note(app: AppHandle, path: String) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
-    fs::remove_file(&note_path).map_err(|e| e.to_string())?;
+    fs::remove_file(&note_path)?;
 
     // Remove from index
-    db::remove_note_from_index(&app, &path).map_err(|e| e.to_string())?;
+    db::remove_note_from_index(&app, &path)?;
 
     Ok(())
 }
@@ -232,170 +460,1721 @@ pub async fn rename_note(
     app: AppHandle,
     old_path: String,
     new_path: String,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    allow_extension_change: Option<bool>,
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    // Read the note before moving it, so one that can't be indexed is refused
+    // rather than left half renamed
     let old_note_path = validate_vault_path(&vault_path, &old_path)?;
-    let new_note_path = validate_vault_path(&vault_path, &new_path)?;
+    if !old_note_path.exists() {
+        return Err(CommandError::not_found(&old_path));
+    }
+    let content = db::decode_note_content(fs::read(&old_note_path)?)
+        .map_err(|_| CommandError::invalid(format!("Note is not valid UTF-8: {}", old_path)))?;
+
+    let new_note_path = move_note_file(
+        &vault_path,
+        &old_path,
+        &new_path,
+        allow_extension_change.unwrap_or(false),
+    )?;
+
+    // Update index in place so the note keeps its id (and the card/diagram links keyed by it)
+    let reindexed = match db::move_note_in_index(&app, &old_path, &new_path) {
+        Ok(()) => match db::index_single_note(&app, &vault_path, Path::new(&new_path)).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let _ = db::move_note_in_index(&app, &new_path, &old_path);
+                Err(e)
+            }
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = reindexed {
+        // Put the file back where the index still has it
+        let _ = move_note_file(&vault_path, &new_path, &old_path, true);
+        return Err(e.into());
+    }
+
+    let metadata = fs::metadata(&new_note_path)?;
+    let modified_at = metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+
+    let created_at = note_created_at(&app, &new_path, &content, &metadata);
+
+    // Encrypted notes only expose the title stored in their envelope
+    let title =
+        crypto::envelope_title(&content).unwrap_or_else(|| extract_title(&content, &new_path));
+    let id = resolve_note_id(&app, &new_path);
+    let archived = extract_archived(&content);
+    let starred = db::get_note_starred(&app, &id).unwrap_or(false);
+    let (outgoing_count, incoming_count) = db::get_note_link_counts(&app, &id).unwrap_or_default();
+
+    Ok(NoteMetadata {
+        id,
+        path: new_path,
+        title,
+        modified_at,
+        created_at,
+        archived,
+        starred,
+        outgoing_count,
+        incoming_count,
+    })
+}
+
+/// Marker file that keeps otherwise empty folders alive through git
+const FOLDER_MARKER: &str = ".gitkeep";
+
+/// Create a folder
+#[tauri::command]
+pub fn create_folder(app: AppHandle, path: String) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let folder_path = validate_vault_path(&vault_path, &path)?;
+
+    fs::create_dir_all(&folder_path)?;
+
+    // Git doesn't track empty directories
+    let marker = folder_path.join(FOLDER_MARKER);
+    if !marker.exists() {
+        fs::write(&marker, "")?;
+    }
+
+    Ok(())
+}
+
+/// Validate a folder path for delete/rename: it must be a folder inside `notes/`,
+/// not the notes root itself
+fn validate_folder_path(vault_path: &Path, path: &str) -> Result<PathBuf, CommandError> {
+    let path = path.trim_end_matches('/');
+    if !path.starts_with(&format!("{}/", NOTES_FOLDER)) {
+        return Err(CommandError::invalid(format!(
+            "Not a notes folder: {}",
+            path
+        )));
+    }
+    validate_vault_path(vault_path, path)
+}
+
+/// Delete a folder. Without `recursive`, only empty folders (or ones holding
+/// just the folder marker) are removed.
+#[tauri::command]
+pub fn delete_folder(app: AppHandle, path: String, recursive: bool) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let folder_path = validate_folder_path(&vault_path, &path)?;
+
+    if !folder_path.is_dir() {
+        return Err(CommandError::not_found(&path));
+    }
+
+    if !recursive {
+        let has_contents = fs::read_dir(&folder_path)?
+            .filter_map(|e| e.ok())
+            .any(|e| e.file_name() != FOLDER_MARKER);
+        if has_contents {
+            return Err(CommandError::invalid(format!(
+                "Folder is not empty: {}",
+                path
+            )));
+        }
+    }
+
+    fs::remove_dir_all(&folder_path)?;
+
+    db::remove_folder_from_index(&app, &path)?;
+
+    Ok(())
+}
+
+/// Rename/move a folder, keeping the ids of the notes inside it
+#[tauri::command]
+pub fn rename_folder(
+    app: AppHandle,
+    old_path: String,
+    new_path: String,
+) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let old_folder_path = validate_folder_path(&vault_path, &old_path)?;
+    let new_folder_path = validate_folder_path(&vault_path, &new_path)?;
+
+    if !old_folder_path.is_dir() {
+        return Err(CommandError::not_found(&old_path));
+    }
+
+    if new_folder_path.exists() {
+        return Err(CommandError::conflict(&new_path));
+    }
+
+    if new_path.starts_with(&format!("{}/", old_path.trim_end_matches('/'))) {
+        return Err(CommandError::invalid("Cannot move a folder into itself"));
+    }
+
+    if let Some(parent) = new_folder_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::rename(&old_folder_path, &new_folder_path)?;
+
+    db::move_folder_in_index(&app, &old_path, &new_path)?;
+
+    Ok(())
+}
+
+/// Move a note's file for `rename_note`, returning its new full path
+fn move_note_file(
+    vault_path: &Path,
+    old_path: &str,
+    new_path: &str,
+    allow_extension_change: bool,
+) -> Result<PathBuf, CommandError> {
+    let old_note_path = validate_vault_path(vault_path, old_path)?;
+    let new_note_path = validate_vault_path(vault_path, new_path)?;
 
     if !old_note_path.exists() {
-        return Err(format!("Note not found: {}", old_path));
+        return Err(CommandError::not_found(old_path));
+    }
+
+    // Dropping the extension takes the note out of the index, so it must be asked for
+    if old_path.ends_with(".md") && !new_path.ends_with(".md") && !allow_extension_change {
+        return Err(CommandError::invalid(format!(
+            "New path must keep the .md extension: {}",
+            new_path
+        )));
+    }
+
+    // On case-insensitive filesystems `Note.md` and `note.md` are the same file,
+    // so the target "exists"; it's only a conflict if that exact name is on disk
+    let case_only = old_path != new_path && old_path.to_lowercase() == new_path.to_lowercase();
+    // Canonicalizing may resolve to the source's casing, so use the requested path as-is
+    let new_note_path = if case_only {
+        vault_path.join(new_path)
+    } else {
+        new_note_path
+    };
+    let target_taken = if case_only {
+        exact_entry_exists(&new_note_path)
+    } else {
+        new_note_path.exists()
+    };
+    if target_taken {
+        return Err(CommandError::conflict(new_path));
+    }
+
+    // Ensure parent directory exists
+    if let Some(parent) = new_note_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Move the file; case-only renames go through a temporary name since some
+    // filesystems treat a direct rename as a no-op
+    if case_only {
+        let temp_path = old_note_path.with_file_name(format!(
+            ".{}.rename-{}",
+            old_note_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            uuid::Uuid::new_v4()
+        ));
+        fs::rename(&old_note_path, &temp_path)?;
+        if let Err(e) = fs::rename(&temp_path, &new_note_path) {
+            let _ = fs::rename(&temp_path, &old_note_path);
+            return Err(e.into());
+        }
+    } else {
+        fs::rename(&old_note_path, &new_note_path)?;
+    }
+
+    Ok(new_note_path)
+}
+
+/// Whether the parent directory holds an entry with exactly this name. On a
+/// case-insensitive filesystem `exists()` also matches other casings.
+fn exact_entry_exists(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .any(|e| e.file_name() == name)
+        })
+        .unwrap_or(false)
+}
+
+/// Folder under the vault root that holds all notes
+const NOTES_FOLDER: &str = "notes";
+
+/// List every folder under `notes/` as a tree, including empty and marker-only
+/// folders on disk
+#[tauri::command]
+pub fn list_folders(app: AppHandle) -> Result<FolderEntry, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let notes_dir = vault_path.join(NOTES_FOLDER);
+
+    // Folder path -> direct note count
+    let mut folders: BTreeMap<String, usize> = BTreeMap::new();
+    folders.insert(NOTES_FOLDER.to_string(), 0);
+
+    for note in db::list_all_notes(&app)? {
+        let path = note.path.replace('\\', "/");
+        if let Some((folder, _)) = path.rsplit_once('/') {
+            if insert_folder(&mut folders, folder) {
+                *folders.entry(folder.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // Empty folders only exist on disk; skip hidden ones like .trash
+    for entry in WalkDir::new(&notes_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_dir() {
+            if let Ok(relative) = entry.path().strip_prefix(&vault_path) {
+                insert_folder(&mut folders, &relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    Ok(build_folder_tree(NOTES_FOLDER, &folders))
+}
+
+/// Add a folder under `notes/` and all of its ancestors. Returns false for
+/// paths outside the notes folder.
+fn insert_folder(folders: &mut BTreeMap<String, usize>, folder: &str) -> bool {
+    if folder != NOTES_FOLDER && !folder.starts_with(&format!("{}/", NOTES_FOLDER)) {
+        return false;
+    }
+
+    let mut current = String::new();
+    for part in folder.split('/') {
+        if !current.is_empty() {
+            current.push('/');
+        }
+        current.push_str(part);
+        folders.entry(current.clone()).or_insert(0);
+    }
+    true
+}
+
+fn build_folder_tree(path: &str, folders: &BTreeMap<String, usize>) -> FolderEntry {
+    let prefix = format!("{}/", path);
+    let children = folders
+        .keys()
+        .filter(|key| {
+            key.strip_prefix(&prefix)
+                .is_some_and(|rest| !rest.contains('/'))
+        })
+        .map(|key| build_folder_tree(key, folders))
+        .collect();
+
+    FolderEntry {
+        name: path.rsplit('/').next().unwrap_or(path).to_string(),
+        path: path.to_string(),
+        is_folder: true,
+        children: Some(children),
+        note_count: folders.get(path).copied().unwrap_or(0),
+    }
+}
+
+/// Set the archived status of a note
+#[tauri::command]
+pub async fn set_note_archived(
+    app: AppHandle,
+    path: String,
+    archived: bool,
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if !note_path.exists() {
+        return Err(CommandError::not_found(&path));
+    }
+
+    // Read current content
+    let content = fs::read_to_string(&note_path)?;
+
+    // Update frontmatter with archived status
+    let new_content = update_frontmatter_archived(&content, archived);
+
+    // Write the updated file
+    fs::write(&note_path, &new_content)?;
+
+    // Re-index the note
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&path)).await?;
+
+    // Return updated metadata
+    let metadata = fs::metadata(&note_path)?;
+    let modified_at = metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+
+    let created_at = note_created_at(&app, &path, &content, &metadata);
+
+    let title = extract_title(&new_content, &path);
+    let id = resolve_note_id(&app, &path);
+
+    // Get starred status from database
+    let starred = db::get_note_starred(&app, &id).unwrap_or(false);
+    let (outgoing_count, incoming_count) = db::get_note_link_counts(&app, &id).unwrap_or_default();
+
+    Ok(NoteMetadata {
+        id,
+        path,
+        title,
+        modified_at,
+        created_at,
+        archived,
+        starred,
+        outgoing_count,
+        incoming_count,
+    })
+}
+
+/// Set the starred status of a note
+#[tauri::command]
+pub async fn set_note_starred(
+    app: AppHandle,
+    path: String,
+    starred: bool,
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if !note_path.exists() {
+        return Err(CommandError::not_found(&path));
+    }
+
+    let id = resolve_note_id(&app, &path);
+
+    // Update starred in database only (not in frontmatter - it's UI state)
+    db::set_note_starred(&app, &id, starred)?;
+
+    // Read note for metadata
+    let content = fs::read_to_string(&note_path)?;
+    let metadata = fs::metadata(&note_path)?;
+
+    let modified_at = metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+
+    let created_at = note_created_at(&app, &path, &content, &metadata);
+
+    let title = extract_title(&content, &path);
+    let archived = extract_archived(&content);
+    let (outgoing_count, incoming_count) = db::get_note_link_counts(&app, &id).unwrap_or_default();
+
+    Ok(NoteMetadata {
+        id,
+        path,
+        title,
+        modified_at,
+        created_at,
+        archived,
+        starred,
+        outgoing_count,
+        incoming_count,
+    })
+}
+
+/// Line-level diff between two arbitrary notes. A path that doesn't exist is
+/// treated as an empty note, so this also shows a whole note as added or removed.
+#[tauri::command]
+pub fn diff_notes(
+    app: AppHandle,
+    path_a: String,
+    path_b: String,
+) -> Result<Vec<DiffHunk>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    let read_side = |path: &str| -> Result<String, CommandError> {
+        let note_path = validate_vault_path(&vault_path, path)?;
+        if !note_path.exists() {
+            return Ok(String::new());
+        }
+        db::decode_note_content(fs::read(&note_path)?)
+            .map_err(|e| CommandError::invalid(format!("Note is not valid UTF-8: {}", e)))
+    };
+
+    let old = read_side(&path_a)?;
+    let new = read_side(&path_b)?;
+
+    Ok(line_diff(&old, &new))
+}
+
+/// Record that a note was opened, for the recent notes jump list
+#[tauri::command]
+pub fn touch_note_opened(app: AppHandle, note_id: String) -> Result<(), CommandError> {
+    db::touch_note_opened(&app, &note_id)?;
+    Ok(())
+}
+
+/// Get the most recently opened notes, newest first
+#[tauri::command]
+pub fn get_recent_notes(
+    app: AppHandle,
+    limit: Option<u32>,
+) -> Result<Vec<NoteMetadata>, CommandError> {
+    db::get_recent_notes(&app, limit.unwrap_or(20)).map_err(CommandError::from)
+}
+
+/// Pin a note to the end of the pinned notes bar
+#[tauri::command]
+pub fn pin_note(app: AppHandle, note_id: String) -> Result<(), CommandError> {
+    db::pin_note(&app, &note_id)?;
+    Ok(())
+}
+
+/// Remove a note from the pinned notes bar
+#[tauri::command]
+pub fn unpin_note(app: AppHandle, note_id: String) -> Result<(), CommandError> {
+    db::unpin_note(&app, &note_id)?;
+    Ok(())
+}
+
+/// Set the order of the pinned notes bar
+#[tauri::command]
+pub fn reorder_pinned(app: AppHandle, ordered_ids: Vec<String>) -> Result<(), CommandError> {
+    db::reorder_pinned(&app, &ordered_ids)?;
+    Ok(())
+}
+
+/// Get pinned notes in their pinned order
+#[tauri::command]
+pub fn get_pinned_notes(app: AppHandle) -> Result<Vec<NoteMetadata>, CommandError> {
+    db::get_pinned_notes(&app).map_err(CommandError::from)
+}
+
+/// Add a colored label to a note, or recolor it if the note already has it
+#[tauri::command]
+pub fn set_note_label(
+    app: AppHandle,
+    note_id: String,
+    label: String,
+    color: Option<String>,
+) -> Result<(), CommandError> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(CommandError::invalid("Label cannot be empty"));
+    }
+    let color = color.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    db::set_note_label(&app, &note_id, label, color)?;
+    Ok(())
+}
+
+/// Remove a label from a note
+#[tauri::command]
+pub fn remove_note_label(
+    app: AppHandle,
+    note_id: String,
+    label: String,
+) -> Result<(), CommandError> {
+    db::remove_note_label(&app, &note_id, label.trim())?;
+    Ok(())
+}
+
+/// Get the labels of a note
+#[tauri::command]
+pub fn get_note_labels(
+    app: AppHandle,
+    note_id: String,
+) -> Result<Vec<db::NoteLabel>, CommandError> {
+    db::get_note_labels(&app, &note_id).map_err(CommandError::from)
+}
+
+/// Get labels for a list of notes (e.g. a listing or search results), keyed by note id
+#[tauri::command]
+pub fn get_note_labels_batch(
+    app: AppHandle,
+    note_ids: Vec<String>,
+) -> Result<HashMap<String, Vec<db::NoteLabel>>, CommandError> {
+    db::get_note_labels_batch(&app, &note_ids).map_err(CommandError::from)
+}
+
+/// Format a link to a note in the vault's configured link style, so links
+/// inserted by the editor match the ones the app writes
+#[tauri::command]
+pub fn format_link(
+    app: AppHandle,
+    target_path: String,
+    display: Option<String>,
+) -> Result<String, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let settings = read_vault_settings(&vault_path).unwrap_or_default();
+    Ok(settings.links.format_link(&target_path, display.as_deref()))
+}
+
+// Helper functions
+
+/// Write a file via a temporary sibling and rename, so readers and the file
+/// watcher never see a half-written note
+pub(crate) fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid file path")
+    })?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    fs::write(&temp_path, content)?;
+    if let Err(e) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Myers line diff grouped into unified-diff style hunks with 3 lines of context
+fn line_diff(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::configure()
+        .algorithm(Algorithm::Myers)
+        .diff_lines(old, new);
+
+    diff.grouped_ops(3)
+        .iter()
+        .map(|group| {
+            let mut lines = Vec::new();
+            for op in group {
+                for change in diff.iter_changes(op) {
+                    let origin = match change.tag() {
+                        ChangeTag::Equal => ' ',
+                        ChangeTag::Insert => '+',
+                        ChangeTag::Delete => '-',
+                    };
+                    lines.push(DiffLine {
+                        origin,
+                        content: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                        old_lineno: change.old_index().map(|i| i as u32 + 1),
+                        new_lineno: change.new_index().map(|i| i as u32 + 1),
+                    });
+                }
+            }
+
+            let old_range =
+                group.first().unwrap().old_range().start..group.last().unwrap().old_range().end;
+            let new_range =
+                group.first().unwrap().new_range().start..group.last().unwrap().new_range().end;
+            let old_lines = old_range.len() as u32;
+            let new_lines = new_range.len() as u32;
+            // Unified diff numbering: an empty range points at the line before it
+            let old_start = old_range.start as u32 + u32::from(old_lines > 0);
+            let new_start = new_range.start as u32 + u32::from(new_lines > 0);
+
+            DiffHunk {
+                header: format!(
+                    "@@ -{},{} +{},{} @@",
+                    old_start, old_lines, new_start, new_lines
+                ),
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                lines,
+            }
+        })
+        .collect()
+}
+
+fn extract_title(content: &str, path: &str) -> String {
+    // Try to extract title from first H1 heading
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(stripped) = trimmed.strip_prefix("# ") {
+            return stripped.trim().to_string();
+        }
+    }
+
+    // Fall back to filename without extension
+    PathBuf::from(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// When a note was created: its `created` frontmatter, then the indexed value,
+/// then filesystem metadata (which some Linux filesystems don't record)
+fn note_created_at(app: &AppHandle, path: &str, content: &str, metadata: &fs::Metadata) -> i64 {
+    let frontmatter_created =
+        frontmatter_value(content, "created").and_then(db::parse_created_timestamp);
+
+    frontmatter_created
+        .or_else(|| db::get_note_created_at(app, path))
+        .unwrap_or_else(|| {
+            metadata
+                .created()
+                .or_else(|_| metadata.modified())
+                .map(|t| {
+                    t.duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0)
+                })
+                .unwrap_or(0)
+        })
+}
+
+/// Id the index holds for a note path, falling back to the path hash for unindexed notes
+fn resolve_note_id(app: &AppHandle, path: &str) -> String {
+    db::get_note_id(app, path).unwrap_or_else(|| generate_note_id(path))
+}
+
+fn generate_note_id(path: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    let result = hasher.finalize();
+    hex::encode(&result[..8])
+}
+
+/// Extract archived status from content frontmatter
+fn extract_archived(content: &str) -> bool {
+    if !content.starts_with("---") {
+        return false;
+    }
+
+    let parts: Vec<&str> = content.splitn(3, "---").collect();
+    if parts.len() < 3 {
+        return false;
+    }
+
+    let yaml = parts[1].trim();
+    for line in yaml.lines() {
+        let line = line.trim();
+        if line.starts_with("archived:") {
+            let value = line.trim_start_matches("archived:").trim();
+            // Handle both `archived: true` and `archived: "true"`
+            return value == "true" || value == "\"true\"" || value == "'true'";
+        }
+    }
+
+    false
+}
+
+/// Update frontmatter with archived status
+fn update_frontmatter_archived(content: &str, archived: bool) -> String {
+    let archived_line = if archived {
+        "archived: true"
+    } else {
+        "archived: false"
+    };
+
+    if content.starts_with("---") {
+        let parts: Vec<&str> = content.splitn(3, "---").collect();
+        if parts.len() >= 3 {
+            let yaml = parts[1].trim();
+            let rest = parts[2];
+
+            // Check if archived field already exists
+            let mut new_yaml_lines: Vec<String> = Vec::new();
+            let mut found_archived = false;
+
+            for line in yaml.lines() {
+                let trimmed = line.trim();
+                if trimmed.starts_with("archived:") {
+                    new_yaml_lines.push(archived_line.to_string());
+                    found_archived = true;
+                } else {
+                    new_yaml_lines.push(line.to_string());
+                }
+            }
+
+            if !found_archived {
+                new_yaml_lines.push(archived_line.to_string());
+            }
+
+            return format!("---\n{}\n---{}", new_yaml_lines.join("\n"), rest);
+        }
+    }
+
+    // No frontmatter exists, create one
+    format!("---\n{}\n---\n\n{}", archived_line, content)
+}
+
+// ============================================================================
+// Note Templates
+// ============================================================================
+
+/// Values for the `{{name}}` placeholders a template can use
+fn template_variables(title: &str, date: chrono::NaiveDate) -> HashMap<&'static str, String> {
+    let now = chrono::Local::now();
+    HashMap::from([
+        ("title", title.to_string()),
+        ("date", date.format("%Y-%m-%d").to_string()),
+        ("time", now.format("%H:%M").to_string()),
+        (
+            "datetime",
+            now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        ),
+        ("year", date.format("%Y").to_string()),
+        ("month", date.format("%m").to_string()),
+        ("day", date.format("%d").to_string()),
+        ("weekday", date.format("%A").to_string()),
+        ("monthName", date.format("%B").to_string()),
+    ])
+}
+
+/// Replace `{{name}}` placeholders (inner whitespace allowed) using `lookup`;
+/// placeholders it doesn't know are left as written
+fn replace_placeholders(text: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let inner = &rest[start + 2..];
+        let Some(end) = inner.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        match lookup(inner[..end].trim()) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &inner[end + 2..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Substitute into every string scalar of a YAML value
+fn substitute_yaml_strings(value: Yaml, substitute: &impl Fn(&str) -> String) -> Yaml {
+    match value {
+        Yaml::String(s) => Yaml::String(substitute(&s)),
+        Yaml::Array(items) => Yaml::Array(
+            items
+                .into_iter()
+                .map(|item| substitute_yaml_strings(item, substitute))
+                .collect(),
+        ),
+        Yaml::Hash(map) => Yaml::Hash(
+            map.into_iter()
+                .map(|(k, v)| (k, substitute_yaml_strings(v, substitute)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Render template content for a new note. Placeholders are substituted in the
+/// body and in frontmatter values, and the frontmatter is re-serialized with
+/// `created` (unless the template sets it) and, under the frontmatter id
+/// scheme, a fresh `id`. Templates without frontmatter get a minimal block.
+fn render_template(
+    template: &str,
+    title: &str,
+    date: chrono::NaiveDate,
+    id_scheme: NoteIdScheme,
+) -> Result<String, CommandError> {
+    let vars = template_variables(title, date);
+    let (yaml, body) = split_frontmatter(template).unwrap_or(("", template));
+
+    // `{{title}}` isn't valid YAML on its own, so parse with stand-in tokens and
+    // substitute into the parsed strings, letting serialization handle quoting
+    let token = |name: &str| format!("__kairo_template_{}__", name);
+    let tokenized = replace_placeholders(yaml, |name| vars.get(name).map(|_| token(name)));
+    let parsed = YamlLoader::load_from_str(&tokenized)
+        .map_err(|e| CommandError::invalid(format!("Invalid template frontmatter: {}", e)))?
+        .into_iter()
+        .next()
+        .unwrap_or(Yaml::Null);
+    let mut frontmatter = match parsed {
+        Yaml::Hash(map) => map,
+        Yaml::Null => Hash::new(),
+        _ => {
+            return Err(CommandError::invalid(
+                "Invalid template frontmatter: expected key/value pairs",
+            ))
+        }
+    };
+
+    let detokenize = |s: &str| {
+        vars.iter().fold(s.to_string(), |s, (name, value)| {
+            s.replace(&token(name), value)
+        })
+    };
+    frontmatter = match substitute_yaml_strings(Yaml::Hash(frontmatter), &detokenize) {
+        Yaml::Hash(map) => map,
+        _ => Hash::new(),
+    };
+
+    // A template's own id would be shared by every note made from it
+    let id_key = Yaml::String("id".to_string());
+    frontmatter.remove(&id_key);
+    if id_scheme == NoteIdScheme::Frontmatter {
+        frontmatter.insert(id_key, Yaml::String(uuid::Uuid::new_v4().to_string()));
+    }
+    let created_key = Yaml::String("created".to_string());
+    if !frontmatter.contains_key(&created_key) {
+        let now = chrono::Utc::now().timestamp();
+        frontmatter.insert(created_key, Yaml::String(db::format_created(now)));
+    }
+
+    let yaml = emit_yaml(&Yaml::Hash(frontmatter)).map_err(CommandError::invalid)?;
+    let body = replace_placeholders(body, |name| vars.get(name).cloned());
+
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+/// Content for a brand-new note at `path` from the template its folder is
+/// mapped to in the vault's folder template settings, or None if there is none
+pub(crate) fn render_folder_template(
+    vault_path: &Path,
+    path: &str,
+) -> Result<Option<String>, CommandError> {
+    let settings = read_vault_settings(vault_path).unwrap_or_default();
+    let Some(template) = settings.folder_templates.template_for(path) else {
+        return Ok(None);
+    };
+
+    let template_path = validate_vault_path(vault_path, template)?;
+    let template =
+        fs::read_to_string(&template_path).map_err(|_| CommandError::not_found(template))?;
+    let title = Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+
+    render_template(&template, &title, today, settings.note_id_scheme).map(Some)
+}
+
+/// Create a note at `path` from template content. `title` defaults to the file name.
+#[tauri::command]
+pub async fn create_note_from_template(
+    app: AppHandle,
+    path: String,
+    template: String,
+    title: Option<String>,
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if note_path.exists() {
+        return Err(CommandError::conflict(&path));
+    }
+
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| {
+            Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+    let scheme = read_vault_settings(&vault_path)
+        .unwrap_or_default()
+        .note_id_scheme;
+    let today = chrono::Local::now().date_naive();
+
+    let content = render_template(&template, &title, today, scheme)?;
+    Ok(write_note(app, path, content, true).await?.metadata)
+}
+
+// ============================================================================
+// Periodic Notes
+// ============================================================================
+
+/// Length of the period a periodic note covers
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PeriodKind {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl PeriodKind {
+    /// Default (folder, filename format) for notes of this kind
+    fn defaults(self) -> (&'static str, &'static str) {
+        match self {
+            PeriodKind::Daily => ("notes/daily", "%Y-%m-%d"),
+            PeriodKind::Weekly => ("notes/weekly", "%G-W%V"),
+            PeriodKind::Monthly => ("notes/monthly", "%Y-%m"),
+            PeriodKind::Quarterly => ("notes/quarterly", "%Y-Q%q"),
+            PeriodKind::Yearly => ("notes/yearly", "%Y"),
+        }
+    }
+
+    /// First day of the period containing `date` (ISO weeks start on Monday)
+    fn period_start(self, date: chrono::NaiveDate) -> chrono::NaiveDate {
+        use chrono::Datelike;
+        let start = match self {
+            PeriodKind::Daily => Some(date),
+            PeriodKind::Weekly => date.checked_sub_days(chrono::Days::new(
+                date.weekday().num_days_from_monday() as u64,
+            )),
+            PeriodKind::Monthly => date.with_day(1),
+            PeriodKind::Quarterly => {
+                chrono::NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1)
+            }
+            PeriodKind::Yearly => chrono::NaiveDate::from_ymd_opt(date.year(), 1, 1),
+        };
+        start.unwrap_or(date)
+    }
+}
+
+/// A periodic note and whether this call created it
+#[derive(Debug, Serialize)]
+pub struct PeriodicNote {
+    pub note: NoteMetadata,
+    pub created: bool,
+}
+
+/// Format a period's start date into a note filename. Supports chrono's
+/// strftime specifiers plus `%q` for the quarter.
+fn format_period_name(format: &str, start: chrono::NaiveDate) -> Result<String, CommandError> {
+    use chrono::Datelike;
+    use std::fmt::Write;
+
+    let format = format.replace("%q", &(start.month0() / 3 + 1).to_string());
+    let mut name = String::new();
+    write!(name, "{}", start.format(&format))
+        .map_err(|_| CommandError::invalid(format!("Invalid periodic note format: {}", format)))?;
+    Ok(name)
+}
+
+/// Open the periodic note of `kind` covering `date` (YYYY-MM-DD, default
+/// today), creating it from the configured template if it doesn't exist
+#[tauri::command]
+pub async fn get_or_create_periodic_note(
+    app: AppHandle,
+    kind: PeriodKind,
+    date: Option<String>,
+) -> Result<PeriodicNote, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| CommandError::invalid(format!("Invalid date: {}", date)))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let start = kind.period_start(date);
+
+    let all = read_vault_settings(&vault_path)
+        .unwrap_or_default()
+        .periodic_notes;
+    let config = match kind {
+        PeriodKind::Daily => all.daily,
+        PeriodKind::Weekly => all.weekly,
+        PeriodKind::Monthly => all.monthly,
+        PeriodKind::Quarterly => all.quarterly,
+        PeriodKind::Yearly => all.yearly,
+    };
+    let (default_folder, default_format) = kind.defaults();
+    let folder = match config.folder.trim().trim_matches('/') {
+        "" => default_folder,
+        folder => folder,
+    };
+    let format = match config.format.trim() {
+        "" => default_format,
+        format => format,
+    };
+
+    let name = format_period_name(format, start)?;
+    let path = format!("{}/{}.md", folder, name);
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if note_path.exists() {
+        let content = fs::read_to_string(&note_path)?;
+        let note = note_metadata(&app, path, &content, &note_path)?;
+        return Ok(PeriodicNote {
+            note,
+            created: false,
+        });
+    }
+
+    let title = Path::new(&name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(name);
+    let content = match config.template.as_deref().filter(|t| !t.trim().is_empty()) {
+        Some(template) => {
+            let template_path = validate_vault_path(&vault_path, template)?;
+            let template = fs::read_to_string(&template_path)
+                .map_err(|_| CommandError::not_found(template))?;
+            let scheme = read_vault_settings(&vault_path)
+                .unwrap_or_default()
+                .note_id_scheme;
+            render_template(&template, &title, start, scheme)?
+        }
+        None => format!("# {}\n\n", title),
+    };
+
+    let note = write_note(app, path, content, true).await?.metadata;
+    Ok(PeriodicNote {
+        note,
+        created: true,
+    })
+}
+
+// ============================================================================
+// Frontmatter Normalization
+// ============================================================================
+
+/// Split content into (frontmatter YAML, body). The frontmatter must open on the
+/// first line with `---` and close with a line containing only `---`.
+pub(crate) fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\r\n")
+        .or_else(|| content.strip_prefix("---\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Value of a root-level frontmatter scalar, unquoted; None if missing or empty
+pub(crate) fn frontmatter_value<'a>(content: &'a str, key: &str) -> Option<&'a str> {
+    let (yaml, _) = split_frontmatter(content)?;
+    yaml.lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+        .map(|value| value.trim().trim_matches('"').trim_matches('\''))
+        .filter(|value| !value.is_empty())
+}
+
+/// Set a root-level frontmatter field, replacing any existing value (including
+/// indented continuation lines) or appending it. Creates frontmatter if missing.
+/// `value` must already be valid YAML.
+pub(crate) fn set_frontmatter_field(content: &str, key: &str, value: &str) -> String {
+    let field_line = format!("{}: {}", key, value);
+
+    let Some((yaml, body)) = split_frontmatter(content) else {
+        return format!("---\n{}\n---\n\n{}", field_line, content);
+    };
+
+    let prefix = format!("{}:", key);
+    let mut lines: Vec<String> = Vec::new();
+    let mut found = false;
+    let mut skipping = false;
+
+    for line in yaml.lines() {
+        if skipping {
+            if line.starts_with(' ') || line.starts_with('\t') || line.starts_with("- ") {
+                continue;
+            }
+            skipping = false;
+        }
+
+        if !found && line.starts_with(&prefix) {
+            lines.push(field_line.clone());
+            found = true;
+            skipping = true;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+
+    if !found {
+        lines.push(field_line);
+    }
+
+    format!("---\n{}\n---\n{}", lines.join("\n"), body)
+}
+
+/// Serialize a YAML value as block-style frontmatter lines, ending in a newline
+pub(crate) fn emit_yaml(value: &Yaml) -> Result<String, String> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(value)
+        .map_err(|e| format!("Failed to write frontmatter: {:?}", e))?;
+    // The emitter opens every document with its own `---` line
+    let mut yaml = out
+        .strip_prefix("---\n")
+        .or_else(|| out.strip_prefix("---"))
+        .unwrap_or(&out)
+        .trim_start_matches(' ')
+        .to_string();
+    yaml.push('\n');
+    Ok(yaml)
+}
+
+/// Recursively sort mapping keys so the output is stable
+fn sort_yaml_value(value: Yaml) -> Yaml {
+    match value {
+        Yaml::Hash(map) => {
+            let mut entries: Vec<(Yaml, Yaml)> = map.into_iter().collect();
+            entries.sort_by_cached_key(|(k, _)| yaml_key_string(k));
+            Yaml::Hash(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_yaml_value(v)))
+                    .collect(),
+            )
+        }
+        Yaml::Array(items) => Yaml::Array(items.into_iter().map(sort_yaml_value).collect()),
+        other => other,
+    }
+}
+
+fn yaml_key_string(key: &Yaml) -> String {
+    match key {
+        Yaml::String(s) => s.clone(),
+        other => emit_yaml(other).unwrap_or_default().trim().to_string(),
+    }
+}
+
+/// Rewrite frontmatter in canonical sorted form, leaving the body untouched.
+/// Returns an error (with the line number in the file) if the YAML is invalid.
+pub(crate) fn normalize_frontmatter_content(content: &str) -> Result<String, String> {
+    let Some((yaml, body)) = split_frontmatter(content) else {
+        return Ok(content.to_string());
+    };
+
+    if yaml.trim().is_empty() {
+        return Ok(content.to_string());
+    }
+
+    let value = YamlLoader::load_from_str(yaml)
+        .map_err(|e| {
+            // +1 for the opening `---` line
+            format!(
+                "Invalid frontmatter at line {}: {}",
+                e.marker().line() + 1,
+                e.info()
+            )
+        })?
+        .into_iter()
+        .next()
+        .unwrap_or(Yaml::Null);
+
+    if value.as_hash().is_none() {
+        return Err("Invalid frontmatter: expected key/value pairs".to_string());
+    }
+
+    let yaml = emit_yaml(&sort_yaml_value(value))?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+/// Validate a note's frontmatter and rewrite it in canonical form.
+/// Returns whether the file was changed.
+#[tauri::command]
+pub async fn normalize_frontmatter(app: AppHandle, path: String) -> Result<bool, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if !note_path.exists() {
+        return Err(CommandError::not_found(&path));
+    }
+
+    let content = fs::read_to_string(&note_path)?;
+    let normalized = normalize_frontmatter_content(&content)?;
+
+    if normalized == content {
+        return Ok(false);
+    }
+
+    fs::write(&note_path, &normalized)?;
+
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&path)).await?;
+
+    Ok(true)
+}
+
+// ============================================================================
+// Bulk Frontmatter
+// ============================================================================
+
+/// Most search matches a bulk edit will touch
+const BULK_QUERY_LIMIT: usize = 10_000;
+
+/// Which notes a bulk operation applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NoteFilter {
+    /// Notes under this vault-relative folder
+    Folder { path: String },
+    /// Notes with this tag (with or without the leading `#`)
+    Tag { tag: String },
+    /// Notes matching a full-text search query, archived notes included
+    Query { query: String },
+}
+
+/// Outcome of a bulk frontmatter edit
+#[derive(Debug, Serialize)]
+pub struct BulkFrontmatterResult {
+    pub matched: usize,
+    pub updated: usize,
+    /// Notes that already had the value
+    pub unchanged: usize,
+    pub failed: Vec<db::FailedFile>,
+}
+
+/// Vault-relative paths of the notes matching a filter
+fn filter_note_paths(app: &AppHandle, filter: &NoteFilter) -> Result<Vec<String>, CommandError> {
+    let paths = match filter {
+        NoteFilter::Folder { path } => {
+            let folder = path.trim_matches('/');
+            if folder.is_empty() {
+                return Err(CommandError::invalid("Folder cannot be empty"));
+            }
+            db::get_notes_by_folder(app, &format!("{}/", folder))?
+                .into_iter()
+                .map(|note| note.path)
+                .collect()
+        }
+        NoteFilter::Tag { tag } => {
+            let tag = tag.trim().trim_start_matches('#');
+            db::get_tag_notes(app)?.remove(tag).unwrap_or_default()
+        }
+        NoteFilter::Query { query } => {
+            let filters = SearchFilters {
+                include_archived: Some(true),
+                ..Default::default()
+            };
+            db::search_notes(app, query, Some(&filters), BULK_QUERY_LIMIT, None, 1, None)?
+                .into_iter()
+                .map(|result| result.path)
+                .collect()
+        }
+    };
+    Ok(paths)
+}
+
+/// Set a frontmatter field on every note matching `filter`, saving each through
+/// `write_note`. Encrypted notes and notes that fail are reported rather than
+/// stopping the run.
+#[tauri::command]
+pub async fn bulk_set_frontmatter(
+    app: AppHandle,
+    filter: NoteFilter,
+    key: String,
+    value: serde_json::Value,
+) -> Result<BulkFrontmatterResult, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    let key = key.trim();
+    if key.is_empty() || key.contains([':', '\n', '\r', '#']) || key.starts_with('-') {
+        return Err(CommandError::invalid(format!(
+            "Invalid frontmatter key: {}",
+            key
+        )));
+    }
+
+    // Scalars as plain YAML; lists and maps as JSON, which is valid inline YAML
+    let value = match &value {
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            serde_json::to_string(&value)?
+        }
+        scalar => {
+            let scalar = match scalar {
+                serde_json::Value::Bool(b) => Yaml::Boolean(*b),
+                serde_json::Value::Number(n) => match n.as_i64() {
+                    Some(i) => Yaml::Integer(i),
+                    None => Yaml::Real(n.to_string()),
+                },
+                serde_json::Value::String(s) => Yaml::String(s.clone()),
+                _ => Yaml::Null,
+            };
+            emit_yaml(&scalar)
+                .map_err(CommandError::invalid)?
+                .trim_end()
+                .to_string()
+        }
+    };
+
+    let paths = filter_note_paths(&app, &filter)?;
+    let mut result = BulkFrontmatterResult {
+        matched: paths.len(),
+        updated: 0,
+        unchanged: 0,
+        failed: Vec::new(),
+    };
+
+    for path in paths {
+        let outcome: Result<bool, CommandError> = async {
+            let note_path = validate_vault_path(&vault_path, &path)?;
+            let content = fs::read_to_string(&note_path)?;
+            if crypto::is_encrypted(&content) {
+                return Err(CommandError::invalid("Note is encrypted"));
+            }
+
+            let updated = set_frontmatter_field(&content, key, &value);
+            if updated == content {
+                return Ok(false);
+            }
+
+            // Saved like an edit: versioned, normalized, written atomically and reindexed
+            write_note(app.clone(), path.clone(), updated, false).await?;
+            Ok(true)
+        }
+        .await;
+
+        match outcome {
+            Ok(true) => result.updated += 1,
+            Ok(false) => result.unchanged += 1,
+            Err(e) => result.failed.push(db::FailedFile {
+                path,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(result)
+}
+
+// ============================================================================
+// Tasks
+// ============================================================================
+
+/// Check or uncheck the task on a 1-based line of a note, so rendered task
+/// lists can be ticked off without opening the editor. Returns the new state.
+#[tauri::command]
+pub async fn toggle_task(
+    app: AppHandle,
+    note_path: String,
+    line_number: i32,
+) -> Result<bool, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let full_path = validate_vault_path(&vault_path, &note_path)?;
+
+    if !full_path.exists() {
+        return Err(CommandError::not_found(&note_path));
+    }
+
+    let content = fs::read_to_string(&full_path)?;
+
+    // Only lines the indexer treats as tasks, so checkboxes inside code fences are left alone
+    let (_, _, completed, _) = db::extract_tasks(&content)
+        .into_iter()
+        .find(|(line, ..)| *line == line_number)
+        .ok_or_else(|| CommandError::invalid(format!("Line {} is not a task", line_number)))?;
+
+    let mut updated = String::with_capacity(content.len());
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        if i as i32 + 1 != line_number {
+            updated.push_str(line);
+            continue;
+        }
+        let body = line.trim_end_matches(['\r', '\n']);
+        let mark = db::TASK_REGEX
+            .captures(body)
+            .and_then(|cap| cap.get(2))
+            .ok_or_else(|| CommandError::invalid(format!("Line {} is not a task", line_number)))?;
+        updated.push_str(&line[..mark.start()]);
+        updated.push(if completed { ' ' } else { 'x' });
+        updated.push_str(&line[mark.end()..]);
+    }
+
+    write_atomic(&full_path, &updated)?;
+
+    // Re-indexing rewrites the note's rows in the tasks table
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&note_path)).await?;
+
+    Ok(!completed)
+}
+
+// ============================================================================
+// Note Splitting
+// ============================================================================
+
+/// Characters that can't appear in a note file name on some platform, or that
+/// would break a wiki link to it
+const INVALID_NAME_CHARS: &[char] = &[
+    '/', '\\', ':', '*', '?', '"', '<', '>', '|', '#', '^', '[', ']',
+];
+
+/// Level and text of an ATX heading line (`## Text`)
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_end_matches(['\r', '\n']).trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) || !line[level..].starts_with([' ', '\t']) {
+        return None;
+    }
+    // Closing hashes are decoration: `## Text ##`
+    let text = line[level..].trim().trim_end_matches('#').trim_end();
+    Some((level, text))
+}
+
+/// Headings of a note as (0-based line, level, text), skipping frontmatter
+/// and fenced code
+fn note_headings(lines: &[&str], frontmatter_lines: usize) -> Vec<(usize, usize, String)> {
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in lines.iter().enumerate().skip(frontmatter_lines) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some((level, text)) = parse_heading(line) {
+            headings.push((i, level, text.to_string()));
+        }
+    }
+    headings
+}
+
+/// Split sections out of a note into notes of their own. `split_points` are
+/// 1-based heading lines; each section runs from its heading to the next
+/// heading of the same or higher level. The new notes are created next to the
+/// original, named from their headings, and each section is replaced with a
+/// link to its note, or a `![[...]]` transclusion when `transclude` is set.
+/// Returns the created notes.
+#[tauri::command]
+pub async fn split_note(
+    app: AppHandle,
+    path: String,
+    split_points: Vec<usize>,
+    transclude: Option<bool>,
+) -> Result<Vec<NoteMetadata>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let note_path = validate_vault_path(&vault_path, &path)?;
+
+    if !note_path.exists() {
+        return Err(CommandError::not_found(&path));
+    }
+    if split_points.is_empty() {
+        return Err(CommandError::invalid("No split points given"));
     }
 
-    if new_note_path.exists() {
-        return Err(format!("Note already exists at: {}", new_path));
+    let content = fs::read_to_string(&note_path)?;
+    if crypto::is_encrypted(&content) {
+        return Err(CommandError::invalid("Encrypted notes can't be split"));
     }
-
-    // Ensure parent directory exists
-    if let Some(parent) = new_note_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let frontmatter_lines = split_frontmatter(&content)
+        .map(|(_, body)| content[..content.len() - body.len()].matches('\n').count())
+        .unwrap_or(0);
+    let headings = note_headings(&lines, frontmatter_lines);
+
+    let mut split_points = split_points;
+    split_points.sort_unstable();
+    split_points.dedup();
+
+    // Resolve each split point to the line range of its section
+    let mut sections: Vec<(usize, usize, String)> = Vec::new();
+    for line_number in split_points {
+        let position = headings
+            .iter()
+            .position(|(line, ..)| *line + 1 == line_number)
+            .ok_or_else(|| {
+                CommandError::invalid(format!("Line {} is not a heading", line_number))
+            })?;
+        let (start, level, ref title) = headings[position];
+        let end = headings[position + 1..]
+            .iter()
+            .find(|(_, l, _)| *l <= level)
+            .map(|(line, ..)| *line)
+            .unwrap_or(lines.len());
+
+        if let Some((prev_start, prev_end, _)) = sections.last() {
+            if start < *prev_end {
+                return Err(CommandError::invalid(format!(
+                    "Line {} is inside the section split at line {}",
+                    line_number,
+                    prev_start + 1
+                )));
+            }
+        }
+        sections.push((start, end, title.clone()));
     }
 
-    // Move the file
-    fs::rename(&old_note_path, &new_note_path).map_err(|e| e.to_string())?;
-
-    // Update index
-    db::remove_note_from_index(&app, &old_path).map_err(|e| e.to_string())?;
-    db::index_single_note(&app, &vault_path, &PathBuf::from(&new_path))
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Read the note to return metadata
-    let content = fs::read_to_string(&new_note_path).map_err(|e| e.to_string())?;
-    let metadata = fs::metadata(&new_note_path).map_err(|e| e.to_string())?;
+    let settings = read_vault_settings(&vault_path).unwrap_or_default();
+    let link_settings = if transclude.unwrap_or(false) {
+        // Transclusion only exists as wiki syntax
+        LinkSettings {
+            style: LinkStyle::Wiki,
+            ..settings.links.clone()
+        }
+    } else {
+        settings.links.clone()
+    };
+    let folder = Path::new(&path)
+        .parent()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("{}/", p))
+        .unwrap_or_default();
 
-    let modified_at = metadata
-        .modified()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(0);
+    // Plan every new note before writing anything, so a bad heading leaves the vault untouched
+    let mut new_notes: Vec<(String, String)> = Vec::new();
+    let mut updated = String::with_capacity(content.len());
+    let mut next_line = 0;
+    for (start, end, title) in &sections {
+        let name: String = title
+            .chars()
+            .filter(|c| !INVALID_NAME_CHARS.contains(c) && !c.is_control())
+            .collect();
+        let name = name.trim().trim_start_matches('.').trim();
+        if name.is_empty() {
+            return Err(CommandError::invalid(format!(
+                "Heading on line {} can't be used as a note name",
+                start + 1
+            )));
+        }
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+        let mut new_path = format!("{}{}.md", folder, name);
+        let mut suffix = 2;
+        while vault_path.join(&new_path).exists()
+            || new_notes
+                .iter()
+                .any(|(p, _)| p.eq_ignore_ascii_case(&new_path))
+        {
+            new_path = format!("{}{} {}.md", folder, name, suffix);
+            suffix += 1;
+        }
+        validate_vault_path(&vault_path, &new_path)?;
+
+        // The heading becomes the new note's title; the body keeps its own subheadings
+        let body = lines[start + 1..*end].concat();
+        let body = body.trim_matches(['\r', '\n']);
+        let mut note_content = format!("# {}{}", title, newline);
+        if !body.is_empty() {
+            note_content.push_str(newline);
+            note_content.push_str(body);
+            note_content.push_str(newline);
+        }
 
-    let title = extract_title(&content, &new_path);
-    let id = generate_note_id(&new_path);
-    let archived = extract_archived(&content);
-    let starred = db::get_note_starred(&app, &id).unwrap_or(false);
+        let mut link = link_settings.format_link(&new_path, None);
+        if transclude.unwrap_or(false) {
+            link.insert(0, '!');
+        }
+        updated.push_str(&lines[next_line..*start].concat());
+        updated.push_str(&link);
+        updated.push_str(newline);
+        if *end < lines.len() {
+            updated.push_str(newline);
+        }
+        next_line = *end;
 
-    Ok(NoteMetadata {
-        id,
-        path: new_path,
-        title,
-        modified_at,
-        created_at,
-        archived,
-        starred,
-    })
-}
+        new_notes.push((new_path, note_content));
+    }
+    updated.push_str(&lines[next_line..].concat());
+
+    // New notes are written first and the original only once they all exist, so
+    // a failure part way never leaves a section both split out and still in place
+    let mut written: Vec<PathBuf> = Vec::with_capacity(new_notes.len());
+    let remove_written = |written: &[PathBuf]| {
+        for full_path in written {
+            let _ = fs::remove_file(full_path);
+        }
+    };
+    for (new_path, note_content) in &new_notes {
+        let full_path = vault_path.join(new_path);
+        if let Err(e) = write_atomic(&full_path, note_content) {
+            remove_written(&written);
+            return Err(e.into());
+        }
+        written.push(full_path);
+    }
 
-/// Create a folder
-#[tauri::command]
-pub fn create_folder(app: AppHandle, path: String) -> Result<(), String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
-    let folder_path = validate_vault_path(&vault_path, &path)?;
+    let note_id = resolve_note_id(&app, &path);
+    let _ = db::create_note_version(&app, &note_id, &content, "split", None);
+    if let Err(e) = write_atomic(&note_path, &updated) {
+        remove_written(&written);
+        return Err(e.into());
+    }
 
-    fs::create_dir_all(&folder_path).map_err(|e| e.to_string())?;
+    // Indexed in the same order, so the original's links resolve
+    let mut created = Vec::with_capacity(new_notes.len());
+    for ((new_path, note_content), full_path) in new_notes.into_iter().zip(&written) {
+        db::index_single_note(&app, &vault_path, Path::new(&new_path)).await?;
+        created.push(note_metadata(&app, new_path, &note_content, full_path)?);
+    }
+    db::index_single_note(&app, &vault_path, Path::new(&path)).await?;
 
-    Ok(())
+    Ok(created)
 }
 
-/// Set the archived status of a note
+// ============================================================================
+// Note Encryption
+// ============================================================================
+
+/// Encrypt a note on disk with a passphrase. Only the title stays searchable.
+/// Stored versions of the note are deleted since they hold the plaintext.
 #[tauri::command]
-pub async fn set_note_archived(
+pub async fn encrypt_note(
     app: AppHandle,
     path: String,
-    archived: bool,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    passphrase: String,
+) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
-    // Read current content
-    let content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
+    if passphrase.is_empty() {
+        return Err(CommandError::invalid("Passphrase cannot be empty"));
+    }
 
-    // Update frontmatter with archived status
-    let new_content = update_frontmatter_archived(&content, archived);
+    let content = fs::read_to_string(&note_path)?;
+    if crypto::is_encrypted(&content) {
+        return Err(CommandError::invalid("Note is already encrypted"));
+    }
 
-    // Write the updated file
-    fs::write(&note_path, &new_content).map_err(|e| e.to_string())?;
+    let title = extract_title(&content, &path);
+    let envelope = crypto::encrypt_note_content(&content, &title, &passphrase)?;
+    fs::write(&note_path, envelope)?;
+
+    db::delete_note_versions(&app, &resolve_note_id(&app, &path))?;
 
-    // Re-index the note
     db::index_single_note(&app, &vault_path, &PathBuf::from(&path))
         .await
-        .map_err(|e| e.to_string())?;
-
-    // Return updated metadata
-    let metadata = fs::metadata(&note_path).map_err(|e| e.to_string())?;
-    let modified_at = metadata
-        .modified()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(0);
-
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
-
-    let title = extract_title(&new_content, &path);
-    let id = generate_note_id(&path);
-
-    // Get starred status from database
-    let starred = db::get_note_starred(&app, &id).unwrap_or(false);
-
-    Ok(NoteMetadata {
-        id,
-        path,
-        title,
-        modified_at,
-        created_at,
-        archived,
-        starred,
-    })
+        .map_err(CommandError::from)
 }
 
-/// Set the starred status of a note
+/// Decrypt an encrypted note back to plaintext on disk
 #[tauri::command]
-pub async fn set_note_starred(
+pub async fn decrypt_note(
     app: AppHandle,
     path: String,
-    starred: bool,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    passphrase: String,
+) -> Result<Note, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
-    let id = generate_note_id(&path);
+    let envelope = fs::read_to_string(&note_path)?;
+    if !crypto::is_encrypted(&envelope) {
+        return Err(CommandError::invalid("Note is not encrypted"));
+    }
 
-    // Update starred in database only (not in frontmatter - it's UI state)
-    db::set_note_starred(&app, &id, starred).map_err(|e| e.to_string())?;
+    let content = crypto::decrypt_note_content(&envelope, &passphrase)?;
+    fs::write(&note_path, &content)?;
 
-    // Read note for metadata
-    let content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
-    let metadata = fs::metadata(&note_path).map_err(|e| e.to_string())?;
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&path)).await?;
 
+    let metadata = fs::metadata(&note_path)?;
     let modified_at = metadata
         .modified()
         .map(|t| {
@@ -405,119 +2184,19 @@ pub async fn set_note_starred(
         })
         .unwrap_or(0);
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
-
-    let title = extract_title(&content, &path);
-    let archived = extract_archived(&content);
+    let created_at = note_created_at(&app, &path, &content, &metadata);
 
-    Ok(NoteMetadata {
-        id,
+    Ok(Note {
+        id: resolve_note_id(&app, &path),
+        title: extract_title(&content, &path),
         path,
-        title,
+        content,
         modified_at,
         created_at,
-        archived,
-        starred,
+        encrypted: false,
     })
 }
 
-// Helper functions
-
-fn extract_title(content: &str, path: &str) -> String {
-    // Try to extract title from first H1 heading
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if let Some(stripped) = trimmed.strip_prefix("# ") {
-            return stripped.trim().to_string();
-        }
-    }
-
-    // Fall back to filename without extension
-    PathBuf::from(path)
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| path.to_string())
-}
-
-fn generate_note_id(path: &str) -> String {
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    hasher.update(path.as_bytes());
-    let result = hasher.finalize();
-    hex::encode(&result[..8])
-}
-
-/// Extract archived status from content frontmatter
-fn extract_archived(content: &str) -> bool {
-    if !content.starts_with("---") {
-        return false;
-    }
-
-    let parts: Vec<&str> = content.splitn(3, "---").collect();
-    if parts.len() < 3 {
-        return false;
-    }
-
-    let yaml = parts[1].trim();
-    for line in yaml.lines() {
-        let line = line.trim();
-        if line.starts_with("archived:") {
-            let value = line.trim_start_matches("archived:").trim();
-            // Handle both `archived: true` and `archived: "true"`
-            return value == "true" || value == "\"true\"" || value == "'true'";
-        }
-    }
-
-    false
-}
-
-/// Update frontmatter with archived status
-fn update_frontmatter_archived(content: &str, archived: bool) -> String {
-    let archived_line = if archived {
-        "archived: true"
-    } else {
-        "archived: false"
-    };
-
-    if content.starts_with("---") {
-        let parts: Vec<&str> = content.splitn(3, "---").collect();
-        if parts.len() >= 3 {
-            let yaml = parts[1].trim();
-            let rest = parts[2];
-
-            // Check if archived field already exists
-            let mut new_yaml_lines: Vec<String> = Vec::new();
-            let mut found_archived = false;
-
-            for line in yaml.lines() {
-                let trimmed = line.trim();
-                if trimmed.starts_with("archived:") {
-                    new_yaml_lines.push(archived_line.to_string());
-                    found_archived = true;
-                } else {
-                    new_yaml_lines.push(line.to_string());
-                }
-            }
-
-            if !found_archived {
-                new_yaml_lines.push(archived_line.to_string());
-            }
-
-            return format!("---\n{}\n---{}", new_yaml_lines.join("\n"), rest);
-        }
-    }
-
-    // No frontmatter exists, create one
-    format!("---\n{}\n---\n\n{}", archived_line, content)
-}
-
 // ============================================================================
 // Transclusion Commands
 // ============================================================================
@@ -529,6 +2208,8 @@ pub struct TranscludedNote {
     pub title: String,
     pub path: String,
     pub exists: bool,
+    /// True if the note is encrypted; `content` is then empty
+    pub encrypted: bool,
 }
 
 /// Result type for block content
@@ -537,6 +2218,8 @@ pub struct BlockContent {
     pub content: String,
     pub line_number: i32,
     pub exists: bool,
+    /// True if the note is encrypted; `content` is then empty
+    pub encrypted: bool,
 }
 
 /// Block info for autocomplete
@@ -552,8 +2235,8 @@ pub struct BlockInfo {
 pub fn get_note_content_for_transclusion(
     app: AppHandle,
     path: String,
-) -> Result<TranscludedNote, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<TranscludedNote, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
 
     // Try to resolve the path - it might be a title or partial path
     let resolved_path = resolve_note_path(&app, &vault_path, &path)?;
@@ -562,7 +2245,19 @@ pub fn get_note_content_for_transclusion(
         let note_path = validate_vault_path(&vault_path, &note_path_str)?;
 
         if note_path.exists() {
-            let content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
+            let content = fs::read_to_string(&note_path)?;
+
+            // Only the title of an encrypted note is shown, never its ciphertext
+            if let Some(title) = crypto::envelope_title(&content) {
+                return Ok(TranscludedNote {
+                    content: String::new(),
+                    title,
+                    path: note_path_str,
+                    exists: true,
+                    encrypted: true,
+                });
+            }
+
             let title = extract_title(&content, &note_path_str);
             let stripped_content = strip_frontmatter_and_title(&content);
 
@@ -571,6 +2266,7 @@ pub fn get_note_content_for_transclusion(
                 title,
                 path: note_path_str,
                 exists: true,
+                encrypted: false,
             });
         }
     }
@@ -581,6 +2277,7 @@ pub fn get_note_content_for_transclusion(
         title: String::new(),
         path,
         exists: false,
+        encrypted: false,
     })
 }
 
@@ -590,13 +2287,24 @@ pub fn get_block_content(
     app: AppHandle,
     note_path: String,
     block_id: String,
-) -> Result<BlockContent, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<BlockContent, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
 
     // Resolve the note path
     let resolved_path = resolve_note_path(&app, &vault_path, &note_path)?;
 
     if let Some(note_path_str) = resolved_path {
+        // Blocks of an encrypted note can't be shown until it is decrypted
+        let full_path = validate_vault_path(&vault_path, &note_path_str)?;
+        if fs::read_to_string(&full_path).is_ok_and(|content| crypto::is_encrypted(&content)) {
+            return Ok(BlockContent {
+                content: String::new(),
+                line_number: 0,
+                exists: true,
+                encrypted: true,
+            });
+        }
+
         // Look up block in database
         let result = db::with_db(&app, |conn| {
             // First get the note ID
@@ -619,6 +2327,7 @@ pub fn get_block_content(
                         content,
                         line_number,
                         exists: true,
+                        encrypted: false,
                     });
                 }
             }
@@ -627,23 +2336,28 @@ pub fn get_block_content(
                 content: String::new(),
                 line_number: 0,
                 exists: false,
+                encrypted: false,
             })
         });
 
-        return result.map_err(|e| e.to_string());
+        return result.map_err(CommandError::from);
     }
 
     Ok(BlockContent {
         content: String::new(),
         line_number: 0,
         exists: false,
+        encrypted: false,
     })
 }
 
 /// List all block IDs in a note (for autocomplete)
 #[tauri::command]
-pub fn list_blocks_for_note(app: AppHandle, note_path: String) -> Result<Vec<BlockInfo>, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub fn list_blocks_for_note(
+    app: AppHandle,
+    note_path: String,
+) -> Result<Vec<BlockInfo>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
 
     // Resolve the note path
     let resolved_path = resolve_note_path(&app, &vault_path, &note_path)?;
@@ -679,7 +2393,7 @@ pub fn list_blocks_for_note(app: AppHandle, note_path: String) -> Result<Vec<Blo
             Ok(Vec::new())
         });
 
-        return result.map_err(|e| e.to_string());
+        return result.map_err(CommandError::from);
     }
 
     Ok(Vec::new())
@@ -791,15 +2505,15 @@ pub struct AliasInfo {
 
 /// Get all aliases for a note by path
 #[tauri::command]
-pub fn get_note_aliases(app: AppHandle, path: String) -> Result<Vec<String>, String> {
-    let note_id = generate_note_id(&path);
-    db::get_note_aliases(&app, &note_id).map_err(|e| e.to_string())
+pub fn get_note_aliases(app: AppHandle, path: String) -> Result<Vec<String>, CommandError> {
+    let note_id = resolve_note_id(&app, &path);
+    db::get_note_aliases(&app, &note_id).map_err(CommandError::from)
 }
 
 /// Get all aliases in the vault (for autocomplete)
 #[tauri::command]
-pub fn get_all_aliases(app: AppHandle) -> Result<Vec<AliasInfo>, String> {
-    let aliases = db::get_all_aliases(&app).map_err(|e| e.to_string())?;
+pub fn get_all_aliases(app: AppHandle) -> Result<Vec<AliasInfo>, CommandError> {
+    let aliases = db::get_all_aliases(&app)?;
     Ok(aliases
         .into_iter()
         .map(|(alias, path, title)| AliasInfo {
@@ -812,8 +2526,8 @@ pub fn get_all_aliases(app: AppHandle) -> Result<Vec<AliasInfo>, String> {
 
 /// Resolve an alias to a note path
 #[tauri::command]
-pub fn resolve_alias(app: AppHandle, alias: String) -> Result<Option<String>, String> {
-    db::resolve_note_by_alias(&app, &alias).map_err(|e| e.to_string())
+pub fn resolve_alias(app: AppHandle, alias: String) -> Result<Option<String>, CommandError> {
+    db::resolve_note_by_alias(&app, &alias).map_err(CommandError::from)
 }
 
 // ============================================================================
@@ -825,15 +2539,21 @@ pub use db::NoteVersionInfo;
 
 /// Get version history for a note
 #[tauri::command]
-pub fn get_note_versions(app: AppHandle, path: String) -> Result<Vec<NoteVersionInfo>, String> {
-    let note_id = generate_note_id(&path);
-    db::get_note_versions(&app, &note_id).map_err(|e| e.to_string())
+pub fn get_note_versions(
+    app: AppHandle,
+    path: String,
+) -> Result<Vec<NoteVersionInfo>, CommandError> {
+    let note_id = resolve_note_id(&app, &path);
+    db::get_note_versions(&app, &note_id).map_err(CommandError::from)
 }
 
 /// Get the content of a specific version
 #[tauri::command]
-pub fn get_version_content(app: AppHandle, version_id: i64) -> Result<Option<String>, String> {
-    db::get_version_content(&app, version_id).map_err(|e| e.to_string())
+pub fn get_version_content(
+    app: AppHandle,
+    version_id: i64,
+) -> Result<Option<String>, CommandError> {
+    db::get_version_content(&app, version_id).map_err(CommandError::from)
 }
 
 /// Create a manual snapshot of the current note
@@ -842,19 +2562,19 @@ pub fn create_note_snapshot(
     app: AppHandle,
     path: String,
     label: Option<String>,
-) -> Result<Option<i64>, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<Option<i64>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
-    let content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
-    let note_id = generate_note_id(&path);
+    let content = fs::read_to_string(&note_path)?;
+    let note_id = resolve_note_id(&app, &path);
 
     db::create_note_version(&app, &note_id, &content, "manual", label.as_deref())
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// Restore a note to a previous version
@@ -863,22 +2583,21 @@ pub async fn restore_note_version(
     app: AppHandle,
     path: String,
     version_id: i64,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
     // Get the version content
-    let content = db::get_version_content(&app, version_id)
-        .map_err(|e| e.to_string())?
-        .ok_or("Version not found")?;
+    let content = db::get_version_content(&app, version_id)?
+        .ok_or_else(|| CommandError::not_found(format!("version {}", version_id)))?;
 
     // Save current content as a version before restoring (so user can undo)
-    let current_content = fs::read_to_string(&note_path).map_err(|e| e.to_string())?;
-    let note_id = generate_note_id(&path);
+    let current_content = fs::read_to_string(&note_path)?;
+    let note_id = resolve_note_id(&app, &path);
     let _ = db::create_note_version(
         &app,
         &note_id,
@@ -888,15 +2607,13 @@ pub async fn restore_note_version(
     );
 
     // Write the restored content
-    fs::write(&note_path, &content).map_err(|e| e.to_string())?;
+    fs::write(&note_path, &content)?;
 
     // Re-index the note
-    db::index_single_note(&app, &vault_path, &PathBuf::from(&path))
-        .await
-        .map_err(|e| e.to_string())?;
+    db::index_single_note(&app, &vault_path, &PathBuf::from(&path)).await?;
 
     // Get updated metadata
-    let metadata = fs::metadata(&note_path).map_err(|e| e.to_string())?;
+    let metadata = fs::metadata(&note_path)?;
     let modified_at = metadata
         .modified()
         .map(|t| {
@@ -906,18 +2623,13 @@ pub async fn restore_note_version(
         })
         .unwrap_or(0);
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+    let created_at = note_created_at(&app, &path, &content, &metadata);
 
     let title = extract_title(&content, &path);
     let archived = extract_archived(&content);
     let starred = db::get_note_starred(&app, &note_id).unwrap_or(false);
+    let (outgoing_count, incoming_count) =
+        db::get_note_link_counts(&app, &note_id).unwrap_or_default();
 
     Ok(NoteMetadata {
         id: note_id,
@@ -927,13 +2639,19 @@ pub async fn restore_note_version(
         created_at,
         archived,
         starred,
+        outgoing_count,
+        incoming_count,
     })
 }
 
 /// Label a version
 #[tauri::command]
-pub fn label_note_version(app: AppHandle, version_id: i64, label: String) -> Result<(), String> {
-    db::label_version(&app, version_id, &label).map_err(|e| e.to_string())
+pub fn label_note_version(
+    app: AppHandle,
+    version_id: i64,
+    label: String,
+) -> Result<(), CommandError> {
+    db::label_version(&app, version_id, &label).map_err(CommandError::from)
 }
 
 // ============================================================================
@@ -962,12 +2680,12 @@ fn get_trash_path(vault_path: &Path) -> Result<PathBuf, String> {
 
 /// Move a note to trash (soft delete)
 #[tauri::command]
-pub async fn move_to_trash(app: AppHandle, path: String) -> Result<TrashItem, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub async fn move_to_trash(app: AppHandle, path: String) -> Result<TrashItem, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let note_path = validate_vault_path(&vault_path, &path)?;
 
     if !note_path.exists() {
-        return Err(format!("Note not found: {}", path));
+        return Err(CommandError::not_found(&path));
     }
 
     // Read content to get title before moving
@@ -989,14 +2707,14 @@ pub async fn move_to_trash(app: AppHandle, path: String) -> Result<TrashItem, St
 
     // Ensure parent directory exists
     if let Some(parent) = dest_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        fs::create_dir_all(parent)?;
     }
 
     // Move file to trash
-    fs::rename(&note_path, &dest_path).map_err(|e| e.to_string())?;
+    fs::rename(&note_path, &dest_path)?;
 
     // Remove from index
-    db::remove_note_from_index(&app, &path).map_err(|e| e.to_string())?;
+    db::remove_note_from_index(&app, &path)?;
 
     // Clean up empty parent directories
     if let Some(parent) = note_path.parent() {
@@ -1013,8 +2731,8 @@ pub async fn move_to_trash(app: AppHandle, path: String) -> Result<TrashItem, St
 
 /// List all items in trash
 #[tauri::command]
-pub fn list_trash(app: AppHandle) -> Result<Vec<TrashItem>, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub fn list_trash(app: AppHandle) -> Result<Vec<TrashItem>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let trash_path = get_trash_path(&vault_path)?;
 
     let mut items = Vec::new();
@@ -1097,19 +2815,19 @@ pub fn list_trash(app: AppHandle) -> Result<Vec<TrashItem>, String> {
 pub async fn restore_from_trash(
     app: AppHandle,
     trash_path: String,
-) -> Result<NoteMetadata, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+) -> Result<NoteMetadata, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let trash_folder = get_trash_path(&vault_path)?;
     let source_path = trash_folder.join(&trash_path);
 
     if !source_path.exists() {
-        return Err(format!("Trash item not found: {}", trash_path));
+        return Err(CommandError::not_found(&trash_path));
     }
 
     // Extract original path from trash_path (format: "timestamp/original/path.md")
     let parts: Vec<&str> = trash_path.splitn(2, '/').collect();
     if parts.len() < 2 {
-        return Err("Invalid trash path format".to_string());
+        return Err(CommandError::invalid("Invalid trash path format"));
     }
     let original_path = parts[1];
 
@@ -1157,14 +2875,14 @@ async fn restore_to_path(
     dest_path: &Path,
     trash_folder: &Path,
     trash_path: &str,
-) -> Result<NoteMetadata, String> {
+) -> Result<NoteMetadata, CommandError> {
     // Ensure parent directory exists
     if let Some(parent) = dest_path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        fs::create_dir_all(parent)?;
     }
 
     // Move file back
-    fs::rename(source_path, dest_path).map_err(|e| e.to_string())?;
+    fs::rename(source_path, dest_path)?;
 
     // Clean up empty timestamp directory
     let timestamp_dir = trash_folder.join(trash_path.split('/').next().unwrap_or(""));
@@ -1177,13 +2895,11 @@ async fn restore_to_path(
         .unwrap_or_default();
 
     // Re-index the note
-    db::index_single_note(app, vault_path, &PathBuf::from(&relative_path))
-        .await
-        .map_err(|e| e.to_string())?;
+    db::index_single_note(app, vault_path, &PathBuf::from(&relative_path)).await?;
 
     // Return metadata
-    let content = fs::read_to_string(dest_path).map_err(|e| e.to_string())?;
-    let metadata = fs::metadata(dest_path).map_err(|e| e.to_string())?;
+    let content = fs::read_to_string(dest_path)?;
+    let metadata = fs::metadata(dest_path)?;
 
     let modified_at = metadata
         .modified()
@@ -1194,19 +2910,13 @@ async fn restore_to_path(
         })
         .unwrap_or(0);
 
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+    let created_at = note_created_at(app, &relative_path, &content, &metadata);
 
     let title = extract_title(&content, &relative_path);
-    let id = generate_note_id(&relative_path);
+    let id = resolve_note_id(app, &relative_path);
     let archived = extract_archived(&content);
     let starred = db::get_note_starred(app, &id).unwrap_or(false);
+    let (outgoing_count, incoming_count) = db::get_note_link_counts(app, &id).unwrap_or_default();
 
     Ok(NoteMetadata {
         id,
@@ -1216,22 +2926,27 @@ async fn restore_to_path(
         created_at,
         archived,
         starred,
+        outgoing_count,
+        incoming_count,
     })
 }
 
 /// Permanently delete an item from trash
 #[tauri::command]
-pub fn permanently_delete_from_trash(app: AppHandle, trash_path: String) -> Result<(), String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub fn permanently_delete_from_trash(
+    app: AppHandle,
+    trash_path: String,
+) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let trash_folder = get_trash_path(&vault_path)?;
     let file_path = trash_folder.join(&trash_path);
 
     if !file_path.exists() {
-        return Err(format!("Trash item not found: {}", trash_path));
+        return Err(CommandError::not_found(&trash_path));
     }
 
     // Delete the file
-    fs::remove_file(&file_path).map_err(|e| e.to_string())?;
+    fs::remove_file(&file_path)?;
 
     // Try to clean up empty timestamp directory
     let timestamp_dir = trash_folder.join(trash_path.split('/').next().unwrap_or(""));
@@ -1245,8 +2960,8 @@ pub fn permanently_delete_from_trash(app: AppHandle, trash_path: String) -> Resu
 
 /// Empty the entire trash
 #[tauri::command]
-pub fn empty_trash(app: AppHandle) -> Result<i32, String> {
-    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+pub fn empty_trash(app: AppHandle) -> Result<i32, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
     let trash_folder = get_trash_path(&vault_path)?;
 
     let mut count = 0;
@@ -1267,3 +2982,42 @@ pub fn empty_trash(app: AppHandle) -> Result<i32, String> {
 
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn case_only_rename_changes_the_name_on_disk() {
+        let vault = tempfile::tempdir().unwrap();
+        fs::create_dir(vault.path().join("notes")).unwrap();
+        fs::write(vault.path().join("notes/Foo.md"), "# Foo").unwrap();
+
+        let moved = move_note_file(vault.path(), "notes/Foo.md", "notes/foo.md", false).unwrap();
+
+        assert_eq!(file_names(&vault.path().join("notes")), vec!["foo.md"]);
+        assert_eq!(fs::read_to_string(moved).unwrap(), "# Foo");
+    }
+
+    #[test]
+    fn rename_within_a_folder_moves_the_file() {
+        let vault = tempfile::tempdir().unwrap();
+        fs::create_dir_all(vault.path().join("notes/a")).unwrap();
+        fs::write(vault.path().join("notes/a/b.md"), "# B").unwrap();
+
+        let moved = move_note_file(vault.path(), "notes/a/b.md", "notes/a/c.md", false).unwrap();
+
+        assert_eq!(moved, vault.path().join("notes/a/c.md"));
+        assert_eq!(file_names(&vault.path().join("notes/a")), vec!["c.md"]);
+        assert_eq!(fs::read_to_string(moved).unwrap(), "# B");
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 use crate::db;
+use crate::error::CommandError;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -23,11 +24,13 @@ pub struct SearchMatch {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EntityResult {
-    pub entity_type: String, // "ip", "domain", "cve", "username", "mention"
+    pub entity_type: String, // "ip", "domain", "url", "email", "cve", "hash", "username", "mention"
     pub value: String,
     pub note_path: String,
     pub note_title: String,
     pub context: String,
+    /// Hash algorithm ("md5", "sha1", "sha256") for hash entities
+    pub subtype: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,12 +45,45 @@ pub struct SearchFilters {
     pub tags: Option<Vec<String>>,
     pub folders: Option<Vec<String>>,
     pub entity_types: Option<Vec<String>>,
-    pub date_from: Option<i64>,
-    pub date_to: Option<i64>,
+    /// Only notes modified on or after this date
+    pub date_from: Option<DateBound>,
+    /// Only notes modified on or before this date
+    pub date_to: Option<DateBound>,
     pub code_only: Option<bool>,
+    /// Only code blocks in this language; aliases (`py`, `python3`) match too
+    pub language: Option<String>,
     pub include_archived: Option<bool>,
 }
 
+/// A date filter bound: unix seconds, or a date expression such as `today`,
+/// `-7d` or `2024-01-01` (see `db::resolve_date_span`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DateBound {
+    Timestamp(i64),
+    Expression(String),
+}
+
+impl DateBound {
+    /// The `[start, end)` span this bound covers, or None for an unparseable expression
+    pub fn span(&self) -> Option<(i64, i64)> {
+        match self {
+            DateBound::Timestamp(ts) => Some((*ts, ts + 1)),
+            DateBound::Expression(expr) => db::resolve_date_span(expr),
+        }
+    }
+}
+
+/// Restricts a search to one note or to a folder subtree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SearchScope {
+    /// Only the note at this path
+    Note { path: String },
+    /// Only notes under this folder prefix
+    Folder { path: String },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SavedSearch {
     pub id: String,
@@ -57,16 +93,73 @@ pub struct SavedSearch {
     pub created_at: i64,
 }
 
-/// Search notes using full-text search
+/// A kanban card matching a search, with its board and column for context
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardSearchResult {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "boardId")]
+    pub board_id: String,
+    #[serde(rename = "boardName")]
+    pub board_name: String,
+    #[serde(rename = "columnName")]
+    pub column_name: Option<String>,
+    pub snippet: String,
+    pub field: String, // "title" or "description"
+    pub archived: bool,
+}
+
+/// A code block matching a code search, with the note it's in
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSearchResult {
+    pub note_id: String,
+    pub note_path: String,
+    pub note_title: String,
+    /// Canonical language, or None for a fence without one
+    pub language: Option<String>,
+    pub content: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    /// Case-insensitive occurrences of the query in the block
+    pub match_count: usize,
+}
+
+/// A quick-open match of any kind. `kind` is one of "note", "card", "board",
+/// "diagram" or "search"; `path_or_route` is a note path for notes and a
+/// `kind/id` route (`card/<board_id>/<card_id>` for cards) otherwise.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickOpenResult {
+    pub kind: String,
+    pub id: String,
+    pub title: String,
+    pub path_or_route: String,
+    pub score: f64,
+}
+
+/// Search notes using full-text search. `max_snippets` (default 1) returns
+/// additional non-overlapping snippets in each result's `matches`.
 #[tauri::command]
 pub fn search_notes(
     app: AppHandle,
     query: String,
     filters: Option<SearchFilters>,
     limit: Option<usize>,
-) -> Result<Vec<SearchResult>, String> {
+    snippet_length: Option<usize>,
+    max_snippets: Option<usize>,
+    scope: Option<SearchScope>,
+) -> Result<Vec<SearchResult>, CommandError> {
     let limit = limit.unwrap_or(50);
-    db::search_notes(&app, &query, filters.as_ref(), limit).map_err(|e| e.to_string())
+    db::search_notes(
+        &app,
+        &query,
+        filters.as_ref(),
+        limit,
+        snippet_length,
+        max_snippets.unwrap_or(1),
+        scope.as_ref(),
+    )
+    .map_err(CommandError::from)
 }
 
 /// Search for specific entities (IPs, domains, CVEs, etc.)
@@ -76,10 +169,10 @@ pub fn search_entities(
     entity_type: Option<String>,
     pattern: Option<String>,
     limit: Option<usize>,
-) -> Result<Vec<EntityResult>, String> {
+) -> Result<Vec<EntityResult>, CommandError> {
     let limit = limit.unwrap_or(100);
     db::search_entities(&app, entity_type.as_deref(), pattern.as_deref(), limit)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// Save a search query for quick access
@@ -89,12 +182,75 @@ pub fn save_search(
     name: String,
     query: String,
     filters: Option<SearchFilters>,
-) -> Result<SavedSearch, String> {
-    db::save_search(&app, &name, &query, filters.as_ref()).map_err(|e| e.to_string())
+) -> Result<SavedSearch, CommandError> {
+    db::save_search(&app, &name, &query, filters.as_ref()).map_err(CommandError::from)
 }
 
 /// Get all saved searches
 #[tauri::command]
-pub fn get_saved_searches(app: AppHandle) -> Result<Vec<SavedSearch>, String> {
-    db::get_saved_searches(&app).map_err(|e| e.to_string())
+pub fn get_saved_searches(app: AppHandle) -> Result<Vec<SavedSearch>, CommandError> {
+    db::get_saved_searches(&app).map_err(CommandError::from)
+}
+
+/// Search kanban card titles and descriptions
+#[tauri::command]
+pub fn search_cards(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<CardSearchResult>, CommandError> {
+    let limit = limit.unwrap_or(50);
+    db::search_cards(&app, &query, limit).map_err(CommandError::from)
+}
+
+/// Search code blocks by text and/or language. An empty query lists every
+/// block in the language.
+#[tauri::command]
+pub fn search_code(
+    app: AppHandle,
+    query: String,
+    language: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<CodeSearchResult>, CommandError> {
+    let limit = limit.unwrap_or(100);
+    db::search_code(&app, &query, language.as_deref(), limit).map_err(CommandError::from)
+}
+
+/// Fuzzy-find notes, cards, boards, diagrams and saved searches from one input
+#[tauri::command]
+pub fn quick_open(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenResult>, CommandError> {
+    let limit = limit.unwrap_or(20);
+    db::quick_open(&app, &query, limit).map_err(CommandError::from)
+}
+
+/// Top keywords of a note by TF-IDF against the rest of the vault
+#[tauri::command]
+pub async fn get_note_keywords(
+    app: AppHandle,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<db::NoteKeyword>, CommandError> {
+    let limit = limit.unwrap_or(10);
+    tauri::async_runtime::spawn_blocking(move || db::get_note_keywords(&app, &path, limit))
+        .await
+        .map_err(|e| CommandError::from(e.to_string()))?
+        .map_err(CommandError::from)
+}
+
+/// Suggest existing vault tags for a note, with a confidence for each
+#[tauri::command]
+pub async fn suggest_tags(
+    app: AppHandle,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<db::TagSuggestion>, CommandError> {
+    let limit = limit.unwrap_or(10);
+    tauri::async_runtime::spawn_blocking(move || db::suggest_tags(&app, &path, limit))
+        .await
+        .map_err(|e| CommandError::from(e.to_string()))?
+        .map_err(CommandError::from)
 }
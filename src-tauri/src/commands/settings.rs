@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::db;
 
 /// Recent vault entry
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -148,3 +152,475 @@ pub fn remove_recent_vault(path: String) -> Result<Vec<RecentVault>, String> {
     write_settings(&settings)?;
     Ok(settings.recent_vaults)
 }
+
+// ============================================================================
+// Vault Settings
+// ============================================================================
+
+/// Per-vault settings stored in <vault>/.kairo/settings.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VaultSettings {
+    pub versioning: VersioningSettings,
+    pub indexing: IndexingSettings,
+    /// Rewrite frontmatter in canonical sorted form on every save
+    pub normalize_frontmatter_on_save: bool,
+    pub note_id_scheme: NoteIdScheme,
+    pub unlinked_mentions: UnlinkedMentionSettings,
+    pub periodic_notes: PeriodicNotesSettings,
+    pub links: LinkSettings,
+    pub reminders: ReminderSettings,
+    pub attachments: AttachmentSettings,
+    pub code_languages: CodeLanguageSettings,
+    pub orphans: OrphanSettings,
+    pub folder_templates: FolderTemplateSettings,
+}
+
+/// How note ids are assigned
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum NoteIdScheme {
+    /// Derived from the note path (legacy); ids change when a note moves outside the app
+    #[default]
+    Path,
+    /// A UUID written to the note's frontmatter as `id:` on first index
+    Frontmatter,
+}
+
+/// Syntax used for links the app writes into notes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkStyle {
+    /// `[[Note]]` or `[[Note|display]]`
+    #[default]
+    Wiki,
+    /// `[display](Note.md)`
+    Markdown,
+}
+
+/// How generated links are written, to match links written by hand
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LinkSettings {
+    pub style: LinkStyle,
+    /// Write the folder path (relative to `notes/`) rather than just the note name
+    pub include_folder: bool,
+    /// Keep the `.md` extension; markdown links always keep it so the indexer resolves them
+    pub include_extension: bool,
+}
+
+impl LinkSettings {
+    /// Format a link to a vault-relative note path. `display` defaults to the note name.
+    pub fn format_link(&self, target_path: &str, display: Option<&str>) -> String {
+        let relative = target_path.strip_prefix("notes/").unwrap_or(target_path);
+        let stem = relative.strip_suffix(".md").unwrap_or(relative);
+        let name = stem.rsplit('/').next().unwrap_or(stem);
+
+        let target = if self.include_folder { stem } else { name };
+        let display = display.map(str::trim).filter(|d| !d.is_empty());
+
+        match self.style {
+            LinkStyle::Wiki => {
+                let target = if self.include_extension {
+                    format!("{}.md", target)
+                } else {
+                    target.to_string()
+                };
+                match display {
+                    Some(display) if display != target => format!("[[{}|{}]]", target, display),
+                    _ => format!("[[{}]]", target),
+                }
+            }
+            LinkStyle::Markdown => {
+                format!("[{}]({}.md)", display.unwrap_or(name), target)
+            }
+        }
+    }
+}
+
+/// How attachment embeds written into notes are addressed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AttachmentEmbedStyle {
+    /// `attachments/image.png`, resolved from the vault root wherever the note lives
+    #[default]
+    VaultRoot,
+    /// `../attachments/image.png`, relative to the note's folder, as other
+    /// markdown tools expect
+    RelativeToNote,
+}
+
+/// Attachment embed settings
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AttachmentSettings {
+    pub embed_style: AttachmentEmbedStyle,
+}
+
+impl AttachmentSettings {
+    /// Path to embed a vault-relative attachment in the note at `note_path`
+    pub fn embed_path(&self, note_path: &str, attachment: &str) -> String {
+        let attachment = attachment.replace('\\', "/");
+        let attachment = attachment.trim_start_matches('/');
+
+        match self.embed_style {
+            AttachmentEmbedStyle::VaultRoot => attachment.to_string(),
+            AttachmentEmbedStyle::RelativeToNote => {
+                let note_path = note_path.replace('\\', "/");
+                let note_dir: Vec<&str> = note_path
+                    .rsplit_once('/')
+                    .map(|(dir, _)| dir.split('/').filter(|c| !c.is_empty()).collect())
+                    .unwrap_or_default();
+                let target: Vec<&str> = attachment.split('/').collect();
+
+                let common = note_dir
+                    .iter()
+                    .zip(&target)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let mut parts = vec![".."; note_dir.len() - common];
+                if parts.is_empty() {
+                    parts.push(".");
+                }
+                parts.extend(&target[common..]);
+                parts.join("/")
+            }
+        }
+    }
+}
+
+/// Controls how often autosaves produce a new note version
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VersioningSettings {
+    /// Minimum number of changed characters since the last version
+    pub min_changed_chars: usize,
+    /// Minimum seconds since the last version; once elapsed, any change is kept
+    pub min_interval_secs: i64,
+}
+
+impl Default for VersioningSettings {
+    fn default() -> Self {
+        Self {
+            min_changed_chars: 200,
+            min_interval_secs: 300,
+        }
+    }
+}
+
+/// Controls which files are indexed and how
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IndexingSettings {
+    /// Notes larger than this are listed but excluded from full-text search
+    pub max_file_size_bytes: u64,
+    /// Folders under `notes/` that are never indexed, e.g. `attachments/raw`
+    pub excluded_paths: Vec<String>,
+    /// Descend into symlinked folders. Off by default, since links can reach
+    /// outside the vault or form loops.
+    pub follow_links: bool,
+    /// Index hashtags inside fenced and inline code. Off by default, since a
+    /// `#` there is usually a comment.
+    pub tags_in_code: bool,
+    /// Index IPs, domains, hashes and other entities inside code
+    pub entities_in_code: bool,
+    /// Index `[[links]]` inside code, which the preview doesn't render as links
+    pub links_in_code: bool,
+}
+
+impl Default for IndexingSettings {
+    fn default() -> Self {
+        Self {
+            max_file_size_bytes: 2 * 1024 * 1024,
+            excluded_paths: Vec::new(),
+            follow_links: false,
+            tags_in_code: false,
+            entities_in_code: true,
+            links_in_code: false,
+        }
+    }
+}
+
+impl IndexingSettings {
+    /// Whether a file or folder path relative to `notes/` is kept out of the
+    /// index: it is hidden, lies in a hidden folder (.git, .obsidian, ...) or
+    /// under an excluded path. The app's own hidden folders (.kairo, .trash)
+    /// sit outside `notes/`.
+    pub fn excludes(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        let path = path.trim_matches('/');
+
+        if path.split('/').any(|c| c.starts_with('.')) {
+            return true;
+        }
+
+        self.excluded_paths.iter().any(|excluded| {
+            let excluded = excluded.replace('\\', "/");
+            let excluded = excluded.trim_matches('/');
+            !excluded.is_empty()
+                && (path == excluded || path.starts_with(&format!("{}/", excluded)))
+        })
+    }
+}
+
+/// Which notes orphan detection looks at
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OrphanSettings {
+    /// Folders under `notes/` whose notes are never reported as orphans,
+    /// e.g. `daily` or `templates`
+    pub excluded_folders: Vec<String>,
+}
+
+impl OrphanSettings {
+    /// Whether the note at `path` (vault-relative) is left out of orphan detection
+    pub fn excludes(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        let path = path.strip_prefix("notes/").unwrap_or(&path);
+
+        self.excluded_folders.iter().any(|folder| {
+            let folder = folder.replace('\\', "/");
+            let folder = folder.trim_matches('/');
+            let folder = folder.strip_prefix("notes/").unwrap_or(folder);
+            !folder.is_empty() && path.starts_with(&format!("{}/", folder))
+        })
+    }
+}
+
+/// Due-date reminders for kanban cards
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReminderSettings {
+    pub enabled: bool,
+    /// How long before a card's due date the "due soon" reminder fires
+    pub lead_time_minutes: i64,
+    /// How often due dates are checked while the vault is open
+    pub check_interval_seconds: u64,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lead_time_minutes: 24 * 60,
+            check_interval_seconds: 60,
+        }
+    }
+}
+
+/// Fence languages that name the same language, mapped to one canonical name
+const CODE_LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("node", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("py", "python"),
+    ("py3", "python"),
+    ("python3", "python"),
+    ("rb", "ruby"),
+    ("rs", "rust"),
+    ("golang", "go"),
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("console", "bash"),
+    ("ps", "powershell"),
+    ("ps1", "powershell"),
+    ("pwsh", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("yml", "yaml"),
+    ("md", "markdown"),
+    ("htm", "html"),
+    ("xhtml", "html"),
+    ("c++", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("h", "c"),
+    ("cs", "csharp"),
+    ("c#", "csharp"),
+    ("kt", "kotlin"),
+    ("postgres", "sql"),
+    ("postgresql", "sql"),
+    ("mysql", "sql"),
+    ("sqlite", "sql"),
+    ("dockerfile", "docker"),
+    ("tf", "terraform"),
+    ("hcl", "terraform"),
+    ("jsonc", "json"),
+];
+
+/// Maps code-block fence languages (`py`, `python3`) to one canonical name
+/// (`python`) so code search and language filters treat them as one language
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodeLanguageSettings {
+    /// Extra alias -> canonical mappings; these win over the built-in ones
+    pub aliases: HashMap<String, String>,
+}
+
+impl CodeLanguageSettings {
+    /// The canonical, lowercase name for a fence language
+    pub fn canonical(&self, language: &str) -> String {
+        let language = language.trim().to_lowercase();
+
+        let custom = self
+            .aliases
+            .iter()
+            .find(|(alias, _)| alias.trim().eq_ignore_ascii_case(&language))
+            .map(|(_, canonical)| canonical.trim().to_lowercase());
+        if let Some(canonical) = custom.filter(|c| !c.is_empty()) {
+            return canonical;
+        }
+
+        CODE_LANGUAGE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == language)
+            .map_or(language.clone(), |(_, canonical)| canonical.to_string())
+    }
+}
+
+/// Controls which note titles are considered by unlinked-mention detection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UnlinkedMentionSettings {
+    /// Titles made up only of these words (case-insensitive) are ignored
+    pub stopwords: Vec<String>,
+    /// Titles with fewer words than this are ignored
+    pub min_word_count: usize,
+    /// Titles shorter than this many characters are ignored
+    pub min_title_length: usize,
+}
+
+impl Default for UnlinkedMentionSettings {
+    fn default() -> Self {
+        const STOPWORDS: &str = "a about all an and archive as at by daily draft for from \
+            home ideas in inbox index into is it journal list log meeting meetings misc note \
+            notes of on or overview project projects readme summary task tasks template \
+            templates test the this to todo untitled welcome with";
+        Self {
+            stopwords: STOPWORDS.split_whitespace().map(String::from).collect(),
+            min_word_count: 1,
+            min_title_length: 3,
+        }
+    }
+}
+
+/// Templates applied to brand-new empty notes by folder
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderTemplateSettings {
+    /// Off by default; notes are only filled in when this is on
+    pub enabled: bool,
+    pub rules: Vec<FolderTemplate>,
+}
+
+/// A template for new notes created anywhere under `folder`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderTemplate {
+    /// Folder under `notes/`, e.g. `meetings`
+    pub folder: String,
+    /// Vault-relative path of the template note
+    pub template: String,
+}
+
+impl FolderTemplateSettings {
+    /// Template for a new note at `path` (vault-relative): the rule with the
+    /// deepest folder containing it. A template never applies to itself.
+    pub fn template_for(&self, path: &str) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+
+        let path = path.replace('\\', "/");
+        let relative = path.strip_prefix("notes/").unwrap_or(&path);
+
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let folder = rule.folder.replace('\\', "/");
+                let folder = folder.trim_matches('/');
+                let folder = folder.strip_prefix("notes/").unwrap_or(folder).to_string();
+                let template = rule.template.trim();
+                (!folder.is_empty()
+                    && !template.is_empty()
+                    && template != path
+                    && relative.starts_with(&format!("{}/", folder)))
+                .then_some((folder.len(), template))
+            })
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, template)| template)
+    }
+}
+
+/// Where each kind of periodic note lives and how it's named
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PeriodicNotesSettings {
+    pub daily: PeriodicNoteSettings,
+    pub weekly: PeriodicNoteSettings,
+    pub monthly: PeriodicNoteSettings,
+    pub quarterly: PeriodicNoteSettings,
+    pub yearly: PeriodicNoteSettings,
+}
+
+/// Settings for one kind of periodic note. Empty fields use the kind's default.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PeriodicNoteSettings {
+    /// Vault-relative folder, e.g. `notes/daily`
+    pub folder: String,
+    /// strftime-style filename format without extension; `%q` is the quarter (1-4)
+    pub format: String,
+    /// Vault-relative path of a note used as the template for new notes
+    pub template: Option<String>,
+}
+
+/// Get the vault settings file path
+fn get_vault_settings_path(vault_path: &Path) -> PathBuf {
+    vault_path.join(".kairo").join("settings.json")
+}
+
+/// Read vault settings, falling back to defaults if the file doesn't exist
+pub fn read_vault_settings(vault_path: &Path) -> Result<VaultSettings, String> {
+    let path = get_vault_settings_path(vault_path);
+
+    if !path.exists() {
+        return Ok(VaultSettings::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Write vault settings to <vault>/.kairo/settings.json
+pub fn write_vault_settings(vault_path: &Path, settings: &VaultSettings) -> Result<(), String> {
+    let path = get_vault_settings_path(vault_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// Get settings for the currently open vault
+#[tauri::command]
+pub fn get_vault_settings(app: AppHandle) -> Result<VaultSettings, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    read_vault_settings(&vault_path)
+}
+
+/// Replace settings for the currently open vault
+#[tauri::command]
+pub fn set_vault_settings(
+    app: AppHandle,
+    settings: VaultSettings,
+) -> Result<VaultSettings, String> {
+    let vault_path = db::get_current_vault_path(&app).ok_or("No vault open")?;
+    write_vault_settings(&vault_path, &settings)?;
+    Ok(settings)
+}
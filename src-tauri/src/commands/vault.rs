@@ -1,14 +1,27 @@
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 use uuid::Uuid;
+use walkdir::WalkDir;
 
+use crate::commands::notes::validate_vault_path;
+use crate::commands::settings::{
+    read_vault_settings, write_vault_settings, NoteIdScheme, VaultSettings,
+};
+use crate::crypto;
 use crate::db;
-use crate::db::with_db;
+use crate::db::with_db_tx;
+use crate::error::CommandError;
+use crate::fs::lock::VaultLock;
 use crate::fs::watcher::VaultWatcher;
+use crate::git::credentials::CredentialCache;
+use crate::reminders::ReminderScheduler;
 
 /// State for the file watcher
 #[derive(Default)]
@@ -16,6 +29,56 @@ pub struct WatcherState {
     pub watcher: Option<VaultWatcher>,
 }
 
+/// State for the due-date reminder scheduler
+#[derive(Default)]
+pub struct ReminderState {
+    pub scheduler: Option<ReminderScheduler>,
+}
+
+/// Start due-date reminders for the open vault, replacing any running scheduler
+fn start_reminders(app: &AppHandle, vault_path: &Path) {
+    let scheduler = ReminderScheduler::start(app.clone(), vault_path.to_path_buf());
+    let state = app.state::<Mutex<ReminderState>>();
+    if let Ok(mut guard) = state.lock() {
+        if let Some(previous) = guard.scheduler.replace(scheduler) {
+            previous.stop();
+        }
+    };
+}
+
+fn stop_reminders(app: &AppHandle) {
+    let state = app.state::<Mutex<ReminderState>>();
+    if let Ok(mut guard) = state.lock() {
+        if let Some(scheduler) = guard.scheduler.take() {
+            scheduler.stop();
+        }
+    };
+}
+
+/// Lock on the currently open vault, held until it is closed or the app exits
+#[derive(Default)]
+pub struct VaultLockState {
+    pub lock: Option<VaultLock>,
+}
+
+/// Take the vault lock, replacing any lock held on a previously open vault
+fn acquire_vault_lock(app: &AppHandle, vault_path: &Path, force: bool) -> Result<(), CommandError> {
+    let state = app.state::<Mutex<VaultLockState>>();
+    let mut guard = state.lock().map_err(|e| e.to_string())?;
+    // Release first so reopening the same vault doesn't trip over our own lock
+    guard.lock = None;
+    guard.lock = Some(VaultLock::acquire(vault_path, force)?);
+    Ok(())
+}
+
+/// Release the vault lock, if one is held
+pub fn release_vault_lock(app: &AppHandle) {
+    let state = app.state::<Mutex<VaultLockState>>();
+    if let Ok(mut guard) = state.lock() {
+        guard.lock = None;
+    };
+}
+
 /// Entries that should be in every vault's .gitignore
 const GITIGNORE_ENTRIES: &[&str] = &[
     ".kairo/index.db",
@@ -23,8 +86,16 @@ const GITIGNORE_ENTRIES: &[&str] = &[
     ".kairo/index.db-wal",
     ".kairo/index.db-shm",
     ".kairo-user",
+    ".kairo/uploads/",
+    ".kairo/vault-auth.json",
+    ".kairo/.lock",
+    ".kairo/.lock.*",
 ];
 
+/// Temp directory for chunked attachment uploads (inside the vault so the
+/// final rename stays on one filesystem)
+const UPLOADS_DIR: &str = ".kairo/uploads";
+
 /// Ensure the vault's .gitignore has all necessary entries
 fn ensure_gitignore(vault_path: &Path) {
     let gitignore_path = vault_path.join(".gitignore");
@@ -63,51 +134,290 @@ fn ensure_gitignore(vault_path: &Path) {
     }
 }
 
+// ============================================================================
+// Vault Scaffolds
+// ============================================================================
+
+/// Built-in folder/note layouts a new vault can start from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VaultScaffold {
+    /// The original single welcome note layout, used when no template is given
+    Default,
+    Empty,
+    Para,
+    Zettelkasten,
+    Research,
+}
+
+impl VaultScaffold {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "default" => Some(Self::Default),
+            "empty" => Some(Self::Empty),
+            "para" => Some(Self::Para),
+            "zettelkasten" => Some(Self::Zettelkasten),
+            "research" => Some(Self::Research),
+            _ => None,
+        }
+    }
+
+    /// Folders to create, relative to the vault root
+    fn folders(self) -> &'static [&'static str] {
+        match self {
+            Self::Default => &["notes", "notes/daily"],
+            Self::Empty => &["notes"],
+            Self::Para => &[
+                "notes",
+                "notes/daily",
+                "notes/projects",
+                "notes/areas",
+                "notes/resources",
+                "notes/archive",
+            ],
+            Self::Zettelkasten => &[
+                "notes",
+                "notes/daily",
+                "notes/fleeting",
+                "notes/literature",
+                "notes/permanent",
+            ],
+            Self::Research => &[
+                "notes",
+                "notes/daily",
+                "notes/questions",
+                "notes/literature",
+                "notes/experiments",
+                "attachments",
+            ],
+        }
+    }
+
+    /// Starter notes as (vault-relative path, content), embedded at build time
+    fn notes(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Default => &[(
+                "notes/welcome.md",
+                include_str!("../../scaffolds/default/welcome.md"),
+            )],
+            Self::Empty => &[],
+            Self::Para => &[
+                (
+                    "notes/welcome.md",
+                    include_str!("../../scaffolds/para/welcome.md"),
+                ),
+                (
+                    "notes/projects/example-project.md",
+                    include_str!("../../scaffolds/para/example-project.md"),
+                ),
+                (
+                    "notes/areas/personal.md",
+                    include_str!("../../scaffolds/para/personal.md"),
+                ),
+            ],
+            Self::Zettelkasten => &[
+                (
+                    "notes/welcome.md",
+                    include_str!("../../scaffolds/zettelkasten/welcome.md"),
+                ),
+                (
+                    "notes/permanent/index.md",
+                    include_str!("../../scaffolds/zettelkasten/index.md"),
+                ),
+                (
+                    "notes/permanent/atomic-notes.md",
+                    include_str!("../../scaffolds/zettelkasten/atomic-notes.md"),
+                ),
+            ],
+            Self::Research => &[
+                (
+                    "notes/welcome.md",
+                    include_str!("../../scaffolds/research/welcome.md"),
+                ),
+                (
+                    "notes/questions/research-questions.md",
+                    include_str!("../../scaffolds/research/research-questions.md"),
+                ),
+                (
+                    "notes/literature/literature-note.md",
+                    include_str!("../../scaffolds/research/literature-note.md"),
+                ),
+            ],
+        }
+    }
+
+    /// Vault settings written to .kairo/settings.json
+    fn settings(self) -> VaultSettings {
+        let mut settings = VaultSettings::default();
+        // Zettelkasten notes link heavily, so give them ids that survive renames
+        if self == Self::Zettelkasten {
+            settings.note_id_scheme = NoteIdScheme::Frontmatter;
+        }
+        settings
+    }
+}
+
+/// Create a scaffold's folders, starter notes and settings. Existing notes and
+/// settings are never overwritten. Returns the number of notes written.
+fn write_scaffold(vault_path: &Path, scaffold: VaultScaffold) -> Result<usize, CommandError> {
+    for folder in scaffold.folders() {
+        fs::create_dir_all(vault_path.join(folder))?;
+    }
+
+    let mut written = 0;
+    for (relative, content) in scaffold.notes() {
+        let note_path = vault_path.join(relative);
+        if note_path.exists() {
+            continue;
+        }
+        fs::write(&note_path, content)?;
+        written += 1;
+    }
+
+    if !vault_path.join(".kairo").join("settings.json").exists() {
+        write_vault_settings(vault_path, &scaffold.settings())?;
+    }
+
+    Ok(written)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VaultInfo {
     pub path: String,
     pub name: String,
     pub note_count: usize,
     pub created_at: Option<i64>,
+    /// Problems found while opening, e.g. a vault from a newer app version
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VaultConfig {
     pub name: String,
+    /// On-disk layout version; vaults from before it was written have none
+    #[serde(default)]
     pub version: String,
     pub created_at: i64,
 }
 
+/// Current vault layout version. Bump it when the on-disk layout changes and
+/// add a `VAULT_MIGRATIONS` entry that brings older vaults up to it.
+const VAULT_VERSION: &str = "0.1.0";
+
+/// An on-disk migration, run against the vault root
+type VaultMigration = fn(&Path) -> Result<(), CommandError>;
+
+/// Migrations in ascending order, each paired with the version it migrates to
+const VAULT_MIGRATIONS: &[(&str, VaultMigration)] = &[];
+
+/// Parse a `major.minor.patch` version; missing or invalid parts count as 0
+fn parse_version(version: &str) -> (u64, u64, u64) {
+    let mut parts = version
+        .trim()
+        .split('.')
+        .map(|part| part.parse::<u64>().unwrap_or(0));
+    (
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+    )
+}
+
+/// Bring an older vault's on-disk layout up to `VAULT_VERSION`, then record
+/// the new version in its config. A vault from a newer app is left untouched
+/// and reported in the returned warnings.
+fn migrate_vault(vault_path: &Path, config: &mut VaultConfig) -> Result<Vec<String>, CommandError> {
+    let current = parse_version(VAULT_VERSION);
+    let found = parse_version(&config.version);
+
+    if found > current {
+        return Ok(vec![format!(
+            "This vault was created by a newer version of Kairo (vault version {}, supported {}); some content may not be handled correctly",
+            config.version, VAULT_VERSION
+        )]);
+    }
+    if found == current {
+        return Ok(Vec::new());
+    }
+
+    for (version, migration) in VAULT_MIGRATIONS {
+        let target = parse_version(version);
+        if target > found && target <= current {
+            migration(vault_path)?;
+        }
+    }
+
+    config.version = VAULT_VERSION.to_string();
+    let config_path = vault_path.join(".kairo").join("config.json");
+    fs::write(&config_path, serde_json::to_string_pretty(config)?)?;
+
+    Ok(Vec::new())
+}
+
 /// Open an existing vault at the given path
 #[tauri::command]
-pub async fn open_vault(app: AppHandle, path: String) -> Result<VaultInfo, String> {
+pub async fn open_vault(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    force: Option<bool>,
+) -> Result<VaultInfo, CommandError> {
     let vault_path = PathBuf::from(&path);
 
     // Check if .kairo directory exists
     let kairo_dir = vault_path.join(".kairo");
     if !kairo_dir.exists() {
-        return Err("Not a valid Kairo vault (missing .kairo directory)".to_string());
+        return Err(CommandError::invalid(
+            "Not a valid Kairo vault (missing .kairo directory)",
+        ));
     }
 
     // Read config
     let config_path = kairo_dir.join("config.json");
-    let config: VaultConfig = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())?
+    let mut config: VaultConfig = if config_path.exists() {
+        let content = fs::read_to_string(&config_path)?;
+        serde_json::from_str(&content)?
     } else {
-        return Err("Vault config not found".to_string());
+        return Err(CommandError::not_found(".kairo/config.json"));
+    };
+
+    // Refuse to open a vault another running instance has open
+    acquire_vault_lock(&app, &vault_path, force.unwrap_or(false))?;
+
+    // Upgrade the on-disk layout before anything reads it
+    let warnings = match migrate_vault(&vault_path, &mut config) {
+        Ok(warnings) => warnings,
+        Err(e) => {
+            release_vault_lock(&app);
+            return Err(e);
+        }
     };
 
     // Ensure gitignore has all necessary entries (for existing vaults)
     ensure_gitignore(&vault_path);
 
+    // Drop temp files left behind by uploads that never finished
+    if let Ok(state) = app.state::<Mutex<AttachmentUploadState>>().lock() {
+        remove_stale_uploads(&vault_path, &state.uploads);
+    }
+
+    // Unlock the index if the vault is passphrase-protected
+    let key = match unlock_vault_key(&app, &vault_path, passphrase) {
+        Ok(key) => key,
+        Err(e) => {
+            release_vault_lock(&app);
+            return Err(e);
+        }
+    };
+
     // Initialize database for this vault
-    db::open_vault_db(&app, &vault_path).map_err(|e| e.to_string())?;
+    if let Err(e) = db::open_vault_db(&app, &vault_path, key.as_ref()) {
+        release_vault_lock(&app);
+        return Err(e.into());
+    }
 
     // Index the vault
-    db::index_vault(&app, &vault_path)
-        .await
-        .map_err(|e| e.to_string())?;
+    db::index_vault(&app, &vault_path).await?;
 
     // Start file watcher
     if let Ok(watcher) = VaultWatcher::new(app.clone(), vault_path.clone()) {
@@ -117,80 +427,68 @@ pub async fn open_vault(app: AppHandle, path: String) -> Result<VaultInfo, Strin
         };
     }
 
+    // Start due-date reminders
+    start_reminders(&app, &vault_path);
+
     // Get note count
-    let note_count = db::get_note_count(&app).map_err(|e| e.to_string())?;
+    let note_count = db::get_note_count(&app)?;
 
     Ok(VaultInfo {
         path: path.clone(),
         name: config.name,
         note_count,
         created_at: Some(config.created_at),
+        warnings,
     })
 }
 
 /// Create a new vault at the given path
 #[tauri::command]
-pub async fn create_vault(app: AppHandle, path: String, name: String) -> Result<VaultInfo, String> {
+pub async fn create_vault(
+    app: AppHandle,
+    path: String,
+    name: String,
+    template: Option<String>,
+) -> Result<VaultInfo, CommandError> {
+    let scaffold = match template.as_deref() {
+        Some(name) => VaultScaffold::from_name(name)
+            .ok_or_else(|| CommandError::invalid(format!("Unknown vault template: {}", name)))?,
+        None => VaultScaffold::Default,
+    };
     let vault_path = PathBuf::from(&path);
 
     // Create vault directory structure
     let kairo_dir = vault_path.join(".kairo");
-    let notes_dir = vault_path.join("notes");
-    let daily_dir = notes_dir.join("daily");
-
-    fs::create_dir_all(&kairo_dir).map_err(|e| e.to_string())?;
-    fs::create_dir_all(&notes_dir).map_err(|e| e.to_string())?;
-    fs::create_dir_all(&daily_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&kairo_dir)?;
 
     // Create config
     let created_at = chrono::Utc::now().timestamp();
     let config = VaultConfig {
         name: name.clone(),
-        version: "0.1.0".to_string(),
+        version: VAULT_VERSION.to_string(),
         created_at,
     };
 
     let config_path = kairo_dir.join("config.json");
-    let config_content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
-    fs::write(&config_path, config_content).map_err(|e| e.to_string())?;
-
-    // Create a welcome note
-    let welcome_note = r#"# Welcome to Kairo
-
-This is your new vault. Here are some tips to get started:
-
-## Quick Tips
-
-- Use `Ctrl+K` to open the command palette
-- Create new notes with `Ctrl+N` or use templates with `Ctrl+Shift+N`
-- Search everything with `Ctrl+Shift+F`
-
-## Folder Structure
-
-- `notes/` - Your markdown notes
-- `notes/daily/` - Daily notes
-- `attachments/` - Images and files (created when you upload)
+    let config_content = serde_json::to_string_pretty(&config)?;
+    fs::write(&config_path, config_content)?;
 
-## Templates
-
-Templates are managed through the app. Press `Ctrl+Shift+N` to create notes from templates, or create your own custom templates.
-
-Happy note-taking!
-"#;
-
-    let welcome_path = notes_dir.join("welcome.md");
-    fs::write(&welcome_path, welcome_note).map_err(|e| e.to_string())?;
+    // Lay down the scaffold's folders, starter notes and settings
+    let note_count = write_scaffold(&vault_path, scaffold)?;
 
     // Create .gitignore for vault-specific files
     ensure_gitignore(&vault_path);
 
+    acquire_vault_lock(&app, &vault_path, false)?;
+
     // Initialize database
-    db::open_vault_db(&app, &vault_path).map_err(|e| e.to_string())?;
+    if let Err(e) = db::open_vault_db(&app, &vault_path, None) {
+        release_vault_lock(&app);
+        return Err(e.into());
+    }
 
     // Index the vault
-    db::index_vault(&app, &vault_path)
-        .await
-        .map_err(|e| e.to_string())?;
+    db::index_vault(&app, &vault_path).await?;
 
     // Start file watcher
     if let Ok(watcher) = VaultWatcher::new(app.clone(), vault_path.clone()) {
@@ -200,17 +498,21 @@ Happy note-taking!
         };
     }
 
+    // Start due-date reminders
+    start_reminders(&app, &vault_path);
+
     Ok(VaultInfo {
         path,
         name,
-        note_count: 1,
+        note_count,
         created_at: Some(created_at),
+        warnings: Vec::new(),
     })
 }
 
 /// Get info about the currently open vault
 #[tauri::command]
-pub fn get_vault_info(app: AppHandle) -> Result<Option<VaultInfo>, String> {
+pub fn get_vault_info(app: AppHandle) -> Result<Option<VaultInfo>, CommandError> {
     let vault_path = match db::get_current_vault_path(&app) {
         Some(p) => p,
         None => return Ok(None),
@@ -220,34 +522,216 @@ pub fn get_vault_info(app: AppHandle) -> Result<Option<VaultInfo>, String> {
     let config_path = kairo_dir.join("config.json");
 
     let config: VaultConfig = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())?
+        let content = fs::read_to_string(&config_path)?;
+        serde_json::from_str(&content)?
     } else {
-        return Err("Vault config not found".to_string());
+        return Err(CommandError::not_found(".kairo/config.json"));
     };
 
-    let note_count = db::get_note_count(&app).map_err(|e| e.to_string())?;
+    let note_count = db::get_note_count(&app)?;
 
     Ok(Some(VaultInfo {
         path: vault_path.to_string_lossy().to_string(),
         name: config.name,
         note_count,
         created_at: Some(config.created_at),
+        warnings: Vec::new(),
     }))
 }
 
 /// Close the currently open vault
 #[tauri::command]
-pub fn close_vault(app: AppHandle) -> Result<(), String> {
-    db::close_vault_db(&app).map_err(|e| e.to_string())
+pub fn close_vault(app: AppHandle) -> Result<(), CommandError> {
+    stop_reminders(&app);
+    // The watcher reindexes changed files, so it must not outlive the database
+    if let Ok(mut guard) = app.state::<Mutex<WatcherState>>().lock() {
+        if let Some(watcher) = guard.watcher.take() {
+            let _ = watcher.stop();
+        }
+    };
+    db::close_vault_db(&app)?;
+    release_vault_lock(&app);
+    Ok(())
 }
 
 /// Get the current vault path
 #[tauri::command]
-pub fn get_vault_path(app: AppHandle) -> Result<Option<String>, String> {
+pub fn get_vault_path(app: AppHandle) -> Result<Option<String>, CommandError> {
     Ok(db::get_current_vault_path(&app).map(|p| p.to_string_lossy().to_string()))
 }
 
+// ============================================================================
+// Vault Passphrase
+// ============================================================================
+
+/// Salted verifier for the vault passphrase (the passphrase itself is never stored)
+/// Stored in .kairo/vault-auth.json (gitignored, since it guards the local index)
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultAuth {
+    salt: String,
+    verifier: String,
+}
+
+/// Session cache for vault passphrases, keyed by vault path
+#[derive(Default)]
+pub struct VaultPassphraseState {
+    pub cache: CredentialCache,
+}
+
+fn vault_auth_path(vault_path: &Path) -> PathBuf {
+    vault_path.join(".kairo").join("vault-auth.json")
+}
+
+fn read_vault_auth(vault_path: &Path) -> Result<Option<VaultAuth>, CommandError> {
+    let path = vault_auth_path(vault_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+fn compute_verifier(key: &[u8; 32]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"kairo-vault-verifier");
+    hasher.update(key);
+    hex::encode(hasher.finalize())
+}
+
+/// Derive the database key if `passphrase` matches the stored verifier
+fn verify_vault_passphrase(auth: &VaultAuth, passphrase: &str) -> Result<[u8; 32], CommandError> {
+    let salt = hex::decode(&auth.salt).map_err(|e| e.to_string())?;
+    let key = crypto::derive_key(passphrase, &salt)?;
+
+    if compute_verifier(&key) != auth.verifier {
+        return Err(CommandError::PassphraseIncorrect);
+    }
+
+    Ok(key)
+}
+
+/// Resolve the database key for a vault, using the session cache when no passphrase is given.
+/// Returns None for vaults without a passphrase.
+fn unlock_vault_key(
+    app: &AppHandle,
+    vault_path: &Path,
+    passphrase: Option<String>,
+) -> Result<Option<[u8; 32]>, CommandError> {
+    let Some(auth) = read_vault_auth(vault_path)? else {
+        return Ok(None);
+    };
+
+    let cache_key = vault_path.to_string_lossy().to_string();
+    let state = app.state::<Mutex<VaultPassphraseState>>();
+
+    let passphrase = match passphrase {
+        Some(p) => p,
+        None => state
+            .lock()
+            .map_err(|e| e.to_string())?
+            .cache
+            .get_passphrase(&cache_key)
+            .ok_or(CommandError::PassphraseRequired)?,
+    };
+
+    let key = verify_vault_passphrase(&auth, &passphrase)?;
+
+    if let Ok(mut guard) = state.lock() {
+        guard.cache.set_passphrase(&cache_key, passphrase);
+    }
+
+    Ok(Some(key))
+}
+
+/// Set, change, or remove (`new_passphrase` = None) the passphrase for the open vault.
+/// The index database is re-encrypted with the new key.
+#[tauri::command]
+pub async fn set_vault_passphrase(
+    app: AppHandle,
+    current_passphrase: Option<String>,
+    new_passphrase: Option<String>,
+) -> Result<(), CommandError> {
+    // Key derivation and waiting out in-flight readers both block
+    tauri::async_runtime::spawn_blocking(move || {
+        change_vault_passphrase(&app, current_passphrase, new_passphrase)
+    })
+    .await
+    .map_err(|e| CommandError::from(e.to_string()))?
+}
+
+fn change_vault_passphrase(
+    app: &AppHandle,
+    current_passphrase: Option<String>,
+    new_passphrase: Option<String>,
+) -> Result<(), CommandError> {
+    let vault_path = db::get_current_vault_path(app).ok_or(CommandError::NoVault)?;
+
+    // Changing an existing passphrase requires the current one
+    let old_key = unlock_vault_key(app, &vault_path, current_passphrase)?;
+
+    let cache_key = vault_path.to_string_lossy().to_string();
+    let state = app.state::<Mutex<VaultPassphraseState>>();
+    let auth_path = vault_auth_path(&vault_path);
+
+    match new_passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let salt = crypto::random_bytes(16);
+            let key = crypto::derive_key(&passphrase, &salt)?;
+
+            // Stage the new verifier before the rekey, so the index is never
+            // encrypted under a key no verifier on disk matches. The old verifier
+            // stays in place until the rename.
+            let auth = VaultAuth {
+                salt: hex::encode(&salt),
+                verifier: compute_verifier(&key),
+            };
+            let content = serde_json::to_string_pretty(&auth)?;
+            let staged_path = auth_path.with_extension("json.new");
+            fs::write(&staged_path, content)?;
+
+            if let Err(e) = db::rekey_vault_db(app, old_key.as_ref(), Some(&key)) {
+                let _ = fs::remove_file(&staged_path);
+                return Err(e.into());
+            }
+            fs::rename(&staged_path, &auth_path)?;
+
+            if let Ok(mut guard) = state.lock() {
+                guard.cache.set_passphrase(&cache_key, passphrase);
+            }
+        }
+        None => {
+            // Set the old verifier aside and put it back if the rekey fails
+            let backup_path = auth_path.with_extension("json.old");
+            let had_auth = auth_path.exists();
+            if had_auth {
+                fs::rename(&auth_path, &backup_path)?;
+            }
+
+            if let Err(e) = db::rekey_vault_db(app, old_key.as_ref(), None) {
+                if had_auth {
+                    fs::rename(&backup_path, &auth_path)?;
+                }
+                return Err(e.into());
+            }
+            if had_auth {
+                let _ = fs::remove_file(&backup_path);
+            }
+
+            if let Ok(mut guard) = state.lock() {
+                guard.cache.remove_passphrase(&cache_key);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check whether a vault requires a passphrase to open
+#[tauri::command]
+pub fn vault_has_passphrase(path: String) -> Result<bool, CommandError> {
+    Ok(read_vault_auth(Path::new(&path))?.is_some())
+}
+
 /// Result of saving an attachment
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AttachmentResult {
@@ -259,26 +743,43 @@ pub struct AttachmentResult {
     pub original_name: Option<String>,
 }
 
-/// Save an attachment to the vault's attachments folder
-/// Returns the relative path to use in markdown
+/// Path to write when embedding an attachment (as returned by `save_attachment`)
+/// in the note at `note_path`, following the vault's attachment embed style
 #[tauri::command]
-pub fn save_attachment(
+pub fn resolve_attachment_path(
     app: AppHandle,
-    filename: String,
-    data: Vec<u8>,
-) -> Result<AttachmentResult, String> {
-    let vault_path =
-        db::get_current_vault_path(&app).ok_or_else(|| "No vault is currently open".to_string())?;
+    note_path: String,
+    attachment_rel: String,
+) -> Result<String, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let settings = read_vault_settings(&vault_path).unwrap_or_default();
 
-    let attachments_dir = vault_path.join("attachments");
+    Ok(settings.attachments.embed_path(&note_path, &attachment_rel))
+}
 
-    // Create attachments directory if it doesn't exist
-    if !attachments_dir.exists() {
-        fs::create_dir_all(&attachments_dir).map_err(|e| e.to_string())?;
+/// Reduce a client-supplied attachment name to a bare file name, so it can't
+/// point outside the attachments folder
+fn attachment_file_name(filename: &str) -> Result<String, CommandError> {
+    // Either separator, whichever platform the name came from
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or("");
+    match Path::new(base).file_name().and_then(|s| s.to_str()) {
+        Some(name) if !name.trim().is_empty() && name != ".." => Ok(name.to_string()),
+        _ => Err(CommandError::invalid(format!(
+            "Invalid attachment name: {}",
+            filename
+        ))),
     }
+}
 
+/// Claim a filename in `attachments_dir` that doesn't collide with an existing
+/// file by creating it empty, so two uploads of the same name can't both take it.
+/// Returns the final name, whether it differs from `filename`, and the new file.
+fn reserve_attachment_name(
+    attachments_dir: &Path,
+    filename: &str,
+) -> Result<(String, bool, fs::File), CommandError> {
     // Parse filename into name and extension
-    let path = PathBuf::from(&filename);
+    let path = PathBuf::from(filename);
     let stem = path
         .file_stem()
         .and_then(|s| s.to_str())
@@ -286,24 +787,60 @@ pub fn save_attachment(
     let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
     // Find a unique filename
-    let mut final_name = filename.clone();
-    let mut target_path = attachments_dir.join(&final_name);
+    let mut final_name = filename.to_string();
     let mut counter = 1;
-    let mut renamed = false;
 
-    while target_path.exists() {
-        renamed = true;
+    loop {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(attachments_dir.join(&final_name))
+        {
+            Ok(file) => return Ok((final_name, counter > 1, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+
         if extension.is_empty() {
             final_name = format!("{}_{}", stem, counter);
         } else {
             final_name = format!("{}_{}.{}", stem, counter, extension);
         }
-        target_path = attachments_dir.join(&final_name);
         counter += 1;
     }
+}
+
+/// Create the attachments directory if it doesn't exist
+fn ensure_attachments_dir(vault_path: &Path) -> Result<PathBuf, CommandError> {
+    let attachments_dir = vault_path.join("attachments");
+
+    if !attachments_dir.exists() {
+        fs::create_dir_all(&attachments_dir)?;
+    }
+
+    Ok(attachments_dir)
+}
+
+/// Save an attachment to the vault's attachments folder
+/// Returns the relative path to use in markdown
+#[tauri::command]
+pub fn save_attachment(
+    app: AppHandle,
+    filename: String,
+    data: Vec<u8>,
+) -> Result<AttachmentResult, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let filename = attachment_file_name(&filename)?;
+
+    let attachments_dir = ensure_attachments_dir(&vault_path)?;
+    let (final_name, renamed, mut file) = reserve_attachment_name(&attachments_dir, &filename)?;
 
     // Write the file
-    fs::write(&target_path, &data).map_err(|e| e.to_string())?;
+    if let Err(e) = file.write_all(&data) {
+        drop(file);
+        let _ = fs::remove_file(attachments_dir.join(&final_name));
+        return Err(e.into());
+    }
 
     let relative_path = format!("attachments/{}", final_name);
 
@@ -314,10 +851,301 @@ pub fn save_attachment(
     })
 }
 
+/// A file in the vault's attachments folder
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+    /// Relative path from vault root (e.g., "attachments/image.png")
+    pub relative_path: String,
+    pub size: u64,
+    /// Inferred from the extension
+    pub mime_type: String,
+    pub modified_at: i64,
+    /// Number of notes that embed or link to the attachment
+    pub reference_count: usize,
+}
+
+/// Largest thumbnail edge when the caller doesn't ask for a size
+const DEFAULT_THUMBNAIL_DIM: u32 = 256;
+
+/// MIME type for an attachment, from its extension
+fn attachment_mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "csv" => "text/csv",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "pcap" | "pcapng" => "application/vnd.tcpdump.pcap",
+        _ => "application/octet-stream",
+    }
+}
+
+/// List every file in the attachments folder with its size, type and how many
+/// notes reference it
+#[tauri::command]
+pub fn list_attachments(app: AppHandle) -> Result<Vec<AttachmentInfo>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    let attachments_dir = vault_path.join("attachments");
+    if !attachments_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let ref_counts = db::get_attachment_ref_counts(&app)?;
+
+    let mut attachments = Vec::new();
+    for entry in WalkDir::new(&attachments_dir)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(&vault_path)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        attachments.push(AttachmentInfo {
+            reference_count: ref_counts.get(&relative_path).copied().unwrap_or(0),
+            mime_type: attachment_mime_type(entry.path()).to_string(),
+            size: metadata.len(),
+            modified_at,
+            relative_path,
+        });
+    }
+
+    attachments.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(attachments)
+}
+
+/// PNG thumbnail of an image attachment, scaled to fit within `max_dim` pixels
+/// (default 256). Images already that small are re-encoded at full size.
+#[tauri::command]
+pub async fn get_attachment_thumbnail(
+    app: AppHandle,
+    path: String,
+    max_dim: Option<u32>,
+) -> Result<Vec<u8>, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    if !path.replace('\\', "/").starts_with("attachments/") {
+        return Err(CommandError::invalid("Not an attachment path"));
+    }
+    let full_path = validate_vault_path(&vault_path, &path)?;
+    let max_dim = max_dim.unwrap_or(DEFAULT_THUMBNAIL_DIM).clamp(16, 2048);
+
+    // Decoding and scaling large images is CPU-bound
+    tauri::async_runtime::spawn_blocking(move || {
+        let bytes = fs::read(&full_path)?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| CommandError::invalid(format!("Cannot decode image: {}", e)))?;
+
+        let image = if image.width() > max_dim || image.height() > max_dim {
+            image.thumbnail(max_dim, max_dim)
+        } else {
+            image
+        };
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(png)
+    })
+    .await
+    .map_err(|e| CommandError::from(e.to_string()))?
+}
+
+// ============================================================================
+// Chunked Attachment Upload
+// ============================================================================
+
+/// An attachment being streamed to a temp file in chunks
+pub struct PendingAttachment {
+    filename: String,
+    temp_path: PathBuf,
+    file: fs::File,
+}
+
+/// State for in-progress chunked attachment uploads, keyed by token
+#[derive(Default)]
+pub struct AttachmentUploadState {
+    pub uploads: HashMap<String, PendingAttachment>,
+}
+
+/// Delete `.part` files in the uploads directory that don't belong to an
+/// upload still in progress (left behind by a crash or a closed window)
+fn remove_stale_uploads(vault_path: &Path, active: &HashMap<String, PendingAttachment>) {
+    let Ok(entries) = fs::read_dir(vault_path.join(UPLOADS_DIR)) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("part") {
+            continue;
+        }
+        let token = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        if !active.contains_key(token) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Start a chunked attachment upload. Returns a token for the following calls.
+#[tauri::command]
+pub fn begin_attachment(app: AppHandle, filename: String) -> Result<String, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+    let filename = attachment_file_name(&filename)?;
+
+    let uploads_dir = vault_path.join(UPLOADS_DIR);
+    fs::create_dir_all(&uploads_dir)?;
+
+    let state = app.state::<Mutex<AttachmentUploadState>>();
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+    remove_stale_uploads(&vault_path, &state.uploads);
+
+    let token = Uuid::new_v4().to_string();
+    let temp_path = uploads_dir.join(format!("{}.part", token));
+    let file = fs::File::create(&temp_path)?;
+
+    state.uploads.insert(
+        token.clone(),
+        PendingAttachment {
+            filename,
+            temp_path,
+            file,
+        },
+    );
+
+    Ok(token)
+}
+
+/// Append a chunk of data to an in-progress upload
+#[tauri::command]
+pub fn append_attachment(
+    app: AppHandle,
+    token: String,
+    chunk: Vec<u8>,
+) -> Result<(), CommandError> {
+    let state = app.state::<Mutex<AttachmentUploadState>>();
+    let mut state = state.lock().map_err(|e| e.to_string())?;
+
+    let upload = state
+        .uploads
+        .get_mut(&token)
+        .ok_or_else(|| format!("Unknown upload: {}", token))?;
+
+    upload.file.write_all(&chunk).map_err(CommandError::from)
+}
+
+/// Finish an upload and move it into the attachments folder
+#[tauri::command]
+pub fn finish_attachment(app: AppHandle, token: String) -> Result<AttachmentResult, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
+
+    let upload = {
+        let state = app.state::<Mutex<AttachmentUploadState>>();
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state
+            .uploads
+            .remove(&token)
+            .ok_or_else(|| format!("Unknown upload: {}", token))?
+    };
+
+    let PendingAttachment {
+        filename,
+        temp_path,
+        file,
+    } = upload;
+
+    file.sync_all()?;
+    drop(file);
+
+    let attachments_dir = ensure_attachments_dir(&vault_path)?;
+    let (final_name, renamed, placeholder) =
+        match reserve_attachment_name(&attachments_dir, &filename) {
+            Ok(reserved) => reserved,
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        };
+    drop(placeholder);
+
+    // Same filesystem as the vault, so this is an atomic move over the reserved name
+    let final_path = attachments_dir.join(&final_name);
+    if let Err(e) = fs::rename(&temp_path, &final_path) {
+        let _ = fs::remove_file(&temp_path);
+        let _ = fs::remove_file(&final_path);
+        return Err(e.into());
+    }
+
+    Ok(AttachmentResult {
+        relative_path: format!("attachments/{}", final_name),
+        renamed,
+        original_name: if renamed { Some(filename) } else { None },
+    })
+}
+
+/// Abort an upload and delete its temp file
+#[tauri::command]
+pub fn cancel_attachment(app: AppHandle, token: String) -> Result<(), CommandError> {
+    let upload = {
+        let state = app.state::<Mutex<AttachmentUploadState>>();
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state.uploads.remove(&token)
+    };
+
+    if let Some(upload) = upload {
+        drop(upload.file);
+        let _ = fs::remove_file(&upload.temp_path);
+    }
+
+    Ok(())
+}
+
 /// Get the current user identity for this vault
 /// Reads from .kairo-user file in the vault root (gitignored)
 #[tauri::command]
-pub fn get_vault_user(app: AppHandle) -> Result<Option<String>, String> {
+pub fn get_vault_user(app: AppHandle) -> Result<Option<String>, CommandError> {
     let vault_path = match db::get_current_vault_path(&app) {
         Some(p) => p,
         None => return Ok(None),
@@ -328,7 +1156,7 @@ pub fn get_vault_user(app: AppHandle) -> Result<Option<String>, String> {
         return Ok(None);
     }
 
-    let content = fs::read_to_string(&user_file).map_err(|e| e.to_string())?;
+    let content = fs::read_to_string(&user_file)?;
     let username = content.trim().to_string();
 
     if username.is_empty() {
@@ -338,6 +1166,84 @@ pub fn get_vault_user(app: AppHandle) -> Result<Option<String>, String> {
     }
 }
 
+/// A board the current user owns or is a member of
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBoard {
+    pub id: String,
+    pub name: String,
+    pub is_owner: bool,
+}
+
+/// The current user and their place in the team roster
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentUserContext {
+    pub username: Option<String>,
+    /// Listed in the board members table under any board
+    pub is_member: bool,
+    pub personal_board_id: Option<String>,
+    pub boards: Vec<UserBoard>,
+}
+
+/// Get the current user together with their personal board and board
+/// memberships, so startup needs a single call
+#[tauri::command]
+pub fn get_current_user_context(app: AppHandle) -> Result<CurrentUserContext, CommandError> {
+    let Some(username) = get_vault_user(app.clone())? else {
+        return Ok(CurrentUserContext::default());
+    };
+
+    let username_lower = username.to_lowercase();
+    let context = db::with_db(&app, |conn| {
+        let is_member: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM kanban_board_members WHERE LOWER(name) = ?1)",
+            params![username_lower],
+            |row| row.get(0),
+        )?;
+
+        let personal_board_id: Option<String> = conn
+            .query_row(
+                "SELECT id FROM kanban_boards WHERE LOWER(owner_name) = ?1",
+                params![username_lower],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT b.id, b.name, COALESCE(LOWER(b.owner_name) = ?1, 0)
+            FROM kanban_boards b
+            WHERE LOWER(b.owner_name) = ?1
+               OR EXISTS (
+                   SELECT 1 FROM kanban_board_members m
+                   WHERE m.board_id = b.id AND LOWER(m.name) = ?1
+               )
+            ORDER BY b.name COLLATE NOCASE
+            "#,
+        )?;
+        let boards = stmt
+            .query_map(params![username_lower], |row| {
+                Ok(UserBoard {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    is_owner: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(CurrentUserContext {
+            username: Some(username),
+            is_member,
+            personal_board_id,
+            boards,
+        })
+    })?;
+
+    Ok(context)
+}
+
 /// Result from setting vault user (includes auto-created board if any)
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -350,17 +1256,16 @@ pub struct SetUserResult {
 /// Set the current user identity for this vault
 /// Writes to .kairo-user file and auto-creates personal kanban board + member entry
 #[tauri::command]
-pub fn set_vault_user(app: AppHandle, username: String) -> Result<SetUserResult, String> {
-    let vault_path =
-        db::get_current_vault_path(&app).ok_or_else(|| "No vault is currently open".to_string())?;
+pub fn set_vault_user(app: AppHandle, username: String) -> Result<SetUserResult, CommandError> {
+    let vault_path = db::get_current_vault_path(&app).ok_or(CommandError::NoVault)?;
 
     let username = username.trim().to_string();
     if username.is_empty() {
-        return Err("Username cannot be empty".to_string());
+        return Err(CommandError::invalid("Username cannot be empty"));
     }
 
     // Check if name is taken and create board/member if needed
-    let (created_board, board_id) = with_db(&app, |conn| {
+    let (created_board, board_id) = with_db_tx(&app, |conn| {
         let username_lower = username.to_lowercase();
 
         // Check if a personal board with this owner already exists
@@ -388,8 +1293,7 @@ pub fn set_vault_user(app: AppHandle, username: String) -> Result<SetUserResult,
                 conn.execute(
                     "INSERT INTO kanban_board_members (id, board_id, name, added_at) VALUES (?1, ?2, ?3, ?4)",
                     params![member_id, board_id, username, now],
-                )
-                .map_err(|e| e.to_string())?;
+                )?;
             }
 
             return Ok((false, Some(board_id)));
@@ -428,30 +1332,27 @@ pub fn set_vault_user(app: AppHandle, username: String) -> Result<SetUserResult,
             })
             .collect();
 
-        let columns_json = serde_json::to_string(&kanban_columns).map_err(|e| e.to_string())?;
+        let columns_json = serde_json::to_string(&kanban_columns)?;
 
         // Create the personal board
         conn.execute(
             "INSERT INTO kanban_boards (id, name, columns, owner_name, created_at, modified_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![board_id, username, columns_json, username, now, now],
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
         // Add user as a board member
         let member_id = Uuid::new_v4().to_string();
         conn.execute(
             "INSERT INTO kanban_board_members (id, board_id, name, added_at) VALUES (?1, ?2, ?3, ?4)",
             params![member_id, board_id, username, now],
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
 
         Ok((true, Some(board_id.clone())))
-    })
-    .map_err(|e| e.to_string())?;
+    })?;
 
     // Write to .kairo-user file
     let user_file = vault_path.join(".kairo-user");
-    fs::write(&user_file, &username).map_err(|e| e.to_string())?;
+    fs::write(&user_file, &username)?;
 
     // Ensure gitignore has all necessary entries (including .kairo-user)
     ensure_gitignore(&vault_path);
@@ -0,0 +1,136 @@
+//! Passphrase-based encryption for notes at rest
+//! Notes are sealed with XChaCha20-Poly1305 using a key derived from the passphrase with Argon2id
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::Rng;
+
+/// First line of every encrypted note. An HTML comment, so it renders invisibly
+/// in any markdown viewer that opens the raw file.
+pub const ENVELOPE_MARKER: &str = "<!-- kairo:encrypted v1 -->";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const LINE_WIDTH: usize = 76;
+
+/// Parsed encrypted note envelope
+struct Envelope {
+    title: String,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+/// Check whether note content is an encrypted envelope
+pub fn is_encrypted(content: &str) -> bool {
+    content.starts_with(ENVELOPE_MARKER)
+}
+
+/// Derive a 256-bit key from a passphrase and salt
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Generate `len` random bytes
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    let mut rng = rand::rng();
+    (0..len).map(|_| rng.random()).collect()
+}
+
+/// Encrypt note content into an envelope. The title is stored in plaintext so
+/// the note can still be listed and linked.
+pub fn encrypt_note_content(
+    plaintext: &str,
+    title: &str,
+    passphrase: &str,
+) -> Result<String, String> {
+    let salt = random_bytes(SALT_LEN);
+    let nonce = random_bytes(NONCE_LEN);
+    let key = derive_key(passphrase, &salt)?;
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let encoded = hex::encode(&ciphertext);
+    let body: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(LINE_WIDTH)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+
+    // Keep the title on one line so the header stays parseable
+    let title = title.replace(['\r', '\n'], " ");
+
+    Ok(format!(
+        "{}\ntitle: {}\nsalt: {}\nnonce: {}\n\n{}\n",
+        ENVELOPE_MARKER,
+        title,
+        hex::encode(&salt),
+        hex::encode(&nonce),
+        body.join("\n")
+    ))
+}
+
+/// Decrypt an envelope back to the original note content
+pub fn decrypt_note_content(content: &str, passphrase: &str) -> Result<String, String> {
+    let envelope = parse_envelope(content).ok_or("Note is not a valid encrypted envelope")?;
+    let key = derive_key(passphrase, &envelope.salt)?;
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&envelope.nonce),
+            envelope.ciphertext.as_slice(),
+        )
+        .map_err(|_| "Incorrect passphrase or corrupted note".to_string())?;
+
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+/// Get the plaintext title stored in an envelope
+pub fn envelope_title(content: &str) -> Option<String> {
+    parse_envelope(content).map(|e| e.title)
+}
+
+fn parse_envelope(content: &str) -> Option<Envelope> {
+    // Tolerate CRLF line endings introduced by git checkouts on Windows
+    let content = content.replace("\r\n", "\n");
+    let rest = content.strip_prefix(ENVELOPE_MARKER)?;
+    let (header, body) = rest.trim_start_matches('\n').split_once("\n\n")?;
+
+    let mut title = String::new();
+    let mut salt = None;
+    let mut nonce = None;
+
+    for line in header.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "title" => title = value.to_string(),
+                "salt" => salt = hex::decode(value).ok(),
+                "nonce" => nonce = hex::decode(value).ok(),
+                _ => {}
+            }
+        }
+    }
+
+    let salt = salt?;
+    let nonce = nonce.filter(|n| n.len() == NONCE_LEN)?;
+    let encoded: String = body.split_whitespace().collect();
+    let ciphertext = hex::decode(encoded).ok()?;
+
+    Some(Envelope {
+        title,
+        salt,
+        nonce,
+        ciphertext,
+    })
+}
@@ -6,6 +6,8 @@ use rusqlite::{params_from_iter, Connection, Row};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::{resolve_date_span, span_start_date, DateRange};
+
 /// Query types supported by Dataview
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -30,6 +32,8 @@ pub struct SerializedCondition {
     pub field: Option<String>,
     pub operator: Option<String>,
     pub value: Option<serde_json::Value>,
+    /// "date" when `value` is a date expression from `date(...)`, e.g. `today-7d`
+    pub value_type: Option<String>,
     pub conditions: Option<Vec<SerializedCondition>>,
 }
 
@@ -38,6 +42,10 @@ pub struct SerializedCondition {
 pub struct SortClause {
     pub field: String,
     pub direction: String, // "ASC" or "DESC"
+    /// Compare values as "number" or "text"; by default frontmatter values keep
+    /// their JSON type, so numbers sort before strings
+    #[serde(default)]
+    pub sort_as: Option<String>,
 }
 
 /// The full serialized query from frontend
@@ -60,6 +68,43 @@ pub struct DataviewRow {
     pub values: HashMap<String, serde_json::Value>,
 }
 
+/// One row of a TABLE result; `cells` line up with the table headers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataviewTableRow {
+    pub path: String,
+    pub title: String,
+    pub cells: Vec<serde_json::Value>,
+}
+
+/// Typed TABLE output. Cells keep their JSON type (number, string, bool,
+/// array); fields a note doesn't have are null.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataviewTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<DataviewTableRow>,
+}
+
+/// A checklist item returned by a TASK query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataviewTask {
+    /// 1-based line of the task in its note
+    pub line_number: i64,
+    pub text: String,
+    pub completed: bool,
+    pub indent: i64,
+}
+
+/// TASK results for one note, in line order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataviewTaskGroup {
+    pub note_id: String,
+    pub path: String,
+    pub title: String,
+    pub tasks: Vec<DataviewTask>,
+}
+
 /// Query execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -68,6 +113,10 @@ pub struct DataviewResult {
     pub result_type: String,
     pub columns: Option<Vec<String>>,
     pub rows: Vec<DataviewRow>,
+    /// Set for TABLE queries
+    pub table: Option<DataviewTable>,
+    /// Set for TASK queries, grouped by parent note
+    pub tasks: Option<Vec<DataviewTaskGroup>>,
     pub error: Option<String>,
     pub execution_time: Option<u64>,
 }
@@ -78,6 +127,8 @@ impl DataviewResult {
             result_type: "LIST".to_string(),
             columns: None,
             rows: vec![],
+            table: None,
+            tasks: None,
             error: Some(message.to_string()),
             execution_time: None,
         }
@@ -101,15 +152,32 @@ fn build_and_execute(conn: &Connection, query: &SerializedQuery) -> Result<Datav
     let mut sql = String::new();
     let mut params: Vec<String> = vec![];
 
-    // SELECT clause
+    // SELECT clause; TABLE columns and TASK columns follow the fixed columns
     sql.push_str("SELECT n.path, n.title, n.created_at, n.modified_at, n.frontmatter");
+    let is_table = query.query_type == "TABLE";
+    let is_task = query.query_type == "TASK";
+    if is_table {
+        for (i, field) in query.fields.iter().enumerate() {
+            sql.push_str(&format!(", {} AS col{}", map_field_to_json_sql(field), i));
+        }
+    }
+    if is_task {
+        sql.push_str(", n.id, tk.line_number, tk.text, tk.completed, tk.indent");
+    }
 
-    // FROM clause
+    // FROM clause; TASK queries return one row per task
     sql.push_str(" FROM notes n");
+    if is_task {
+        sql.push_str(" INNER JOIN tasks tk ON tk.note_id = n.id");
+    }
 
     // Join with tags if needed for filtering
     let needs_tags_join = query.from_sources.iter().any(|s| s.source_type == "tag")
-        || condition_references_tags(&query.where_clause);
+        || condition_references_tags(&query.where_clause)
+        || query
+            .sort_clauses
+            .iter()
+            .any(|s| s.field.starts_with("file.tags") || s.field == "tags");
 
     if needs_tags_join {
         sql.push_str(" LEFT JOIN tags t ON t.note_id = n.id");
@@ -149,7 +217,7 @@ fn build_and_execute(conn: &Connection, query: &SerializedQuery) -> Result<Datav
 
     // WHERE conditions
     if let Some(ref condition) = query.where_clause {
-        let (cond_sql, cond_params) = build_condition(condition)?;
+        let (cond_sql, cond_params) = build_condition(condition, is_task)?;
         where_parts.push(cond_sql);
         params.extend(cond_params);
     }
@@ -161,26 +229,40 @@ fn build_and_execute(conn: &Connection, query: &SerializedQuery) -> Result<Datav
 
     // GROUP BY (if we joined tags, we need to group)
     if needs_tags_join {
-        sql.push_str(" GROUP BY n.id");
+        sql.push_str(if is_task {
+            " GROUP BY n.id, tk.id"
+        } else {
+            " GROUP BY n.id"
+        });
     }
 
     // ORDER BY clause
     if !query.sort_clauses.is_empty() {
         sql.push_str(" ORDER BY ");
-        let order_parts: Vec<String> = query
-            .sort_clauses
-            .iter()
-            .map(|s| {
-                let field = map_field_to_sql(&s.field);
-                let dir = if s.direction.to_uppercase() == "DESC" {
-                    "DESC"
-                } else {
-                    "ASC"
-                };
-                format!("{} {}", field, dir)
-            })
-            .collect();
+        let mut order_parts: Vec<String> = vec![];
+        for s in &query.sort_clauses {
+            let field = map_query_field(&s.field, is_task);
+            let dir = if s.direction.to_uppercase() == "DESC" {
+                "DESC"
+            } else {
+                "ASC"
+            };
+            let key = match s.sort_as.as_deref().map(str::to_lowercase).as_deref() {
+                None | Some("auto") => field.clone(),
+                Some("number") => format!("CAST({} AS REAL)", field),
+                Some("text") => format!("CAST({} AS TEXT) COLLATE NOCASE", field),
+                Some(other) => return Err(format!("Unknown sort type: {}", other)),
+            };
+            // Notes missing the field go last in either direction
+            order_parts.push(format!("{} IS NULL, {} {}", field, key, dir));
+        }
+        if is_task {
+            order_parts.push("tk.line_number".to_string());
+        }
         sql.push_str(&order_parts.join(", "));
+    } else if is_task {
+        // Keep each note's tasks together, in document order
+        sql.push_str(" ORDER BY n.modified_at DESC, n.id, tk.line_number");
     } else {
         // Default sort by modified time
         sql.push_str(" ORDER BY n.modified_at DESC");
@@ -188,6 +270,9 @@ fn build_and_execute(conn: &Connection, query: &SerializedQuery) -> Result<Datav
 
     // LIMIT clause
     if let Some(limit) = query.limit {
+        if limit < 0 {
+            return Err(format!("LIMIT must not be negative: {}", limit));
+        }
         sql.push_str(&format!(" LIMIT {}", limit));
     }
 
@@ -201,26 +286,72 @@ fn build_and_execute(conn: &Connection, query: &SerializedQuery) -> Result<Datav
 
     let rows = stmt
         .query_map(params_from_iter(param_refs.iter()), |row| {
-            Ok(extract_row(row, &query.fields))
+            let cells = if is_table {
+                extract_cells(row, query.fields.len())
+            } else {
+                vec![]
+            };
+            let task = if is_task {
+                Some(extract_task(row)?)
+            } else {
+                None
+            };
+            Ok((extract_row(row, &query.fields), cells, task))
         })
         .map_err(|e| format!("Query error: {}", e))?;
 
     let mut result_rows = vec![];
+    let mut table_rows = vec![];
+    let mut task_groups: Vec<DataviewTaskGroup> = vec![];
+    let mut group_index: HashMap<String, usize> = HashMap::new();
     for row_result in rows {
         match row_result {
-            Ok(row) => result_rows.push(row),
+            Ok((row, _, Some((note_id, task)))) => {
+                // One result row per note; its tasks are collected under it
+                if let Some(&i) = group_index.get(&note_id) {
+                    task_groups[i].tasks.push(task);
+                } else {
+                    group_index.insert(note_id.clone(), task_groups.len());
+                    task_groups.push(DataviewTaskGroup {
+                        note_id,
+                        path: row.path.clone(),
+                        title: row.title.clone(),
+                        tasks: vec![task],
+                    });
+                    result_rows.push(row);
+                }
+            }
+            Ok((mut row, cells, None)) => {
+                if is_table {
+                    // Projected cells also fill `values`, so nested fields and file.tags render
+                    for (field, cell) in query.fields.iter().zip(&cells) {
+                        row.values.insert(field.clone(), cell.clone());
+                    }
+                    table_rows.push(DataviewTableRow {
+                        path: row.path.clone(),
+                        title: row.title.clone(),
+                        cells,
+                    });
+                }
+                result_rows.push(row);
+            }
             Err(e) => return Err(format!("Row error: {}", e)),
         }
     }
 
     Ok(DataviewResult {
         result_type: query.query_type.clone(),
-        columns: if query.query_type == "TABLE" {
+        columns: if is_table {
             Some(query.fields.clone())
         } else {
             None
         },
         rows: result_rows,
+        table: is_table.then(|| DataviewTable {
+            headers: query.fields.clone(),
+            rows: table_rows,
+        }),
+        tasks: is_task.then_some(task_groups),
         error: None,
         execution_time: None,
     })
@@ -245,7 +376,10 @@ fn condition_references_tags(condition: &Option<SerializedCondition>) -> bool {
     }
 }
 
-fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<String>), String> {
+fn build_condition(
+    condition: &SerializedCondition,
+    is_task: bool,
+) -> Result<(String, Vec<String>), String> {
     match condition.condition_type.as_str() {
         "comparison" => {
             let field = condition
@@ -261,7 +395,23 @@ fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<Strin
                 .as_ref()
                 .ok_or("Missing value in comparison")?;
 
-            let sql_field = map_field_to_sql(field);
+            let sql_field = map_query_field(field, is_task);
+            let is_date_value = condition.value_type.as_deref() == Some("date");
+            if is_date_column(field) {
+                if let Some(date_condition) =
+                    build_date_condition(&sql_field, operator, value, is_date_value)?
+                {
+                    return Ok(date_condition);
+                }
+            }
+            // Frontmatter dates are text, so `date(...)` compares as an ISO date
+            let date_text;
+            let value = if is_date_value {
+                date_text = date_value_text(value)?;
+                &date_text
+            } else {
+                value
+            };
             let (sql_op, sql_value) = map_operator_and_value(operator, value)?;
 
             // Add ESCAPE clause for LIKE operators to support escaped wildcards
@@ -281,7 +431,7 @@ fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<Strin
             let mut parts = vec![];
             let mut params = vec![];
             for c in conditions {
-                let (sql, p) = build_condition(c)?;
+                let (sql, p) = build_condition(c, is_task)?;
                 parts.push(sql);
                 params.extend(p);
             }
@@ -295,7 +445,7 @@ fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<Strin
             let mut parts = vec![];
             let mut params = vec![];
             for c in conditions {
-                let (sql, p) = build_condition(c)?;
+                let (sql, p) = build_condition(c, is_task)?;
                 parts.push(sql);
                 params.extend(p);
             }
@@ -309,7 +459,7 @@ fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<Strin
             if conditions.is_empty() {
                 return Err("Empty NOT conditions".to_string());
             }
-            let (sql, params) = build_condition(&conditions[0])?;
+            let (sql, params) = build_condition(&conditions[0], is_task)?;
             Ok((format!("NOT ({})", sql), params))
         }
         _ => Err(format!(
@@ -319,6 +469,57 @@ fn build_condition(condition: &SerializedCondition) -> Result<(String, Vec<Strin
     }
 }
 
+fn is_date_column(field: &str) -> bool {
+    matches!(field, "file.ctime" | "created" | "file.mtime" | "modified")
+}
+
+/// Compare a timestamp column against a date expression, or None if the value
+/// isn't one. Any string that parses as a date is accepted; `=` matches the
+/// whole day, and a `date(...)` value that doesn't parse is an error.
+fn build_date_condition(
+    sql_field: &str,
+    operator: &str,
+    value: &serde_json::Value,
+    is_date_value: bool,
+) -> Result<Option<(String, Vec<String>)>, String> {
+    let Some(expr) = value.as_str() else {
+        return Ok(None);
+    };
+    let span = match resolve_date_span(expr) {
+        Some(span) => span,
+        None if is_date_value => return Err(format!("Invalid date: {}", expr)),
+        None => return Ok(None),
+    };
+
+    let negate = operator == "!=";
+    let range = DateRange::from_comparison(if negate { "=" } else { operator }, span)
+        .ok_or_else(|| format!("Operator {} can't be used with dates", operator))?;
+    let mut parts = vec![];
+    let mut params = vec![];
+    if let Some(from) = range.from {
+        parts.push(format!("{} >= ?", sql_field));
+        params.push(from.to_string());
+    }
+    if let Some(to) = range.to {
+        parts.push(format!("{} < ?", sql_field));
+        params.push(to.to_string());
+    }
+    let sql = format!("({})", parts.join(" AND "));
+    Ok(Some((
+        if negate { format!("NOT {}", sql) } else { sql },
+        params,
+    )))
+}
+
+/// Resolve a `date(...)` value to the ISO date it starts on
+fn date_value_text(value: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let expr = value.as_str().unwrap_or_default();
+    resolve_date_span(expr)
+        .and_then(span_start_date)
+        .map(serde_json::Value::String)
+        .ok_or_else(|| format!("Invalid date: {}", expr))
+}
+
 /// Validate that a field name is safe for use in SQL
 /// Only allows alphanumeric characters, underscores, dots, and hyphens
 fn is_safe_field_name(field: &str) -> bool {
@@ -329,6 +530,20 @@ fn is_safe_field_name(field: &str) -> bool {
             .all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '-')
 }
 
+/// Map a field for WHERE and SORT; TASK queries resolve task fields first
+fn map_query_field(field: &str, is_task: bool) -> String {
+    let task_column = match field {
+        "completed" | "task.completed" => Some("tk.completed"),
+        "text" | "task.text" => Some("tk.text"),
+        "line" | "task.line" => Some("tk.line_number"),
+        _ => None,
+    };
+    match task_column {
+        Some(column) if is_task => column.to_string(),
+        _ => map_field_to_sql(field),
+    }
+}
+
 fn map_field_to_sql(field: &str) -> String {
     match field {
         "file.name" | "title" => "n.title".to_string(),
@@ -351,6 +566,27 @@ fn map_field_to_sql(field: &str) -> String {
     }
 }
 
+/// SQL expression yielding a field's value as JSON text (NULL when missing),
+/// so TABLE cells keep their type
+fn map_field_to_json_sql(field: &str) -> String {
+    match field {
+        "file.tags" | "tags" => {
+            "(SELECT json_group_array(tag) FROM tags WHERE note_id = n.id)".to_string()
+        }
+        "file.name" | "title" | "file.path" | "path" | "file.ctime" | "created" | "file.mtime"
+        | "modified" | "file.folder" => format!("json_quote({})", map_field_to_sql(field)),
+        _ if is_safe_field_name(field) => {
+            // Quote each key so names like `due-date` form a valid JSON path
+            let path: String = field
+                .split('.')
+                .map(|key| format!(".\"{}\"", key))
+                .collect();
+            format!("(n.frontmatter -> '${}')", path)
+        }
+        _ => "NULL".to_string(),
+    }
+}
+
 /// Escape SQL LIKE pattern special characters
 fn escape_like_pattern(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -395,6 +631,33 @@ fn map_operator_and_value(
     Ok((sql_op.to_string(), sql_value))
 }
 
+/// Parse the projected TABLE columns, which follow the five fixed columns
+fn extract_cells(row: &Row, count: usize) -> Vec<serde_json::Value> {
+    (0..count)
+        .map(|i| {
+            row.get::<_, Option<String>>(5 + i)
+                .ok()
+                .flatten()
+                .map(|text| serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text)))
+                .unwrap_or(serde_json::Value::Null)
+        })
+        .collect()
+}
+
+/// Parse the TASK columns, which follow the five fixed columns
+fn extract_task(row: &Row) -> Result<(String, DataviewTask), rusqlite::Error> {
+    let note_id: String = row.get(5)?;
+    Ok((
+        note_id,
+        DataviewTask {
+            line_number: row.get(6)?,
+            text: row.get(7)?,
+            completed: row.get(8)?,
+            indent: row.get(9)?,
+        },
+    ))
+}
+
 fn extract_row(row: &Row, fields: &[String]) -> DataviewRow {
     let path: String = row.get(0).unwrap_or_default();
     let title: String = row.get(1).unwrap_or_default();
@@ -437,3 +700,90 @@ fn extract_row(row: &Row, fields: &[String]) -> DataviewRow {
         values,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    /// Three notes whose mtime order differs from their `priority` order
+    fn fixture_vault() -> Connection {
+        let conn = open_test_db();
+        for (id, modified_at, priority) in [("a", 300, 2), ("b", 100, 10), ("c", 200, 1)] {
+            conn.execute(
+                "INSERT INTO notes (id, path, title, content, created_at, modified_at, frontmatter)
+                 VALUES (?1, ?2, ?1, '', 0, ?3, ?4)",
+                rusqlite::params![
+                    id,
+                    format!("notes/{}.md", id),
+                    modified_at,
+                    format!("{{\"priority\": {}}}", priority)
+                ],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn list_query(field: &str, direction: &str, limit: Option<i32>) -> SerializedQuery {
+        SerializedQuery {
+            query_type: "LIST".to_string(),
+            fields: vec![],
+            from_sources: vec![],
+            where_clause: None,
+            sort_clauses: vec![SortClause {
+                field: field.to_string(),
+                direction: direction.to_string(),
+                sort_as: None,
+            }],
+            group_by: None,
+            limit,
+        }
+    }
+
+    fn titles(result: &DataviewResult) -> Vec<&str> {
+        assert_eq!(result.error, None);
+        result.rows.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn sorts_by_file_mtime() {
+        let conn = fixture_vault();
+        let asc = execute_query(&conn, &list_query("file.mtime", "ASC", None));
+        let desc = execute_query(&conn, &list_query("file.mtime", "DESC", None));
+
+        assert_eq!(titles(&asc), ["b", "c", "a"]);
+        assert_eq!(titles(&desc), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn sorts_numeric_frontmatter_as_numbers() {
+        let conn = fixture_vault();
+        let asc = execute_query(&conn, &list_query("priority", "ASC", None));
+        let desc = execute_query(&conn, &list_query("priority", "DESC", None));
+
+        // 10 sorts after 2, not between 1 and 2 as it would as text
+        assert_eq!(titles(&asc), ["c", "a", "b"]);
+        assert_eq!(titles(&desc), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn limit_truncates_after_sorting() {
+        let conn = fixture_vault();
+        let by_mtime = execute_query(&conn, &list_query("file.mtime", "DESC", Some(2)));
+        let by_priority = execute_query(&conn, &list_query("priority", "DESC", Some(1)));
+        let none = execute_query(&conn, &list_query("priority", "ASC", Some(0)));
+
+        assert_eq!(titles(&by_mtime), ["a", "c"]);
+        assert_eq!(titles(&by_priority), ["b"]);
+        assert!(titles(&none).is_empty());
+    }
+
+    #[test]
+    fn negative_limit_is_an_error() {
+        let conn = fixture_vault();
+        let result = execute_query(&conn, &list_query("file.mtime", "ASC", Some(-1)));
+        assert!(result.error.is_some());
+        assert!(result.rows.is_empty());
+    }
+}
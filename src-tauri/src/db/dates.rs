@@ -0,0 +1,208 @@
+//! Date expressions for search filters and dataview queries
+
+use chrono::{DateTime, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// An optional base followed by an offset, e.g. `-7d`, `today-2w`, `now+1m`
+static OFFSET_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(now|today|yesterday|tomorrow|\d{4}-\d{2}-\d{2})?([+-])(\d+)([dwmy])$").unwrap()
+});
+
+/// Half-open range of unix seconds, `[from, to)`; a missing end is unbounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl DateRange {
+    /// Range matched by comparing a timestamp against `span` with `operator`.
+    /// `=` covers the whole span and `<=` runs to its end; the other
+    /// operators compare against its start, so `> today` means after midnight.
+    pub fn from_comparison(operator: &str, span: (i64, i64)) -> Option<Self> {
+        let (start, end) = span;
+        let (from, to) = match operator {
+            ">" => (Some(start + 1), None),
+            ">=" => (Some(start), None),
+            "<" => (None, Some(start)),
+            "<=" => (None, Some(end)),
+            "=" => (Some(start), Some(end)),
+            _ => return None,
+        };
+        Some(Self { from, to })
+    }
+
+    /// Narrow this range to also satisfy `other`
+    pub fn intersect(self, other: DateRange) -> DateRange {
+        DateRange {
+            from: self.from.max(other.from),
+            to: match (self.to, other.to) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+/// Resolve a date expression to the span it covers, as unix seconds
+/// `[start, end)`. Days are local calendar days.
+///
+/// Accepts `now`, `today`, `yesterday`, `tomorrow`, offsets such as `-7d`,
+/// `-2w`, `-1m` or `+1y` (from today, or from a base like `now-1d` or
+/// `2024-01-01+1w`), ISO dates and date-times, and unix timestamps.
+pub fn resolve_date_span(expr: &str) -> Option<(i64, i64)> {
+    resolve_date_span_at(expr, Local::now())
+}
+
+/// [`resolve_date_span`] with relative expressions counted from `now`
+pub fn resolve_date_span_at(expr: &str, now: DateTime<Local>) -> Option<(i64, i64)> {
+    let today = now.date_naive();
+    let expr: String = expr
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+
+    if let Some(caps) = OFFSET_REGEX.captures(&expr) {
+        let amount: u32 = caps[3].parse().ok()?;
+        let backwards = &caps[2] == "-";
+        let base = match caps.get(1).map(|m| m.as_str()) {
+            Some("now") => now.naive_local(),
+            Some(day) => resolve_day(day, today)?.and_hms_opt(0, 0, 0)?,
+            None => today.and_hms_opt(0, 0, 0)?,
+        };
+        let shifted = shift(base, backwards, amount, &caps[4])?;
+        if caps.get(1).is_some_and(|m| m.as_str() == "now") {
+            let ts = Local.from_local_datetime(&shifted).earliest()?.timestamp();
+            return Some((ts, ts + 1));
+        }
+        return day_span(shifted.date());
+    }
+
+    if expr == "now" {
+        let ts = now.timestamp();
+        return Some((ts, ts + 1));
+    }
+    if let Some(day) = resolve_day(&expr, today) {
+        return day_span(day);
+    }
+    // Timestamps and date-times are instants
+    super::parse_created_timestamp(&expr).map(|ts| (ts, ts + 1))
+}
+
+/// A named day relative to `today`, or an ISO date
+fn resolve_day(expr: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match expr {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        "tomorrow" => today.succ_opt(),
+        _ => NaiveDate::parse_from_str(expr, "%Y-%m-%d").ok(),
+    }
+}
+
+fn shift(base: NaiveDateTime, backwards: bool, amount: u32, unit: &str) -> Option<NaiveDateTime> {
+    match unit {
+        "d" | "w" => {
+            let days = Duration::days(amount as i64 * if unit == "w" { 7 } else { 1 });
+            if backwards {
+                base.checked_sub_signed(days)
+            } else {
+                base.checked_add_signed(days)
+            }
+        }
+        _ => {
+            let months = Months::new(if unit == "y" {
+                amount.saturating_mul(12)
+            } else {
+                amount
+            });
+            if backwards {
+                base.checked_sub_months(months)
+            } else {
+                base.checked_add_months(months)
+            }
+        }
+    }
+}
+
+/// Local midnight of `date` up to local midnight of the next day
+fn day_span(date: NaiveDate) -> Option<(i64, i64)> {
+    let start = local_midnight(date)?;
+    let end = local_midnight(date.succ_opt()?)?;
+    Some((start, end))
+}
+
+fn local_midnight(date: NaiveDate) -> Option<i64> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+/// Format the start of a span as an ISO date, for comparing against
+/// frontmatter dates stored as text
+pub fn span_start_date(span: (i64, i64)) -> Option<String> {
+    let dt = Local.timestamp_opt(span.0, 0).earliest()?;
+    Some(dt.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 15, 14, 30, 0).unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> Option<(i64, i64)> {
+        day_span(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    #[test]
+    fn named_days_cover_a_local_day() {
+        assert_eq!(resolve_date_span_at("today", now()), day(2024, 3, 15));
+        assert_eq!(resolve_date_span_at("Yesterday", now()), day(2024, 3, 14));
+        assert_eq!(resolve_date_span_at("tomorrow", now()), day(2024, 3, 16));
+    }
+
+    #[test]
+    fn offsets_count_back_from_today() {
+        assert_eq!(resolve_date_span_at("-7d", now()), day(2024, 3, 8));
+        assert_eq!(resolve_date_span_at("-2w", now()), day(2024, 3, 1));
+        assert_eq!(resolve_date_span_at("-1m", now()), day(2024, 2, 15));
+        assert_eq!(resolve_date_span_at("today - 1y", now()), day(2023, 3, 15));
+    }
+
+    #[test]
+    fn month_offsets_clamp_to_the_end_of_shorter_months() {
+        let end_of_march = Local.with_ymd_and_hms(2024, 3, 31, 9, 0, 0).unwrap();
+        assert_eq!(resolve_date_span_at("-1m", end_of_march), day(2024, 2, 29));
+    }
+
+    #[test]
+    fn iso_dates_ignore_now() {
+        assert_eq!(resolve_date_span_at("2024-01-01", now()), day(2024, 1, 1));
+        assert_eq!(
+            resolve_date_span_at("2024-01-01+1w", now()),
+            day(2024, 1, 8)
+        );
+    }
+
+    #[test]
+    fn now_is_an_instant() {
+        let ts = now().timestamp();
+        assert_eq!(resolve_date_span_at("now", now()), Some((ts, ts + 1)));
+        assert_eq!(
+            resolve_date_span_at("now-1d", now()),
+            Some((ts - 86_400, ts - 86_400 + 1))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_expressions() {
+        assert_eq!(resolve_date_span_at("last tuesday", now()), None);
+        assert_eq!(resolve_date_span_at("-3q", now()), None);
+    }
+}
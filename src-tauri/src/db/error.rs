@@ -0,0 +1,68 @@
+//! Database error types with frontend-friendly serialization
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// Database operation errors that can be serialized to the frontend
+#[derive(Debug, Error, Serialize)]
+#[serde(tag = "type", content = "message", rename_all = "camelCase")]
+pub enum DbError {
+    #[error("No database connection")]
+    NoConnection,
+
+    #[error("Database error: {0}")]
+    Sqlite(String),
+
+    #[error("Serialization error: {0}")]
+    Serde(String),
+
+    #[error("Not found")]
+    NotFound,
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => DbError::NotFound,
+            _ => DbError::Sqlite(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for DbError {
+    fn from(err: serde_json::Error) -> Self {
+        DbError::Serde(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for DbError {
+    fn from(err: std::io::Error) -> Self {
+        DbError::Io(err.to_string())
+    }
+}
+
+// Note files that are not valid UTF-8
+impl From<std::string::FromUtf8Error> for DbError {
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        DbError::Io(err.to_string())
+    }
+}
+
+// Validation messages raised inside database closures
+impl From<String> for DbError {
+    fn from(message: String) -> Self {
+        DbError::Other(message)
+    }
+}
+
+impl From<&str> for DbError {
+    fn from(message: &str) -> Self {
+        DbError::Other(message.to_string())
+    }
+}
@@ -1,12 +1,15 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
 use walkdir::WalkDir;
 
-use super::with_db;
+use super::{resolve_member_mentions, with_db, with_db_tx, with_read_db, DbError};
 
 // Pre-compiled regex patterns for entity extraction (compiled once, reused)
 static IP_REGEX: Lazy<Regex> =
@@ -17,7 +20,21 @@ static DOMAIN_REGEX: Lazy<Regex> = Lazy::new(|| {
 static CVE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(CVE-\d{4}-\d{4,})\b").unwrap());
 static USERNAME_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\b((?:admin|root|user|guest|administrator)[\w]*)\b").unwrap());
+pub(crate) static TASK_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)[-*+] \[([ xX])\](?:\s+(.*))?$").unwrap());
 static MENTION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"@(\w+)").unwrap());
+static URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b(https?://[^\s<>()\[\]"'`]+)"#).unwrap());
+// Exact MD5/SHA-1/SHA-256 lengths; word boundaries keep longer hex runs and
+// short git hashes out
+static HASH_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b([a-fA-F0-9]{64}|[a-fA-F0-9]{40}|[a-fA-F0-9]{32})\b").unwrap());
+static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b([A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,})\b").unwrap()
+});
+static ATTACHMENT_REF_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\[[^\]]*\]\(<?([^)<>]+?)>?(?:\s+"[^"]*")?\)|!\[\[([^\]|#]+)"#).unwrap()
+});
 
 /// Safely find a character boundary at or before the given byte index
 fn floor_char_boundary(s: &str, index: usize) -> usize {
@@ -44,54 +61,40 @@ fn ceil_char_boundary(s: &str, index: usize) -> usize {
         idx
     }
 }
-use crate::commands::notes::NoteMetadata;
+use crate::commands::notes::{set_frontmatter_field, write_atomic};
+use crate::commands::notes::{NoteMetadata, NoteSort};
+use crate::commands::settings::{
+    read_vault_settings, IndexingSettings, NoteIdScheme, VaultSettings,
+};
+use crate::crypto;
 
 /// Clean up notes that no longer exist on disk
-fn cleanup_deleted_notes(
-    app: &AppHandle,
-    vault_path: &Path,
-) -> Result<usize, Box<dyn std::error::Error>> {
-    // Get all note paths from the database
-    let db_paths: Vec<String> = with_db(app, |conn| {
-        let mut stmt = conn.prepare("SELECT path FROM notes")?;
-        let paths = stmt
-            .query_map([], |row| row.get::<_, String>(0))?
+fn cleanup_deleted_notes(app: &AppHandle, vault_path: &Path) -> Result<usize, DbError> {
+    // Get all indexed notes from the database
+    let db_notes: Vec<(String, String)> = with_db(app, |conn| {
+        let mut stmt = conn.prepare("SELECT id, path FROM notes")?;
+        let notes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
             .filter_map(|r| r.ok())
             .collect();
-        Ok(paths)
+        Ok(notes)
     })?;
 
     let mut deleted_count = 0;
+    let indexing = read_vault_settings(vault_path).unwrap_or_default().indexing;
 
-    for db_path in db_paths {
+    for (note_id, db_path) in db_notes {
         // Construct the full path
         let full_path = vault_path.join(&db_path);
+        let excluded = db_path
+            .strip_prefix("notes/")
+            .is_some_and(|path| indexing.excludes(path));
 
-        // Check if the file still exists
-        if !full_path.exists() {
-            // File no longer exists - remove from database
-            let note_id = generate_note_id(&db_path);
-
+        // Check if the file still exists and is still indexed
+        if !full_path.exists() || excluded {
+            // File no longer exists or is now excluded - remove from database
             with_db(app, |conn| {
-                // Delete all related data
-                conn.execute("DELETE FROM entities WHERE note_id = ?1", params![note_id])?;
-                conn.execute("DELETE FROM tags WHERE note_id = ?1", params![note_id])?;
-                conn.execute(
-                    "DELETE FROM code_blocks WHERE note_id = ?1",
-                    params![note_id],
-                )?;
-                // Delete outgoing backlinks (by source_id)
-                conn.execute(
-                    "DELETE FROM backlinks WHERE source_id = ?1",
-                    params![note_id],
-                )?;
-                conn.execute(
-                    "DELETE FROM card_backlinks WHERE source_id = ?1",
-                    params![note_id],
-                )?;
-                conn.execute("DELETE FROM blocks WHERE note_id = ?1", params![note_id])?;
-                conn.execute("DELETE FROM aliases WHERE note_id = ?1", params![note_id])?;
-                // Delete the note itself
+                // Related rows (entities, tags, backlinks, ...) cascade via foreign keys
                 conn.execute("DELETE FROM notes WHERE id = ?1", params![note_id])?;
                 Ok(())
             })?;
@@ -100,42 +103,665 @@ fn cleanup_deleted_notes(
         }
     }
 
+    with_db(app, drop_orphaned_note_refs)?;
+
+    // Links from removed notes no longer count towards their targets
+    if deleted_count > 0 {
+        with_db(app, refresh_link_counts)?;
+    }
+
     Ok(deleted_count)
 }
 
-/// Index the entire vault
-pub async fn index_vault(
-    app: &AppHandle,
-    vault_path: &PathBuf,
-) -> Result<usize, Box<dyn std::error::Error>> {
-    let notes_dir = vault_path.join("notes");
-    let mut count = 0;
+/// Drop recent, pinned and starred entries and graph positions whose note is gone
+fn drop_orphaned_note_refs(conn: &Connection) -> Result<(), DbError> {
+    conn.execute(
+        "DELETE FROM recent_notes WHERE note_id NOT IN (SELECT id FROM notes)",
+        [],
+    )?;
+    conn.execute(
+        "DELETE FROM pinned_notes WHERE note_id NOT IN (SELECT id FROM notes)",
+        [],
+    )?;
+    conn.execute(
+        "DELETE FROM starred_notes WHERE note_id NOT IN (SELECT id FROM notes)",
+        [],
+    )?;
+    conn.execute(
+        "DELETE FROM graph_positions WHERE note_id NOT IN (SELECT id FROM notes)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Note references left without their note after notes were deleted with
+/// foreign keys off, resolved the way each reference's ON DELETE action would
+fn drop_dangling_note_refs(conn: &Connection) -> Result<(), DbError> {
+    for (table, column) in NOTE_ID_REFERENCES {
+        let action = if NULLED_NOTE_REFERENCES.contains(table) {
+            format!("UPDATE {} SET {} = NULL", table, column)
+        } else {
+            format!("DELETE FROM {}", table)
+        };
+        conn.execute(
+            &format!(
+                "{} WHERE {} IS NOT NULL AND {} NOT IN (SELECT id FROM notes)",
+                action, column, column
+            ),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// Whether an indexing run must stop: it was cancelled, or its vault is no longer
+/// the open one, in which case writing on would land in another vault's database
+fn run_stopped(app: &AppHandle, vault_path: &Path, cancelled: &AtomicBool) -> bool {
+    cancelled.load(Ordering::SeqCst)
+        || super::get_current_vault_path(app).as_deref() != Some(vault_path)
+}
+
+/// A note that could not be read or indexed
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FailedFile {
+    pub path: String,
+    pub error: String,
+}
+
+/// Payload of `index-progress`, emitted after each note `index_vault` processes
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexProgress {
+    /// Notes processed so far, including ones that failed
+    pub current: usize,
+    pub total: usize,
+    /// Vault-relative path of the note just processed
+    pub path: String,
+}
 
-    // First, clean up deleted files from the database
+/// Outcome of indexing the whole vault
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct IndexVaultResult {
+    pub indexed: usize,
+    pub failed_files: Vec<FailedFile>,
+    /// The run was stopped by `cancel_indexing` or by closing the vault
+    pub cancelled: bool,
+}
+
+/// Index the entire vault. A note that fails to index is recorded in
+/// `failed_files` and skipped rather than aborting the whole run.
+/// Cancellation is checked between files; a cancelled run skips cleanup.
+/// The notes are collected up front so `index-progress` can report a total.
+pub async fn index_vault(app: &AppHandle, vault_path: &Path) -> Result<IndexVaultResult, DbError> {
+    let mut result = IndexVaultResult::default();
+    let cancelled = super::start_indexing(app)?;
+    let indexing = read_vault_settings(vault_path).unwrap_or_default().indexing;
+
+    let Some(files) = collect_note_files(vault_path, &indexing, || {
+        run_stopped(app, vault_path, &cancelled)
+    }) else {
+        result.cancelled = true;
+        return Ok(result);
+    };
+
+    let total = files.len();
+    for (i, relative_path) in files.into_iter().enumerate() {
+        if run_stopped(app, vault_path, &cancelled) {
+            result.cancelled = true;
+            return Ok(result);
+        }
+
+        match index_single_note(app, vault_path, &PathBuf::from(&relative_path)).await {
+            Ok(()) => result.indexed += 1,
+            Err(e) => result.failed_files.push(FailedFile {
+                path: relative_path.clone(),
+                error: e.to_string(),
+            }),
+        }
+
+        let _ = app.emit(
+            "index-progress",
+            IndexProgress {
+                current: i + 1,
+                total,
+                path: relative_path,
+            },
+        );
+    }
+
+    // Clean up deleted files afterwards, so notes moved outside the app are
+    // matched to their old rows by frontmatter id before those rows are dropped
     cleanup_deleted_notes(app, vault_path)?;
 
-    // Walk through all markdown files
+    Ok(result)
+}
+
+/// Vault-relative paths of every indexed markdown file, or `None` if `stopped`
+/// reported true part way through the walk
+fn collect_note_files(
+    vault_path: &Path,
+    indexing: &IndexingSettings,
+    stopped: impl Fn() -> bool,
+) -> Option<Vec<String>> {
+    let notes_dir = vault_path.join("notes");
+
+    // Folders already walked, by canonical path. Walkdir reports symlink loops
+    // back to an ancestor; this also stops two links to one folder indexing it twice.
+    let mut visited_dirs: HashSet<PathBuf> = HashSet::new();
+
+    let mut files: Vec<String> = Vec::new();
     for entry in WalkDir::new(&notes_dir)
-        .follow_links(true)
+        .follow_links(indexing.follow_links)
         .into_iter()
+        .filter_entry(|e| {
+            let relative = e.path().strip_prefix(&notes_dir).unwrap_or(e.path());
+            if e.depth() > 0 && indexing.excludes(&relative.to_string_lossy()) {
+                return false;
+            }
+            if !e.file_type().is_dir() {
+                return true;
+            }
+            match e.path().canonicalize() {
+                Ok(canonical) => visited_dirs.insert(canonical),
+                Err(_) => false,
+            }
+        })
         .filter_map(|e| e.ok())
     {
+        if stopped() {
+            return None;
+        }
+
         let path = entry.path();
 
         if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
             // Get relative path from vault root
-            let relative_path = path
-                .strip_prefix(vault_path)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
+            files.push(
+                path.strip_prefix(vault_path)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+    }
+
+    Some(files)
+}
 
-            index_single_note(app, vault_path, &PathBuf::from(&relative_path)).await?;
-            count += 1;
+/// Counts reported after a full index rebuild
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct IndexRebuildResult {
+    pub notes_cleared: usize,
+    pub notes_indexed: usize,
+    pub failed_files: Vec<FailedFile>,
+    /// Indexing was cancelled part way, so the rebuilt index is incomplete
+    pub cancelled: bool,
+    pub entities: usize,
+    pub tags: usize,
+    pub backlinks: usize,
+    pub blocks: usize,
+    pub aliases: usize,
+}
+
+/// Drop all derived index data and re-index the vault from disk.
+/// User data (kanban, diagrams, saved searches, note versions) is left untouched.
+/// Note files are read outside the database lock; the lock is only taken to
+/// swap in the rebuilt index, in one transaction, so a failure or cancel
+/// leaves the index as it was.
+pub async fn rebuild_index(
+    app: &AppHandle,
+    vault_path: &Path,
+) -> Result<IndexRebuildResult, DbError> {
+    let cancelled = super::start_indexing(app)?;
+    let settings = read_vault_settings(vault_path).unwrap_or_default();
+
+    let Some(files) = collect_note_files(vault_path, &settings.indexing, || {
+        run_stopped(app, vault_path, &cancelled)
+    }) else {
+        return Ok(IndexRebuildResult {
+            cancelled: true,
+            ..Default::default()
+        });
+    };
+
+    let app = app.clone();
+    let vault_path = vault_path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || {
+        // A read connection is enough to look up what the first index persists
+        let read = with_read_db(&app, |conn| {
+            read_rebuild_notes(
+                conn,
+                &vault_path,
+                &settings,
+                &files,
+                || run_stopped(&app, &vault_path, &cancelled),
+                |progress| {
+                    let _ = app.emit("index-progress", progress);
+                },
+            )
+        })?;
+        let Some(read) = read else {
+            return Ok(IndexRebuildResult {
+                cancelled: true,
+                ..Default::default()
+            });
+        };
+
+        with_db(&app, |conn| {
+            apply_rebuild(conn, &vault_path, &settings, &files, read, &cancelled)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Note files read for a rebuild, before any of it reaches the index
+struct RebuildNotes {
+    notes: Vec<NoteFile>,
+    failed_files: Vec<FailedFile>,
+}
+
+/// Read and decode `files` for a rebuild, persisting first-index fields on
+/// the way. Returns `None` once `stopped` says so.
+fn read_rebuild_notes(
+    conn: &Connection,
+    vault_path: &Path,
+    settings: &VaultSettings,
+    files: &[String],
+    stopped: impl Fn() -> bool,
+    progress: impl Fn(IndexProgress),
+) -> Result<Option<RebuildNotes>, DbError> {
+    let mut read = RebuildNotes {
+        notes: Vec::with_capacity(files.len()),
+        failed_files: Vec::new(),
+    };
+
+    let total = files.len();
+    for (i, relative_path) in files.iter().enumerate() {
+        if stopped() {
+            return Ok(None);
+        }
+
+        let note = read_note_file_blocking(
+            vault_path,
+            Path::new(relative_path),
+            settings.indexing.max_file_size_bytes,
+        )
+        .and_then(|note| persist_first_index_fields(conn, vault_path, settings, note));
+        match note {
+            Ok(note) => read.notes.push(note),
+            Err(e) => read.failed_files.push(FailedFile {
+                path: relative_path.clone(),
+                error: e.to_string(),
+            }),
+        }
+
+        progress(IndexProgress {
+            current: i + 1,
+            total,
+            path: relative_path.clone(),
+        });
+    }
+
+    Ok(Some(read))
+}
+
+/// Replace the index with the notes read by `read_rebuild_notes`: clear the
+/// derived tables and `notes`, then index every note again.
+/// Foreign keys are off meanwhile so clearing `notes` doesn't cascade into
+/// user data; notes keep their ids, and references to notes that didn't come
+/// back are dropped at the end.
+fn apply_rebuild(
+    conn: &Connection,
+    vault_path: &Path,
+    settings: &VaultSettings,
+    files: &[String],
+    read: RebuildNotes,
+    cancelled: &AtomicBool,
+) -> Result<IndexRebuildResult, DbError> {
+    // Has no effect inside a transaction, so it wraps the whole rebuild
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    let result = apply_rebuild_tx(conn, vault_path, settings, files, read, cancelled);
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    result
+}
+
+/// The transaction of `apply_rebuild`
+fn apply_rebuild_tx(
+    conn: &Connection,
+    vault_path: &Path,
+    settings: &VaultSettings,
+    files: &[String],
+    read: RebuildNotes,
+    cancelled: &AtomicBool,
+) -> Result<IndexRebuildResult, DbError> {
+    let tx = conn.unchecked_transaction()?;
+
+    // Rows as they were, to take ids from and to fall back to for failed notes
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS temp.rebuild_prev_notes;
+         CREATE TEMP TABLE rebuild_prev_notes AS SELECT * FROM notes;",
+    )?;
+
+    let mut result = IndexRebuildResult {
+        notes_cleared: reset_derived_tables(&tx)?,
+        failed_files: read.failed_files,
+        ..Default::default()
+    };
+
+    // Seed each note's identity so reconciling ids works as on any other index run
+    tx.execute_batch(
+        "INSERT INTO notes (id, path, created_at)
+         SELECT id, path, created_at FROM temp.rebuild_prev_notes;",
+    )?;
+    for failed in &result.failed_files {
+        restore_previous_note_row(&tx, &failed.path)?;
+    }
+
+    for note in &read.notes {
+        // Only the token is checked: closing the vault cancels before it waits
+        // for the connection this run holds
+        if cancelled.load(Ordering::SeqCst) {
+            // Dropping `tx` rolls the index back to where it was
+            return Ok(IndexRebuildResult {
+                cancelled: true,
+                ..Default::default()
+            });
+        }
+
+        tx.execute_batch("SAVEPOINT rebuild_note;")?;
+        match write_note_rows(&tx, vault_path, settings, note) {
+            Ok(()) => {
+                tx.execute_batch("RELEASE rebuild_note;")?;
+                result.notes_indexed += 1;
+            }
+            Err(e) => {
+                tx.execute_batch("ROLLBACK TO rebuild_note; RELEASE rebuild_note;")?;
+                restore_previous_note_row(&tx, &note.path)?;
+                result.failed_files.push(FailedFile {
+                    path: note.path.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    // Notes deleted or excluded since the last index
+    let walked: HashSet<&str> = files.iter().map(String::as_str).collect();
+    let indexed_paths: Vec<String> = tx
+        .prepare("SELECT path FROM notes")?
+        .query_map([], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+    for path in indexed_paths
+        .iter()
+        .filter(|path| !walked.contains(path.as_str()))
+    {
+        tx.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
+    }
+    tx.execute_batch("DROP TABLE temp.rebuild_prev_notes;")?;
+    drop_dangling_note_refs(&tx)?;
+    refresh_link_counts(&tx)?;
+
+    let count = |table: &str| -> Result<usize, rusqlite::Error> {
+        let count: i64 = tx.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })?;
+        Ok(count as usize)
+    };
+    result.entities = count("entities")?;
+    result.tags = count("tags")?;
+    result.backlinks = count("backlinks")?;
+    result.blocks = count("blocks")?;
+    result.aliases = count("aliases")?;
+
+    tx.commit()?;
+    Ok(result)
+}
+
+/// Put back the row a note had before the rebuild, so a note that couldn't be
+/// read or indexed stays listed with its last indexed content
+fn restore_previous_note_row(conn: &Connection, path: &str) -> Result<(), DbError> {
+    conn.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
+    conn.execute(
+        "INSERT INTO notes SELECT * FROM temp.rebuild_prev_notes WHERE path = ?1",
+        params![path],
+    )?;
+    Ok(())
+}
+
+/// Problems found by `check_index_integrity`
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct IndexIntegrityReport {
+    /// FTS entries whose note row no longer exists
+    pub fts_orphans: usize,
+    /// Notes with no FTS entry, so they can't be found by search
+    pub fts_missing: usize,
+    /// FTS5's own check of its index against the notes table
+    pub fts_consistent: bool,
+    /// Backlinks whose source note no longer exists
+    pub orphan_backlinks: usize,
+    /// Card backlinks whose source note or card no longer exists
+    pub orphan_card_backlinks: usize,
+    /// Messages from `PRAGMA integrity_check` (empty when the database is ok)
+    pub integrity_errors: Vec<String>,
+    /// Human-readable summary of each problem found
+    pub problems: Vec<String>,
+    /// Whether `rebuild_index` should fix what was found
+    pub rebuild_recommended: bool,
+}
+
+/// Most `PRAGMA integrity_check` messages to collect
+const INTEGRITY_CHECK_MAX_ERRORS: usize = 20;
+
+/// Compare the notes table with its FTS index and derived link tables, and
+/// run SQLite's integrity check. Read-only apart from FTS5's check command.
+pub fn check_index_integrity(app: &AppHandle) -> Result<IndexIntegrityReport, DbError> {
+    with_db(app, |conn| {
+        let count = |sql: &str| -> Result<usize, rusqlite::Error> {
+            conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+                .map(|n| n as usize)
+        };
+
+        let mut report = IndexIntegrityReport {
+            // The docsize shadow table has one row per document in the FTS index
+            fts_orphans: count(
+                "SELECT COUNT(*) FROM notes_fts_docsize WHERE id NOT IN (SELECT rowid FROM notes)",
+            )?,
+            fts_missing: count(
+                "SELECT COUNT(*) FROM notes WHERE rowid NOT IN (SELECT id FROM notes_fts_docsize)",
+            )?,
+            fts_consistent: conn
+                .execute(
+                    "INSERT INTO notes_fts(notes_fts) VALUES('integrity-check')",
+                    [],
+                )
+                .is_ok(),
+            orphan_backlinks: count(
+                "SELECT COUNT(*) FROM backlinks WHERE source_id NOT IN (SELECT id FROM notes)",
+            )?,
+            orphan_card_backlinks: count(
+                "SELECT COUNT(*) FROM card_backlinks
+                 WHERE source_id NOT IN (SELECT id FROM notes)
+                    OR card_id NOT IN (SELECT id FROM kanban_cards)",
+            )?,
+            ..Default::default()
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            "PRAGMA integrity_check({})",
+            INTEGRITY_CHECK_MAX_ERRORS
+        ))?;
+        report.integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter(|message| message != "ok")
+            .collect();
+
+        if report.fts_orphans > 0 {
+            report.problems.push(format!(
+                "{} search index entries point at deleted notes",
+                report.fts_orphans
+            ));
+        }
+        if report.fts_missing > 0 {
+            report.problems.push(format!(
+                "{} notes are missing from the search index",
+                report.fts_missing
+            ));
         }
+        if !report.fts_consistent {
+            report
+                .problems
+                .push("The search index doesn't match note contents".to_string());
+        }
+        if report.orphan_backlinks > 0 {
+            report.problems.push(format!(
+                "{} backlinks come from deleted notes",
+                report.orphan_backlinks
+            ));
+        }
+        if report.orphan_card_backlinks > 0 {
+            report.problems.push(format!(
+                "{} card links reference deleted notes or cards",
+                report.orphan_card_backlinks
+            ));
+        }
+        if !report.integrity_errors.is_empty() {
+            report.problems.push(format!(
+                "SQLite integrity check failed: {}",
+                report.integrity_errors.join("; ")
+            ));
+        }
+
+        report.rebuild_recommended = !report.problems.is_empty();
+
+        Ok(report)
+    })
+}
+
+/// Drop and recreate every table derived from note files, and clear `notes`.
+/// Run with foreign keys off, or clearing `notes` cascades into versions,
+/// stars and other user data. Returns the number of notes indexed before.
+fn reset_derived_tables(conn: &Connection) -> Result<usize, DbError> {
+    let notes_cleared: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
+
+    conn.execute_batch(
+        r#"
+        DROP TRIGGER IF EXISTS notes_ai;
+        DROP TRIGGER IF EXISTS notes_ad;
+        DROP TRIGGER IF EXISTS notes_au;
+        DROP TABLE IF EXISTS notes_fts;
+        DROP TABLE IF EXISTS entities;
+        DROP TABLE IF EXISTS tags;
+        DROP TABLE IF EXISTS code_blocks;
+        DROP TABLE IF EXISTS backlinks;
+        DROP TABLE IF EXISTS card_backlinks;
+        DROP TABLE IF EXISTS blocks;
+        DROP TABLE IF EXISTS aliases;
+        DROP TABLE IF EXISTS tasks;
+        DROP TABLE IF EXISTS attachment_refs;
+        DELETE FROM notes;
+        "#,
+    )?;
+
+    // Recreates the dropped tables, triggers and indexes
+    super::schema::init_schema(conn)?;
+
+    Ok(notes_cleared as usize)
+}
+
+/// A note file as read from disk, ready to be written to the index
+struct NoteFile {
+    path: String,
+    /// Empty for encrypted notes and notes over the size limit
+    content: String,
+    title: String,
+    modified_at: i64,
+    fs_created_at: i64,
+}
+
+impl NoteFile {
+    /// `bytes` is `None` when the file is over the size limit
+    fn new(
+        relative_path: &Path,
+        bytes: Option<Vec<u8>>,
+        metadata: &std::fs::Metadata,
+    ) -> Result<Self, DbError> {
+        // Oversized notes are still listed, but their content is kept out of FTS and extraction
+        let content = match bytes {
+            Some(bytes) => decode_note_content(bytes)?,
+            None => String::new(),
+        };
+        let path = relative_path.to_string_lossy().to_string();
+
+        // Encrypted notes only expose their title; the ciphertext stays out of FTS and extraction
+        let (content, title) = match crypto::envelope_title(&content) {
+            Some(title) => (String::new(), title),
+            None => {
+                let title = extract_title(&content, &path);
+                (content, title)
+            }
+        };
+
+        let modified_at = metadata
+            .modified()
+            .map(|t| {
+                t.duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0);
+
+        // Only a fallback: some Linux filesystems don't record creation time
+        let fs_created_at = metadata
+            .created()
+            .map(|t| {
+                t.duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or(0)
+            })
+            .unwrap_or(modified_at);
+
+        Ok(Self {
+            path,
+            content,
+            title,
+            modified_at,
+            fs_created_at,
+        })
     }
+}
+
+/// Read a note for indexing without blocking the async runtime
+async fn read_note_file(
+    vault_path: &Path,
+    relative_path: &Path,
+    max_size: u64,
+) -> Result<NoteFile, DbError> {
+    let full_path = vault_path.join(relative_path);
+    let metadata = tokio::fs::metadata(&full_path).await?;
+    let bytes = if metadata.len() > max_size {
+        None
+    } else {
+        Some(tokio::fs::read(&full_path).await?)
+    };
+    NoteFile::new(relative_path, bytes, &metadata)
+}
 
-    Ok(count)
+/// Read a note for indexing from a blocking context, such as a rebuild
+fn read_note_file_blocking(
+    vault_path: &Path,
+    relative_path: &Path,
+    max_size: u64,
+) -> Result<NoteFile, DbError> {
+    let full_path = vault_path.join(relative_path);
+    let metadata = std::fs::metadata(&full_path)?;
+    let bytes = if metadata.len() > max_size {
+        None
+    } else {
+        Some(std::fs::read(&full_path)?)
+    };
+    NoteFile::new(relative_path, bytes, &metadata)
 }
 
 /// Index a single note
@@ -143,179 +769,680 @@ pub async fn index_single_note(
     app: &AppHandle,
     vault_path: &Path,
     relative_path: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let full_path = vault_path.join(relative_path);
-    // Use tokio async file operations to avoid blocking
-    let content = tokio::fs::read_to_string(&full_path).await?;
-    let metadata = tokio::fs::metadata(&full_path).await?;
+) -> Result<(), DbError> {
+    let settings = read_vault_settings(vault_path).unwrap_or_default();
+    let note = read_note_file(
+        vault_path,
+        relative_path,
+        settings.indexing.max_file_size_bytes,
+    )
+    .await?;
+
+    with_db_tx(app, |conn| {
+        let note = persist_first_index_fields(conn, vault_path, &settings, note)?;
+        write_note_rows(conn, vault_path, &settings, &note)
+    })
+}
 
-    let path_str = relative_path.to_string_lossy().to_string();
-    let id = generate_note_id(&path_str);
-    let title = extract_title(&content, &path_str);
-    let content_hash = hash_content(&content);
-
-    let modified_at = metadata
-        .modified()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(0);
-
-    let created_at = metadata
-        .created()
-        .map(|t| {
-            t.duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or(0)
-        })
-        .unwrap_or(modified_at);
+/// On a note's first index, write the fields it should carry but lacks:
+/// `created`, and `id` under the frontmatter id scheme. Values already indexed
+/// for the path are reused, so nothing keyed by them changes. Returns the note
+/// as re-read after the write, or `note` as it was when nothing is missing or
+/// the file can't be written.
+fn persist_first_index_fields(
+    conn: &Connection,
+    vault_path: &Path,
+    settings: &VaultSettings,
+    note: NoteFile,
+) -> Result<NoteFile, DbError> {
+    // Encrypted, oversized and empty notes have no content to add fields to
+    if note.content.trim().is_empty() {
+        return Ok(note);
+    }
+
+    let frontmatter = extract_frontmatter(&note.content);
+    let needs_id = settings.note_id_scheme == NoteIdScheme::Frontmatter
+        && extract_frontmatter_id(&frontmatter).is_none();
+    let needs_created = extract_frontmatter_created(&frontmatter).is_none();
+    if !needs_id && !needs_created {
+        return Ok(note);
+    }
+
+    let indexed: Option<(String, Option<i64>)> = conn
+        .prepare_cached("SELECT id, created_at FROM notes WHERE path = ?1")?
+        .query_row(params![note.path], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?;
+
+    let mut content = note.content.clone();
+    if needs_id {
+        let id = indexed
+            .as_ref()
+            .map(|(id, _)| id.clone())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        content = set_frontmatter_field(&content, "id", &id);
+    }
+    if needs_created {
+        let created_at = indexed
+            .and_then(|(_, created_at)| created_at)
+            .unwrap_or(note.fs_created_at);
+        content = set_frontmatter_field(&content, "created", &format_created(created_at));
+    }
+
+    // A read-only vault is still indexed, just without the persisted fields
+    if write_atomic(&vault_path.join(&note.path), &content).is_err() {
+        return Ok(note);
+    }
+    read_note_file_blocking(
+        vault_path,
+        Path::new(&note.path),
+        settings.indexing.max_file_size_bytes,
+    )
+}
+
+/// Write a note's row and everything extracted from it, replacing what was
+/// indexed for it before
+fn write_note_rows(
+    conn: &Connection,
+    vault_path: &Path,
+    settings: &VaultSettings,
+    note: &NoteFile,
+) -> Result<(), DbError> {
+    let NoteFile {
+        path: path_str,
+        content,
+        title,
+        modified_at,
+        fs_created_at,
+    } = note;
+    let content_hash = hash_content(content);
 
     // Parse frontmatter
-    let frontmatter = extract_frontmatter(&content);
+    let frontmatter = extract_frontmatter(content);
+
+    // Code is skipped by the extractors whose setting leaves it out
+    let indexing = &settings.indexing;
+    let code_spans = CodeSpans::find(content);
+    let code_filter = |include_code: bool| (!include_code).then_some(&code_spans);
+
+    // A persisted `created` field is authoritative; the first index adds it
+    let frontmatter_created = extract_frontmatter_created(&frontmatter);
 
     // Extract archived status from frontmatter
     let archived = extract_archived(&frontmatter);
 
-    with_db(app, |conn| {
-        // Insert or update the note
-        conn.execute(
-            r#"
-            INSERT INTO notes (id, path, title, content, content_hash, created_at, modified_at, frontmatter, archived)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-            ON CONFLICT(path) DO UPDATE SET
-                title = excluded.title,
-                content = excluded.content,
-                content_hash = excluded.content_hash,
-                modified_at = excluded.modified_at,
-                frontmatter = excluded.frontmatter,
-                archived = excluded.archived
-            "#,
-            params![id, path_str, title, content, content_hash, created_at, modified_at, frontmatter, archived as i32],
-        )?;
-
-        // Clear existing entities, tags, code blocks, backlinks, card backlinks, blocks, and aliases for this note
-        conn.execute("DELETE FROM entities WHERE note_id = ?1", params![id])?;
-        conn.execute("DELETE FROM tags WHERE note_id = ?1", params![id])?;
-        conn.execute("DELETE FROM code_blocks WHERE note_id = ?1", params![id])?;
-        conn.execute("DELETE FROM backlinks WHERE source_id = ?1", params![id])?;
-        conn.execute(
-            "DELETE FROM card_backlinks WHERE source_id = ?1",
-            params![id],
-        )?;
-        conn.execute("DELETE FROM blocks WHERE note_id = ?1", params![id])?;
-        conn.execute("DELETE FROM aliases WHERE note_id = ?1", params![id])?;
+    // Prefer a stable id from frontmatter; notes without one (under the path
+    // scheme) keep the id indexed for their path, else get the path hash
+    let frontmatter_id = extract_frontmatter_id(&frontmatter);
+    let fallback_id = generate_note_id(path_str);
+
+    let id = reconcile_note_id(
+        conn,
+        vault_path,
+        path_str,
+        frontmatter_id.as_deref(),
+        fallback_id,
+    )?;
+
+    // Keep the first-indexed creation time rather than re-reading the filesystem
+    let created_at = match frontmatter_created {
+        Some(created_at) => created_at,
+        None => conn
+            .prepare_cached("SELECT created_at FROM notes WHERE id = ?1")?
+            .query_row(params![id], |row| row.get::<_, Option<i64>>(0))
+            .optional()?
+            .flatten()
+            .unwrap_or(*fs_created_at),
+    };
+
+    // Insert or update the note
+    conn.prepare_cached(
+        r#"
+        INSERT INTO notes (id, path, title, content, content_hash, created_at, modified_at, frontmatter, archived)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        ON CONFLICT(path) DO UPDATE SET
+            title = excluded.title,
+            content = excluded.content,
+            content_hash = excluded.content_hash,
+            created_at = excluded.created_at,
+            modified_at = excluded.modified_at,
+            frontmatter = excluded.frontmatter,
+            archived = excluded.archived
+        "#,
+    )?
+    .execute(params![id, path_str, title, content, content_hash, created_at, modified_at, frontmatter, archived as i32])?;
+
+    // Remember where the note linked before, so those targets' incoming counts are refreshed
+    let old_targets: Vec<String> = conn
+        .prepare_cached("SELECT target_path FROM backlinks WHERE source_id = ?1")?
+        .query_map(params![id], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Clear existing entities, tags, code blocks, backlinks, card backlinks, blocks, aliases, and tasks for this note
+    conn.prepare_cached("DELETE FROM entities WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM tags WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM code_blocks WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM backlinks WHERE source_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM card_backlinks WHERE source_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM blocks WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM aliases WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM tasks WHERE note_id = ?1")?
+        .execute(params![id])?;
+    conn.prepare_cached("DELETE FROM attachment_refs WHERE note_id = ?1")?
+        .execute(params![id])?;
+
+    // Extract and insert entities
+    let entities: Vec<_> = extract_entities(content, code_filter(indexing.entities_in_code))
+        .into_iter()
+        .map(|(entity_type, value, context, line)| {
+            let subtype = (entity_type == "hash").then(|| hash_algorithm(&value));
+            (entity_type, value, context, line, subtype)
+        })
+        .collect();
+    let rows: Vec<[&dyn ToSql; 6]> = entities
+        .iter()
+        .map(|(entity_type, value, context, line, subtype)| {
+            [
+                &id as &dyn ToSql,
+                entity_type,
+                value,
+                context,
+                line,
+                subtype,
+            ]
+        })
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT INTO entities (note_id, entity_type, value, context, line_number, subtype)",
+        "",
+        &rows,
+    )?;
+
+    // Link @mentions of board members to the member
+    resolve_member_mentions(conn, Some(&id))?;
+
+    // Extract and insert tags
+    let tags = extract_tags(content, &frontmatter, code_filter(indexing.tags_in_code));
+    let rows: Vec<[&dyn ToSql; 2]> = tags.iter().map(|tag| [&id as &dyn ToSql, tag]).collect();
+    insert_rows(conn, "INSERT INTO tags (note_id, tag)", "", &rows)?;
+
+    // Extract and insert code blocks
+    let code_blocks: Vec<_> = extract_code_blocks(content)
+        .into_iter()
+        .map(|(language, block_content, line_start, line_end)| {
+            let language = language.map(|l| settings.code_languages.canonical(&l));
+            (language, block_content, line_start, line_end)
+        })
+        .collect();
+    let rows: Vec<[&dyn ToSql; 5]> = code_blocks
+        .iter()
+        .map(|(language, block_content, line_start, line_end)| {
+            [
+                &id as &dyn ToSql,
+                language,
+                block_content,
+                line_start,
+                line_end,
+            ]
+        })
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT INTO code_blocks (note_id, language, content, line_start, line_end)",
+        "",
+        &rows,
+    )?;
+
+    // Extract and insert backlinks, one per target and none to the note itself
+    let links: Vec<(String, String, i32)> = unique_links(
+        path_str,
+        extract_links(content, code_filter(indexing.links_in_code)),
+    )
+    .into_iter()
+    .map(|(target_path, context, in_heading)| (target_path, context, in_heading as i32))
+    .collect();
+    let rows: Vec<[&dyn ToSql; 4]> = links
+        .iter()
+        .map(|(target_path, context, in_heading)| {
+            [&id as &dyn ToSql, target_path, context, in_heading]
+        })
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT INTO backlinks (source_id, target_path, context, in_heading)",
+        "ON CONFLICT(source_id, target_path) DO UPDATE SET in_heading = MAX(in_heading, excluded.in_heading)",
+        &rows,
+    )?;
+    refresh_note_link_counts(
+        conn,
+        &id,
+        old_targets
+            .iter()
+            .chain(links.iter().map(|(target, _, _)| target)),
+    )?;
+
+    // Extract and insert card backlinks
+    let mut card_backlinks = Vec::new();
+    for (card_title, board_name, context) in extract_card_links(content) {
+        // Find the card by title (and optionally board name)
+        let card_result = if let Some(bn) = &board_name {
+            conn.prepare_cached(
+                r#"
+                SELECT c.id FROM kanban_cards c
+                JOIN kanban_boards b ON c.board_id = b.id
+                WHERE LOWER(c.title) = LOWER(?1) AND LOWER(b.name) = LOWER(?2)
+                LIMIT 1
+                "#,
+            )?
+            .query_row(params![card_title, bn], |row| row.get::<_, String>(0))
+        } else {
+            conn.prepare_cached(
+                "SELECT id FROM kanban_cards WHERE LOWER(title) = LOWER(?1) LIMIT 1",
+            )?
+            .query_row(params![card_title], |row| row.get::<_, String>(0))
+        };
 
-        // Extract and insert entities
-        let entities = extract_entities(&content);
-        for (entity_type, value, context, line) in entities {
-            conn.execute(
-                "INSERT INTO entities (note_id, entity_type, value, context, line_number) VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![id, entity_type, value, context, line],
-            )?;
+        if let Ok(card_id) = card_result {
+            card_backlinks.push((card_id, context));
         }
+    }
+    let rows: Vec<[&dyn ToSql; 3]> = card_backlinks
+        .iter()
+        .map(|(card_id, context)| [&id as &dyn ToSql, card_id, context])
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT OR IGNORE INTO card_backlinks (source_id, card_id, context)",
+        "",
+        &rows,
+    )?;
+
+    // Extract and insert block references (for transclusion)
+    let blocks = extract_blocks(content);
+    let rows: Vec<[&dyn ToSql; 4]> = blocks
+        .iter()
+        .map(|(block_id, block_content, line_number)| {
+            [&id as &dyn ToSql, block_id, block_content, line_number]
+        })
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT OR REPLACE INTO blocks (note_id, block_id, content, line_number)",
+        "",
+        &rows,
+    )?;
+
+    // Extract and insert aliases from frontmatter
+    let aliases = extract_aliases(&frontmatter);
+    let rows: Vec<[&dyn ToSql; 2]> = aliases
+        .iter()
+        .map(|alias| [&id as &dyn ToSql, alias])
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT OR IGNORE INTO aliases (note_id, alias)",
+        "",
+        &rows,
+    )?;
+
+    // Extract and insert checklist tasks
+    let tasks = extract_tasks(content);
+    let rows: Vec<[&dyn ToSql; 5]> = tasks
+        .iter()
+        .map(|(line, indent, completed, text)| [&id as &dyn ToSql, line, indent, completed, text])
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT INTO tasks (note_id, line_number, indent, completed, text)",
+        "",
+        &rows,
+    )?;
+
+    // Extract and insert attachment references
+    let attachments = extract_attachment_refs(path_str, content);
+    let rows: Vec<[&dyn ToSql; 2]> = attachments
+        .iter()
+        .map(|attachment| [&id as &dyn ToSql, attachment])
+        .collect();
+    insert_rows(
+        conn,
+        "INSERT OR IGNORE INTO attachment_refs (note_id, attachment_path)",
+        "",
+        &rows,
+    )?;
+
+    Ok(())
+}
 
-        // Extract and insert tags
-        let tags = extract_tags(&content, &frontmatter);
-        for tag in tags {
-            conn.execute(
-                "INSERT INTO tags (note_id, tag) VALUES (?1, ?2)",
-                params![id, tag],
-            )?;
-        }
+/// Tables holding a reference to `notes.id`, as (table, column)
+const NOTE_ID_REFERENCES: &[(&str, &str)] = &[
+    ("entities", "note_id"),
+    ("tags", "note_id"),
+    ("code_blocks", "note_id"),
+    ("blocks", "note_id"),
+    ("aliases", "note_id"),
+    ("tasks", "note_id"),
+    ("attachment_refs", "note_id"),
+    ("backlinks", "source_id"),
+    ("card_backlinks", "source_id"),
+    ("kanban_cards", "note_id"),
+    ("diagram_boards", "note_id"),
+    ("diagram_board_notes", "note_id"),
+    ("note_versions", "note_id"),
+    ("recent_notes", "note_id"),
+    ("pinned_notes", "note_id"),
+    ("note_labels", "note_id"),
+    ("starred_notes", "note_id"),
+    ("graph_positions", "note_id"),
+];
+
+/// Tables in `NOTE_ID_REFERENCES` whose reference is `ON DELETE SET NULL`
+const NULLED_NOTE_REFERENCES: &[&str] = &["kanban_cards", "diagram_boards"];
+
+/// Outgoing links of the `notes` row being updated
+const OUTGOING_COUNT_SQL: &str = "(SELECT COUNT(*) FROM backlinks b WHERE b.source_id = notes.id)";
+
+/// Distinct notes linking to the `notes` row being updated, by full path,
+/// path without the `notes/` prefix, or that without the `.md` extension
+const INCOMING_COUNT_SQL: &str = "(SELECT COUNT(DISTINCT b.source_id) FROM backlinks b
+     WHERE b.target_path IN (
+         notes.path,
+         replace(notes.path, 'notes/', ''),
+         replace(replace(notes.path, 'notes/', ''), '.md', '')
+     ))";
+
+/// Recompute the cached link counts of every note
+pub(crate) fn refresh_link_counts(conn: &Connection) -> Result<(), DbError> {
+    conn.execute(
+        &format!(
+            "UPDATE notes SET outgoing_count = {}, incoming_count = {}",
+            OUTGOING_COUNT_SQL, INCOMING_COUNT_SQL
+        ),
+        [],
+    )?;
+    Ok(())
+}
 
-        // Extract and insert code blocks
-        let code_blocks = extract_code_blocks(&content);
-        for (language, block_content, line_start, line_end) in code_blocks {
-            conn.execute(
-                "INSERT INTO code_blocks (note_id, language, content, line_start, line_end) VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![id, language, block_content, line_start, line_end],
-            )?;
+/// Recompute cached link counts after a note's links changed: its own counts,
+/// plus the incoming count of each note its old or new links point at
+fn refresh_note_link_counts<'a>(
+    conn: &Connection,
+    note_id: &str,
+    targets: impl IntoIterator<Item = &'a String>,
+) -> Result<(), DbError> {
+    conn.prepare_cached(&format!(
+        "UPDATE notes SET outgoing_count = {}, incoming_count = {} WHERE id = ?1",
+        OUTGOING_COUNT_SQL, INCOMING_COUNT_SQL
+    ))?
+    .execute(params![note_id])?;
+
+    // Candidate paths are the inverse of the matching in INCOMING_COUNT_SQL
+    let mut stmt = conn.prepare_cached(&format!(
+        "UPDATE notes SET incoming_count = {}
+         WHERE path IN (?1, ?1 || '.md', 'notes/' || ?1, 'notes/' || ?1 || '.md')",
+        INCOMING_COUNT_SQL
+    ))?;
+    let mut seen = std::collections::HashSet::new();
+    for target in targets {
+        if seen.insert(target) {
+            stmt.execute(params![target])?;
         }
+    }
+    Ok(())
+}
+
+/// Stay under SQLite's host parameter limit on older builds
+pub(crate) const MAX_SQL_PARAMS: usize = 999;
+
+/// Insert rows with multi-row `INSERT ... VALUES (...), (...)` statements,
+/// chunked to respect the parameter limit. `head` is the statement up to
+/// `VALUES`; `tail` follows the value list (e.g. an upsert clause).
+fn insert_rows<const N: usize>(
+    conn: &Connection,
+    head: &str,
+    tail: &str,
+    rows: &[[&dyn ToSql; N]],
+) -> Result<(), DbError> {
+    let per_chunk = (MAX_SQL_PARAMS / N).max(1);
+    let placeholder = format!("({})", vec!["?"; N].join(", "));
+
+    for chunk in rows.chunks(per_chunk) {
+        let values = vec![placeholder.as_str(); chunk.len()].join(", ");
+        let sql = format!("{} VALUES {} {}", head, values, tail);
+        let params: Vec<&dyn ToSql> = chunk.iter().flatten().copied().collect();
+        // Row counts repeat from note to note, so the cached statements get reused
+        conn.prepare_cached(&sql)?.execute(params.as_slice())?;
+    }
+
+    Ok(())
+}
 
-        // Extract and insert backlinks
-        let links = extract_links(&content);
-        for (target_path, context) in links {
+/// Decide which id a note is indexed under.
+/// - A frontmatter id wins; if its row sits at a path that no longer exists the
+///   note was moved, so the row is re-pointed at the new path.
+/// - A frontmatter id already owned by another existing file (a copy) is ignored.
+/// - Otherwise the id already indexed for this path is kept, else `fallback_id`.
+fn reconcile_note_id(
+    conn: &Connection,
+    vault_path: &Path,
+    path: &str,
+    frontmatter_id: Option<&str>,
+    fallback_id: String,
+) -> Result<String, DbError> {
+    let existing_id: Option<String> = conn
+        .prepare_cached("SELECT id FROM notes WHERE path = ?1")?
+        .query_row(params![path], |row| row.get(0))
+        .ok();
+
+    let Some(fm_id) = frontmatter_id else {
+        return Ok(existing_id.unwrap_or(fallback_id));
+    };
+
+    let owner_path: Option<String> = conn
+        .prepare_cached("SELECT path FROM notes WHERE id = ?1")?
+        .query_row(params![fm_id], |row| row.get(0))
+        .ok();
+
+    match owner_path {
+        Some(owner) if owner == path => Ok(fm_id.to_string()),
+        Some(owner) if vault_path.join(&owner).exists() => {
+            // Duplicate id (copied file): leave the original its id
+            Ok(existing_id.unwrap_or_else(|| generate_note_id(path)))
+        }
+        Some(_) => {
+            // Moved: drop any stale row at the new path, then move the old row here
+            if existing_id.is_some() {
+                conn.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
+            }
             conn.execute(
-                "INSERT OR IGNORE INTO backlinks (source_id, target_path, context) VALUES (?1, ?2, ?3)",
-                params![id, target_path, context],
+                "UPDATE notes SET path = ?1 WHERE id = ?2",
+                params![path, fm_id],
             )?;
+            Ok(fm_id.to_string())
         }
-
-        // Extract and insert card backlinks
-        let card_links = extract_card_links(&content);
-        for (card_title, board_name, context) in card_links {
-            // Find the card by title (and optionally board name)
-            let card_result = if let Some(bn) = &board_name {
-                conn.query_row(
-                    r#"
-                    SELECT c.id FROM kanban_cards c
-                    JOIN kanban_boards b ON c.board_id = b.id
-                    WHERE LOWER(c.title) = LOWER(?1) AND LOWER(b.name) = LOWER(?2)
-                    LIMIT 1
-                    "#,
-                    params![card_title, bn],
-                    |row| row.get::<_, String>(0),
-                )
-            } else {
-                conn.query_row(
-                    "SELECT id FROM kanban_cards WHERE LOWER(title) = LOWER(?1) LIMIT 1",
-                    params![card_title],
-                    |row| row.get::<_, String>(0),
-                )
-            };
-
-            if let Ok(card_id) = card_result {
-                conn.execute(
-                    "INSERT OR IGNORE INTO card_backlinks (source_id, card_id, context) VALUES (?1, ?2, ?3)",
-                    params![id, card_id, context],
-                )?;
+        None => {
+            if let Some(old_id) = existing_id {
+                rekey_note(conn, &old_id, fm_id)?;
             }
+            Ok(fm_id.to_string())
         }
+    }
+}
 
-        // Extract and insert block references (for transclusion)
-        let blocks = extract_blocks(&content);
-        for (block_id, block_content, line_number) in blocks {
-            conn.execute(
-                "INSERT OR REPLACE INTO blocks (note_id, block_id, content, line_number) VALUES (?1, ?2, ?3, ?4)",
-                params![id, block_id, block_content, line_number],
-            )?;
+/// Change a note's id, carrying every reference along with it
+fn rekey_note(conn: &Connection, old_id: &str, new_id: &str) -> Result<(), DbError> {
+    // Parent and children are updated separately; check constraints at commit
+    conn.execute_batch("PRAGMA defer_foreign_keys = ON;")?;
+    conn.execute(
+        "UPDATE notes SET id = ?1 WHERE id = ?2",
+        params![new_id, old_id],
+    )?;
+    for (table, column) in NOTE_ID_REFERENCES {
+        conn.execute(
+            &format!("UPDATE {} SET {} = ?1 WHERE {} = ?2", table, column, column),
+            params![new_id, old_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the indexed id into the frontmatter of every note that lacks one.
+/// Existing ids are reused, so nothing keyed by id changes.
+pub async fn backfill_note_ids(app: &AppHandle, vault_path: &Path) -> Result<usize, DbError> {
+    let notes: Vec<(String, String, Option<String>)> = with_db(app, |conn| {
+        let mut stmt = conn.prepare("SELECT id, path, frontmatter FROM notes")?;
+        let notes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(notes)
+    })?;
+
+    let mut updated = 0;
+
+    for (id, path, frontmatter) in notes {
+        if extract_frontmatter_id(&frontmatter).is_some() {
+            continue;
         }
 
-        // Extract and insert aliases from frontmatter
-        let aliases = extract_aliases(&frontmatter);
-        for alias in aliases {
-            conn.execute(
-                "INSERT OR IGNORE INTO aliases (note_id, alias) VALUES (?1, ?2)",
-                params![id, alias],
-            )?;
+        let full_path = vault_path.join(&path);
+        let Ok(content) = tokio::fs::read_to_string(&full_path).await else {
+            continue;
+        };
+        if crypto::is_encrypted(&content) {
+            continue;
         }
 
-        Ok(())
-    })
+        tokio::fs::write(&full_path, set_frontmatter_field(&content, "id", &id)).await?;
+        index_single_note(app, vault_path, Path::new(&path)).await?;
+        updated += 1;
+    }
+
+    Ok(updated)
 }
 
 /// Remove a note from the index
-pub fn remove_note_from_index(
+pub fn remove_note_from_index(app: &AppHandle, path: &str) -> Result<(), DbError> {
+    with_db_tx(app, |conn| {
+        conn.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
+        refresh_link_counts(conn)
+    })
+}
+
+/// Move a note's index row to a new path, keeping its id and everything keyed by it
+pub fn move_note_in_index(app: &AppHandle, old_path: &str, new_path: &str) -> Result<(), DbError> {
+    with_db_tx(app, |conn| {
+        conn.execute("DELETE FROM notes WHERE path = ?1", params![new_path])?;
+        conn.execute(
+            "UPDATE notes SET path = ?1 WHERE path = ?2",
+            params![new_path, old_path],
+        )?;
+        // Links by the old path no longer reach the note; links by the new one now do
+        refresh_link_counts(conn)
+    })
+}
+
+/// Move every note under a folder to a new folder path, keeping ids
+pub fn move_folder_in_index(
     app: &AppHandle,
-    path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+    old_folder: &str,
+    new_folder: &str,
+) -> Result<(), DbError> {
+    let old_prefix = format!("{}/", old_folder.trim_end_matches('/'));
+    let new_prefix = format!("{}/", new_folder.trim_end_matches('/'));
+
+    with_db_tx(app, |conn| {
+        conn.execute(
+            "UPDATE notes SET path = ?3 || substr(path, ?2 + 1) WHERE substr(path, 1, ?2) = ?1",
+            params![old_prefix, old_prefix.chars().count() as i64, new_prefix],
+        )?;
+        refresh_link_counts(conn)
+    })
+}
+
+/// Remove every note under a folder from the index
+pub fn remove_folder_from_index(app: &AppHandle, folder: &str) -> Result<(), DbError> {
+    let prefix = format!("{}/", folder.trim_end_matches('/'));
+
+    with_db_tx(app, |conn| {
+        conn.execute(
+            "DELETE FROM notes WHERE substr(path, 1, ?2) = ?1",
+            params![prefix, prefix.chars().count() as i64],
+        )?;
+        refresh_link_counts(conn)
+    })
+}
+
+/// Get the indexed creation time for a note path
+pub fn get_note_created_at(app: &AppHandle, path: &str) -> Option<i64> {
     with_db(app, |conn| {
-        conn.execute("DELETE FROM notes WHERE path = ?1", params![path])?;
-        Ok(())
+        let created_at = conn
+            .query_row(
+                "SELECT created_at FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(created_at)
     })
+    .ok()
+    .flatten()
+}
+
+/// Get the indexed id for a note path
+pub fn get_note_id(app: &AppHandle, path: &str) -> Option<String> {
+    with_db(app, |conn| {
+        let id = conn
+            .query_row(
+                "SELECT id FROM notes WHERE path = ?1",
+                params![path],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(id)
+    })
+    .ok()
+    .flatten()
 }
 
 /// List all notes
-pub fn list_all_notes(app: &AppHandle) -> Result<Vec<NoteMetadata>, Box<dyn std::error::Error>> {
+pub fn list_all_notes(app: &AppHandle) -> Result<Vec<NoteMetadata>, DbError> {
+    list_notes_page(app, 0, u32::MAX, NoteSort::Modified).map(|(notes, _)| notes)
+}
+
+/// List a page of notes in the given order, returning it with the total note count
+pub fn list_notes_page(
+    app: &AppHandle,
+    offset: u32,
+    limit: u32,
+    sort: NoteSort,
+) -> Result<(Vec<NoteMetadata>, i64), DbError> {
+    let order_by = match sort {
+        NoteSort::Modified => "modified_at DESC, path",
+        NoteSort::Created => "created_at DESC, path",
+        NoteSort::Title => "title COLLATE NOCASE, path",
+        NoteSort::Path => "path COLLATE NOCASE",
+    };
+
     with_db(app, |conn| {
-        let mut stmt = conn.prepare(
-            "SELECT id, path, title, modified_at, created_at, COALESCE(archived, 0), COALESCE(starred, 0) FROM notes ORDER BY modified_at DESC",
-        )?;
+        let total: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, path, title, modified_at, created_at, COALESCE(archived, 0), EXISTS(SELECT 1 FROM starred_notes s WHERE s.note_id = notes.id), outgoing_count, incoming_count FROM notes ORDER BY {} LIMIT ?1 OFFSET ?2",
+            order_by
+        ))?;
 
         let notes = stmt
-            .query_map([], |row| {
+            .query_map(params![limit as i64, offset as i64], |row| {
                 Ok(NoteMetadata {
                     id: row.get(0)?,
                     path: row.get(1)?,
@@ -324,12 +1451,14 @@ pub fn list_all_notes(app: &AppHandle) -> Result<Vec<NoteMetadata>, Box<dyn std:
                     created_at: row.get(4)?,
                     archived: row.get::<_, i32>(5)? != 0,
                     starred: row.get::<_, i32>(6)? != 0,
+                    outgoing_count: row.get::<_, i64>(7)? as usize,
+                    incoming_count: row.get::<_, i64>(8)? as usize,
                 })
             })?
             .filter_map(|r| r.ok())
             .collect();
 
-        Ok(notes)
+        Ok((notes, total))
     })
 }
 
@@ -349,6 +1478,15 @@ fn hash_content(content: &str) -> String {
     hex::encode(&result[..16])
 }
 
+/// Decode raw note bytes as UTF-8, dropping a leading byte-order mark
+pub fn decode_note_content(bytes: Vec<u8>) -> Result<String, std::string::FromUtf8Error> {
+    let bytes = match bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(rest) => rest.to_vec(),
+        None => bytes,
+    };
+    String::from_utf8(bytes)
+}
+
 fn extract_title(content: &str, path: &str) -> String {
     // Try to extract title from first H1 heading
     for line in content.lines() {
@@ -380,6 +1518,53 @@ fn extract_frontmatter(content: &str) -> Option<String> {
     None
 }
 
+/// Extract a stable note id from frontmatter JSON
+fn extract_frontmatter_id(frontmatter: &Option<String>) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(frontmatter.as_ref()?).ok()?;
+    json.get("id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Extract the persisted creation time from frontmatter JSON
+fn extract_frontmatter_created(frontmatter: &Option<String>) -> Option<i64> {
+    let json: serde_json::Value = serde_json::from_str(frontmatter.as_ref()?).ok()?;
+    match json.get("created")? {
+        serde_json::Value::Number(n) => n.as_i64(),
+        serde_json::Value::String(s) => parse_created_timestamp(s),
+        _ => None,
+    }
+}
+
+/// Parse a `created` frontmatter value: RFC 3339, a date-time, a date, or epoch seconds.
+/// Values without a timezone are taken as UTC.
+pub fn parse_created_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(ts) = value.parse::<i64>() {
+        return Some(ts);
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt.and_utc().timestamp());
+        }
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Format a creation time for the `created` frontmatter field
+pub(crate) fn format_created(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
 /// Extract archived status from frontmatter JSON
 fn extract_archived(frontmatter: &Option<String>) -> bool {
     if let Some(fm) = frontmatter {
@@ -393,7 +1578,7 @@ fn extract_archived(frontmatter: &Option<String>) -> bool {
     false
 }
 
-fn serde_yaml_to_json(yaml: &str) -> Result<String, Box<dyn std::error::Error>> {
+fn serde_yaml_to_json(yaml: &str) -> Result<String, DbError> {
     use std::collections::HashMap;
     let mut map: HashMap<String, serde_json::Value> = HashMap::new();
 
@@ -486,15 +1671,20 @@ fn serde_yaml_to_json(yaml: &str) -> Result<String, Box<dyn std::error::Error>>
     Ok(serde_json::to_string(&map)?)
 }
 
-fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
+fn extract_entities(content: &str, skip: Option<&CodeSpans>) -> Vec<(String, String, String, i32)> {
     let mut entities = Vec::new();
 
     // Use pre-compiled static regex patterns for performance
     for (line_num, line) in content.lines().enumerate() {
         let line_num = (line_num + 1) as i32;
         let context = line.chars().take(100).collect::<String>();
+        // `lines()` yields subslices, so this is the line's offset in `content`
+        let line_start = line.as_ptr() as usize - content.as_ptr() as usize;
+        let outside_code = |cap: &regex::Captures| {
+            !skip.is_some_and(|code| code.contains(line_start + cap.get(0).unwrap().start()))
+        };
 
-        for cap in IP_REGEX.captures_iter(line) {
+        for cap in IP_REGEX.captures_iter(line).filter(outside_code) {
             entities.push((
                 "ip".to_string(),
                 cap[1].to_string(),
@@ -503,7 +1693,7 @@ fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
             ));
         }
 
-        for cap in DOMAIN_REGEX.captures_iter(line) {
+        for cap in DOMAIN_REGEX.captures_iter(line).filter(outside_code) {
             let domain = &cap[1];
             // Filter out common non-domains
             if !domain.ends_with(".md") && !domain.ends_with(".rs") && !domain.ends_with(".ts") {
@@ -516,7 +1706,39 @@ fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
             }
         }
 
-        for cap in CVE_REGEX.captures_iter(line) {
+        for cap in URL_REGEX.captures_iter(line).filter(outside_code) {
+            // Sentence punctuation directly after a URL isn't part of it
+            let url = cap[1].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            entities.push((
+                "url".to_string(),
+                url.to_string(),
+                context.clone(),
+                line_num,
+            ));
+        }
+
+        for cap in EMAIL_REGEX.captures_iter(line).filter(outside_code) {
+            entities.push((
+                "email".to_string(),
+                cap[1].to_string(),
+                context.clone(),
+                line_num,
+            ));
+        }
+
+        for cap in HASH_REGEX.captures_iter(line).filter(outside_code) {
+            // All-digit runs are numbers, not hashes
+            if cap[1].bytes().any(|b| b.is_ascii_alphabetic()) {
+                entities.push((
+                    "hash".to_string(),
+                    cap[1].to_lowercase(),
+                    context.clone(),
+                    line_num,
+                ));
+            }
+        }
+
+        for cap in CVE_REGEX.captures_iter(line).filter(outside_code) {
             entities.push((
                 "cve".to_string(),
                 cap[1].to_string(),
@@ -525,7 +1747,7 @@ fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
             ));
         }
 
-        for cap in USERNAME_REGEX.captures_iter(line) {
+        for cap in USERNAME_REGEX.captures_iter(line).filter(outside_code) {
             entities.push((
                 "username".to_string(),
                 cap[1].to_string(),
@@ -534,7 +1756,7 @@ fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
             ));
         }
 
-        for cap in MENTION_REGEX.captures_iter(line) {
+        for cap in MENTION_REGEX.captures_iter(line).filter(outside_code) {
             entities.push((
                 "mention".to_string(),
                 cap[1].to_string(),
@@ -547,7 +1769,20 @@ fn extract_entities(content: &str) -> Vec<(String, String, String, i32)> {
     entities
 }
 
-fn extract_tags(content: &str, frontmatter: &Option<String>) -> Vec<String> {
+/// Algorithm of a hash entity, from its length
+fn hash_algorithm(hash: &str) -> &'static str {
+    match hash.len() {
+        32 => "md5",
+        40 => "sha1",
+        _ => "sha256",
+    }
+}
+
+fn extract_tags(
+    content: &str,
+    frontmatter: &Option<String>,
+    skip: Option<&CodeSpans>,
+) -> Vec<String> {
     let mut tags = Vec::new();
 
     // Extract from frontmatter
@@ -566,6 +1801,9 @@ fn extract_tags(content: &str, frontmatter: &Option<String>) -> Vec<String> {
     // Extract hashtags from content
     let tag_re = Regex::new(r"#([a-zA-Z]\w*)").unwrap();
     for cap in tag_re.captures_iter(content) {
+        if skip.is_some_and(|code| code.contains(cap.get(0).unwrap().start())) {
+            continue;
+        }
         let tag = cap[1].to_string();
         if !tags.contains(&tag) {
             tags.push(tag);
@@ -619,7 +1857,10 @@ fn extract_code_blocks(content: &str) -> Vec<(Option<String>, String, i32, i32)>
     blocks
 }
 
-fn extract_links(content: &str) -> Vec<(String, String)> {
+pub(crate) fn extract_links(
+    content: &str,
+    skip: Option<&CodeSpans>,
+) -> Vec<(String, String, bool)> {
     let mut links = Vec::new();
 
     // Wiki-style links: [[path]] or [[path|display]]
@@ -636,35 +1877,92 @@ fn extract_links(content: &str) -> Vec<(String, String)> {
             continue;
         }
 
-        let context = content
-            .find(&cap[0])
-            .map(|i| {
-                // Use safe character boundary functions to avoid panics on multi-byte chars
-                let start = floor_char_boundary(content, i.saturating_sub(30));
-                let end = ceil_char_boundary(content, (i + cap[0].len() + 30).min(content.len()));
-                content[start..end].to_string()
-            })
-            .unwrap_or_default();
-        links.push((path, context));
+        let m = cap.get(0).unwrap();
+        if skip.is_some_and(|code| code.contains(m.start())) {
+            continue;
+        }
+        links.push((
+            path,
+            link_context(content, m.start(), m.end()),
+            is_in_heading(content, m.start()),
+        ));
     }
 
     for cap in md_re.captures_iter(content) {
         let path = cap[2].to_string();
-        let context = content
-            .find(&cap[0])
-            .map(|i| {
-                // Use safe character boundary functions to avoid panics on multi-byte chars
-                let start = floor_char_boundary(content, i.saturating_sub(30));
-                let end = ceil_char_boundary(content, (i + cap[0].len() + 30).min(content.len()));
-                content[start..end].to_string()
-            })
-            .unwrap_or_default();
-        links.push((path, context));
+        let m = cap.get(0).unwrap();
+        if skip.is_some_and(|code| code.contains(m.start())) {
+            continue;
+        }
+        links.push((
+            path,
+            link_context(content, m.start(), m.end()),
+            is_in_heading(content, m.start()),
+        ));
     }
 
     links
 }
 
+/// Normalized link target: spellings of the same note (`notes/a.md`, `a.md`,
+/// `A`) share a key
+fn link_key(target: &str) -> String {
+    let target = target.trim().replace('\\', "/");
+    let target = target.strip_prefix("notes/").unwrap_or(&target);
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    target.to_lowercase()
+}
+
+/// Whether a link target from the note at `note_path` points back at the note,
+/// by path or by bare file name
+pub(crate) fn is_self_link(note_path: &str, target: &str) -> bool {
+    let own = link_key(note_path);
+    let target = link_key(target);
+    let own_name = own.rsplit('/').next().unwrap_or(&own);
+    target == own || target == own_name
+}
+
+/// Drop self-links and keep one link per target spelling; the first
+/// occurrence's context is kept and `in_heading` is set if any occurrence is
+fn unique_links(
+    note_path: &str,
+    links: Vec<(String, String, bool)>,
+) -> Vec<(String, String, bool)> {
+    let mut unique: Vec<(String, String, bool)> = Vec::new();
+    let mut seen: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for (target, context, in_heading) in links {
+        if is_self_link(note_path, &target) {
+            continue;
+        }
+        match seen.get(&link_key(&target)) {
+            Some(&i) => unique[i].2 |= in_heading,
+            None => {
+                seen.insert(link_key(&target), unique.len());
+                unique.push((target, context, in_heading));
+            }
+        }
+    }
+
+    unique
+}
+
+/// Text surrounding a link, widened to character boundaries
+fn link_context(content: &str, start: usize, end: usize) -> String {
+    // Use safe character boundary functions to avoid panics on multi-byte chars
+    let start = floor_char_boundary(content, start.saturating_sub(30));
+    let end = ceil_char_boundary(content, (end + 30).min(content.len()));
+    content[start..end].to_string()
+}
+
+/// Whether the line containing `pos` is an ATX heading
+fn is_in_heading(content: &str, pos: usize) -> bool {
+    let line_start = content[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = content[line_start..].trim_start();
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&hashes) && line[hashes..].starts_with([' ', '\t'])
+}
+
 /// Extract aliases from frontmatter
 /// Returns: Vec<String> of aliases
 fn extract_aliases(frontmatter: &Option<String>) -> Vec<String> {
@@ -712,6 +2010,133 @@ fn extract_aliases(frontmatter: &Option<String>) -> Vec<String> {
     aliases
 }
 
+/// Attachments a note embeds or links to, as vault-relative paths under
+/// `attachments/`. Bare wiki embed names (`![[image.png]]`) are looked up in
+/// `attachments/`; other targets starting with `./`, `../` or no folder are
+/// resolved against the note's folder, the rest against the vault root.
+fn extract_attachment_refs(note_path: &str, content: &str) -> Vec<String> {
+    let note_path = note_path.replace('\\', "/");
+    let note_dir: Vec<&str> = note_path
+        .rsplit_once('/')
+        .map(|(dir, _)| dir.split('/').filter(|c| !c.is_empty()).collect())
+        .unwrap_or_default();
+
+    let mut refs: Vec<String> = Vec::new();
+    for cap in ATTACHMENT_REF_REGEX.captures_iter(content) {
+        let (target, wiki) = match (cap.get(1), cap.get(2)) {
+            (Some(target), _) => (target, false),
+            (None, Some(target)) => (target, true),
+            (None, None) => continue,
+        };
+        let target = target.as_str().trim().replace("%20", " ");
+        if target.contains("://") {
+            continue;
+        }
+
+        let mut parts: Vec<&str> = if wiki && !target.contains('/') {
+            vec!["attachments"]
+        } else if target.starts_with("./") || target.starts_with("../") || !target.contains('/') {
+            note_dir.clone()
+        } else {
+            Vec::new()
+        };
+        for component in target.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                c => parts.push(c),
+            }
+        }
+
+        let path = parts.join("/");
+        if path.starts_with("attachments/") && !refs.contains(&path) {
+            refs.push(path);
+        }
+    }
+
+    refs
+}
+
+/// Byte ranges of fenced code blocks and inline code spans in a note, in order
+pub(crate) struct CodeSpans(Vec<Range<usize>>);
+
+impl CodeSpans {
+    pub(crate) fn find(content: &str) -> Self {
+        let mut spans = Vec::new();
+        let mut fence_start: Option<usize> = None;
+        let mut offset = 0;
+
+        for line in content.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                match fence_start.take() {
+                    Some(fence) => spans.push(fence..offset),
+                    None => fence_start = Some(start),
+                }
+            } else if fence_start.is_none() {
+                push_inline_code_spans(line, start, &mut spans);
+            }
+        }
+
+        // An unclosed fence runs to the end of the note
+        if let Some(fence) = fence_start {
+            spans.push(fence..offset);
+        }
+
+        CodeSpans(spans)
+    }
+
+    /// Whether the byte at `pos` is inside code
+    pub(crate) fn contains(&self, pos: usize) -> bool {
+        let i = self.0.partition_point(|span| span.end <= pos);
+        self.0.get(i).is_some_and(|span| span.start <= pos)
+    }
+}
+
+/// Inline code in one line: a backtick run closed by a run of the same length.
+/// An unmatched run is literal text.
+fn push_inline_code_spans(line: &str, offset: usize, spans: &mut Vec<Range<usize>>) {
+    let bytes = line.as_bytes();
+    let run_at = |i: usize| bytes[i..].iter().take_while(|&&b| b == b'`').count();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+
+        let open = run_at(i);
+        let mut j = i + open;
+        let mut close = None;
+        while j < bytes.len() {
+            if bytes[j] == b'`' {
+                let run = run_at(j);
+                if run == open {
+                    close = Some(j + run);
+                    break;
+                }
+                j += run;
+            } else {
+                j += 1;
+            }
+        }
+
+        match close {
+            Some(end) => {
+                spans.push(offset + i..offset + end);
+                i = end;
+            }
+            None => i += open,
+        }
+    }
+}
+
 /// Extract block references from content: lines ending with ^block-id
 /// Returns: Vec<(block_id, content, line_number)>
 fn extract_blocks(content: &str) -> Vec<(String, String, i32)> {
@@ -733,6 +2158,38 @@ fn extract_blocks(content: &str) -> Vec<(String, String, i32)> {
     blocks
 }
 
+/// Extract checklist items (`- [ ] text` / `- [x] text`) outside fenced code
+/// Returns: Vec<(line_number, indent, completed, text)>
+pub(crate) fn extract_tasks(content: &str) -> Vec<(i32, i32, bool, String)> {
+    let mut tasks = Vec::new();
+    let mut in_fence = false;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if let Some(cap) = TASK_REGEX.captures(line) {
+            // Tabs count as four columns so mixed indentation still nests
+            let indent: i32 = cap[1].chars().map(|c| if c == '\t' { 4 } else { 1 }).sum();
+            let completed = &cap[2] != " ";
+            tasks.push((
+                (line_num + 1) as i32,
+                indent,
+                completed,
+                cap.get(3).map_or("", |m| m.as_str()).trim().to_string(),
+            ));
+        }
+    }
+
+    tasks
+}
+
 /// Extract card links from content: [[card:Card Title]] or [[card:Board Name/Card Title]]
 fn extract_card_links(content: &str) -> Vec<(String, Option<String>, String)> {
     let mut card_links = Vec::new();
@@ -766,3 +2223,216 @@ fn extract_card_links(content: &str) -> Vec<(String, Option<String>, String)> {
 
     card_links
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::open_test_db;
+
+    /// Rebuild the index of the vault at `vault_path` into `conn`
+    fn rebuild(conn: &Connection, vault_path: &Path) -> IndexRebuildResult {
+        rebuild_with_token(conn, vault_path, &AtomicBool::new(false))
+    }
+
+    fn rebuild_with_token(
+        conn: &Connection,
+        vault_path: &Path,
+        cancelled: &AtomicBool,
+    ) -> IndexRebuildResult {
+        let settings = VaultSettings::default();
+        let files = collect_note_files(vault_path, &settings.indexing, || false).unwrap();
+        let read = read_rebuild_notes(conn, vault_path, &settings, &files, || false, |_| {})
+            .unwrap()
+            .unwrap();
+        apply_rebuild(conn, vault_path, &settings, &files, read, cancelled).unwrap()
+    }
+
+    #[test]
+    fn starred_note_stays_starred_after_rebuild() {
+        let vault = tempfile::tempdir().unwrap();
+        std::fs::create_dir(vault.path().join("notes")).unwrap();
+        std::fs::write(vault.path().join("notes/a.md"), "# A\n\nSome text").unwrap();
+
+        let conn = open_test_db();
+        rebuild(&conn, vault.path());
+        let id: String = conn
+            .query_row(
+                "SELECT id FROM notes WHERE path = 'notes/a.md'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        conn.execute(
+            "INSERT INTO starred_notes (note_id, starred_at) VALUES (?1, 0)",
+            params![id],
+        )
+        .unwrap();
+
+        let result = rebuild(&conn, vault.path());
+
+        assert_eq!(result.notes_indexed, 1);
+        let starred: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM starred_notes WHERE note_id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(starred, 1);
+    }
+
+    #[test]
+    fn cancelled_rebuild_leaves_the_index_as_it_was() {
+        let vault = tempfile::tempdir().unwrap();
+        std::fs::create_dir(vault.path().join("notes")).unwrap();
+        std::fs::write(vault.path().join("notes/a.md"), "# A\n\n#tag").unwrap();
+
+        let conn = open_test_db();
+        rebuild(&conn, vault.path());
+
+        let result = rebuild_with_token(&conn, vault.path(), &AtomicBool::new(true));
+
+        assert!(result.cancelled);
+        let (notes, tags): (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM notes), (SELECT COUNT(*) FROM tags)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((notes, tags), (1, 1));
+    }
+
+    #[test]
+    fn rebuild_drops_user_data_of_deleted_notes_only() {
+        let vault = tempfile::tempdir().unwrap();
+        std::fs::create_dir(vault.path().join("notes")).unwrap();
+        std::fs::write(vault.path().join("notes/a.md"), "# A").unwrap();
+        std::fs::write(vault.path().join("notes/b.md"), "# B").unwrap();
+
+        let conn = open_test_db();
+        rebuild(&conn, vault.path());
+        conn.execute_batch(
+            "INSERT INTO note_labels (note_id, label) SELECT id, 'keep' FROM notes;",
+        )
+        .unwrap();
+        std::fs::remove_file(vault.path().join("notes/b.md")).unwrap();
+
+        let result = rebuild(&conn, vault.path());
+
+        assert_eq!((result.notes_cleared, result.notes_indexed), (2, 1));
+        let labelled: Vec<String> = conn
+            .prepare("SELECT n.path FROM note_labels l JOIN notes n ON n.id = l.note_id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(labelled, ["notes/a.md"]);
+        let labels: i64 = conn
+            .query_row("SELECT COUNT(*) FROM note_labels", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(labels, 1);
+    }
+
+    fn hashes(content: &str, skip: Option<&CodeSpans>) -> Vec<String> {
+        extract_entities(content, skip)
+            .into_iter()
+            .filter(|(kind, ..)| kind == "hash")
+            .map(|(_, value, ..)| value)
+            .collect()
+    }
+
+    #[test]
+    fn hashes_in_prose_are_extracted_with_their_algorithm() {
+        let content = "Dropper MD5 D41D8CD98F00B204E9800998ECF8427E, \
+                       SHA-1 da39a3ee5e6b4b0d3255bfef95601890afd80709 and\n\
+                       SHA-256 e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855.";
+
+        let found = hashes(content, None);
+
+        assert_eq!(
+            found,
+            [
+                "d41d8cd98f00b204e9800998ecf8427e",
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ]
+        );
+        let algorithms: Vec<_> = found.iter().map(|h| hash_algorithm(h)).collect();
+        assert_eq!(algorithms, ["md5", "sha1", "sha256"]);
+    }
+
+    #[test]
+    fn hashes_in_code_fences_follow_the_code_setting() {
+        let content = "Hashes:\n\
+                       ```\n\
+                       e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  payload.bin\n\
+                       ```\n";
+
+        assert_eq!(
+            hashes(content, None),
+            ["e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"]
+        );
+        assert!(hashes(content, Some(&CodeSpans::find(content))).is_empty());
+    }
+
+    #[test]
+    fn short_git_hashes_are_not_hashes() {
+        // A short hash, a 41-char hex run and an all-digit 32-char build number
+        let content = "Fixed in 9fceb02 (see 1a410efbd135), \
+                       blob 1a410efbd13591db07496601ebc7a059dd55cfe9b, \
+                       build 12345678901234567890123456789012.";
+
+        assert!(hashes(content, None).is_empty());
+    }
+
+    #[test]
+    fn tags_in_code_are_not_indexed_by_default() {
+        let vault = tempfile::tempdir().unwrap();
+        std::fs::create_dir(vault.path().join("notes")).unwrap();
+        std::fs::write(
+            vault.path().join("notes/a.md"),
+            "# A\n\n#prose and `#inline`\n\n```bash\n# comment\necho #fenced\n```\n",
+        )
+        .unwrap();
+
+        let conn = open_test_db();
+        rebuild(&conn, vault.path());
+
+        let mut stmt = conn.prepare("SELECT tag FROM tags ORDER BY tag").unwrap();
+        let tags: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tags, ["prose"]);
+    }
+
+    #[test]
+    fn tags_in_code_are_kept_without_code_spans() {
+        let content = "#prose\n\n```\necho #fenced\n```\n";
+        assert_eq!(extract_tags(content, &None, None), ["prose", "fenced"]);
+    }
+
+    #[test]
+    fn first_index_persists_created_once() {
+        let vault = tempfile::tempdir().unwrap();
+        std::fs::create_dir(vault.path().join("notes")).unwrap();
+        let note_path = vault.path().join("notes/a.md");
+        std::fs::write(&note_path, "# A\n\nSome text").unwrap();
+
+        let conn = open_test_db();
+        rebuild(&conn, vault.path());
+        let persisted = std::fs::read_to_string(&note_path).unwrap();
+        rebuild(&conn, vault.path());
+
+        let created = crate::commands::notes::frontmatter_value(&persisted, "created").unwrap();
+        let created_at: i64 = conn
+            .query_row("SELECT created_at FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert!(persisted.ends_with("# A\n\nSome text"));
+        assert_eq!(parse_created_timestamp(created), Some(created_at));
+        assert_eq!(std::fs::read_to_string(&note_path).unwrap(), persisted);
+    }
+}
@@ -1,31 +1,68 @@
 pub mod dataview;
+mod dates;
+mod error;
 mod indexer;
+mod pool;
 mod schema;
 mod search;
 
 use rusqlite::Connection;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager};
 
+use crate::commands::notes::NoteMetadata;
+
+pub use dates::*;
+pub use error::DbError;
 pub use indexer::*;
+use pool::{ReadPool, READ_POOL_SIZE};
 pub use search::*;
 
+/// How long a connection waits on a lock held by another connection
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Compiled statements kept per connection by `prepare_cached`
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Page cache per connection, in KiB
+const CACHE_SIZE_KIB: i64 = 16 * 1024;
+
+/// How often a rekey checks whether in-flight readers have let go of the pool
+const POOL_DRAIN_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
 /// Database state managed by Tauri
 #[derive(Default)]
 pub struct DatabaseState {
     pub conn: Option<Connection>,
+    /// Read-only connections for slow analytic queries, so they don't hold up
+    /// `conn` or each other
+    pub read_pool: Option<Arc<ReadPool>>,
     pub vault_path: Option<PathBuf>,
 }
 
+/// Cancellation token of the current indexing runs. Once set it stays set for
+/// the runs holding it; the next run gets a fresh token. Kept apart from
+/// `DatabaseState` so a cancel gets through while a rebuild holds the connection.
+#[derive(Default)]
+pub struct IndexingState {
+    cancelled: Mutex<Arc<AtomicBool>>,
+}
+
 /// Initialize database state
-pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+pub fn init(app: &AppHandle) -> Result<(), DbError> {
     app.manage(Mutex::new(DatabaseState::default()));
+    app.manage(IndexingState::default());
     Ok(())
 }
 
-/// Open database for a vault
-pub fn open_vault_db(app: &AppHandle, vault_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Open database for a vault. `key` is the SQLCipher key for vaults protected by a passphrase.
+pub fn open_vault_db(
+    app: &AppHandle,
+    vault_path: &Path,
+    key: Option<&[u8; 32]>,
+) -> Result<(), DbError> {
     let db_path = vault_path.join(".kairo").join("index.db");
 
     // Ensure .kairo directory exists
@@ -33,29 +70,235 @@ pub fn open_vault_db(app: &AppHandle, vault_path: &Path) -> Result<(), Box<dyn s
         std::fs::create_dir_all(parent)?;
     }
 
-    let conn = Connection::open(&db_path)?;
+    let conn = open_write_connection(&db_path, key)?;
 
-    // Initialize schema
-    schema::init_schema(&conn)?;
+    // Opened after the schema exists so readers never see a half-migrated database
+    let read_conns = (0..READ_POOL_SIZE)
+        .map(|_| open_connection(&db_path, key))
+        .collect::<Result<Vec<_>, _>>()?;
+    let read_pool = ReadPool::new(read_conns)?;
 
     // Store in state
     let state = app.state::<Mutex<DatabaseState>>();
     let mut state = state.lock().map_err(|e| e.to_string())?;
     state.conn = Some(conn);
+    state.read_pool = Some(Arc::new(read_pool));
     state.vault_path = Some(vault_path.to_path_buf());
 
     Ok(())
 }
 
+/// Open the write connection: switches the index to WAL and brings the schema up to date
+fn open_write_connection(db_path: &Path, key: Option<&[u8; 32]>) -> Result<Connection, DbError> {
+    let conn = open_connection(db_path, key)?;
+
+    // WAL lets the read pool run queries while the write connection commits.
+    // The mode is persistent, so this also applies to the readers opened after.
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+
+    // Initialize schema
+    schema::init_schema(&conn)?;
+
+    Ok(conn)
+}
+
+/// Open a connection to the index, unlocking it when the vault is encrypted
+fn open_connection(db_path: &Path, key: Option<&[u8; 32]>) -> Result<Connection, DbError> {
+    let conn = Connection::open(db_path)?;
+
+    if let Some(key) = key {
+        // Raw key syntax: the key is already derived with Argon2id, so skip SQLCipher's own KDF
+        conn.pragma_update(None, "key", format!("x'{}'", hex::encode(key)))?;
+    }
+
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // Room for the indexer's per-note statements plus the common queries
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+    // Per-connection tuning. SQLite leaves foreign keys off by default; the
+    // schema relies on ON DELETE CASCADE / SET NULL to clean up dependent rows.
+    // NORMAL sync is durable under WAL except on power loss, and skips an
+    // fsync per commit during bulk indexing.
+    conn.execute_batch(&format!(
+        "PRAGMA foreign_keys = ON;
+         PRAGMA synchronous = NORMAL;
+         PRAGMA cache_size = -{};
+         PRAGMA temp_store = MEMORY;",
+        CACHE_SIZE_KIB
+    ))?;
+
+    Ok(conn)
+}
+
+/// In-memory index with the same schema and foreign-key enforcement as a vault's
+#[cfg(test)]
+pub(crate) fn open_test_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+    schema::init_schema(&conn).unwrap();
+    conn
+}
+
+/// Ask any in-flight `index_vault` to stop after the current file
+pub fn cancel_indexing(app: &AppHandle) -> Result<(), DbError> {
+    let state = app.state::<IndexingState>();
+    let cancelled = state.cancelled.lock().map_err(|e| e.to_string())?;
+    cancelled.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Cancellation token for a new indexing run. Runs started together share a
+/// token, so one cancel stops them all; a token that was already cancelled is
+/// replaced rather than cleared, so older runs stay stopped.
+pub(crate) fn start_indexing(app: &AppHandle) -> Result<Arc<AtomicBool>, DbError> {
+    let state = app.state::<IndexingState>();
+    let mut cancelled = state.cancelled.lock().map_err(|e| e.to_string())?;
+    if cancelled.load(Ordering::SeqCst) {
+        *cancelled = Arc::new(AtomicBool::new(false));
+    }
+    Ok(cancelled.clone())
+}
+
 /// Close the current vault database
-pub fn close_vault_db(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+pub fn close_vault_db(app: &AppHandle) -> Result<(), DbError> {
+    // Stop indexing before the connections it writes through go away. This comes
+    // first so a rebuild holding the connection rolls back and lets go of it.
+    cancel_indexing(app)?;
+
     let state = app.state::<Mutex<DatabaseState>>();
     let mut state = state.lock().map_err(|e| e.to_string())?;
     state.conn = None;
+    state.read_pool = None;
     state.vault_path = None;
     Ok(())
 }
 
+/// Re-encrypt the open vault's database with a new key (or decrypt it when `new_key` is None).
+/// The database is exported to a temp file, swapped in, and reopened. `old_key` is the key
+/// the database is open with now: if the swap fails, the original file is put back and
+/// reopened with it, so the vault stays open either way.
+pub fn rekey_vault_db(
+    app: &AppHandle,
+    old_key: Option<&[u8; 32]>,
+    new_key: Option<&[u8; 32]>,
+) -> Result<(), DbError> {
+    let vault_path = get_current_vault_path(app).ok_or("No vault open")?;
+    let kairo_dir = vault_path.join(".kairo");
+    let db_path = kairo_dir.join("index.db");
+    let tmp_path = kairo_dir.join("index.db.rekey");
+    let backup_path = kairo_dir.join("index.db.old");
+
+    for leftover in [&tmp_path, &backup_path] {
+        if leftover.exists() {
+            std::fs::remove_file(leftover)?;
+        }
+    }
+
+    let key_spec = new_key
+        .map(|k| format!("x'{}'", hex::encode(k)))
+        .unwrap_or_default();
+
+    // The export copies FTS shadow tables verbatim; repopulate to be safe. The
+    // FTS table has columns `notes` lacks, so 'rebuild' can't read them from there.
+    let exported = with_db(app, |conn| {
+        conn.execute(
+            "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+            rusqlite::params![tmp_path.to_string_lossy(), key_spec],
+        )?;
+        let result = conn
+            .query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()))
+            .and_then(|()| {
+                conn.execute_batch(
+                    "INSERT INTO rekeyed.notes_fts(notes_fts) VALUES('delete-all');
+                     INSERT INTO rekeyed.notes_fts(rowid, title, content, tags, code_blocks)
+                     SELECT rowid, title, content, '', '' FROM rekeyed.notes;",
+                )
+            });
+        conn.execute_batch("DETACH DATABASE rekeyed;")?;
+        Ok(result?)
+    });
+    if let Err(e) = exported {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    // Take the connections out of the state so nothing new starts on them
+    cancel_indexing(app)?;
+    let (conn, read_pool) = {
+        let state = app.state::<Mutex<DatabaseState>>();
+        let mut state = state.lock().map_err(|e| e.to_string())?;
+        state.vault_path = None;
+        (state.conn.take(), state.read_pool.take())
+    };
+
+    // Readers already running hold their own handle on the pool; its
+    // connections close once the last of them is done
+    if let Some(mut pool) = read_pool {
+        while let Err(shared) = Arc::try_unwrap(pool) {
+            pool = shared;
+            std::thread::sleep(POOL_DRAIN_INTERVAL);
+        }
+    }
+
+    let swapped = swap_in_rekeyed_db(conn, &db_path, &tmp_path, &backup_path)
+        .and_then(|()| open_vault_db(app, &vault_path, new_key));
+    match swapped {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&backup_path);
+            Ok(())
+        }
+        Err(e) => {
+            // Once the original is set aside its WAL is already folded in, so
+            // any WAL left now belongs to the rekeyed file
+            if backup_path.exists() {
+                remove_wal_files(&db_path)?;
+                std::fs::rename(&backup_path, &db_path)?;
+            }
+            let _ = std::fs::remove_file(&tmp_path);
+            open_vault_db(app, &vault_path, old_key)?;
+            Err(e)
+        }
+    }
+}
+
+/// Replace the index at `db_path` with the rekeyed copy at `tmp_path`, keeping
+/// the original at `backup_path` until the caller has reopened the index
+fn swap_in_rekeyed_db(
+    conn: Option<Connection>,
+    db_path: &Path,
+    tmp_path: &Path,
+    backup_path: &Path,
+) -> Result<(), DbError> {
+    // Fold the WAL into the old file, so none of it is replayed against the
+    // new one, which is encrypted under a different key
+    if let Some(conn) = conn {
+        let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+        if busy != 0 {
+            return Err("Could not checkpoint the index before rekeying"
+                .to_string()
+                .into());
+        }
+    }
+    remove_wal_files(db_path)?;
+
+    std::fs::rename(db_path, backup_path)?;
+    std::fs::rename(tmp_path, db_path)?;
+    Ok(())
+}
+
+/// Delete the `-wal` and `-shm` files next to a closed database
+fn remove_wal_files(db_path: &Path) -> Result<(), DbError> {
+    for suffix in ["-wal", "-shm"] {
+        let mut stale = db_path.as_os_str().to_owned();
+        stale.push(suffix);
+        let stale = PathBuf::from(stale);
+        if stale.exists() {
+            std::fs::remove_file(&stale)?;
+        }
+    }
+    Ok(())
+}
+
 /// Get the current vault path
 pub fn get_current_vault_path(app: &AppHandle) -> Option<PathBuf> {
     let state = app.state::<Mutex<DatabaseState>>();
@@ -64,21 +307,54 @@ pub fn get_current_vault_path(app: &AppHandle) -> Option<PathBuf> {
 }
 
 /// Execute a database operation with the connection
-pub fn with_db<F, T>(app: &AppHandle, f: F) -> Result<T, Box<dyn std::error::Error>>
+pub fn with_db<F, T>(app: &AppHandle, f: F) -> Result<T, DbError>
 where
-    F: FnOnce(&Connection) -> Result<T, Box<dyn std::error::Error>>,
+    F: FnOnce(&Connection) -> Result<T, DbError>,
 {
     let state = app.state::<Mutex<DatabaseState>>();
     let state = state.lock().map_err(|e| e.to_string())?;
 
     match &state.conn {
         Some(conn) => f(conn),
-        None => Err("No database connection".into()),
+        None => Err(DbError::NoConnection),
     }
 }
 
+/// Execute a read-only query on a connection checked out from the read pool.
+/// The state lock is only held long enough to grab the pool, so long-running
+/// queries here don't block `with_db` callers. Writes go through `with_db`,
+/// which keeps the single write connection.
+pub fn with_read_db<F, T>(app: &AppHandle, f: F) -> Result<T, DbError>
+where
+    F: FnOnce(&Connection) -> Result<T, DbError>,
+{
+    let pool = {
+        let state = app.state::<Mutex<DatabaseState>>();
+        let state = state.lock().map_err(|e| e.to_string())?;
+        state.read_pool.clone().ok_or(DbError::NoConnection)?
+    };
+
+    let conn = pool.get()?;
+    f(&conn)
+}
+
+/// Execute a database operation inside a transaction.
+/// Commits if the closure succeeds; rolls back if it returns an error.
+pub fn with_db_tx<F, T>(app: &AppHandle, f: F) -> Result<T, DbError>
+where
+    F: FnOnce(&Connection) -> Result<T, DbError>,
+{
+    with_db(app, |conn| {
+        let tx = conn.unchecked_transaction()?;
+        // Dropping `tx` without committing rolls it back
+        let result = f(&tx)?;
+        tx.commit()?;
+        Ok(result)
+    })
+}
+
 /// Get note count in the current vault
-pub fn get_note_count(app: &AppHandle) -> Result<usize, Box<dyn std::error::Error>> {
+pub fn get_note_count(app: &AppHandle) -> Result<usize, DbError> {
     with_db(app, |conn| {
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
         Ok(count as usize)
@@ -86,42 +362,365 @@ pub fn get_note_count(app: &AppHandle) -> Result<usize, Box<dyn std::error::Erro
 }
 
 /// Get starred status for a note
-pub fn get_note_starred(
-    app: &AppHandle,
-    note_id: &str,
-) -> Result<bool, Box<dyn std::error::Error>> {
+pub fn get_note_starred(app: &AppHandle, note_id: &str) -> Result<bool, DbError> {
     with_db(app, |conn| {
-        let starred: i32 = conn
+        let starred: bool = conn
             .query_row(
-                "SELECT COALESCE(starred, 0) FROM notes WHERE id = ?1",
+                "SELECT EXISTS(SELECT 1 FROM starred_notes WHERE note_id = ?1)",
                 rusqlite::params![note_id],
                 |row| row.get(0),
             )
-            .unwrap_or(0);
-        Ok(starred != 0)
+            .unwrap_or(false);
+        Ok(starred)
+    })
+}
+
+/// Set starred status for a note. Starred state is keyed by note id, so it
+/// follows the note through renames and index rebuilds.
+pub fn set_note_starred(app: &AppHandle, note_id: &str, starred: bool) -> Result<(), DbError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    with_db(app, |conn| {
+        if starred {
+            conn.execute(
+                "INSERT OR IGNORE INTO starred_notes (note_id, starred_at)
+                 SELECT id, ?2 FROM notes WHERE id = ?1",
+                rusqlite::params![note_id, now],
+            )?;
+        } else {
+            conn.execute(
+                "DELETE FROM starred_notes WHERE note_id = ?1",
+                rusqlite::params![note_id],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+/// Indexed fields needed to preview a note, read without touching disk
+pub struct NotePreviewRow {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub modified_at: i64,
+}
+
+/// Get the stored title, content, tags and timestamps of a note by path
+pub fn get_note_preview_row(app: &AppHandle, path: &str) -> Result<NotePreviewRow, DbError> {
+    with_db(app, |conn| {
+        let mut row = conn.query_row(
+            "SELECT id, title, content, created_at, modified_at FROM notes WHERE path = ?1",
+            rusqlite::params![path],
+            |row| {
+                Ok(NotePreviewRow {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    content: row.get(2)?,
+                    tags: Vec::new(),
+                    created_at: row.get(3)?,
+                    modified_at: row.get(4)?,
+                })
+            },
+        )?;
+
+        let mut stmt =
+            conn.prepare("SELECT DISTINCT tag FROM tags WHERE note_id = ?1 ORDER BY tag")?;
+        row.tags = stmt
+            .query_map(rusqlite::params![row.id], |r| r.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(row)
+    })
+}
+
+/// Indexed content and counts behind a note's detail stats
+pub struct NoteStatsRow {
+    pub id: String,
+    pub content: String,
+    pub outgoing_count: usize,
+    pub incoming_count: usize,
+    pub open_tasks: usize,
+    pub done_tasks: usize,
+    pub attachment_count: usize,
+}
+
+/// Get a note's stored content, cached link counts, task counts and number of
+/// referenced attachments by path
+pub fn get_note_stats_row(app: &AppHandle, path: &str) -> Result<NoteStatsRow, DbError> {
+    with_read_db(app, |conn| {
+        let row = conn.query_row(
+            r#"
+            SELECT n.id, n.content, n.outgoing_count, n.incoming_count,
+                   (SELECT COUNT(*) FROM tasks t WHERE t.note_id = n.id AND t.completed = 0),
+                   (SELECT COUNT(*) FROM tasks t WHERE t.note_id = n.id AND t.completed = 1),
+                   (SELECT COUNT(*) FROM attachment_refs a WHERE a.note_id = n.id)
+            FROM notes n
+            WHERE n.path = ?1
+            "#,
+            rusqlite::params![path],
+            |row| {
+                Ok(NoteStatsRow {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    outgoing_count: row.get::<_, i64>(2)? as usize,
+                    incoming_count: row.get::<_, i64>(3)? as usize,
+                    open_tasks: row.get::<_, i64>(4)? as usize,
+                    done_tasks: row.get::<_, i64>(5)? as usize,
+                    attachment_count: row.get::<_, i64>(6)? as usize,
+                })
+            },
+        )?;
+        Ok(row)
+    })
+}
+
+/// Get the cached (outgoing, incoming) link counts for a note
+pub fn get_note_link_counts(app: &AppHandle, note_id: &str) -> Result<(usize, usize), DbError> {
+    with_db(app, |conn| {
+        let counts: (i64, i64) = conn
+            .query_row(
+                "SELECT outgoing_count, incoming_count FROM notes WHERE id = ?1",
+                rusqlite::params![note_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap_or((0, 0));
+        Ok((counts.0 as usize, counts.1 as usize))
+    })
+}
+
+/// Record the time a note was opened; reopening moves it to the front
+pub fn touch_note_opened(app: &AppHandle, note_id: &str) -> Result<(), DbError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT INTO recent_notes (note_id, opened_at) VALUES (?1, ?2)
+             ON CONFLICT(note_id) DO UPDATE SET opened_at = excluded.opened_at",
+            rusqlite::params![note_id, now],
+        )?;
+        Ok(())
+    })
+}
+
+/// Get recently opened notes, newest first
+pub fn get_recent_notes(app: &AppHandle, limit: u32) -> Result<Vec<NoteMetadata>, DbError> {
+    with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.path, n.title, n.modified_at, n.created_at, COALESCE(n.archived, 0), EXISTS(SELECT 1 FROM starred_notes s WHERE s.note_id = n.id),
+                    n.outgoing_count, n.incoming_count
+             FROM recent_notes r
+             JOIN notes n ON n.id = r.note_id
+             ORDER BY r.opened_at DESC
+             LIMIT ?1",
+        )?;
+
+        let notes = stmt
+            .query_map(rusqlite::params![limit as i64], |row| {
+                Ok(NoteMetadata {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    title: row.get(2)?,
+                    modified_at: row.get(3)?,
+                    created_at: row.get(4)?,
+                    archived: row.get::<_, i32>(5)? != 0,
+                    starred: row.get::<_, i32>(6)? != 0,
+                    outgoing_count: row.get::<_, i64>(7)? as usize,
+                    incoming_count: row.get::<_, i64>(8)? as usize,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(notes)
+    })
+}
+
+/// Pin a note to the end of the pinned list; pinning it again keeps its place
+pub fn pin_note(app: &AppHandle, note_id: &str) -> Result<(), DbError> {
+    with_db(app, |conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO pinned_notes (note_id, position)
+             SELECT ?1, COALESCE(MAX(position), -1) + 1 FROM pinned_notes",
+            rusqlite::params![note_id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Remove a note from the pinned list
+pub fn unpin_note(app: &AppHandle, note_id: &str) -> Result<(), DbError> {
+    with_db(app, |conn| {
+        conn.execute(
+            "DELETE FROM pinned_notes WHERE note_id = ?1",
+            rusqlite::params![note_id],
+        )?;
+        Ok(())
+    })
+}
+
+/// Reorder pinned notes. Listed ids come first in the given order; pinned
+/// notes missing from the list keep their relative order after them.
+/// Ids that aren't pinned are ignored.
+pub fn reorder_pinned(app: &AppHandle, ordered_ids: &[String]) -> Result<(), DbError> {
+    with_db_tx(app, |conn| {
+        let current: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT note_id FROM pinned_notes ORDER BY position")?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+
+        let mut order: Vec<&String> = Vec::with_capacity(current.len());
+        for id in ordered_ids {
+            if current.contains(id) && !order.contains(&id) {
+                order.push(id);
+            }
+        }
+        for id in &current {
+            if !order.contains(&id) {
+                order.push(id);
+            }
+        }
+
+        let mut stmt = conn.prepare("UPDATE pinned_notes SET position = ?1 WHERE note_id = ?2")?;
+        for (position, id) in order.iter().enumerate() {
+            stmt.execute(rusqlite::params![position as i64, id])?;
+        }
+        Ok(())
+    })
+}
+
+/// Get pinned notes in pinned order
+pub fn get_pinned_notes(app: &AppHandle) -> Result<Vec<NoteMetadata>, DbError> {
+    with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.path, n.title, n.modified_at, n.created_at, COALESCE(n.archived, 0), EXISTS(SELECT 1 FROM starred_notes s WHERE s.note_id = n.id),
+                    n.outgoing_count, n.incoming_count
+             FROM pinned_notes p
+             JOIN notes n ON n.id = p.note_id
+             ORDER BY p.position",
+        )?;
+
+        let notes = stmt
+            .query_map([], |row| {
+                Ok(NoteMetadata {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    title: row.get(2)?,
+                    modified_at: row.get(3)?,
+                    created_at: row.get(4)?,
+                    archived: row.get::<_, i32>(5)? != 0,
+                    starred: row.get::<_, i32>(6)? != 0,
+                    outgoing_count: row.get::<_, i64>(7)? as usize,
+                    incoming_count: row.get::<_, i64>(8)? as usize,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(notes)
     })
 }
 
-/// Set starred status for a note
-pub fn set_note_starred(
+/// A colored label attached to a note
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct NoteLabel {
+    pub label: String,
+    pub color: Option<String>,
+}
+
+/// Add a label to a note, or change the color of an existing one
+pub fn set_note_label(
     app: &AppHandle,
     note_id: &str,
-    starred: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+    label: &str,
+    color: Option<&str>,
+) -> Result<(), DbError> {
     with_db(app, |conn| {
         conn.execute(
-            "UPDATE notes SET starred = ?1 WHERE id = ?2",
-            rusqlite::params![if starred { 1 } else { 0 }, note_id],
+            "INSERT INTO note_labels (note_id, label, color) VALUES (?1, ?2, ?3)
+             ON CONFLICT(note_id, label) DO UPDATE SET color = excluded.color",
+            rusqlite::params![note_id, label, color],
         )?;
         Ok(())
     })
 }
 
-/// Get aliases for a note
-pub fn get_note_aliases(
+/// Remove a label from a note
+pub fn remove_note_label(app: &AppHandle, note_id: &str, label: &str) -> Result<(), DbError> {
+    with_db(app, |conn| {
+        conn.execute(
+            "DELETE FROM note_labels WHERE note_id = ?1 AND label = ?2",
+            rusqlite::params![note_id, label],
+        )?;
+        Ok(())
+    })
+}
+
+/// Get the labels of a note, by name
+pub fn get_note_labels(app: &AppHandle, note_id: &str) -> Result<Vec<NoteLabel>, DbError> {
+    with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT label, color FROM note_labels WHERE note_id = ?1 ORDER BY label COLLATE NOCASE",
+        )?;
+        let labels = stmt
+            .query_map(rusqlite::params![note_id], |row| {
+                Ok(NoteLabel {
+                    label: row.get(0)?,
+                    color: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(labels)
+    })
+}
+
+/// Get labels for many notes at once, keyed by note id. Notes without labels are omitted.
+pub fn get_note_labels_batch(
     app: &AppHandle,
-    note_id: &str,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    note_ids: &[String],
+) -> Result<std::collections::HashMap<String, Vec<NoteLabel>>, DbError> {
+    with_db(app, |conn| {
+        let mut labels: std::collections::HashMap<String, Vec<NoteLabel>> =
+            std::collections::HashMap::new();
+
+        for chunk in note_ids.chunks(MAX_SQL_PARAMS) {
+            let placeholders: Vec<String> = (1..=chunk.len()).map(|i| format!("?{}", i)).collect();
+            let mut stmt = conn.prepare(&format!(
+                "SELECT note_id, label, color FROM note_labels WHERE note_id IN ({})
+                 ORDER BY label COLLATE NOCASE",
+                placeholders.join(", ")
+            ))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(chunk), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    NoteLabel {
+                        label: row.get(1)?,
+                        color: row.get(2)?,
+                    },
+                ))
+            })?;
+            for (note_id, label) in rows.filter_map(|r| r.ok()) {
+                labels.entry(note_id).or_default().push(label);
+            }
+        }
+
+        Ok(labels)
+    })
+}
+
+/// Get aliases for a note
+pub fn get_note_aliases(app: &AppHandle, note_id: &str) -> Result<Vec<String>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare("SELECT alias FROM aliases WHERE note_id = ?1")?;
         let aliases = stmt
@@ -133,10 +732,7 @@ pub fn get_note_aliases(
 }
 
 /// Resolve a note path by alias (case-insensitive)
-pub fn resolve_note_by_alias(
-    app: &AppHandle,
-    alias: &str,
-) -> Result<Option<String>, Box<dyn std::error::Error>> {
+pub fn resolve_note_by_alias(app: &AppHandle, alias: &str) -> Result<Option<String>, DbError> {
     with_db(app, |conn| {
         let path: Result<String, _> = conn.query_row(
             r#"
@@ -156,7 +752,7 @@ pub fn resolve_note_by_alias(
 pub type AliasInfo = (String, String, String);
 
 /// Get all aliases with their note paths (for autocomplete)
-pub fn get_all_aliases(app: &AppHandle) -> Result<Vec<AliasInfo>, Box<dyn std::error::Error>> {
+pub fn get_all_aliases(app: &AppHandle) -> Result<Vec<AliasInfo>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             r#"
@@ -183,14 +779,15 @@ pub fn get_all_aliases(app: &AppHandle) -> Result<Vec<AliasInfo>, Box<dyn std::e
 // Note Versioning Functions
 // ============================================================================
 
-/// Create a new version of a note (auto-deduplicates based on content hash)
+/// Create a new version of a note (auto-deduplicates based on content hash).
+/// "save" versions are additionally throttled by the vault's versioning settings.
 pub fn create_note_version(
     app: &AppHandle,
     note_id: &str,
     content: &str,
-    trigger: &str, // "save", "auto", "manual"
+    trigger: &str, // "save", "auto", "manual", "split"
     label: Option<&str>,
-) -> Result<Option<i64>, Box<dyn std::error::Error>> {
+) -> Result<Option<i64>, DbError> {
     use sha2::{Digest, Sha256};
 
     // Hash the content
@@ -199,7 +796,12 @@ pub fn create_note_version(
     let result = hasher.finalize();
     let content_hash = hex::encode(&result[..16]);
 
-    with_db(app, |conn| {
+    let policy = get_current_vault_path(app)
+        .and_then(|p| crate::commands::settings::read_vault_settings(&p).ok())
+        .unwrap_or_default()
+        .versioning;
+
+    with_db_tx(app, |conn| {
         // Check if this exact version already exists (deduplication)
         let existing: Result<i64, _> = conn.query_row(
             "SELECT id FROM note_versions WHERE note_id = ?1 AND content_hash = ?2",
@@ -218,6 +820,26 @@ pub fn create_note_version(
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
 
+        // Throttle save-triggered versions: keep one only if enough changed or enough time passed
+        if trigger == "save" {
+            let latest: Option<(String, i64)> = conn
+                .query_row(
+                    "SELECT content, created_at FROM note_versions WHERE note_id = ?1 ORDER BY created_at DESC, id DESC LIMIT 1",
+                    rusqlite::params![note_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .ok();
+
+            if let Some((last_content, last_created_at)) = latest {
+                let elapsed = created_at - last_created_at;
+                if elapsed < policy.min_interval_secs
+                    && changed_char_count(&last_content, content) < policy.min_changed_chars
+                {
+                    return Ok(None);
+                }
+            }
+        }
+
         // Insert new version
         conn.execute(
             r#"
@@ -245,6 +867,27 @@ pub fn create_note_version(
     })
 }
 
+/// Approximate size of an edit: the length of the region left after trimming
+/// the common prefix and suffix of both strings
+fn changed_char_count(old: &str, new: &str) -> usize {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (old.len() - prefix - suffix).max(new.len() - prefix - suffix)
+}
+
 /// Version info for listing
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NoteVersionInfo {
@@ -257,10 +900,7 @@ pub struct NoteVersionInfo {
 }
 
 /// Get version history for a note
-pub fn get_note_versions(
-    app: &AppHandle,
-    note_id: &str,
-) -> Result<Vec<NoteVersionInfo>, Box<dyn std::error::Error>> {
+pub fn get_note_versions(app: &AppHandle, note_id: &str) -> Result<Vec<NoteVersionInfo>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             r#"
@@ -292,10 +932,7 @@ pub fn get_note_versions(
 }
 
 /// Get a specific version's content
-pub fn get_version_content(
-    app: &AppHandle,
-    version_id: i64,
-) -> Result<Option<String>, Box<dyn std::error::Error>> {
+pub fn get_version_content(app: &AppHandle, version_id: i64) -> Result<Option<String>, DbError> {
     with_db(app, |conn| {
         let content: Result<String, _> = conn.query_row(
             "SELECT content FROM note_versions WHERE id = ?1",
@@ -307,11 +944,7 @@ pub fn get_version_content(
 }
 
 /// Label a version (for manual snapshots)
-pub fn label_version(
-    app: &AppHandle,
-    version_id: i64,
-    label: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn label_version(app: &AppHandle, version_id: i64, label: &str) -> Result<(), DbError> {
     with_db(app, |conn| {
         conn.execute(
             "UPDATE note_versions SET label = ?1 WHERE id = ?2",
@@ -320,3 +953,33 @@ pub fn label_version(
         Ok(())
     })
 }
+
+/// Delete all stored versions of a note
+pub fn delete_note_versions(app: &AppHandle, note_id: &str) -> Result<usize, DbError> {
+    with_db(app, |conn| {
+        let deleted = conn.execute(
+            "DELETE FROM note_versions WHERE note_id = ?1",
+            rusqlite::params![note_id],
+        )?;
+        Ok(deleted)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_opens_in_wal_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("index.db");
+        open_write_connection(&db_path, None).unwrap();
+
+        // The mode is persistent, so a fresh connection sees it too
+        let reader = open_connection(&db_path, None).unwrap();
+        let mode: String = reader
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+    }
+}
@@ -0,0 +1,71 @@
+//! Small pool of read-only connections so slow analytic queries can run
+//! alongside each other and alongside the single write connection
+
+use rusqlite::Connection;
+use std::ops::Deref;
+use std::sync::{Condvar, Mutex};
+
+use super::DbError;
+
+/// Number of read connections kept open per vault
+pub const READ_POOL_SIZE: usize = 4;
+
+/// A fixed set of read-only connections handed out one caller at a time
+pub struct ReadPool {
+    idle: Mutex<Vec<Connection>>,
+    returned: Condvar,
+}
+
+impl ReadPool {
+    /// Build a pool from already-opened connections, marking each read-only
+    pub fn new(conns: Vec<Connection>) -> Result<Self, DbError> {
+        for conn in &conns {
+            conn.execute_batch("PRAGMA query_only = ON;")?;
+        }
+        Ok(Self {
+            idle: Mutex::new(conns),
+            returned: Condvar::new(),
+        })
+    }
+
+    /// Check out a connection, waiting for one to be returned if all are busy
+    pub fn get(&self) -> Result<PooledConnection<'_>, DbError> {
+        let mut idle = self.idle.lock().map_err(|e| e.to_string())?;
+        loop {
+            if let Some(conn) = idle.pop() {
+                return Ok(PooledConnection {
+                    pool: self,
+                    conn: Some(conn),
+                });
+            }
+            idle = self.returned.wait(idle).map_err(|e| e.to_string())?;
+        }
+    }
+}
+
+/// A checked-out read connection, returned to the pool on drop
+pub struct PooledConnection<'a> {
+    pool: &'a ReadPool,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("pooled connection used after release")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            if let Ok(mut idle) = self.pool.idle.lock() {
+                idle.push(conn);
+            }
+            self.pool.returned.notify_one();
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use rusqlite::Connection;
 
+use super::DbError;
+
 /// Initialize the database schema
-pub fn init_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+pub fn init_schema(conn: &Connection) -> Result<(), DbError> {
     conn.execute_batch(
         r#"
         -- Core note index
@@ -54,7 +56,7 @@ pub fn init_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>>
         CREATE TABLE IF NOT EXISTS entities (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             note_id TEXT REFERENCES notes(id) ON DELETE CASCADE,
-            entity_type TEXT NOT NULL,  -- 'ip', 'domain', 'cve', 'username', 'mention'
+            entity_type TEXT NOT NULL,  -- 'ip', 'domain', 'url', 'email', 'cve', 'hash', 'username', 'mention', 'member'
             value TEXT NOT NULL,
             context TEXT,  -- Surrounding text for preview
             line_number INTEGER
@@ -70,6 +72,7 @@ pub fn init_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>>
             source_id TEXT REFERENCES notes(id) ON DELETE CASCADE,
             target_path TEXT NOT NULL,  -- Path of the target note
             context TEXT,  -- The text surrounding the link
+            in_heading INTEGER DEFAULT 0,  -- 1 if the link sits in a markdown heading
             PRIMARY KEY (source_id, target_path)
         );
 
@@ -253,7 +256,7 @@ pub fn init_schema(conn: &Connection) -> Result<(), Box<dyn std::error::Error>>
 }
 
 /// Run database migrations for schema updates
-fn run_migrations(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
+fn run_migrations(conn: &Connection) -> Result<(), DbError> {
     // Check if kanban_cards has the new columns by trying to select them
     // If they don't exist, add them via ALTER TABLE
 
@@ -480,5 +483,322 @@ fn run_migrations(conn: &Connection) -> Result<(), Box<dyn std::error::Error>> {
         )?;
     }
 
+    // Migration: Create recent_notes table for the recently opened jump list
+    let has_recent_table = conn
+        .prepare("SELECT note_id FROM recent_notes LIMIT 0")
+        .is_ok();
+
+    if !has_recent_table {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS recent_notes (
+                note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+                opened_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_recent_notes_opened ON recent_notes(opened_at);
+            "#,
+        )?;
+    }
+
+    // Migration: Create kanban_card_history table for the card audit log
+    let has_card_history = conn
+        .prepare("SELECT id FROM kanban_card_history LIMIT 0")
+        .is_ok();
+
+    if !has_card_history {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS kanban_card_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                card_id TEXT REFERENCES kanban_cards(id) ON DELETE CASCADE,
+                field TEXT NOT NULL,  -- 'column', 'board', 'assignees', 'complete'
+                old_value TEXT,
+                new_value TEXT,
+                actor TEXT,  -- Vault user who made the change
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_kanban_card_history_card ON kanban_card_history(card_id);
+            "#,
+        )?;
+    }
+
+    // Migration: Record whether a backlink sits in a heading, for backlink ranking
+    let has_in_heading = conn
+        .prepare("SELECT in_heading FROM backlinks LIMIT 0")
+        .is_ok();
+
+    if !has_in_heading {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE backlinks ADD COLUMN in_heading INTEGER DEFAULT 0;
+            "#,
+        )?;
+    }
+
+    // Migration: Create diagram_layers table for named, orderable node layers
+    let has_diagram_layers = conn
+        .prepare("SELECT id FROM diagram_layers LIMIT 0")
+        .is_ok();
+
+    if !has_diagram_layers {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS diagram_layers (
+                id TEXT PRIMARY KEY,
+                board_id TEXT NOT NULL REFERENCES diagram_boards(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                visible INTEGER NOT NULL DEFAULT 1,
+                sort_order INTEGER NOT NULL DEFAULT 0,  -- Lower values draw first
+                locked INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_diagram_layers_board ON diagram_layers(board_id);
+            "#,
+        )?;
+    }
+
+    // Migration: Create pinned_notes table for the ordered pinned notes bar
+    let has_pinned_table = conn
+        .prepare("SELECT note_id FROM pinned_notes LIMIT 0")
+        .is_ok();
+
+    if !has_pinned_table {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS pinned_notes (
+                note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+                position INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_pinned_notes_position ON pinned_notes(position);
+            "#,
+        )?;
+    }
+
+    // Migration: Cache link counts on notes so listings and the graph don't re-aggregate backlinks
+    let has_link_counts = conn
+        .prepare("SELECT outgoing_count FROM notes LIMIT 0")
+        .is_ok();
+
+    if !has_link_counts {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE notes ADD COLUMN outgoing_count INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE notes ADD COLUMN incoming_count INTEGER NOT NULL DEFAULT 0;
+            "#,
+        )?;
+        super::refresh_link_counts(conn)?;
+    }
+
+    // Migration: Create note_labels table for colored labels kept outside note content
+    let has_note_labels = conn
+        .prepare("SELECT note_id FROM note_labels LIMIT 0")
+        .is_ok();
+
+    if !has_note_labels {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS note_labels (
+                note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+                label TEXT NOT NULL,
+                color TEXT,  -- Any CSS color; NULL uses the UI default
+                PRIMARY KEY (note_id, label)
+            );
+            CREATE INDEX IF NOT EXISTS idx_note_labels_label ON note_labels(label);
+            "#,
+        )?;
+    }
+
+    // Migration: Key starred state by note id in its own table so it survives index
+    // rebuilds; the old notes.starred column is left in place but no longer read
+    let has_starred_table = conn
+        .prepare("SELECT note_id FROM starred_notes LIMIT 0")
+        .is_ok();
+
+    if !has_starred_table {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS starred_notes (
+                note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+                starred_at INTEGER NOT NULL
+            );
+            INSERT OR IGNORE INTO starred_notes (note_id, starred_at)
+                SELECT id, CAST(strftime('%s', 'now') AS INTEGER) FROM notes WHERE starred = 1;
+            "#,
+        )?;
+    }
+
+    // Migration: Create tasks table so checklist items can be queried across notes
+    let has_tasks_table = conn.prepare("SELECT id FROM tasks LIMIT 0").is_ok();
+
+    if !has_tasks_table {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS tasks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+                line_number INTEGER NOT NULL,  -- 1-based line of the task in the note
+                indent INTEGER NOT NULL DEFAULT 0,
+                completed INTEGER NOT NULL DEFAULT 0,
+                text TEXT NOT NULL,
+                UNIQUE (note_id, line_number)
+            );
+            CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks(completed);
+            "#,
+        )?;
+    }
+
+    // Migration: Track which due-date reminders have fired so each card is notified once per threshold
+    let has_card_reminders = conn
+        .prepare("SELECT card_id FROM kanban_card_reminders LIMIT 0")
+        .is_ok();
+
+    if !has_card_reminders {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS kanban_card_reminders (
+                card_id TEXT PRIMARY KEY REFERENCES kanban_cards(id) ON DELETE CASCADE,
+                due_date INTEGER NOT NULL,  -- Due date the reminder was sent for; a new date re-arms it
+                stage INTEGER NOT NULL      -- 1 = due soon, 2 = overdue
+            );
+            "#,
+        )?;
+    }
+
+    // Migration: Track which attachments each note embeds or links to
+    let has_attachment_refs = conn
+        .prepare("SELECT note_id FROM attachment_refs LIMIT 0")
+        .is_ok();
+
+    if !has_attachment_refs {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS attachment_refs (
+                note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+                attachment_path TEXT NOT NULL,  -- Vault-relative, e.g. attachments/image.png
+                UNIQUE (note_id, attachment_path)
+            );
+            CREATE INDEX IF NOT EXISTS idx_attachment_refs_path ON attachment_refs(attachment_path);
+            "#,
+        )?;
+    }
+
+    // Migration: Add subtype to entities (the algorithm of hash entities)
+    let has_entity_subtype = conn.prepare("SELECT subtype FROM entities LIMIT 0").is_ok();
+
+    if !has_entity_subtype {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE entities ADD COLUMN subtype TEXT;
+            "#,
+        )?;
+    }
+
+    // Migration: Create graph_positions table for manually arranged graph layouts
+    let has_graph_positions = conn
+        .prepare("SELECT note_id FROM graph_positions LIMIT 0")
+        .is_ok();
+
+    if !has_graph_positions {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS graph_positions (
+                note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+                x REAL NOT NULL,
+                y REAL NOT NULL
+            );
+            "#,
+        )?;
+    }
+
+    // Migration: One-time cleanup of rows orphaned while foreign keys were not enforced
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    if user_version < 1 {
+        cleanup_orphaned_rows(conn)?;
+        conn.execute_batch("PRAGMA user_version = 1;")?;
+    }
+
     Ok(())
 }
+
+/// Remove rows whose parent was deleted before `PRAGMA foreign_keys` was enabled
+fn cleanup_orphaned_rows(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        r#"
+        BEGIN;
+
+        -- Note-derived index data
+        DELETE FROM entities WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM tags WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM code_blocks WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM blocks WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM aliases WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM note_versions WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM backlinks WHERE source_id IS NOT NULL AND source_id NOT IN (SELECT id FROM notes);
+
+        -- Kanban
+        DELETE FROM kanban_cards WHERE board_id IS NOT NULL AND board_id NOT IN (SELECT id FROM kanban_boards);
+        DELETE FROM kanban_labels WHERE board_id IS NOT NULL AND board_id NOT IN (SELECT id FROM kanban_boards);
+        DELETE FROM kanban_board_members WHERE board_id IS NOT NULL AND board_id NOT IN (SELECT id FROM kanban_boards);
+        UPDATE kanban_cards SET note_id = NULL WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM card_backlinks
+            WHERE (source_id IS NOT NULL AND source_id NOT IN (SELECT id FROM notes))
+               OR (card_id IS NOT NULL AND card_id NOT IN (SELECT id FROM kanban_cards));
+
+        -- Diagrams
+        UPDATE diagram_boards SET note_id = NULL WHERE note_id IS NOT NULL AND note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM diagram_board_notes
+            WHERE board_id NOT IN (SELECT id FROM diagram_boards)
+               OR note_id NOT IN (SELECT id FROM notes);
+        DELETE FROM diagram_nodes WHERE board_id NOT IN (SELECT id FROM diagram_boards);
+        DELETE FROM diagram_edges
+            WHERE board_id NOT IN (SELECT id FROM diagram_boards)
+               OR source_node_id NOT IN (SELECT id FROM diagram_nodes)
+               OR target_node_id NOT IN (SELECT id FROM diagram_nodes);
+
+        COMMIT;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::params;
+
+    use crate::db::open_test_db;
+
+    #[test]
+    fn deleting_a_board_cascades_to_its_cards_and_labels() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO kanban_boards (id, name, columns, created_at, modified_at)
+             VALUES ('board', 'Board', '[]', 0, 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO kanban_cards (id, board_id, column_id, title, position)
+             VALUES ('card', 'board', 'todo', 'Card', 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO kanban_labels (id, board_id, name) VALUES ('label', 'board', 'bug')",
+            [],
+        )
+        .unwrap();
+
+        conn.execute("DELETE FROM kanban_boards WHERE id = ?1", params!["board"])
+            .unwrap();
+
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(count("kanban_cards"), 0);
+        assert_eq!(count("kanban_labels"), 0);
+    }
+}
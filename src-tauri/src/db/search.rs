@@ -1,7 +1,7 @@
 use rusqlite::params;
 use tauri::AppHandle;
 
-use super::with_db;
+use super::{resolve_date_span, with_db, with_read_db, DateRange, DbError};
 
 /// Safely find a character boundary at or before the given byte index
 fn floor_char_boundary(s: &str, index: usize) -> usize {
@@ -29,9 +29,12 @@ fn ceil_char_boundary(s: &str, index: usize) -> usize {
     }
 }
 use crate::commands::db::Backlink;
+use crate::commands::kanban::KanbanColumn;
 use crate::commands::search::{
-    EntityResult, SavedSearch, SearchFilters, SearchMatch, SearchResult,
+    CardSearchResult, CodeSearchResult, DateBound, EntityResult, QuickOpenResult, SavedSearch,
+    SearchFilters, SearchMatch, SearchResult, SearchScope,
 };
+use crate::commands::settings::{read_vault_settings, OrphanSettings, UnlinkedMentionSettings};
 
 /// Escape SQL LIKE pattern special characters to prevent pattern injection
 fn escape_like_pattern(s: &str) -> String {
@@ -40,16 +43,50 @@ fn escape_like_pattern(s: &str) -> String {
         .replace('_', "\\_")
 }
 
-/// Search notes using FTS5
+/// Search notes using FTS5. Each result carries up to `max_snippets`
+/// non-overlapping snippets of `snippet_length` chars (one per match cluster)
+/// in `matches`; `snippet` is the first of them.
 pub fn search_notes(
     app: &AppHandle,
     query: &str,
     filters: Option<&SearchFilters>,
     limit: usize,
-) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    snippet_length: Option<usize>,
+    max_snippets: usize,
+    scope: Option<&SearchScope>,
+) -> Result<Vec<SearchResult>, DbError> {
     with_db(app, |conn| {
         // Parse query for special syntax
-        let (fts_query, code_only) = parse_search_query(query);
+        let (query_text, mut modified, created) = parse_date_filters(query)?;
+        let (fts_query, code_only) = parse_search_query(&query_text);
+
+        // The filter bounds are inclusive, so `date_to` runs to the end of its day
+        if let Some(f) = filters {
+            if let Some(bound) = &f.date_from {
+                let (start, _) = bound.span().ok_or_else(|| invalid_date(bound))?;
+                modified = modified.intersect(DateRange {
+                    from: Some(start),
+                    to: None,
+                });
+            }
+            if let Some(bound) = &f.date_to {
+                let (_, end) = bound.span().ok_or_else(|| invalid_date(bound))?;
+                modified = modified.intersect(DateRange {
+                    from: None,
+                    to: Some(end),
+                });
+            }
+        }
+
+        // Scope becomes an exact path or a LIKE prefix; unused one stays NULL
+        let (scope_path, scope_prefix) = match scope {
+            Some(SearchScope::Note { path }) => (Some(path.clone()), None),
+            Some(SearchScope::Folder { path }) if !path.trim_matches('/').is_empty() => {
+                let folder = path.trim_end_matches('/');
+                (None, Some(format!("{}/%", escape_like_pattern(folder))))
+            }
+            _ => (None, None),
+        };
 
         let mut results = Vec::new();
 
@@ -59,7 +96,17 @@ pub fn search_notes(
             .and_then(|f| f.include_archived)
             .unwrap_or(false);
 
+        // A language filter implies a code search
+        let language = filters.and_then(|f| f.language.as_deref()).map(|l| {
+            super::get_current_vault_path(app)
+                .and_then(|p| read_vault_settings(&p).ok())
+                .unwrap_or_default()
+                .code_languages
+                .canonical(l)
+        });
+
         if code_only
+            || language.is_some()
             || filters
                 .as_ref()
                 .is_some_and(|f| f.code_only.unwrap_or(false))
@@ -72,6 +119,13 @@ pub fn search_notes(
                 JOIN notes n ON cb.note_id = n.id
                 WHERE cb.content LIKE ?1 ESCAPE '\'
                 AND (COALESCE(n.archived, 0) = 0 OR ?2 = 1)
+                AND (?4 IS NULL OR n.path = ?4)
+                AND (?5 IS NULL OR n.path LIKE ?5 ESCAPE '\')
+                AND (?6 IS NULL OR cb.language = ?6)
+                AND (?7 IS NULL OR n.modified_at >= ?7)
+                AND (?8 IS NULL OR n.modified_at < ?8)
+                AND (?9 IS NULL OR n.created_at >= ?9)
+                AND (?10 IS NULL OR n.created_at < ?10)
                 LIMIT ?3
                 "#,
             )?;
@@ -80,7 +134,18 @@ pub fn search_notes(
             let escaped = escape_like_pattern(&fts_query);
             let pattern = format!("%{}%", escaped.replace('*', "%"));
             let rows = stmt.query_map(
-                params![pattern, include_archived as i32, limit as i64],
+                params![
+                    pattern,
+                    include_archived as i32,
+                    limit as i64,
+                    scope_path,
+                    scope_prefix,
+                    language,
+                    modified.from,
+                    modified.to,
+                    created.from,
+                    created.to
+                ],
                 |row| {
                     Ok((
                         row.get::<_, String>(0)?,
@@ -95,7 +160,8 @@ pub fn search_notes(
 
             for row in rows.filter_map(|r| r.ok()) {
                 let (id, path, title, code_content, language, archived) = row;
-                let snippet = create_snippet(&code_content, &fts_query, 100);
+                let snippet =
+                    create_snippet(&code_content, &fts_query, snippet_length.unwrap_or(100));
 
                 results.push(SearchResult {
                     id,
@@ -117,12 +183,29 @@ pub fn search_notes(
             }
         } else {
             // Full-text search using FTS5
+            let terms = proximity_terms(&fts_query);
             let fts_query = fts_query
                 .split_whitespace()
                 .collect::<Vec<_>>()
                 .join(" OR ");
 
-            let mut stmt = conn.prepare(
+            // A query of only filters (e.g. `modified:>-7d`) lists the matching
+            // notes, newest first, instead of running an empty MATCH
+            let sql = if fts_query.is_empty() {
+                r#"
+                SELECT n.id, n.path, n.title, n.content, 0.0, COALESCE(n.archived, 0)
+                FROM notes n
+                WHERE (COALESCE(n.archived, 0) = 0 OR ?2 = 1)
+                AND (?4 IS NULL OR n.path = ?4)
+                AND (?5 IS NULL OR n.path LIKE ?5 ESCAPE '\')
+                AND (?6 IS NULL OR n.modified_at >= ?6)
+                AND (?7 IS NULL OR n.modified_at < ?7)
+                AND (?8 IS NULL OR n.created_at >= ?8)
+                AND (?9 IS NULL OR n.created_at < ?9)
+                ORDER BY n.modified_at DESC
+                LIMIT ?3
+                "#
+            } else {
                 r#"
                 SELECT n.id, n.path, n.title, n.content,
                        bm25(notes_fts, 1.0, 0.75, 0.5, 0.25) as score,
@@ -131,13 +214,30 @@ pub fn search_notes(
                 JOIN notes n ON notes_fts.rowid = n.rowid
                 WHERE notes_fts MATCH ?1
                 AND (COALESCE(n.archived, 0) = 0 OR ?2 = 1)
+                AND (?4 IS NULL OR n.path = ?4)
+                AND (?5 IS NULL OR n.path LIKE ?5 ESCAPE '\')
+                AND (?6 IS NULL OR n.modified_at >= ?6)
+                AND (?7 IS NULL OR n.modified_at < ?7)
+                AND (?8 IS NULL OR n.created_at >= ?8)
+                AND (?9 IS NULL OR n.created_at < ?9)
                 ORDER BY score
                 LIMIT ?3
-                "#,
-            )?;
+                "#
+            };
+            let mut stmt = conn.prepare(sql)?;
 
             let rows = stmt.query_map(
-                params![fts_query, include_archived as i32, limit as i64],
+                params![
+                    fts_query,
+                    include_archived as i32,
+                    limit as i64,
+                    scope_path,
+                    scope_prefix,
+                    modified.from,
+                    modified.to,
+                    created.from,
+                    created.to
+                ],
                 |row| {
                     Ok((
                         row.get::<_, String>(0)?,
@@ -162,22 +262,36 @@ pub fn search_notes(
                     }
                 }
 
-                let snippet = create_snippet(&content, query, 150);
+                let snippets =
+                    create_snippets(&content, query, snippet_length.unwrap_or(150), max_snippets);
+
+                // Rows arrive best-first, so only the top candidates get the proximity pass
+                let proximity = if results.len() < PROXIMITY_RERANK_LIMIT {
+                    proximity_bonus(&content, &terms)
+                } else {
+                    0.0
+                };
 
                 results.push(SearchResult {
                     id,
                     path,
                     title,
-                    snippet: snippet.clone(),
-                    score: -score, // bm25 returns negative scores, lower is better
-                    matches: vec![SearchMatch {
-                        field: "content".to_string(),
-                        text: query.to_string(),
-                        context: snippet,
-                    }],
+                    snippet: snippets.first().cloned().unwrap_or_default(),
+                    // bm25 returns negative scores, lower is better
+                    score: -score * (1.0 + PROXIMITY_WEIGHT * proximity),
+                    matches: snippets
+                        .into_iter()
+                        .map(|context| SearchMatch {
+                            field: "content".to_string(),
+                            text: query.to_string(),
+                            context,
+                        })
+                        .collect(),
                     archived,
                 });
             }
+
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
         }
 
         // Apply tag filters if specified - batch fetch tags to avoid N+1 query
@@ -197,7 +311,7 @@ pub fn search_notes(
                         placeholders.join(", ")
                     );
 
-                    let mut batch_stmt = conn.prepare(&batch_query)?;
+                    let mut batch_stmt = conn.prepare_cached(&batch_query)?;
 
                     // Build params vector
                     let params: Vec<&dyn rusqlite::ToSql> = note_ids
@@ -234,13 +348,84 @@ pub fn search_notes(
     })
 }
 
+/// Number of top FTS results re-ranked by term proximity
+const PROXIMITY_RERANK_LIMIT: usize = 50;
+
+/// How much a perfect proximity bonus (all terms adjacent) can raise a score
+const PROXIMITY_WEIGHT: f64 = 0.5;
+
+/// Distinct lowercase terms of a search query, without FTS wildcards or quotes
+fn proximity_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        let term = term
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if !term.is_empty() && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Proximity bonus in [0, 1] from the smallest window of words containing
+/// every query term: 1.0 when the terms are adjacent, approaching 0 as they
+/// spread apart, and 0 when some term is missing or there is only one term.
+fn proximity_bonus(content: &str, terms: &[String]) -> f64 {
+    if terms.len() < 2 {
+        return 0.0;
+    }
+
+    // (word index, term index) for every word that matches a term
+    let hits: Vec<(usize, usize)> = content
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .enumerate()
+        .filter_map(|(i, word)| {
+            let word = word.to_lowercase();
+            terms.iter().position(|t| *t == word).map(|t| (i, t))
+        })
+        .collect();
+
+    // Sliding window over the hits for the shortest span covering all terms
+    let mut counts = vec![0usize; terms.len()];
+    let mut covered = 0;
+    let mut best_span = usize::MAX;
+    let mut left = 0;
+    for right in 0..hits.len() {
+        let (_, term) = hits[right];
+        if counts[term] == 0 {
+            covered += 1;
+        }
+        counts[term] += 1;
+
+        while covered == terms.len() {
+            let span = hits[right].0 - hits[left].0 + 1;
+            best_span = best_span.min(span);
+
+            let (_, left_term) = hits[left];
+            counts[left_term] -= 1;
+            if counts[left_term] == 0 {
+                covered -= 1;
+            }
+            left += 1;
+        }
+    }
+
+    if best_span == usize::MAX {
+        0.0
+    } else {
+        terms.len() as f64 / best_span as f64
+    }
+}
+
 /// Search for entities
 pub fn search_entities(
     app: &AppHandle,
     entity_type: Option<&str>,
     pattern: Option<&str>,
     limit: usize,
-) -> Result<Vec<EntityResult>, Box<dyn std::error::Error>> {
+) -> Result<Vec<EntityResult>, DbError> {
     with_db(app, |conn| {
         // Escape SQL wildcards, then allow * as user wildcard
         let pattern_like = pattern.map(|p| {
@@ -252,7 +437,7 @@ pub fn search_entities(
         let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
             match (entity_type, pattern_like.as_ref()) {
                 (Some(et), Some(p)) => (
-                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context
+                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context, e.subtype
                    FROM entities e
                    JOIN notes n ON e.note_id = n.id
                    WHERE e.entity_type = ?1 AND e.value LIKE ?2 ESCAPE '\'
@@ -265,7 +450,7 @@ pub fn search_entities(
                     ],
                 ),
                 (Some(et), None) => (
-                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context
+                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context, e.subtype
                    FROM entities e
                    JOIN notes n ON e.note_id = n.id
                    WHERE e.entity_type = ?1
@@ -277,7 +462,7 @@ pub fn search_entities(
                     ],
                 ),
                 (None, Some(p)) => (
-                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context
+                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context, e.subtype
                    FROM entities e
                    JOIN notes n ON e.note_id = n.id
                    WHERE e.value LIKE ?1 ESCAPE '\'
@@ -289,7 +474,7 @@ pub fn search_entities(
                     ],
                 ),
                 (None, None) => (
-                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context
+                    r#"SELECT e.entity_type, e.value, n.path, n.title, e.context, e.subtype
                    FROM entities e
                    JOIN notes n ON e.note_id = n.id
                    ORDER BY e.value LIMIT ?1"#
@@ -312,6 +497,7 @@ pub fn search_entities(
                 note_path: row.get(2)?,
                 note_title: row.get(3)?,
                 context: row.get(4)?,
+                subtype: row.get(5)?,
             });
         }
 
@@ -319,13 +505,81 @@ pub fn search_entities(
     })
 }
 
+/// Search kanban cards by title and description. Title matches rank first,
+/// archived cards last, then most recently updated.
+pub fn search_cards(
+    app: &AppHandle,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<CardSearchResult>, DbError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    with_db(app, |conn| {
+        let pattern = format!("%{}%", escape_like_pattern(query));
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT c.id, c.title, c.description, c.board_id, b.name, b.columns, c.column_id,
+                   COALESCE(c.archived, 0)
+            FROM kanban_cards c
+            JOIN kanban_boards b ON c.board_id = b.id
+            WHERE c.title LIKE ?1 ESCAPE '\' OR c.description LIKE ?1 ESCAPE '\'
+            ORDER BY (c.title LIKE ?1 ESCAPE '\') DESC, COALESCE(c.archived, 0), c.updated_at DESC
+            LIMIT ?2
+            "#,
+        )?;
+
+        let results = stmt
+            .query_map(params![pattern, limit as i64], |row| {
+                let title: String = row.get(1)?;
+                let description: Option<String> = row.get(2)?;
+                let columns_json: String = row.get(5)?;
+                let column_id: String = row.get(6)?;
+                let archived: i32 = row.get(7)?;
+
+                let columns: Vec<KanbanColumn> =
+                    serde_json::from_str(&columns_json).unwrap_or_default();
+                let column_name = columns
+                    .iter()
+                    .find(|c| c.id == column_id)
+                    .map(|c| c.name.clone());
+
+                let title_match = title.to_lowercase().contains(&query.to_lowercase());
+                let (field, snippet) = match description {
+                    Some(desc) if !title_match => {
+                        ("description", create_snippet(&desc, query, 150))
+                    }
+                    _ => ("title", title.clone()),
+                };
+
+                Ok(CardSearchResult {
+                    id: row.get(0)?,
+                    title,
+                    board_id: row.get(3)?,
+                    board_name: row.get(4)?,
+                    column_name,
+                    snippet,
+                    field: field.to_string(),
+                    archived: archived != 0,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(results)
+    })
+}
+
 /// Save a search query
 pub fn save_search(
     app: &AppHandle,
     name: &str,
     query: &str,
     filters: Option<&SearchFilters>,
-) -> Result<SavedSearch, Box<dyn std::error::Error>> {
+) -> Result<SavedSearch, DbError> {
     with_db(app, |conn| {
         let id = uuid::Uuid::new_v4().to_string();
         let created_at = chrono::Utc::now().timestamp();
@@ -347,7 +601,7 @@ pub fn save_search(
 }
 
 /// Get all saved searches
-pub fn get_saved_searches(app: &AppHandle) -> Result<Vec<SavedSearch>, Box<dyn std::error::Error>> {
+pub fn get_saved_searches(app: &AppHandle) -> Result<Vec<SavedSearch>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             "SELECT id, name, query, filters, created_at FROM saved_searches ORDER BY created_at DESC",
@@ -375,6 +629,173 @@ pub fn get_saved_searches(app: &AppHandle) -> Result<Vec<SavedSearch>, Box<dyn s
     })
 }
 
+/// Candidate for quick open: (kind, id, title, path or route, extra names, timestamp).
+/// Items without a timestamp get no recency boost.
+type QuickOpenCandidate = (
+    &'static str,
+    String,
+    String,
+    String,
+    Vec<String>,
+    Option<i64>,
+);
+
+/// Fuzzy-search every openable item and rank by match quality plus recency
+pub fn quick_open(
+    app: &AppHandle,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<QuickOpenResult>, DbError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let candidates: Vec<QuickOpenCandidate> = with_db(app, |conn| {
+        let mut candidates = Vec::new();
+
+        // Notes, matched on title and aliases
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT n.id, n.title, n.path, n.modified_at, GROUP_CONCAT(a.alias, char(31))
+            FROM notes n
+            LEFT JOIN aliases a ON a.note_id = n.id
+            WHERE COALESCE(n.archived, 0) = 0
+            GROUP BY n.id
+            "#,
+        )?;
+        let notes = stmt.query_map([], |row| {
+            let aliases: Option<String> = row.get(4)?;
+            let aliases = aliases
+                .map(|a| a.split('\u{1f}').map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+            Ok((
+                "note",
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                aliases,
+                row.get(3)?,
+            ))
+        })?;
+        candidates.extend(notes.filter_map(|r| r.ok()));
+
+        let mut stmt = conn.prepare(
+            "SELECT id, title, board_id, updated_at FROM kanban_cards WHERE COALESCE(archived, 0) = 0",
+        )?;
+        let cards = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            let board_id: String = row.get(2)?;
+            let route = format!("card/{}/{}", board_id, id);
+            Ok(("card", id, row.get(1)?, route, Vec::new(), row.get(3)?))
+        })?;
+        candidates.extend(cards.filter_map(|r| r.ok()));
+
+        let mut stmt = conn.prepare("SELECT id, name, modified_at FROM kanban_boards")?;
+        let boards = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            let route = format!("board/{}", id);
+            Ok(("board", id, row.get(1)?, route, Vec::new(), row.get(2)?))
+        })?;
+        candidates.extend(boards.filter_map(|r| r.ok()));
+
+        let mut stmt = conn.prepare(
+            "SELECT id, name, modified_at FROM diagram_boards WHERE COALESCE(archived, 0) = 0",
+        )?;
+        let diagrams = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            let route = format!("diagram/{}", id);
+            Ok(("diagram", id, row.get(1)?, route, Vec::new(), row.get(2)?))
+        })?;
+        candidates.extend(diagrams.filter_map(|r| r.ok()));
+
+        let mut stmt = conn.prepare("SELECT id, name, created_at FROM saved_searches")?;
+        let searches = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            let route = format!("search/{}", id);
+            Ok(("search", id, row.get(1)?, route, Vec::new(), row.get(2)?))
+        })?;
+        candidates.extend(searches.filter_map(|r| r.ok()));
+
+        Ok(candidates)
+    })?;
+
+    let now = chrono::Utc::now().timestamp();
+
+    let mut results: Vec<QuickOpenResult> = candidates
+        .into_iter()
+        .filter_map(|(kind, id, title, path_or_route, names, timestamp)| {
+            let best = std::iter::once(title.as_str())
+                .chain(names.iter().map(|s| s.as_str()))
+                .filter_map(|name| fuzzy_score(query, name))
+                .max_by(f64::total_cmp)?;
+
+            Some(QuickOpenResult {
+                kind: kind.to_string(),
+                id,
+                title,
+                path_or_route,
+                score: best + timestamp.map_or(0.0, |t| recency_boost(t, now)),
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results.truncate(limit);
+
+    Ok(results)
+}
+
+/// Score how well `query` fuzzy-matches `candidate`, or None if it doesn't.
+/// Exact, prefix and substring matches outrank scattered subsequence matches.
+pub(crate) fn fuzzy_score(query: &str, candidate: &str) -> Option<f64> {
+    let query = query.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    if candidate == query {
+        return Some(100.0);
+    }
+    if candidate.starts_with(&query) {
+        return Some(80.0);
+    }
+    if let Some(pos) = candidate.find(&query) {
+        return Some(60.0 - (pos as f64).min(20.0));
+    }
+
+    // Subsequence match, rewarding runs of consecutive characters
+    let mut remaining = candidate.chars();
+    let mut score = 0.0;
+    let mut query_len = 0;
+    let mut previous_matched = false;
+
+    for qc in query.chars().filter(|c| !c.is_whitespace()) {
+        query_len += 1;
+        loop {
+            match remaining.next() {
+                Some(c) if c == qc => {
+                    score += if previous_matched { 2.0 } else { 1.0 };
+                    previous_matched = true;
+                    break;
+                }
+                Some(_) => previous_matched = false,
+                None => return None,
+            }
+        }
+    }
+
+    if query_len == 0 {
+        return None;
+    }
+
+    Some(40.0 * score / (query_len as f64 * 2.0))
+}
+
+/// Small boost for recently touched items, decaying over a few weeks
+fn recency_boost(timestamp: i64, now: i64) -> f64 {
+    let age_days = (now - timestamp).max(0) as f64 / 86400.0;
+    10.0 / (1.0 + age_days / 7.0)
+}
+
 /// Graph node for visualization
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -385,6 +806,20 @@ pub struct GraphNode {
     pub link_count: usize,
     pub backlink_count: usize,
     pub archived: bool,
+    /// Saved position from a manually arranged layout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<f64>,
+}
+
+/// A note's saved position in the graph view
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphPosition {
+    pub note_id: String,
+    pub x: f64,
+    pub y: f64,
 }
 
 /// Graph link for visualization
@@ -403,33 +838,15 @@ pub struct GraphData {
 }
 
 /// Get graph data for visualization
-pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, Box<dyn std::error::Error>> {
-    with_db(app, |conn| {
-        // Use CTEs to pre-compute link counts efficiently instead of correlated subqueries
+pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, DbError> {
+    with_read_db(app, |conn| {
+        // Link counts are cached on the notes row by the indexer
         let mut nodes_stmt = conn.prepare(
             r#"
-            WITH outgoing_links AS (
-                SELECT source_id, COUNT(*) as cnt
-                FROM backlinks
-                GROUP BY source_id
-            ),
-            incoming_links AS (
-                SELECT n.id, COUNT(DISTINCT b.source_id) as cnt
-                FROM notes n
-                LEFT JOIN backlinks b ON (
-                    b.target_path = n.path
-                    OR b.target_path = replace(n.path, 'notes/', '')
-                    OR b.target_path = replace(replace(n.path, 'notes/', ''), '.md', '')
-                )
-                GROUP BY n.id
-            )
-            SELECT n.id, n.path, n.title,
-                   COALESCE(ol.cnt, 0) as link_count,
-                   COALESCE(il.cnt, 0) as backlink_count,
-                   COALESCE(n.archived, 0)
+            SELECT n.id, n.path, n.title, n.outgoing_count, n.incoming_count,
+                   COALESCE(n.archived, 0), gp.x, gp.y
             FROM notes n
-            LEFT JOIN outgoing_links ol ON ol.source_id = n.id
-            LEFT JOIN incoming_links il ON il.id = n.id
+            LEFT JOIN graph_positions gp ON gp.note_id = n.id
             "#,
         )?;
 
@@ -442,6 +859,8 @@ pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, Box<dyn std::error::
                     link_count: row.get::<_, i64>(3)? as usize,
                     backlink_count: row.get::<_, i64>(4)? as usize,
                     archived: row.get::<_, i32>(5)? != 0,
+                    x: row.get(6)?,
+                    y: row.get(7)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -471,6 +890,9 @@ pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, Box<dyn std::error::
             "#,
         )?;
 
+        // One edge per resolved pair; self-links don't make edges
+        let mut seen_edges: std::collections::HashSet<(String, String)> =
+            std::collections::HashSet::new();
         let links: Vec<GraphLink> = links_stmt
             .query_map([], |row| {
                 Ok((
@@ -492,6 +914,12 @@ pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, Box<dyn std::error::
                         filename_to_id.get(&target_lower)
                     })?;
 
+                if *target_id == source_id
+                    || !seen_edges.insert((source_id.clone(), target_id.clone()))
+                {
+                    return None;
+                }
+
                 Some(GraphLink {
                     source: source_id,
                     target: target_id.clone(),
@@ -504,11 +932,345 @@ pub fn get_graph_data(app: &AppHandle) -> Result<GraphData, Box<dyn std::error::
     })
 }
 
-/// Get backlinks to a specific note
-pub fn get_backlinks(
+/// Saved graph node positions
+pub fn get_graph_positions(app: &AppHandle) -> Result<Vec<GraphPosition>, DbError> {
+    with_read_db(app, |conn| {
+        let mut stmt = conn.prepare("SELECT note_id, x, y FROM graph_positions")?;
+        let positions = stmt
+            .query_map([], |row| {
+                Ok(GraphPosition {
+                    note_id: row.get(0)?,
+                    x: row.get(1)?,
+                    y: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(positions)
+    })
+}
+
+/// Save graph node positions, replacing any saved earlier for the same notes.
+/// Positions of notes that aren't indexed are ignored.
+pub fn save_graph_positions(app: &AppHandle, positions: &[GraphPosition]) -> Result<(), DbError> {
+    super::with_db_tx(app, |conn| {
+        let mut stmt = conn.prepare(
+            "INSERT INTO graph_positions (note_id, x, y)
+             SELECT id, ?2, ?3 FROM notes WHERE id = ?1
+             ON CONFLICT(note_id) DO UPDATE SET x = excluded.x, y = excluded.y",
+        )?;
+        for position in positions
+            .iter()
+            .filter(|p| p.x.is_finite() && p.y.is_finite())
+        {
+            stmt.execute(params![position.note_id, position.x, position.y])?;
+        }
+        Ok(())
+    })
+}
+
+/// Output formats for `export_graph`
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    Graphml,
+    Dot,
+    Json,
+}
+
+/// Serialize the link graph for external tools (Gephi, Graphviz, ...)
+pub fn export_graph(app: &AppHandle, format: GraphExportFormat) -> Result<String, DbError> {
+    let graph = get_graph_data(app)?;
+
+    match format {
+        GraphExportFormat::Graphml => Ok(graph_to_graphml(&graph)),
+        GraphExportFormat::Dot => Ok(graph_to_dot(&graph)),
+        GraphExportFormat::Json => Ok(serde_json::to_string_pretty(&graph)?),
+    }
+}
+
+fn graph_to_graphml(graph: &GraphData) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="title" for="node" attr.name="title" attr.type="string"/>
+  <key id="path" for="node" attr.name="path" attr.type="string"/>
+  <key id="link_count" for="node" attr.name="link_count" attr.type="int"/>
+  <key id="backlink_count" for="node" attr.name="backlink_count" attr.type="int"/>
+  <key id="archived" for="node" attr.name="archived" attr.type="boolean"/>
+  <key id="context" for="edge" attr.name="context" attr.type="string"/>
+  <graph id="notes" edgedefault="directed">
+"#,
+    );
+
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "    <node id=\"{}\">\n      <data key=\"title\">{}</data>\n      <data key=\"path\">{}</data>\n      <data key=\"link_count\">{}</data>\n      <data key=\"backlink_count\">{}</data>\n      <data key=\"archived\">{}</data>\n    </node>\n",
+            escape_xml(&node.id),
+            escape_xml(&node.title),
+            escape_xml(&node.path),
+            node.link_count,
+            node.backlink_count,
+            node.archived
+        ));
+    }
+
+    for (i, link) in graph.links.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+            i,
+            escape_xml(&link.source),
+            escape_xml(&link.target)
+        ));
+        if let Some(context) = &link.context {
+            out.push_str(&format!(
+                "\n      <data key=\"context\">{}</data>\n    ",
+                escape_xml(context)
+            ));
+        }
+        out.push_str("</edge>\n");
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn graph_to_dot(graph: &GraphData) -> String {
+    let mut out = String::from("digraph notes {\n");
+
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\", path=\"{}\", link_count={}, backlink_count={}, archived={}];\n",
+            escape_dot(&node.id),
+            escape_dot(&node.title),
+            escape_dot(&node.path),
+            node.link_count,
+            node.backlink_count,
+            node.archived
+        ));
+    }
+
+    for link in &graph.links {
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\"",
+            escape_dot(&link.source),
+            escape_dot(&link.target)
+        ));
+        if let Some(context) = &link.context {
+            out.push_str(&format!(" [context=\"{}\"]", escape_dot(context)));
+        }
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Escape text for XML content and attributes, dropping characters XML 1.0 forbids
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text for a double-quoted DOT string
+fn escape_dot(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Entity types exported as indicators of compromise
+const IOC_ENTITY_TYPES: &[&str] = &["ip", "domain", "url", "email", "cve", "hash"];
+
+/// Output formats for `export_note_iocs`
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IocExportFormat {
+    /// A STIX 2.1 bundle of indicators (and vulnerabilities for CVEs)
+    Stix,
+    /// `type,value,context` rows with a header
+    Csv,
+}
+
+/// Export the IOCs extracted from a note, one per distinct type and value,
+/// keeping the context of the first occurrence. With `defang`, values are
+/// made non-clickable (`1[.]2[.]3[.]4`, `hxxp://`); STIX patterns keep the
+/// real value so the bundle stays machine-readable.
+pub fn export_note_iocs(
     app: &AppHandle,
     note_path: &str,
-) -> Result<Vec<Backlink>, Box<dyn std::error::Error>> {
+    format: IocExportFormat,
+    defang: bool,
+) -> Result<String, DbError> {
+    let iocs: Vec<(String, String, String)> = with_read_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT e.entity_type, e.value, e.context FROM entities e
+             JOIN notes n ON e.note_id = n.id
+             WHERE n.path = ?1
+             ORDER BY e.line_number, e.id",
+        )?;
+        let mut seen = std::collections::HashSet::new();
+        let iocs = stmt
+            .query_map(params![note_path], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter(|(entity_type, value, _): &(String, String, String)| {
+                IOC_ENTITY_TYPES.contains(&entity_type.as_str())
+                    && seen.insert((entity_type.clone(), value.to_lowercase()))
+            })
+            .collect();
+        Ok(iocs)
+    })?;
+
+    match format {
+        IocExportFormat::Csv => {
+            let mut out = String::from("type,value,context\n");
+            for (entity_type, value, context) in &iocs {
+                let value = if defang {
+                    defang_ioc(entity_type, value)
+                } else {
+                    value.clone()
+                };
+                out.push_str(&format!(
+                    "{},{},{}\n",
+                    escape_csv(entity_type),
+                    escape_csv(&value),
+                    escape_csv(context.trim())
+                ));
+            }
+            Ok(out)
+        }
+        IocExportFormat::Stix => {
+            let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            let objects: Vec<serde_json::Value> = iocs
+                .iter()
+                .map(|(entity_type, value, context)| {
+                    let name = if defang {
+                        defang_ioc(entity_type, value)
+                    } else {
+                        value.clone()
+                    };
+                    stix_object(entity_type, value, &name, context.trim(), &now)
+                })
+                .collect();
+
+            let bundle = serde_json::json!({
+                "type": "bundle",
+                "id": format!("bundle--{}", uuid::Uuid::new_v4()),
+                "x_kairo_source": note_path,
+                "objects": objects,
+            });
+            Ok(serde_json::to_string_pretty(&bundle)?)
+        }
+    }
+}
+
+/// A STIX object for one IOC: a vulnerability for a CVE, otherwise an indicator
+fn stix_object(
+    entity_type: &str,
+    value: &str,
+    name: &str,
+    context: &str,
+    now: &str,
+) -> serde_json::Value {
+    if entity_type == "cve" {
+        return serde_json::json!({
+            "type": "vulnerability",
+            "spec_version": "2.1",
+            "id": format!("vulnerability--{}", uuid::Uuid::new_v4()),
+            "created": now,
+            "modified": now,
+            "name": value,
+            "description": context,
+            "external_references": [{ "source_name": "cve", "external_id": value }],
+        });
+    }
+
+    let escaped = value.replace('\\', "\\\\").replace('\'', "\\'");
+    let pattern = match entity_type {
+        "ip" if value.contains(':') => format!("[ipv6-addr:value = '{}']", escaped),
+        "ip" => format!("[ipv4-addr:value = '{}']", escaped),
+        "domain" => format!("[domain-name:value = '{}']", escaped),
+        "url" => format!("[url:value = '{}']", escaped),
+        "email" => format!("[email-addr:value = '{}']", escaped),
+        _ => {
+            let algorithm = match value.len() {
+                32 => "MD5",
+                40 => "SHA-1",
+                _ => "SHA-256",
+            };
+            format!("[file:hashes.'{}' = '{}']", algorithm, escaped)
+        }
+    };
+
+    serde_json::json!({
+        "type": "indicator",
+        "spec_version": "2.1",
+        "id": format!("indicator--{}", uuid::Uuid::new_v4()),
+        "created": now,
+        "modified": now,
+        "valid_from": now,
+        "name": name,
+        "description": context,
+        "indicator_types": ["malicious-activity"],
+        "pattern": pattern,
+        "pattern_type": "stix",
+        "labels": [entity_type],
+    })
+}
+
+/// Make an IOC safe to paste: dots become `[.]`, `@` becomes `[@]` and
+/// URL schemes become `hxxp`/`hxxps`. CVEs and hashes are left as they are.
+fn defang_ioc(entity_type: &str, value: &str) -> String {
+    match entity_type {
+        "ip" | "domain" => value.replace('.', "[.]"),
+        "email" => value.replace('.', "[.]").replace('@', "[@]"),
+        "url" => {
+            let (scheme, rest) = value.split_once("://").unwrap_or(("", value));
+            let scheme = scheme.replacen("http", "hxxp", 1);
+            let rest = rest.replace('.', "[.]");
+            if scheme.is_empty() {
+                rest
+            } else {
+                format!("{}://{}", scheme, rest)
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Get backlinks to a specific note
+pub fn get_backlinks(app: &AppHandle, note_path: &str) -> Result<Vec<Backlink>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             r#"
@@ -537,6 +1299,7 @@ pub fn get_backlinks(
                         source_title: row.get(2)?,
                         context: row.get(3)?,
                         archived: row.get::<_, i32>(4)? != 0,
+                        score: None,
                     })
                 },
             )?
@@ -547,8 +1310,99 @@ pub fn get_backlinks(
     })
 }
 
+/// Get backlinks to a note, most relevant first.
+///
+/// Each backlink is scored on three signals:
+/// - specificity: sources that link to few notes rank above hub/index pages
+/// - recency: recently modified sources rank higher (30-day half-life)
+/// - placement: links inside a heading count more than links in body text
+pub fn get_ranked_backlinks(app: &AppHandle, note_path: &str) -> Result<Vec<Backlink>, DbError> {
+    const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT n.id, n.path, n.title, b.context, COALESCE(n.archived, 0),
+                   n.modified_at, COALESCE(b.in_heading, 0),
+                   (SELECT COUNT(*) FROM backlinks o WHERE o.source_id = n.id)
+            FROM backlinks b
+            JOIN notes n ON b.source_id = n.id
+            WHERE b.target_path = ?1 OR b.target_path LIKE ?2 ESCAPE '\'
+            "#,
+        )?;
+
+        let filename = std::path::PathBuf::from(note_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let escaped_filename = escape_like_pattern(&filename);
+
+        let mut backlinks: Vec<Backlink> = stmt
+            .query_map(
+                params![note_path, format!("%{}", escaped_filename)],
+                |row| {
+                    let modified_at: i64 = row.get(5)?;
+                    let in_heading = row.get::<_, i32>(6)? != 0;
+                    let outgoing: i64 = row.get(7)?;
+
+                    let specificity = 1.0 / (outgoing.max(1) as f64).sqrt();
+                    let age_days = (now - modified_at).max(0) as f64 / 86_400.0;
+                    let recency = 0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+                    let placement = if in_heading { 1.0 } else { 0.0 };
+
+                    Ok(Backlink {
+                        source_id: row.get(0)?,
+                        source_path: row.get(1)?,
+                        source_title: row.get(2)?,
+                        context: row.get(3)?,
+                        archived: row.get::<_, i32>(4)? != 0,
+                        score: Some(0.4 * specificity + 0.4 * recency + 0.2 * placement),
+                    })
+                },
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        backlinks.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+
+        Ok(backlinks)
+    })
+}
+
 // Helper functions
 
+/// Pull `modified:` and `created:` filters out of a query, e.g.
+/// `modified:>-7d` or `created:2024-01-01`, returning the remaining text and
+/// the modified and created ranges
+fn parse_date_filters(query: &str) -> Result<(String, DateRange, DateRange), DbError> {
+    let date_re = regex::Regex::new(r"\b(modified|created):(>=|<=|>|<|=)?(\S+)\s*").unwrap();
+
+    let mut modified = DateRange::default();
+    let mut created = DateRange::default();
+    for caps in date_re.captures_iter(query) {
+        let operator = caps.get(2).map_or("=", |m| m.as_str());
+        let range = resolve_date_span(&caps[3])
+            .and_then(|span| DateRange::from_comparison(operator, span))
+            .ok_or_else(|| DbError::Other(format!("Invalid date in search: {}", &caps[3])))?;
+        if &caps[1] == "modified" {
+            modified = modified.intersect(range);
+        } else {
+            created = created.intersect(range);
+        }
+    }
+    let text = date_re.replace_all(query, "").trim().to_string();
+    Ok((text, modified, created))
+}
+
+fn invalid_date(bound: &DateBound) -> DbError {
+    DbError::Other(format!("Invalid date filter: {:?}", bound))
+}
+
 fn parse_search_query(query: &str) -> (String, bool) {
     let mut code_only = false;
     let mut clean_query = query.to_string();
@@ -575,42 +1429,84 @@ fn parse_search_query(query: &str) -> (String, bool) {
 }
 
 fn create_snippet(content: &str, query: &str, max_len: usize) -> String {
-    let query_lower = query.to_lowercase();
-    let content_lower = content.to_lowercase();
+    create_snippets(content, query, max_len, 1)
+        .pop()
+        .unwrap_or_default()
+}
 
-    // Find the position of the query in the content
-    if let Some(pos) = content_lower.find(&query_lower) {
-        // Use safe character boundary functions to avoid panics on multi-byte chars
-        let start = floor_char_boundary(content, pos.saturating_sub(max_len / 2));
-        let end = ceil_char_boundary(
-            content,
-            (pos + query.len() + max_len / 2).min(content.len()),
-        );
+/// Build up to `max_snippets` non-overlapping snippets around matches of
+/// `query`. Matches of the whole query are preferred; if there are none, the
+/// individual query terms are used. Matches that fall inside an earlier
+/// snippet's window are folded into it.
+fn create_snippets(content: &str, query: &str, max_len: usize, max_snippets: usize) -> Vec<String> {
+    let max_snippets = max_snippets.max(1);
+    let content_lower = content.to_lowercase();
 
-        let mut snippet = String::new();
-        if start > 0 {
-            snippet.push_str("...");
-        }
-        snippet.push_str(&content[start..end]);
-        if end < content.len() {
-            snippet.push_str("...");
+    let find_all = |needle: &str| -> Vec<(usize, usize)> {
+        if needle.is_empty() {
+            return Vec::new();
         }
+        content_lower
+            .match_indices(needle)
+            .map(|(pos, m)| (pos, m.len()))
+            .collect()
+    };
+
+    let query_lower = query.trim().to_lowercase();
+    let mut hits = find_all(&query_lower);
+    if hits.is_empty() {
+        hits = query_lower
+            .split_whitespace()
+            .filter(|term| term.chars().count() > 1)
+            .flat_map(find_all)
+            .collect();
+        hits.sort_unstable();
+    }
 
-        // Clean up newlines
-        snippet.replace('\n', " ").replace("  ", " ")
-    } else {
+    if hits.is_empty() {
         // No match found, return the beginning of the content
         let end = ceil_char_boundary(content, max_len.min(content.len()));
-        let mut snippet = content[..end].to_string();
-        if end < content.len() {
-            snippet.push_str("...");
+        return vec![snippet_window(content, 0, end)];
+    }
+
+    let mut snippets = Vec::new();
+    let mut covered_until = 0;
+    for (pos, len) in hits {
+        if snippets.len() >= max_snippets {
+            break;
+        }
+        if !snippets.is_empty() && pos < covered_until {
+            continue;
         }
-        snippet.replace('\n', " ").replace("  ", " ")
+
+        // Use safe character boundary functions to avoid panics on multi-byte chars
+        let start =
+            floor_char_boundary(content, pos.saturating_sub(max_len / 2).max(covered_until));
+        let end = ceil_char_boundary(content, (pos + len + max_len / 2).min(content.len()));
+        snippets.push(snippet_window(content, start, end));
+        covered_until = end;
+    }
+
+    snippets
+}
+
+/// Render `content[start..end]` as a snippet, with ellipses where it was cut
+fn snippet_window(content: &str, start: usize, end: usize) -> String {
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push_str("...");
     }
+    snippet.push_str(&content[start..end]);
+    if end < content.len() {
+        snippet.push_str("...");
+    }
+
+    // Clean up newlines
+    snippet.replace('\n', " ").replace("  ", " ")
 }
 
 /// Get all unique tags in the vault
-pub fn get_all_tags(app: &AppHandle) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+pub fn get_all_tags(app: &AppHandle) -> Result<Vec<String>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare("SELECT DISTINCT tag FROM tags ORDER BY tag")?;
         let tags: Vec<String> = stmt
@@ -621,10 +1517,117 @@ pub fn get_all_tags(app: &AppHandle) -> Result<Vec<String>, Box<dyn std::error::
     })
 }
 
+/// Search code blocks in non-archived notes. Blocks are ranked by how often
+/// they contain `query`; with an empty query every block in `language` is
+/// listed in note order. Aliases of `language` (`py` for `python`) match.
+pub fn search_code(
+    app: &AppHandle,
+    query: &str,
+    language: Option<&str>,
+    limit: usize,
+) -> Result<Vec<CodeSearchResult>, DbError> {
+    let query = query.trim();
+    let language = language.map(str::trim).filter(|l| !l.is_empty()).map(|l| {
+        super::get_current_vault_path(app)
+            .and_then(|p| read_vault_settings(&p).ok())
+            .unwrap_or_default()
+            .code_languages
+            .canonical(l)
+    });
+
+    // Without either there's nothing to narrow the blocks down by
+    if query.is_empty() && language.is_none() {
+        return Ok(Vec::new());
+    }
+
+    with_read_db(app, |conn| {
+        let pattern = format!("%{}%", escape_like_pattern(query));
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT n.id, n.path, n.title, cb.language, cb.content, cb.line_start, cb.line_end
+            FROM code_blocks cb
+            JOIN notes n ON cb.note_id = n.id
+            WHERE cb.content LIKE ?1 ESCAPE '\'
+            AND (?2 IS NULL OR cb.language = ?2)
+            AND COALESCE(n.archived, 0) = 0
+            ORDER BY n.path, cb.line_start
+            "#,
+        )?;
+
+        let needle = query.to_lowercase();
+        let mut results: Vec<CodeSearchResult> = stmt
+            .query_map(params![pattern, language], |row| {
+                Ok(CodeSearchResult {
+                    note_id: row.get(0)?,
+                    note_path: row.get(1)?,
+                    note_title: row.get(2)?,
+                    language: row.get(3)?,
+                    content: row.get(4)?,
+                    line_start: row.get(5)?,
+                    line_end: row.get(6)?,
+                    match_count: 0,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|mut result| {
+                if !needle.is_empty() {
+                    // LIKE only prefilters; count the actual occurrences
+                    result.match_count = result.content.to_lowercase().matches(&needle).count();
+                    if result.match_count == 0 {
+                        return None;
+                    }
+                }
+                Some(result)
+            })
+            .collect();
+
+        // Stable, so equal counts keep note order
+        results.sort_by_key(|r| std::cmp::Reverse(r.match_count));
+        results.truncate(limit);
+
+        Ok(results)
+    })
+}
+
+/// A code-block language and how often it's used
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLanguageCount {
+    /// Canonical language name, e.g. `python` for `py` fences
+    pub language: String,
+    pub block_count: usize,
+    pub note_count: usize,
+}
+
+/// Languages used in code blocks, most used first. Languages are canonicalized
+/// at index time, so aliases are already counted together.
+pub fn get_code_languages(app: &AppHandle) -> Result<Vec<CodeLanguageCount>, DbError> {
+    with_read_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT language, COUNT(*), COUNT(DISTINCT note_id) FROM code_blocks
+             WHERE language IS NOT NULL AND language != ''
+             GROUP BY language
+             ORDER BY COUNT(*) DESC, language",
+        )?;
+        let languages = stmt
+            .query_map([], |row| {
+                Ok(CodeLanguageCount {
+                    language: row.get(0)?,
+                    block_count: row.get::<_, i64>(1)? as usize,
+                    note_count: row.get::<_, i64>(2)? as usize,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(languages)
+    })
+}
+
 /// Get tags with their associated note paths
 pub fn get_tag_notes(
     app: &AppHandle,
-) -> Result<std::collections::HashMap<String, Vec<String>>, Box<dyn std::error::Error>> {
+) -> Result<std::collections::HashMap<String, Vec<String>>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             "SELECT t.tag, n.path FROM tags t
@@ -648,10 +1651,11 @@ pub fn get_tag_notes(
 }
 
 /// Get all unique mentions in the vault
-pub fn get_all_mentions(app: &AppHandle) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+pub fn get_all_mentions(app: &AppHandle) -> Result<Vec<String>, DbError> {
     with_db(app, |conn| {
-        let mut stmt = conn
-            .prepare("SELECT DISTINCT value FROM entities WHERE type = 'mention' ORDER BY value")?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT value FROM entities WHERE entity_type = 'mention' ORDER BY value",
+        )?;
         let mentions: Vec<String> = stmt
             .query_map([], |row| row.get(0))?
             .filter_map(|r| r.ok())
@@ -660,6 +1664,78 @@ pub fn get_all_mentions(app: &AppHandle) -> Result<Vec<String>, Box<dyn std::err
     })
 }
 
+/// A note that @mentions a kanban board member
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberMention {
+    pub note_id: String,
+    pub note_path: String,
+    pub note_title: String,
+    /// Number of times the member is mentioned in the note
+    pub count: i64,
+    /// Line of the first mention
+    pub context: String,
+    pub line_number: i64,
+}
+
+/// Record `member` entities for @mentions that match a kanban board member
+/// name (case-insensitive), storing the member's name as written on the board.
+/// Resolves a single note, or every note when `note_id` is None (after the
+/// member roster changes).
+pub(crate) fn resolve_member_mentions(
+    conn: &rusqlite::Connection,
+    note_id: Option<&str>,
+) -> Result<usize, rusqlite::Error> {
+    // Cached: this runs once per note during indexing
+    conn.prepare_cached(
+        "DELETE FROM entities WHERE entity_type = 'member' AND (?1 IS NULL OR note_id = ?1)",
+    )?
+    .execute(params![note_id])?;
+    conn.prepare_cached(
+        "INSERT INTO entities (note_id, entity_type, value, context, line_number)
+         SELECT e.note_id, 'member', m.name, e.context, e.line_number
+         FROM entities e
+         JOIN (SELECT MIN(name) AS name FROM kanban_board_members GROUP BY LOWER(name)) m
+           ON LOWER(m.name) = LOWER(e.value)
+         WHERE e.entity_type = 'mention' AND (?1 IS NULL OR e.note_id = ?1)",
+    )?
+    .execute(params![note_id])
+}
+
+/// Get notes that @mention a board member, most mentions first
+pub fn get_notes_mentioning_member(
+    app: &AppHandle,
+    name: &str,
+) -> Result<Vec<MemberMention>, DbError> {
+    with_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.path, n.title, COUNT(*), MIN(e.line_number),
+                    (SELECT e2.context FROM entities e2
+                     WHERE e2.note_id = n.id AND e2.entity_type = 'member' AND LOWER(e2.value) = LOWER(?1)
+                     ORDER BY e2.line_number LIMIT 1)
+             FROM entities e
+             JOIN notes n ON n.id = e.note_id
+             WHERE e.entity_type = 'member' AND LOWER(e.value) = LOWER(?1)
+             GROUP BY n.id
+             ORDER BY COUNT(*) DESC, n.modified_at DESC",
+        )?;
+        let mentions = stmt
+            .query_map(params![name], |row| {
+                Ok(MemberMention {
+                    note_id: row.get(0)?,
+                    note_path: row.get(1)?,
+                    note_title: row.get(2)?,
+                    count: row.get(3)?,
+                    line_number: row.get::<_, Option<i64>>(4)?.unwrap_or_default(),
+                    context: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(mentions)
+    })
+}
+
 // =============================================================================
 // Vault Health Functions
 // =============================================================================
@@ -699,45 +1775,155 @@ pub struct VaultHealth {
     pub recently_modified: Vec<OrphanNote>,
 }
 
-/// Get orphan notes (notes with no incoming or outgoing links)
-pub fn get_orphan_notes(app: &AppHandle) -> Result<Vec<OrphanNote>, Box<dyn std::error::Error>> {
-    with_db(app, |conn| {
+/// Which missing links make a note an orphan
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanMode {
+    /// No incoming and no outgoing links
+    #[default]
+    Isolated,
+    /// Nothing links to the note, whatever it links to
+    NoIncoming,
+    /// The note links to nothing, whatever links to it
+    NoOutgoing,
+}
+
+impl OrphanMode {
+    fn condition(self) -> &'static str {
+        match self {
+            OrphanMode::Isolated => "n.outgoing_count = 0 AND n.incoming_count = 0",
+            OrphanMode::NoIncoming => "n.incoming_count = 0",
+            OrphanMode::NoOutgoing => "n.outgoing_count = 0",
+        }
+    }
+}
+
+fn orphan_settings(app: &AppHandle) -> OrphanSettings {
+    super::get_current_vault_path(app)
+        .and_then(|p| read_vault_settings(&p).ok())
+        .unwrap_or_default()
+        .orphans
+}
+
+/// Orphans by `mode`, newest first, leaving out notes in excluded folders
+fn query_orphan_notes(
+    conn: &rusqlite::Connection,
+    mode: OrphanMode,
+    settings: &OrphanSettings,
+) -> Result<Vec<OrphanNote>, DbError> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT n.id, n.path, n.title, n.created_at, n.modified_at
+        FROM notes n
+        WHERE {}
+        ORDER BY n.modified_at DESC
+        "#,
+        mode.condition()
+    ))?;
+
+    let orphans: Vec<OrphanNote> = stmt
+        .query_map([], |row| {
+            Ok(OrphanNote {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                title: row.get(2)?,
+                created_at: row.get(3)?,
+                modified_at: row.get(4)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .filter(|note| !settings.excludes(&note.path))
+        .collect();
+
+    Ok(orphans)
+}
+
+/// Get orphan notes; by default notes with no incoming or outgoing links
+pub fn get_orphan_notes(app: &AppHandle, mode: OrphanMode) -> Result<Vec<OrphanNote>, DbError> {
+    let settings = orphan_settings(app);
+    with_db(app, |conn| query_orphan_notes(conn, mode, &settings))
+}
+
+/// How many notes embed or link to each attachment, keyed by vault-relative path
+pub fn get_attachment_ref_counts(
+    app: &AppHandle,
+) -> Result<std::collections::HashMap<String, usize>, DbError> {
+    with_read_db(app, |conn| {
         let mut stmt = conn.prepare(
-            r#"
-            SELECT n.id, n.path, n.title, n.created_at, n.modified_at
-            FROM notes n
-            WHERE NOT EXISTS (
-                SELECT 1 FROM backlinks b WHERE b.source_id = n.id
-            )
-            AND NOT EXISTS (
-                SELECT 1 FROM backlinks b2
-                JOIN notes n2 ON b2.source_id = n2.id
-                WHERE b2.target_path = n.path
-                   OR b2.target_path LIKE '%' || replace(replace(n.path, 'notes/', ''), '.md', '') || '%'
-            )
-            ORDER BY n.modified_at DESC
-            "#,
+            "SELECT attachment_path, COUNT(DISTINCT note_id) FROM attachment_refs
+             GROUP BY attachment_path",
+        )?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(counts)
+    })
+}
+
+/// A note that links to itself
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SelfReference {
+    pub note_id: String,
+    pub path: String,
+    pub title: String,
+    /// The self-links as written
+    pub targets: Vec<String>,
+}
+
+/// Find notes that link to themselves. Self-links are skipped when indexing
+/// backlinks, so this rescans the stored content.
+pub fn get_self_referencing_notes(app: &AppHandle) -> Result<Vec<SelfReference>, DbError> {
+    let links_in_code = super::get_current_vault_path(app)
+        .and_then(|p| read_vault_settings(&p).ok())
+        .unwrap_or_default()
+        .indexing
+        .links_in_code;
+
+    with_read_db(app, |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, path, title, content FROM notes
+             WHERE content LIKE '%[[%' OR content LIKE '%.md)%'
+             ORDER BY path",
         )?;
 
-        let orphans: Vec<OrphanNote> = stmt
+        let notes = stmt
             .query_map([], |row| {
-                Ok(OrphanNote {
-                    id: row.get(0)?,
-                    path: row.get(1)?,
-                    title: row.get(2)?,
-                    created_at: row.get(3)?,
-                    modified_at: row.get(4)?,
-                })
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
             })?
             .filter_map(|r| r.ok())
+            .filter_map(|(note_id, path, title, content)| {
+                let mut targets: Vec<String> = Vec::new();
+                let code_spans = super::CodeSpans::find(&content);
+                let skip = (!links_in_code).then_some(&code_spans);
+                for (target, _, _) in super::extract_links(&content, skip) {
+                    if super::is_self_link(&path, &target) && !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                }
+
+                (!targets.is_empty()).then_some(SelfReference {
+                    note_id,
+                    path,
+                    title,
+                    targets,
+                })
+            })
             .collect();
 
-        Ok(orphans)
+        Ok(notes)
     })
 }
 
 /// Get broken links (links pointing to non-existent notes)
-pub fn get_broken_links(app: &AppHandle) -> Result<Vec<BrokenLink>, Box<dyn std::error::Error>> {
+pub fn get_broken_links(app: &AppHandle) -> Result<Vec<BrokenLink>, DbError> {
     with_db(app, |conn| {
         // Get all note paths for comparison
         let mut paths_stmt = conn.prepare("SELECT path FROM notes")?;
@@ -805,8 +1991,10 @@ pub fn get_broken_links(app: &AppHandle) -> Result<Vec<BrokenLink>, Box<dyn std:
 }
 
 /// Get overall vault health statistics
-pub fn get_vault_health(app: &AppHandle) -> Result<VaultHealth, Box<dyn std::error::Error>> {
-    with_db(app, |conn| {
+pub fn get_vault_health(app: &AppHandle, orphan_mode: OrphanMode) -> Result<VaultHealth, DbError> {
+    let orphan_settings = orphan_settings(app);
+
+    with_read_db(app, |conn| {
         // Total notes
         let total_notes: usize =
             conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get::<_, i64>(0))? as usize;
@@ -817,23 +2005,7 @@ pub fn get_vault_health(app: &AppHandle) -> Result<VaultHealth, Box<dyn std::err
         })? as usize;
 
         // Get orphan count
-        let orphan_count: usize = conn.query_row(
-            r#"
-            SELECT COUNT(*)
-            FROM notes n
-            WHERE NOT EXISTS (
-                SELECT 1 FROM backlinks b WHERE b.source_id = n.id
-            )
-            AND NOT EXISTS (
-                SELECT 1 FROM backlinks b2
-                JOIN notes n2 ON b2.source_id = n2.id
-                WHERE b2.target_path = n.path
-                   OR b2.target_path LIKE '%' || replace(replace(n.path, 'notes/', ''), '.md', '') || '%'
-            )
-            "#,
-            [],
-            |row| row.get::<_, i64>(0),
-        )? as usize;
+        let orphan_count = query_orphan_notes(conn, orphan_mode, &orphan_settings)?.len();
 
         // Average links per note
         let avg_links_per_note = if total_notes > 0 {
@@ -842,29 +2014,13 @@ pub fn get_vault_health(app: &AppHandle) -> Result<VaultHealth, Box<dyn std::err
             0.0
         };
 
-        // Most connected notes (top 5) - using CTEs for efficient aggregation
+        // Most connected notes (top 5), from the cached link counts
         let mut connected_stmt = conn.prepare(
             r#"
-            WITH outgoing_links AS (
-                SELECT source_id, COUNT(*) as out_count
-                FROM backlinks
-                GROUP BY source_id
-            ),
-            incoming_links AS (
-                SELECT n.id as note_id, COUNT(DISTINCT b.source_id) as in_count
-                FROM notes n
-                LEFT JOIN backlinks b ON b.target_path = n.path
-                   OR b.target_path LIKE '%' || replace(replace(n.path, 'notes/', ''), '.md', '') || '%'
-                GROUP BY n.id
-            )
-            SELECT n.id, n.path, n.title,
-                   COALESCE(ol.out_count, 0) as out_links,
-                   COALESCE(il.in_count, 0) as in_links,
+            SELECT n.id, n.path, n.title, n.outgoing_count, n.incoming_count,
                    COALESCE(n.archived, 0)
             FROM notes n
-            LEFT JOIN outgoing_links ol ON ol.source_id = n.id
-            LEFT JOIN incoming_links il ON il.note_id = n.id
-            ORDER BY (COALESCE(ol.out_count, 0) + COALESCE(il.in_count, 0)) DESC
+            ORDER BY (n.outgoing_count + n.incoming_count) DESC
             LIMIT 5
             "#,
         )?;
@@ -878,6 +2034,8 @@ pub fn get_vault_health(app: &AppHandle) -> Result<VaultHealth, Box<dyn std::err
                     link_count: row.get::<_, i64>(3)? as usize,
                     backlink_count: row.get::<_, i64>(4)? as usize,
                     archived: row.get::<_, i32>(5)? != 0,
+                    x: None,
+                    y: None,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -924,9 +2082,7 @@ pub fn get_vault_health(app: &AppHandle) -> Result<VaultHealth, Box<dyn std::err
     })
 }
 
-fn get_broken_links_count(
-    conn: &rusqlite::Connection,
-) -> Result<usize, Box<dyn std::error::Error>> {
+fn get_broken_links_count(conn: &rusqlite::Connection) -> Result<usize, DbError> {
     // Get all note paths for comparison
     let mut paths_stmt = conn.prepare("SELECT path FROM notes")?;
     let note_paths: std::collections::HashSet<String> = paths_stmt
@@ -974,14 +2130,54 @@ pub struct UnlinkedMention {
     pub mentioned_in_path: String,
     pub mentioned_in_title: String,
     pub context: String,
+    /// Relevance in [0, 1]: higher for specific, rarely-mentioned titles
+    /// that appear several times in the mentioning note
+    pub score: f64,
+}
+
+/// FTS hits per title before the mentioning note cap; one extra covers the
+/// note itself, which is filtered out after the (cached) query
+const UNLINKED_MENTION_CANDIDATES: usize = 51;
+
+/// Whether a title is specific enough to be worth flagging as an unlinked mention
+fn is_mentionable_title(title: &str, settings: &UnlinkedMentionSettings) -> bool {
+    if title.chars().count() < settings.min_title_length {
+        return false;
+    }
+
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    if words.is_empty() || words.len() < settings.min_word_count {
+        return false;
+    }
+
+    // A title made only of common words ("Index", "The Notes") is noise
+    !words
+        .iter()
+        .all(|w| settings.stopwords.iter().any(|s| s.eq_ignore_ascii_case(w)))
+}
+
+/// Relevance of an unlinked mention from how specific the title is (word
+/// count), how rare it is across the vault, and how often it occurs in the note
+fn unlinked_mention_score(word_count: usize, mentioning_notes: usize, occurrences: usize) -> f64 {
+    let specificity = 1.0 - 1.0 / (1.0 + word_count as f64);
+    let rarity = 1.0 - (mentioning_notes.saturating_sub(1) as f64 / 50.0).min(1.0);
+    let frequency = occurrences.min(3) as f64 / 3.0;
+    0.4 * specificity + 0.3 * rarity + 0.3 * frequency
 }
 
 /// Get unlinked mentions (note titles that appear in content but aren't wiki-linked)
 /// Optimized to use FTS5 for O(n) instead of O(n²) performance
-pub fn get_unlinked_mentions(
-    app: &AppHandle,
-) -> Result<Vec<UnlinkedMention>, Box<dyn std::error::Error>> {
-    with_db(app, |conn| {
+pub fn get_unlinked_mentions(app: &AppHandle) -> Result<Vec<UnlinkedMention>, DbError> {
+    let settings = super::get_current_vault_path(app)
+        .and_then(|p| read_vault_settings(&p).ok())
+        .unwrap_or_default()
+        .unlinked_mentions;
+
+    with_read_db(app, |conn| {
         // Get all notes with their titles (we'll use FTS5 to search content)
         let mut notes_stmt = conn.prepare("SELECT id, path, title FROM notes")?;
         let notes: Vec<(String, String, String)> = notes_stmt
@@ -1016,40 +2212,62 @@ pub fn get_unlinked_mentions(
             FROM notes_fts
             JOIN notes n ON notes_fts.rowid = n.rowid
             WHERE notes_fts MATCH ?1
-            AND n.id != ?2
-            LIMIT 50
+            LIMIT ?2
             "#,
         )?;
 
+        // Notes sharing a title (same name in different folders) reuse one search
+        type Candidates = Vec<(String, String, String, String)>;
+        let mut search_cache: std::collections::HashMap<String, std::rc::Rc<Candidates>> =
+            std::collections::HashMap::new();
+
         // For each note, use FTS5 to find other notes containing the title
         // This is O(n * log(m)) instead of O(n * m) where m is total content size
         for (note_id, note_path, note_title) in &notes {
-            if note_title.len() < 3 {
-                continue; // Skip very short titles
+            if !is_mentionable_title(note_title, &settings) {
+                continue;
             }
 
             let title_lower = note_title.to_lowercase();
+            let word_count = title_lower.split_whitespace().count();
             let note_filename = std::path::PathBuf::from(note_path)
                 .file_stem()
                 .map(|s| s.to_string_lossy().to_lowercase())
                 .unwrap_or_default();
 
-            // Use FTS5 to search for notes containing this title
-            // Quote the title to search for exact phrase
-            let fts_query = format!("\"{}\"", note_title.replace('"', ""));
+            let candidates = match search_cache.get(&title_lower) {
+                Some(cached) => cached.clone(),
+                None => {
+                    // Use FTS5 to search for notes containing this title
+                    // Quote the title to search for exact phrase
+                    let fts_query = format!("\"{}\"", note_title.replace('"', ""));
+                    let rows: Candidates = search_stmt
+                        .query_map(
+                            params![fts_query, UNLINKED_MENTION_CANDIDATES as i64],
+                            |row| {
+                                Ok((
+                                    row.get::<_, String>(0)?,
+                                    row.get::<_, String>(1)?,
+                                    row.get::<_, String>(2)?,
+                                    row.get::<_, String>(3)?,
+                                ))
+                            },
+                        )?
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    let rows = std::rc::Rc::new(rows);
+                    search_cache.insert(title_lower.clone(), rows.clone());
+                    rows
+                }
+            };
 
-            let matches = search_stmt
-                .query_map(params![fts_query, note_id], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, String>(1)?,
-                        row.get::<_, String>(2)?,
-                        row.get::<_, String>(3)?,
-                    ))
-                })?
-                .filter_map(|r| r.ok());
+            let mentioning_notes = candidates.iter().filter(|c| c.0 != *note_id).count();
+
+            for (other_id, other_path, other_title, other_content) in candidates.iter() {
+                if other_id == note_id {
+                    continue;
+                }
 
-            for (other_id, other_path, other_title, other_content) in matches {
                 // Check if already linked
                 let is_linked = existing_links.contains(&(other_id.clone(), title_lower.clone()))
                     || existing_links.contains(&(other_id.clone(), note_filename.clone()))
@@ -1063,32 +2281,36 @@ pub fn get_unlinked_mentions(
                 let content_lower = other_content.to_lowercase();
                 if let Some(pos) = content_lower.find(&title_lower) {
                     // Use safe character boundary functions to avoid panics on multi-byte chars
-                    let start = floor_char_boundary(&other_content, pos.saturating_sub(40));
+                    let start = floor_char_boundary(other_content, pos.saturating_sub(40));
                     let end = ceil_char_boundary(
-                        &other_content,
+                        other_content,
                         (pos + note_title.len() + 40).min(other_content.len()),
                     );
                     let context = other_content[start..end].to_string();
+                    let occurrences = content_lower.matches(&title_lower).count();
 
                     unlinked.push(UnlinkedMention {
                         note_id: note_id.clone(),
                         note_path: note_path.clone(),
                         note_title: note_title.clone(),
-                        mentioned_in_id: other_id,
-                        mentioned_in_path: other_path,
-                        mentioned_in_title: other_title,
+                        mentioned_in_id: other_id.clone(),
+                        mentioned_in_path: other_path.clone(),
+                        mentioned_in_title: other_title.clone(),
                         context: format!("...{}...", context.replace('\n', " ")),
+                        score: unlinked_mention_score(word_count, mentioning_notes, occurrences),
                     });
                 }
             }
         }
 
+        unlinked.sort_by(|a, b| b.score.total_cmp(&a.score));
+
         Ok(unlinked)
     })
 }
 
 /// Get a random note for review (Zettelkasten practice)
-pub fn get_random_note(app: &AppHandle) -> Result<Option<OrphanNote>, Box<dyn std::error::Error>> {
+pub fn get_random_note(app: &AppHandle) -> Result<Option<OrphanNote>, DbError> {
     with_db(app, |conn| {
         let mut stmt = conn.prepare(
             "SELECT id, path, title, created_at, modified_at FROM notes ORDER BY RANDOM() LIMIT 1",
@@ -1111,35 +2333,16 @@ pub fn get_random_note(app: &AppHandle) -> Result<Option<OrphanNote>, Box<dyn st
 }
 
 /// Get notes that could be MOCs (Maps of Content) - notes with many outgoing links
-pub fn get_potential_mocs(
-    app: &AppHandle,
-    min_links: usize,
-) -> Result<Vec<GraphNode>, Box<dyn std::error::Error>> {
+pub fn get_potential_mocs(app: &AppHandle, min_links: usize) -> Result<Vec<GraphNode>, DbError> {
     with_db(app, |conn| {
-        // Use CTEs to pre-aggregate link counts (avoids correlated subqueries)
+        // Link counts are cached on the notes row by the indexer
         let mut stmt = conn.prepare(
             r#"
-            WITH outgoing_links AS (
-                SELECT source_id, COUNT(*) as out_count
-                FROM backlinks
-                GROUP BY source_id
-            ),
-            incoming_links AS (
-                SELECT n.id as note_id, COUNT(DISTINCT b.source_id) as in_count
-                FROM notes n
-                LEFT JOIN backlinks b ON b.target_path = n.path
-                   OR b.target_path LIKE '%' || replace(replace(n.path, 'notes/', ''), '.md', '') || '%'
-                GROUP BY n.id
-            )
-            SELECT n.id, n.path, n.title,
-                   COALESCE(ol.out_count, 0) as out_links,
-                   COALESCE(il.in_count, 0) as in_links,
+            SELECT n.id, n.path, n.title, n.outgoing_count, n.incoming_count,
                    COALESCE(n.archived, 0)
             FROM notes n
-            LEFT JOIN outgoing_links ol ON ol.source_id = n.id
-            LEFT JOIN incoming_links il ON il.note_id = n.id
-            WHERE COALESCE(ol.out_count, 0) >= ?1
-            ORDER BY out_links DESC
+            WHERE n.outgoing_count >= ?1
+            ORDER BY n.outgoing_count DESC
             "#,
         )?;
 
@@ -1152,6 +2355,8 @@ pub fn get_potential_mocs(
                     link_count: row.get::<_, i64>(3)? as usize,
                     backlink_count: row.get::<_, i64>(4)? as usize,
                     archived: row.get::<_, i32>(5)? != 0,
+                    x: None,
+                    y: None,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -1165,7 +2370,7 @@ pub fn get_potential_mocs(
 pub fn get_notes_by_folder(
     app: &AppHandle,
     folder_prefix: &str,
-) -> Result<Vec<OrphanNote>, Box<dyn std::error::Error>> {
+) -> Result<Vec<OrphanNote>, DbError> {
     with_db(app, |conn| {
         let escaped = escape_like_pattern(folder_prefix);
         let pattern = format!("{}%", escaped);
@@ -1189,3 +2394,303 @@ pub fn get_notes_by_folder(
         Ok(notes)
     })
 }
+
+/// A distinctive term in a note, scored by TF-IDF against the rest of the vault
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct NoteKeyword {
+    pub term: String,
+    /// Occurrences in the note
+    pub count: usize,
+    pub score: f64,
+}
+
+/// Most frequent terms in a note that get a document-frequency lookup
+const KEYWORD_CANDIDATES: usize = 100;
+
+/// Common words never worth suggesting as keywords
+const KEYWORD_STOPWORDS: &str =
+    "about above after again against all also and any are because been \
+    before being below between both but can could did does doing down during each few for from \
+    further had has have having her here hers herself him himself his how http https into its \
+    itself just more most myself nor not now off once only other our ours ourselves out over own \
+    same she should some such than that the their theirs them themselves then there these they \
+    this those through too under until very was were what when where which while who whom why \
+    will with would www you your yours yourself yourselves";
+
+/// Body text worth mining for keywords: no frontmatter, code blocks, inline
+/// code or URLs
+fn keyword_text(content: &str) -> String {
+    let body = crate::commands::notes::split_frontmatter(content)
+        .map(|(_, body)| body)
+        .unwrap_or(content);
+
+    let mut text = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        // Odd backtick-delimited segments are inline code
+        for (i, segment) in line.split('`').enumerate() {
+            if i % 2 == 0 {
+                text.push_str(segment);
+                text.push(' ');
+            }
+        }
+        text.push('\n');
+    }
+
+    text.split_whitespace()
+        .filter(|word| !word.contains("://"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Get the top keywords of a note. Term frequency comes from the note body;
+/// document frequency from the FTS index (which stems, so "parsers" in another
+/// note counts towards "parser").
+pub fn get_note_keywords(
+    app: &AppHandle,
+    note_path: &str,
+    limit: usize,
+) -> Result<Vec<NoteKeyword>, DbError> {
+    with_read_db(app, |conn| {
+        let content: String = conn.query_row(
+            "SELECT COALESCE(content, '') FROM notes WHERE path = ?1",
+            params![note_path],
+            |row| row.get(0),
+        )?;
+        let total_notes: i64 =
+            conn.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))?;
+
+        let stopwords: std::collections::HashSet<&str> =
+            KEYWORD_STOPWORDS.split_whitespace().collect();
+        let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        let mut total_terms = 0usize;
+
+        for word in keyword_text(&content).split(|c: char| !c.is_alphabetic()) {
+            if word.chars().count() < 3 {
+                continue;
+            }
+            let term = word.to_lowercase();
+            if stopwords.contains(term.as_str()) {
+                continue;
+            }
+            total_terms += 1;
+            *counts.entry(term).or_default() += 1;
+        }
+
+        let mut candidates: Vec<(String, usize)> = counts.into_iter().collect();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        candidates.truncate(KEYWORD_CANDIDATES);
+
+        let mut df_stmt =
+            conn.prepare_cached("SELECT COUNT(*) FROM notes_fts WHERE notes_fts MATCH ?1")?;
+        let mut keywords = Vec::with_capacity(candidates.len());
+
+        for (term, count) in candidates {
+            let fts_query = format!("content:\"{}\"", term);
+            let df: i64 = df_stmt
+                .query_row(params![fts_query], |row| row.get(0))
+                .unwrap_or(1);
+            // Documents other than this note that use the term
+            let df_rest = (df - 1).max(0);
+
+            let tf = count as f64 / total_terms as f64;
+            let idf = ((total_notes as f64 + 1.0) / (df_rest as f64 + 1.0)).ln();
+            keywords.push(NoteKeyword {
+                term,
+                count,
+                score: tf * idf,
+            });
+        }
+
+        keywords.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        keywords.truncate(limit);
+        Ok(keywords)
+    })
+}
+
+/// An existing vault tag suggested for a note
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    /// 0.0 - 1.0
+    pub confidence: f64,
+    /// The note keyword the tag matched, if any
+    pub keyword: Option<String>,
+    /// How many similar notes carry the tag
+    pub similar_notes: usize,
+}
+
+/// Keywords used to find similar notes for tag co-occurrence
+const SIMILAR_NOTE_KEYWORDS: usize = 10;
+/// Similar notes whose tags are considered
+const SIMILAR_NOTE_LIMIT: i64 = 20;
+/// Weight of a keyword match vs. co-occurrence on similar notes
+const TAG_KEYWORD_WEIGHT: f64 = 0.6;
+
+/// Whether a tag word and a keyword are the same word, allowing for simple
+/// inflection ("parser" / "parsers", "deploy" / "deployment")
+fn tag_word_matches(tag_word: &str, keyword: &str) -> bool {
+    if tag_word == keyword {
+        return true;
+    }
+    let (short, long) = if tag_word.len() <= keyword.len() {
+        (tag_word, keyword)
+    } else {
+        (keyword, tag_word)
+    };
+    short.chars().count() >= 4 && long.starts_with(short)
+}
+
+/// Suggest existing vault tags for a note, ranked by how well they match the
+/// note's keywords and how often they appear on notes with similar keywords.
+/// Tags the note already has are left out.
+pub fn suggest_tags(
+    app: &AppHandle,
+    note_path: &str,
+    limit: usize,
+) -> Result<Vec<TagSuggestion>, DbError> {
+    let keywords = get_note_keywords(app, note_path, KEYWORD_CANDIDATES)?;
+    let top_score = keywords.first().map(|k| k.score).unwrap_or(0.0);
+
+    with_read_db(app, |conn| {
+        let note_id: String = conn.query_row(
+            "SELECT id FROM notes WHERE path = ?1",
+            params![note_path],
+            |row| row.get(0),
+        )?;
+
+        let own_tags: std::collections::HashSet<String> = conn
+            .prepare("SELECT tag FROM tags WHERE note_id = ?1")?
+            .query_map(params![note_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let vault_tags: Vec<String> = conn
+            .prepare("SELECT DISTINCT tag FROM tags")?
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .filter(|tag| !own_tags.contains(tag))
+            .collect();
+
+        // Tags on the notes that best match this note's top keywords
+        let mut co_occurrence: std::collections::HashMap<String, usize> =
+            std::collections::HashMap::new();
+        let mut similar_count = 0usize;
+        if !keywords.is_empty() {
+            let fts_query = keywords
+                .iter()
+                .take(SIMILAR_NOTE_KEYWORDS)
+                .map(|k| format!("content:\"{}\"", k.term))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            let similar: Vec<String> = conn
+                .prepare(
+                    "SELECT n.id FROM notes_fts
+                     JOIN notes n ON n.rowid = notes_fts.rowid
+                     WHERE notes_fts MATCH ?1 AND n.id != ?2
+                     ORDER BY rank
+                     LIMIT ?3",
+                )?
+                .query_map(params![fts_query, note_id, SIMILAR_NOTE_LIMIT], |row| {
+                    row.get(0)
+                })?
+                .filter_map(|r| r.ok())
+                .collect();
+            similar_count = similar.len();
+
+            let mut tag_stmt = conn.prepare_cached("SELECT tag FROM tags WHERE note_id = ?1")?;
+            for id in &similar {
+                let tags = tag_stmt.query_map(params![id], |row| row.get::<_, String>(0))?;
+                for tag in tags.filter_map(|r| r.ok()) {
+                    *co_occurrence.entry(tag).or_default() += 1;
+                }
+            }
+        }
+
+        let mut suggestions: Vec<TagSuggestion> = vault_tags
+            .into_iter()
+            .filter_map(|tag| {
+                let tag_words: Vec<String> = tag
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|w| !w.is_empty())
+                    .map(|w| w.to_lowercase())
+                    .collect();
+
+                // Keywords are sorted by score, so the first match is the best
+                let matched = keywords
+                    .iter()
+                    .find(|k| tag_words.iter().any(|word| tag_word_matches(word, &k.term)));
+                let keyword_score = match matched {
+                    Some(k) if top_score > 0.0 => k.score / top_score,
+                    Some(_) => 1.0,
+                    None => 0.0,
+                };
+
+                let similar_notes = co_occurrence.get(&tag).copied().unwrap_or(0);
+                let co_score = if similar_count > 0 {
+                    similar_notes as f64 / similar_count as f64
+                } else {
+                    0.0
+                };
+
+                let confidence =
+                    TAG_KEYWORD_WEIGHT * keyword_score + (1.0 - TAG_KEYWORD_WEIGHT) * co_score;
+                (confidence > 0.0).then(|| TagSuggestion {
+                    tag,
+                    confidence: confidence.min(1.0),
+                    keyword: matched.map(|k| k.term.clone()),
+                    similar_notes,
+                })
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjacent_terms_outrank_scattered_ones() {
+        let terms = proximity_terms("lateral movement");
+        let adjacent = proximity_bonus("Signs of lateral movement on the host", &terms);
+        let scattered = proximity_bonus(
+            "Lateral thinking helped, and after a long night the attacker's movement was traced",
+            &terms,
+        );
+
+        assert_eq!(adjacent, 1.0);
+        assert!(scattered > 0.0);
+        assert!(adjacent > scattered);
+    }
+
+    #[test]
+    fn missing_term_or_single_term_gives_no_bonus() {
+        let terms = proximity_terms("lateral movement");
+        assert_eq!(proximity_bonus("lateral thinking only", &terms), 0.0);
+        assert_eq!(
+            proximity_bonus("lateral movement", &proximity_terms("lateral")),
+            0.0
+        );
+    }
+}
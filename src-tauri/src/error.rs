@@ -0,0 +1,192 @@
+//! Command error type with frontend-friendly serialization
+//!
+//! Serialized as `{ "code": "...", "message": "..." }` (plus `path`, or `pid` and
+//! `since` for a held vault lock, where relevant)
+//! so the UI can branch on `code` instead of matching message text.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use thiserror::Error;
+
+use crate::db::DbError;
+use crate::fs::lock::LockError;
+
+/// Errors returned by Tauri commands
+#[derive(Debug, Error)]
+pub enum CommandError {
+    #[error("No vault is currently open")]
+    NoVault,
+
+    /// `path` is empty when the missing thing is an index row rather than a file
+    #[error("Not found{}", path_suffix(path))]
+    NotFound { path: String },
+
+    #[error("Access denied: path traversal detected")]
+    PathTraversal { path: String },
+
+    #[error("Already exists: {path}")]
+    Conflict { path: String },
+
+    #[error("{message}")]
+    InvalidInput { message: String },
+
+    #[error("This vault is protected by a passphrase")]
+    PassphraseRequired,
+
+    #[error("Incorrect vault passphrase")]
+    PassphraseIncorrect,
+
+    #[error("Vault is in use by another Kairo instance (pid {pid})")]
+    VaultInUse { pid: u32, since: i64 },
+
+    #[error("Database error: {message}")]
+    Db { message: String },
+
+    #[error("I/O error: {message}")]
+    Io { message: String },
+
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl CommandError {
+    /// Stable identifier the frontend can match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NoVault => "noVault",
+            CommandError::NotFound { .. } => "notFound",
+            CommandError::PathTraversal { .. } => "pathTraversal",
+            CommandError::Conflict { .. } => "conflict",
+            CommandError::InvalidInput { .. } => "invalidInput",
+            CommandError::PassphraseRequired => "passphraseRequired",
+            CommandError::PassphraseIncorrect => "passphraseIncorrect",
+            CommandError::VaultInUse { .. } => "vaultInUse",
+            CommandError::Db { .. } => "db",
+            CommandError::Io { .. } => "io",
+            CommandError::Other { .. } => "other",
+        }
+    }
+
+    pub fn not_found(path: impl Into<String>) -> Self {
+        CommandError::NotFound { path: path.into() }
+    }
+
+    pub fn conflict(path: impl Into<String>) -> Self {
+        CommandError::Conflict { path: path.into() }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        CommandError::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    fn lock_holder(&self) -> Option<(u32, i64)> {
+        match self {
+            CommandError::VaultInUse { pid, since } => Some((*pid, *since)),
+            _ => None,
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            CommandError::NotFound { path }
+            | CommandError::PathTraversal { path }
+            | CommandError::Conflict { path } => Some(path.as_str()).filter(|p| !p.is_empty()),
+            _ => None,
+        }
+    }
+}
+
+fn path_suffix(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", path)
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let path = self.path();
+        let holder = self.lock_holder();
+        let len = 2 + usize::from(path.is_some()) + 2 * usize::from(holder.is_some());
+        let mut state = serializer.serialize_struct("CommandError", len)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let Some(path) = path {
+            state.serialize_field("path", path)?;
+        }
+        if let Some((pid, since)) = holder {
+            state.serialize_field("pid", &pid)?;
+            state.serialize_field("since", &since)?;
+        }
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError::Io {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(err: rusqlite::Error) -> Self {
+        CommandError::Db {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<DbError> for CommandError {
+    fn from(err: DbError) -> Self {
+        match err {
+            DbError::NoConnection => CommandError::NoVault,
+            DbError::NotFound => CommandError::NotFound {
+                path: String::new(),
+            },
+            DbError::Io(message) => CommandError::Io { message },
+            DbError::Other(message) => CommandError::Other { message },
+            err => CommandError::Db {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl From<LockError> for CommandError {
+    fn from(err: LockError) -> Self {
+        match err {
+            LockError::InUse(info) => CommandError::VaultInUse {
+                pid: info.pid,
+                since: info.acquired_at,
+            },
+            LockError::Io(err) => err.into(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CommandError {
+    fn from(err: serde_json::Error) -> Self {
+        CommandError::InvalidInput {
+            message: err.to_string(),
+        }
+    }
+}
+
+// Helpers that still report plain strings
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::Other { message }
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError::Other {
+            message: message.to_string(),
+        }
+    }
+}
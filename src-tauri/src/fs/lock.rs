@@ -0,0 +1,145 @@
+//! Vault lock file so two app instances don't open the same vault at once
+//!
+//! The lock lives at `.kairo/.lock` and records the owning process id and
+//! when it was taken. A lock whose process is no longer running is stale and
+//! can be reclaimed.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const LOCK_FILE: &str = ".lock";
+
+/// How long to wait for another instance's lock write to land
+const SETTLE_DELAY: Duration = Duration::from_millis(50);
+
+/// Contents of the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub pid: u32,
+    pub acquired_at: i64,
+}
+
+/// Why a lock could not be taken
+#[derive(Debug)]
+pub enum LockError {
+    /// Another running process holds the lock
+    InUse(LockInfo),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for LockError {
+    fn from(err: std::io::Error) -> Self {
+        LockError::Io(err)
+    }
+}
+
+/// A held vault lock. The lock file is removed when this is dropped.
+#[derive(Debug)]
+pub struct VaultLock {
+    path: PathBuf,
+}
+
+impl VaultLock {
+    /// Take the lock for a vault. A stale lock (its process has exited) is
+    /// reclaimed; a live lock held by another process is only taken with `force`.
+    pub fn acquire(vault_path: &Path, force: bool) -> Result<Self, LockError> {
+        let kairo_dir = vault_path.join(".kairo");
+        fs::create_dir_all(&kairo_dir)?;
+        let path = kairo_dir.join(LOCK_FILE);
+
+        let info = LockInfo {
+            pid: std::process::id(),
+            acquired_at: chrono::Utc::now().timestamp(),
+        };
+        let content = serde_json::to_string(&info)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+
+        // Creating the file is the atomic step: of two instances starting
+        // together, only one gets it
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()?;
+                return Ok(Self { path });
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // The lock exists. A lock that can't be read may still be being
+        // written by the instance that just created it, so give it a moment.
+        let existing = read_lock(&path).or_else(|| {
+            thread::sleep(SETTLE_DELAY);
+            read_lock(&path)
+        });
+        if let Some(existing) = existing {
+            let ours = existing.pid == info.pid;
+            if !ours && !force && is_process_running(existing.pid) {
+                return Err(LockError::InUse(existing));
+            }
+        }
+
+        // Reclaim it. Write to a temp file and rename so a concurrent reader
+        // never sees a partial lock.
+        let tmp_path = kairo_dir.join(format!("{}.{}", LOCK_FILE, info.pid));
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+
+        // Another instance reclaiming the same stale lock may have renamed over
+        // ours; whoever's lock is on disk once both have written keeps it
+        thread::sleep(SETTLE_DELAY);
+        match read_lock(&path) {
+            Some(holder) if holder.pid != info.pid => Err(LockError::InUse(holder)),
+            _ => Ok(Self { path }),
+        }
+    }
+}
+
+impl Drop for VaultLock {
+    fn drop(&mut self) {
+        // Only remove the file if it's still ours (it may have been force-taken)
+        if read_lock(&self.path).is_some_and(|info| info.pid == std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_lock(path: &Path) -> Option<LockInfo> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(unix)]
+fn is_process_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 checks for existence without delivering anything; EPERM means
+    // the process exists but belongs to another user
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_process_running(pid: u32) -> bool {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).contains(&format!("\"{}\"", pid)))
+        // If we can't tell, assume the other instance is alive
+        .unwrap_or(true)
+}
@@ -1,11 +1,48 @@
 #![allow(dead_code)]
 
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
+use crate::commands::notes::render_folder_template;
+use crate::commands::settings::read_vault_settings;
+use crate::db;
+
+/// Events arriving within this window of each other are handled as one batch,
+/// so an editor's burst of writes reindexes and notifies once
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Payload of `note-changed`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteChangedEvent {
+    /// Vault-relative path
+    pub path: String,
+    pub content_hash: Option<String>,
+}
+
+/// Payload of `note-deleted`
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteDeletedEvent {
+    /// Vault-relative path
+    pub path: String,
+}
+
+/// Tell the UI a changed note couldn't be reindexed (`index-error`)
+fn emit_index_error(app: &AppHandle, path: String, error: db::DbError) {
+    let _ = app.emit(
+        "index-error",
+        db::FailedFile {
+            path,
+            error: error.to_string(),
+        },
+    );
+}
+
 /// File watcher for detecting changes in the vault
 pub struct VaultWatcher {
     watcher: RecommendedWatcher,
@@ -34,8 +71,10 @@ impl VaultWatcher {
         // Spawn a thread to handle events
         let app_handle = app.clone();
         std::thread::spawn(move || {
-            while let Ok(event) = rx.recv() {
-                handle_fs_event(&app_handle, event);
+            while let Some(paths) = next_batch(&rx) {
+                for path in paths {
+                    handle_note_change(&app_handle, &vault_path, &path);
+                }
             }
         });
 
@@ -50,33 +89,111 @@ impl VaultWatcher {
     }
 }
 
-fn handle_fs_event(app: &AppHandle, event: Event) {
-    use notify::EventKind;
+/// Wait for the next burst of events and return the markdown files it touched.
+/// Returns `None` once the watcher has been dropped.
+fn next_batch(rx: &Receiver<Event>) -> Option<BTreeSet<PathBuf>> {
+    let mut paths = BTreeSet::new();
+    collect_paths(&mut paths, rx.recv().ok()?);
 
-    match event.kind {
-        EventKind::Create(_) => {
-            for path in event.paths {
-                if is_markdown_file(&path) {
-                    let _ = app.emit("file-created", path.to_string_lossy().to_string());
-                }
-            }
+    loop {
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(event) => collect_paths(&mut paths, event),
+            Err(RecvTimeoutError::Timeout) => return Some(paths),
+            Err(RecvTimeoutError::Disconnected) => return None,
         }
-        EventKind::Modify(_) => {
-            for path in event.paths {
-                if is_markdown_file(&path) {
-                    let _ = app.emit("file-modified", path.to_string_lossy().to_string());
-                }
-            }
+    }
+}
+
+fn collect_paths(paths: &mut BTreeSet<PathBuf>, event: Event) {
+    use notify::EventKind;
+
+    if matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        paths.extend(event.paths.into_iter().filter(|p| is_markdown_file(p)));
+    }
+}
+
+/// Reindex or drop a changed file, then tell the frontend. The file's state
+/// after the burst decides the event, so a rename reports the old path as
+/// deleted and the new one as changed.
+fn handle_note_change(app: &AppHandle, vault_path: &Path, path: &Path) {
+    let Some(relative) = vault_relative(vault_path, path) else {
+        return;
+    };
+    let relative_str = relative.to_string_lossy().replace('\\', "/");
+
+    // Excluded folders stay out of the index, whatever happens to them
+    let indexing = read_vault_settings(vault_path).unwrap_or_default().indexing;
+    if relative_str
+        .strip_prefix("notes/")
+        .is_some_and(|path| indexing.excludes(path))
+    {
+        return;
+    }
+
+    if path.exists() {
+        apply_folder_template(app, vault_path, path, &relative_str);
+
+        if let Err(e) =
+            tauri::async_runtime::block_on(db::index_single_note(app, vault_path, &relative))
+        {
+            emit_index_error(app, relative_str, e);
+            return;
         }
-        EventKind::Remove(_) => {
-            for path in event.paths {
-                if is_markdown_file(&path) {
-                    let _ = app.emit("file-deleted", path.to_string_lossy().to_string());
-                }
-            }
+        let content_hash = db::with_db(app, |conn| {
+            Ok(conn
+                .query_row(
+                    "SELECT content_hash FROM notes WHERE path = ?1",
+                    [&relative_str],
+                    |row| row.get(0),
+                )
+                .ok())
+        })
+        .ok()
+        .flatten();
+
+        let _ = app.emit(
+            "note-changed",
+            NoteChangedEvent {
+                path: relative_str,
+                content_hash,
+            },
+        );
+    } else {
+        if let Err(e) = db::remove_note_from_index(app, &relative_str) {
+            emit_index_error(app, relative_str.clone(), e);
         }
-        _ => {}
+        let _ = app.emit("note-deleted", NoteDeletedEvent { path: relative_str });
+    }
+}
+
+/// Fill a new, empty note made outside the app with its folder's template.
+/// Notes already in the index aren't new, so emptying an existing note is
+/// left alone.
+fn apply_folder_template(app: &AppHandle, vault_path: &Path, path: &Path, relative: &str) {
+    // Size first, so ordinary edits don't read the file twice
+    let is_empty = std::fs::metadata(path).is_ok_and(|m| m.len() < 1024)
+        && std::fs::read_to_string(path).is_ok_and(|c| c.trim().is_empty());
+    if !is_empty || db::get_note_id(app, relative).is_some() {
+        return;
+    }
+
+    // The note is indexed as it is when the template can't be applied
+    if let Ok(Some(content)) = render_folder_template(vault_path, relative) {
+        let _ = std::fs::write(path, content);
+    }
+}
+
+/// Path relative to the vault, also matching when the watcher reports
+/// canonicalized paths (e.g. /private/var on macOS)
+fn vault_relative(vault_path: &Path, path: &Path) -> Option<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(vault_path) {
+        return Some(relative.to_path_buf());
     }
+    let canonical = vault_path.canonicalize().ok()?;
+    path.strip_prefix(canonical).ok().map(Path::to_path_buf)
 }
 
 fn is_markdown_file(path: &Path) -> bool {
@@ -30,6 +30,29 @@ pub struct UserGitConfig {
     /// Git user email for commits (overrides global config)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_email: Option<String>,
+
+    /// SSH-sign commits with the SSH key, like git's `gpg.format=ssh`
+    #[serde(default)]
+    pub sign_commits: bool,
+
+    /// How a pull integrates diverged history
+    #[serde(default)]
+    pub pull_mode: PullMode,
+
+    /// Refuse to commit with the placeholder identity when no name/email is configured
+    #[serde(default)]
+    pub strict_identity: bool,
+}
+
+/// How `pull` handles local commits when the remote has diverged
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PullMode {
+    /// Stop and report that a merge is required
+    #[default]
+    Merge,
+    /// Replay local commits onto the fetched head
+    Rebase,
 }
 
 impl UserGitConfig {
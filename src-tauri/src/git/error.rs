@@ -34,6 +34,12 @@ pub enum GitError {
     #[error("Merge required - cannot fast-forward")]
     MergeRequired,
 
+    #[error("Conflicts in {}", .paths.join(", "))]
+    MergeConflict { paths: Vec<String> },
+
+    #[error("No git identity configured - set a name and email before committing")]
+    IdentityRequired,
+
     #[error("Repository has no commits yet")]
     EmptyRepository,
 
@@ -7,6 +7,7 @@ pub mod config;
 pub mod credentials;
 pub mod error;
 pub mod operations;
+pub mod signing;
 
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
@@ -18,7 +19,10 @@ use crate::db;
 pub use config::UserGitConfig;
 pub use credentials::{create_credential_state, GitCredentialState};
 pub use error::GitError;
-pub use operations::{GitStatus, NoteVersion};
+pub use operations::{
+    CommitDetail, DiffHunk, DiffLine, GitStatus, GitTag, IdentityStatus, NoteVersion, ResetMode,
+};
+pub use signing::CommitVerification;
 
 // Re-export for Tauri command registration
 use operations::CredentialConfig;
@@ -82,13 +86,46 @@ pub fn git_status(app: AppHandle) -> Result<GitStatus, String> {
     operations::get_status(&repo).map_err(|e| e.to_string())
 }
 
+/// Fetch from remote without touching the working tree, returning the
+/// refreshed status so ahead/behind reflect the remote
+#[tauri::command]
+pub fn git_fetch(app: AppHandle, passphrase: Option<String>) -> Result<GitStatus, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    let (_user_config, ssh_key_path, cached_pass) =
+        get_cred_config(&app, &vault_path, passphrase.as_deref()).map_err(|e| e.to_string())?;
+
+    let final_passphrase = passphrase.or(cached_pass);
+
+    let creds = CredentialConfig {
+        ssh_key_path: ssh_key_path.as_deref(),
+        passphrase: final_passphrase.as_deref(),
+    };
+
+    // Check if passphrase might be needed
+    if let Some(ref key_path) = ssh_key_path {
+        if UserGitConfig::key_is_encrypted(key_path) && final_passphrase.is_none() {
+            return Err(serde_json::to_string(&GitError::PassphraseRequired {
+                key_path: key_path.to_string_lossy().to_string(),
+            })
+            .unwrap());
+        }
+    }
+
+    operations::fetch(&repo, &creds)
+        .map_err(|e| serde_json::to_string(&e).unwrap_or(e.to_string()))?;
+
+    operations::get_status(&repo).map_err(|e| e.to_string())
+}
+
 /// Pull from remote
 #[tauri::command]
 pub async fn git_pull(app: AppHandle, passphrase: Option<String>) -> Result<String, String> {
     let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
     let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
 
-    let (_user_config, ssh_key_path, cached_pass) =
+    let (user_config, ssh_key_path, cached_pass) =
         get_cred_config(&app, &vault_path, passphrase.as_deref()).map_err(|e| e.to_string())?;
 
     let final_passphrase = passphrase.or(cached_pass);
@@ -108,7 +145,7 @@ pub async fn git_pull(app: AppHandle, passphrase: Option<String>) -> Result<Stri
         }
     }
 
-    let result = operations::pull(&repo, &creds)
+    let result = operations::pull(&repo, &creds, &user_config)
         .map_err(|e| serde_json::to_string(&e).unwrap_or(e.to_string()))?;
 
     // Re-index the vault to pick up any new/changed files from the pull
@@ -121,7 +158,11 @@ pub async fn git_pull(app: AppHandle, passphrase: Option<String>) -> Result<Stri
 
 /// Push to remote
 #[tauri::command]
-pub fn git_push(app: AppHandle, passphrase: Option<String>) -> Result<String, String> {
+pub fn git_push(
+    app: AppHandle,
+    passphrase: Option<String>,
+    include_tags: Option<bool>,
+) -> Result<String, String> {
     let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
     let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
 
@@ -145,7 +186,8 @@ pub fn git_push(app: AppHandle, passphrase: Option<String>) -> Result<String, St
         }
     }
 
-    operations::push(&repo, &creds).map_err(|e| serde_json::to_string(&e).unwrap_or(e.to_string()))
+    operations::push(&repo, &creds, include_tags.unwrap_or(false))
+        .map_err(|e| serde_json::to_string(&e).unwrap_or(e.to_string()))
 }
 
 /// Stage all changes
@@ -175,6 +217,37 @@ pub fn git_unstage_file(app: AppHandle, path: String) -> Result<(), String> {
     operations::unstage_file(&repo, &path).map_err(|e| e.to_string())
 }
 
+/// Get the diff hunks of a file's unstaged (or, with `staged`, staged) changes
+#[tauri::command]
+pub fn git_diff_file(
+    app: AppHandle,
+    path: String,
+    staged: Option<bool>,
+) -> Result<Vec<DiffHunk>, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::diff_file(&repo, &path, staged.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Stage one hunk of a file's unstaged changes, by its index in `git_diff_file`
+#[tauri::command]
+pub fn git_stage_hunk(app: AppHandle, path: String, hunk_index: usize) -> Result<(), String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::stage_hunk(&repo, &path, hunk_index).map_err(|e| e.to_string())
+}
+
+/// Unstage one hunk of a file's staged changes, by its index in `git_diff_file` with `staged`
+#[tauri::command]
+pub fn git_unstage_hunk(app: AppHandle, path: String, hunk_index: usize) -> Result<(), String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::unstage_hunk(&repo, &path, hunk_index).map_err(|e| e.to_string())
+}
+
 /// Commit staged changes
 #[tauri::command]
 pub fn git_commit(app: AppHandle, message: String) -> Result<String, String> {
@@ -185,6 +258,100 @@ pub fn git_commit(app: AppHandle, message: String) -> Result<String, String> {
     operations::commit(&repo, &message, &user_config).map_err(|e| e.to_string())
 }
 
+/// Get a commit's full message, authorship, parents and changed files
+#[tauri::command]
+pub fn git_commit_detail(app: AppHandle, commit_hash: String) -> Result<CommitDetail, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::get_commit_detail(&repo, &commit_hash).map_err(|e| e.to_string())
+}
+
+/// Check a commit's SSH signature, returning the signer when it verifies
+#[tauri::command]
+pub fn git_verify_commit(
+    app: AppHandle,
+    commit_hash: String,
+) -> Result<CommitVerification, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+    let user_config = UserGitConfig::read(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::verify_commit(&repo, &commit_hash, &user_config).map_err(|e| e.to_string())
+}
+
+/// Reset the vault to a previous commit. `hard` discards working-tree changes,
+/// so it needs `confirm`; `force` also allows overwriting untracked files.
+#[tauri::command]
+pub async fn git_reset_to_commit(
+    app: AppHandle,
+    commit_hash: String,
+    mode: ResetMode,
+    confirm: Option<bool>,
+    force: Option<bool>,
+) -> Result<GitStatus, String> {
+    if matches!(mode, ResetMode::Hard) && !confirm.unwrap_or(false) {
+        return Err("A hard reset discards uncommitted changes and must be confirmed".to_string());
+    }
+
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::reset_to_commit(&repo, &commit_hash, mode, force.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+
+    // Files on disk may have changed, so bring the index back in line
+    db::index_vault(&app, &vault_path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    operations::get_status(&repo).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Tag Commands
+// ============================================================================
+
+/// Create a tag at a commit (HEAD by default); annotated when a message is given
+#[tauri::command]
+pub fn git_create_tag(
+    app: AppHandle,
+    name: String,
+    message: Option<String>,
+    commit_hash: Option<String>,
+) -> Result<String, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+    let user_config = UserGitConfig::read(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::create_tag(
+        &repo,
+        &name,
+        message.as_deref(),
+        commit_hash.as_deref(),
+        &user_config,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// List all tags in the vault repository
+#[tauri::command]
+pub fn git_list_tags(app: AppHandle) -> Result<Vec<GitTag>, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::list_tags(&repo).map_err(|e| e.to_string())
+}
+
+/// Delete a local tag
+#[tauri::command]
+pub fn git_delete_tag(app: AppHandle, name: String) -> Result<(), String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::delete_tag(&repo, &name).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // User Configuration Commands
 // ============================================================================
@@ -203,6 +370,16 @@ pub fn git_set_user_config(app: AppHandle, config: UserGitConfig) -> Result<(),
     config.write(&vault_path).map_err(|e| e.to_string())
 }
 
+/// Report which identity commits will use and whether it's a real one
+#[tauri::command]
+pub fn git_identity_status(app: AppHandle) -> Result<IdentityStatus, String> {
+    let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
+    let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
+    let user_config = UserGitConfig::read(&vault_path).map_err(|e| e.to_string())?;
+
+    operations::get_identity(&repo, &user_config).map_err(|e| e.to_string())
+}
+
 /// Store a passphrase in the session cache
 #[tauri::command]
 pub fn git_set_session_passphrase(
@@ -284,19 +461,23 @@ pub fn git_note_at_commit(
     operations::get_note_at_commit(&repo, &note_path, &commit_hash).map_err(|e| e.to_string())
 }
 
-/// Restore a note to a specific version (creates a new commit)
+/// Restore a note to a specific version (creates a new commit).
+/// `source_path` is the note's path in that commit, if it has been renamed since;
+/// the content is always written to `note_path`.
 #[tauri::command]
 pub fn git_restore_note_version(
     app: AppHandle,
     note_path: String,
     commit_hash: String,
+    source_path: Option<String>,
 ) -> Result<String, String> {
     let vault_path = get_vault_path(&app).map_err(|e| e.to_string())?;
     let repo = open_repo(&vault_path).map_err(|e| e.to_string())?;
     let user_config = UserGitConfig::read(&vault_path).map_err(|e| e.to_string())?;
 
     // Get the content at the specified commit
-    let content = operations::get_note_at_commit(&repo, &note_path, &commit_hash)
+    let source_path = source_path.as_deref().unwrap_or(&note_path);
+    let content = operations::get_note_at_commit(&repo, source_path, &commit_hash)
         .map_err(|e| e.to_string())?;
 
     // Write the content to the file
@@ -1,14 +1,16 @@
 //! Core git2 operations for repository management
 
 use git2::{
-    build::CheckoutBuilder, Cred, FetchOptions, IndexAddOption, PushOptions, RemoteCallbacks,
-    Repository, Signature, StatusOptions,
+    build::CheckoutBuilder, ApplyLocation, ApplyOptions, Cred, DiffOptions, FetchOptions,
+    IndexAddOption, PushOptions, RemoteCallbacks, Repository, Signature, StatusOptions,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
-use super::config::UserGitConfig;
+use super::config::{PullMode, UserGitConfig};
 use super::error::GitError;
+use super::signing::{self, CommitVerification, SignatureStatus};
 
 /// Git repository status
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -33,8 +35,114 @@ pub struct NoteVersion {
     pub date: i64,
     pub message: String,
     pub author: String,
+    /// Path of the note in this commit, which differs from the current path after a rename
+    pub path: String,
 }
 
+/// A git tag, used for named vault snapshots
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitTag {
+    pub name: String,
+    /// Hash of the commit the tag points at
+    pub target: String,
+    /// Tag message; only annotated tags have one
+    pub message: Option<String>,
+}
+
+/// A file changed by a commit
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFile {
+    pub path: String,
+    /// Previous path, for renamed files
+    pub old_path: Option<String>,
+    /// `added`, `deleted`, `modified`, `renamed`, ...
+    pub status: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Full details of a single commit
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitDetail {
+    pub commit_hash: String,
+    pub short_hash: String,
+    pub message: String,
+    pub author: String,
+    pub author_email: String,
+    pub author_date: i64,
+    pub committer: String,
+    pub committer_email: String,
+    pub committer_date: i64,
+    pub parents: Vec<String>,
+    pub files: Vec<ChangedFile>,
+}
+
+/// A single line of a diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// `' '` for context, `'+'` for added, `'-'` for removed
+    pub origin: char,
+    pub content: String,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+}
+
+/// A contiguous block of changes with surrounding context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// Unified diff header, e.g. `@@ -1,3 +1,4 @@`
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+/// How far `reset_to_commit` rewinds, mirroring `git reset --soft/--mixed/--hard`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetMode {
+    /// Move HEAD only
+    Soft,
+    /// Move HEAD and reset the index
+    Mixed,
+    /// Move HEAD and reset the index and working tree
+    Hard,
+}
+
+/// Where the commit identity comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdentitySource {
+    /// Name and email from the per-vault user config
+    UserConfig,
+    /// Name and email from git config (repo, then global)
+    GitConfig,
+    /// At least one of them is the "Kairo User <user@kairo.local>" placeholder
+    Placeholder,
+}
+
+/// The identity commits will be made with
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityStatus {
+    /// Whether a real name and email are configured
+    pub configured: bool,
+    pub name: String,
+    pub email: String,
+    pub source: IdentitySource,
+}
+
+/// Placeholder author used when no identity is configured
+const PLACEHOLDER_NAME: &str = "Kairo User";
+const PLACEHOLDER_EMAIL: &str = "user@kairo.local";
+
 /// Credential configuration for git operations
 pub struct CredentialConfig<'a> {
     pub ssh_key_path: Option<&'a Path>,
@@ -195,8 +303,13 @@ pub fn fetch(repo: &Repository, creds: &CredentialConfig) -> Result<(), GitError
     Ok(())
 }
 
-/// Pull from remote (fetch + merge)
-pub fn pull(repo: &Repository, creds: &CredentialConfig) -> Result<String, GitError> {
+/// Pull from remote (fetch + fast-forward). Diverged history is rebased when
+/// the user's pull mode is `rebase`; otherwise a merge is reported as required.
+pub fn pull(
+    repo: &Repository,
+    creds: &CredentialConfig,
+    config: &UserGitConfig,
+) -> Result<String, GitError> {
     // Fetch first
     fetch(repo, creds)?;
 
@@ -230,8 +343,10 @@ pub fn pull(repo: &Repository, creds: &CredentialConfig) -> Result<String, GitEr
             &fetch_commit.id().to_string()[..7]
         ))
     } else if analysis.is_normal() {
-        // Normal merge required
-        Err(GitError::MergeRequired)
+        match config.pull_mode {
+            PullMode::Merge => Err(GitError::MergeRequired),
+            PullMode::Rebase => rebase_onto(repo, &fetch_commit, config),
+        }
     } else {
         Err(GitError::OperationFailed {
             message: "Unable to determine merge strategy".to_string(),
@@ -239,21 +354,107 @@ pub fn pull(repo: &Repository, creds: &CredentialConfig) -> Result<String, GitEr
     }
 }
 
-/// Push to remote
-pub fn push(repo: &Repository, creds: &CredentialConfig) -> Result<String, GitError> {
+/// Replay local commits onto `upstream`. On conflicts or any other failure the
+/// rebase is aborted, leaving the branch as it was; conflicted paths are reported.
+fn rebase_onto(
+    repo: &Repository,
+    upstream: &git2::AnnotatedCommit,
+    config: &UserGitConfig,
+) -> Result<String, GitError> {
+    // git2 replays commits unsigned, which would quietly drop their signatures
+    if config.sign_commits {
+        return Err(GitError::OperationFailed {
+            message: "Rebase pulls can't sign the replayed commits; switch to merge pulls or turn off commit signing".to_string(),
+        });
+    }
+
+    let signature = get_signature(repo, config)?;
+    let mut rebase = repo.rebase(None, Some(upstream), None, None)?;
+
+    let replayed = match replay_rebase(repo, &mut rebase, &signature) {
+        Ok(replayed) => replayed,
+        Err(e) => {
+            // Report what went wrong rather than a failure to abort
+            let _ = rebase.abort();
+            return Err(e);
+        }
+    };
+
+    Ok(format!(
+        "Rebased {} local commit(s) onto {}",
+        replayed,
+        &upstream.id().to_string()[..7]
+    ))
+}
+
+/// Apply every operation of `rebase` and finish it, returning how many commits
+/// were replayed. The caller aborts the rebase if this fails.
+fn replay_rebase(
+    repo: &Repository,
+    rebase: &mut git2::Rebase,
+    signature: &git2::Signature,
+) -> Result<usize, GitError> {
+    let mut replayed = 0;
+    while let Some(operation) = rebase.next() {
+        operation?;
+
+        let index = repo.index()?;
+        if index.has_conflicts() {
+            let mut paths = Vec::new();
+            for conflict in index.conflicts()? {
+                let conflict = conflict?;
+                if let Some(entry) = conflict.our.or(conflict.their) {
+                    paths.push(String::from_utf8_lossy(&entry.path).into_owned());
+                }
+            }
+            return Err(GitError::MergeConflict { paths });
+        }
+
+        // A commit whose changes are already upstream has nothing left to apply
+        match rebase.commit(None, signature, None) {
+            Ok(_) => replayed += 1,
+            Err(e) if e.code() == git2::ErrorCode::Applied => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    rebase.finish(Some(signature))?;
+
+    Ok(replayed)
+}
+
+/// Push to remote, optionally including every local tag
+pub fn push(
+    repo: &Repository,
+    creds: &CredentialConfig,
+    include_tags: bool,
+) -> Result<String, GitError> {
     let mut remote = repo.find_remote("origin").map_err(|_| GitError::NoRemote)?;
 
     // Get current branch
     let head = repo.head()?;
     let branch_name = head.shorthand().unwrap_or("HEAD");
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+    let mut refspecs = vec![format!(
+        "refs/heads/{}:refs/heads/{}",
+        branch_name, branch_name
+    )];
+
+    // Tags aren't pushed implicitly, so each one needs its own refspec
+    if include_tags {
+        for tag in repo.tag_names(None)?.iter().flatten() {
+            refspecs.push(format!("refs/tags/{}:refs/tags/{}", tag, tag));
+        }
+    }
 
     let mut push_opts = PushOptions::new();
     push_opts.remote_callbacks(creds.create_callbacks());
 
-    remote.push(&[&refspec], Some(&mut push_opts))?;
+    remote.push(&refspecs, Some(&mut push_opts))?;
 
-    Ok(format!("Pushed to origin/{}", branch_name))
+    if include_tags {
+        Ok(format!("Pushed to origin/{} with tags", branch_name))
+    } else {
+        Ok(format!("Pushed to origin/{}", branch_name))
+    }
 }
 
 /// Stage all changes
@@ -282,6 +483,127 @@ pub fn unstage_file(repo: &Repository, path: &str) -> Result<(), GitError> {
     Ok(())
 }
 
+/// Diff of one file: index vs HEAD when `staged`, otherwise working tree vs index.
+/// `reverse` swaps the sides, which turns a staged diff into one that undoes it.
+fn file_diff<'r>(
+    repo: &'r Repository,
+    path: &str,
+    staged: bool,
+    reverse: bool,
+) -> Result<git2::Diff<'r>, GitError> {
+    let mut opts = DiffOptions::new();
+    opts.pathspec(path)
+        .disable_pathspec_match(true)
+        .reverse(reverse);
+
+    let diff = if staged {
+        let head_tree = match repo.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(_) => None,
+        };
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?
+    } else {
+        opts.include_untracked(true).show_untracked_content(true);
+        repo.diff_index_to_workdir(None, Some(&mut opts))?
+    };
+
+    Ok(diff)
+}
+
+/// Get the hunks of a file's staged or unstaged changes
+pub fn diff_file(repo: &Repository, path: &str, staged: bool) -> Result<Vec<DiffHunk>, GitError> {
+    let diff = file_diff(repo, path, staged, false)?;
+    let Some(patch) = git2::Patch::from_diff(&diff, 0)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut hunks = Vec::with_capacity(patch.num_hunks());
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)?;
+
+        let mut lines = Vec::with_capacity(line_count);
+        for line_idx in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+            lines.push(DiffLine {
+                origin: line.origin(),
+                content: String::from_utf8_lossy(line.content())
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string(),
+                old_lineno: line.old_lineno(),
+                new_lineno: line.new_lineno(),
+            });
+        }
+
+        hunks.push(DiffHunk {
+            header: String::from_utf8_lossy(hunk.header())
+                .trim_end()
+                .to_string(),
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            lines,
+        });
+    }
+
+    Ok(hunks)
+}
+
+/// Apply only the hunk at `hunk_index` of `diff` to the index
+fn apply_hunk_to_index(
+    repo: &Repository,
+    diff: &git2::Diff,
+    hunk_index: usize,
+) -> Result<(), GitError> {
+    let hunk_count = match git2::Patch::from_diff(diff, 0)? {
+        Some(patch) => patch.num_hunks(),
+        None => 0,
+    };
+    if hunk_index >= hunk_count {
+        return Err(GitError::OperationFailed {
+            message: format!(
+                "Hunk {} out of range ({} hunks available)",
+                hunk_index, hunk_count
+            ),
+        });
+    }
+
+    let mut current = 0;
+    let mut opts = ApplyOptions::new();
+    opts.hunk_callback(|_| {
+        let keep = current == hunk_index;
+        current += 1;
+        keep
+    });
+
+    repo.apply(diff, ApplyLocation::Index, Some(&mut opts))?;
+    Ok(())
+}
+
+/// Stage a single hunk of a file's unstaged changes, like `git add -p`
+pub fn stage_hunk(repo: &Repository, path: &str, hunk_index: usize) -> Result<(), GitError> {
+    // An untracked file is a single all-added hunk, and git can't apply a
+    // patch to an index entry that doesn't exist yet, so stage it whole
+    if repo.index()?.get_path(Path::new(path), 0).is_none() {
+        if hunk_index != 0 {
+            return Err(GitError::OperationFailed {
+                message: format!("Hunk {} out of range (1 hunk available)", hunk_index),
+            });
+        }
+        return stage_file(repo, path);
+    }
+
+    let diff = file_diff(repo, path, false, false)?;
+    apply_hunk_to_index(repo, &diff, hunk_index)
+}
+
+/// Unstage a single hunk of a file's staged changes
+pub fn unstage_hunk(repo: &Repository, path: &str, hunk_index: usize) -> Result<(), GitError> {
+    // Hunks of the reversed diff line up with the staged diff's hunks
+    let diff = file_diff(repo, path, true, true)?;
+    apply_hunk_to_index(repo, &diff, hunk_index)
+}
+
 /// Commit staged changes
 pub fn commit(
     repo: &Repository,
@@ -294,6 +616,10 @@ pub fn commit(
         });
     }
 
+    if config.strict_identity && !get_identity(repo, config)?.configured {
+        return Err(GitError::IdentityRequired);
+    }
+
     // Get signature
     let signature = get_signature(repo, config)?;
 
@@ -314,110 +640,441 @@ pub fn commit(
     let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
 
     // Create the commit
-    let oid = repo.commit(
-        Some("HEAD"),
-        &signature,
-        &signature,
-        message,
-        &tree,
-        &parent_refs,
-    )?;
+    let oid = if config.sign_commits {
+        let key_path = config
+            .get_ssh_key_path()
+            .ok_or_else(|| GitError::OperationFailed {
+                message: "Commit signing is enabled but no SSH key is configured".to_string(),
+            })?;
+
+        let buffer =
+            repo.commit_create_buffer(&signature, &signature, message, &tree, &parent_refs)?;
+        let content = buffer.as_str().ok_or_else(|| GitError::OperationFailed {
+            message: "Commit content is not valid UTF-8".to_string(),
+        })?;
+        let commit_signature = signing::sign_buffer(&key_path, content)?;
+        let oid = repo.commit_signed(content, &commit_signature, None)?;
+
+        // commit_signed only writes the object, so move the branch ourselves
+        let reflog = format!("commit: {}", message.lines().next().unwrap_or(""));
+        let head = repo.find_reference("HEAD")?;
+        match head.symbolic_target() {
+            Some(branch) => {
+                repo.reference(branch, oid, true, &reflog)?;
+            }
+            None => repo.set_head_detached(oid)?,
+        }
+        oid
+    } else {
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent_refs,
+        )?
+    };
 
     Ok(format!("Committed: {}", &oid.to_string()[..7]))
 }
 
-/// Get the signature for commits
-fn get_signature(
+/// Check a commit's SSH signature against the configured key's public half.
+/// The committer's email is the principal the key is trusted for.
+pub fn verify_commit(
     repo: &Repository,
+    commit_hash: &str,
     config: &UserGitConfig,
-) -> Result<Signature<'static>, GitError> {
+) -> Result<CommitVerification, GitError> {
+    let commit = resolve_commit(repo, Some(commit_hash))?;
+
+    let (signature, signed_data) = match repo.extract_signature(&commit.id(), None) {
+        Ok(extracted) => extracted,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => {
+            return Ok(CommitVerification {
+                status: SignatureStatus::Unsigned,
+                signer: None,
+                message: None,
+            })
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let signature = signature
+        .as_str()
+        .ok_or_else(|| GitError::OperationFailed {
+            message: "Commit signature is not valid UTF-8".to_string(),
+        })?;
+
+    let key_path = config
+        .get_ssh_key_path()
+        .ok_or_else(|| GitError::OperationFailed {
+            message: "No SSH key is configured to verify against".to_string(),
+        })?;
+    let public_key = signing::read_public_key(&key_path)?;
+
+    let committer = commit.committer();
+    let email = committer.email().unwrap_or("");
+    let mut verification = signing::verify_buffer(&public_key, email, signature, &signed_data)?;
+    if verification.status == SignatureStatus::Valid {
+        verification.signer = Some(format!(
+            "{} <{}>",
+            committer.name().unwrap_or("Unknown"),
+            email
+        ));
+    }
+    Ok(verification)
+}
+
+/// Resolve a commit hash, or HEAD when none is given
+fn resolve_commit<'r>(
+    repo: &'r Repository,
+    commit_hash: Option<&str>,
+) -> Result<git2::Commit<'r>, GitError> {
+    match commit_hash {
+        Some(hash) => {
+            let oid = git2::Oid::from_str(hash).map_err(|_| GitError::InvalidReference {
+                reference: hash.to_string(),
+            })?;
+            Ok(repo.find_commit(oid)?)
+        }
+        None => repo
+            .head()
+            .map_err(|_| GitError::EmptyRepository)?
+            .peel_to_commit()
+            .map_err(GitError::from),
+    }
+}
+
+/// Create a tag at a commit (HEAD by default). A message makes it an
+/// annotated tag signed with the user's identity; otherwise it is lightweight.
+pub fn create_tag(
+    repo: &Repository,
+    name: &str,
+    message: Option<&str>,
+    commit_hash: Option<&str>,
+    config: &UserGitConfig,
+) -> Result<String, GitError> {
+    if !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+        return Err(GitError::InvalidReference {
+            reference: name.to_string(),
+        });
+    }
+
+    let commit = resolve_commit(repo, commit_hash)?;
+    let target = commit.as_object();
+
+    match message.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => {
+            let signature = get_signature(repo, config)?;
+            repo.tag(name, target, &signature, message, false)?;
+        }
+        None => {
+            repo.tag_lightweight(name, target, false)?;
+        }
+    }
+
+    Ok(format!(
+        "Tagged {} as {}",
+        &commit.id().to_string()[..7],
+        name
+    ))
+}
+
+/// List all tags with the commit they point at
+pub fn list_tags(repo: &Repository) -> Result<Vec<GitTag>, GitError> {
+    let mut tags = Vec::new();
+
+    for name in repo.tag_names(None)?.iter().flatten() {
+        // Annotated tags point at a tag object that carries the message
+        let reference = repo.find_reference(&format!("refs/tags/{}", name))?;
+        let Some(oid) = reference.target() else {
+            continue;
+        };
+        let object = repo.find_object(oid, None)?;
+
+        let (target, message) = match object.as_tag() {
+            Some(tag) => (
+                tag.target_id().to_string(),
+                tag.message().map(|m| m.trim_end().to_string()),
+            ),
+            None => (object.id().to_string(), None),
+        };
+
+        tags.push(GitTag {
+            name: name.to_string(),
+            target,
+            message,
+        });
+    }
+
+    Ok(tags)
+}
+
+/// Delete a local tag
+pub fn delete_tag(repo: &Repository, name: &str) -> Result<(), GitError> {
+    repo.tag_delete(name)
+        .map_err(|_| GitError::InvalidReference {
+            reference: name.to_string(),
+        })
+}
+
+/// Get a commit's metadata and the files it changed relative to its first parent
+pub fn get_commit_detail(repo: &Repository, commit_hash: &str) -> Result<CommitDetail, GitError> {
+    let commit = resolve_commit(repo, Some(commit_hash))?;
+    let tree = commit.tree()?;
+
+    // The initial commit has no parent, so it is diffed against an empty tree
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    diff.find_similar(None)?;
+
+    let mut files = Vec::with_capacity(diff.deltas().len());
+    for (idx, delta) in diff.deltas().enumerate() {
+        let path_of = |file: git2::DiffFile| {
+            file.path()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default()
+        };
+        let new_path = path_of(delta.new_file());
+        let old_path = path_of(delta.old_file());
+
+        let (additions, deletions) = match git2::Patch::from_diff(&diff, idx)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions, deletions)
+            }
+            None => (0, 0),
+        };
+
+        let status = match delta.status() {
+            git2::Delta::Added => "added",
+            git2::Delta::Deleted => "deleted",
+            git2::Delta::Modified => "modified",
+            git2::Delta::Renamed => "renamed",
+            git2::Delta::Copied => "copied",
+            git2::Delta::Typechange => "typechange",
+            _ => "unknown",
+        };
+
+        files.push(ChangedFile {
+            path: if delta.status() == git2::Delta::Deleted {
+                old_path.clone()
+            } else {
+                new_path.clone()
+            },
+            old_path: (old_path != new_path && !old_path.is_empty()).then_some(old_path),
+            status: status.to_string(),
+            additions,
+            deletions,
+        });
+    }
+
+    let author = commit.author();
+    let committer = commit.committer();
+    let id = commit.id().to_string();
+
+    Ok(CommitDetail {
+        short_hash: id[..7].to_string(),
+        commit_hash: id,
+        message: commit.message().unwrap_or("").to_string(),
+        author: author.name().unwrap_or("Unknown").to_string(),
+        author_email: author.email().unwrap_or("").to_string(),
+        author_date: author.when().seconds(),
+        committer: committer.name().unwrap_or("Unknown").to_string(),
+        committer_email: committer.email().unwrap_or("").to_string(),
+        committer_date: committer.when().seconds(),
+        parents: commit.parent_ids().map(|oid| oid.to_string()).collect(),
+        files,
+    })
+}
+
+/// Reset HEAD to a commit. A hard reset refuses to run while untracked files
+/// would be overwritten by the target tree unless `force` is set.
+pub fn reset_to_commit(
+    repo: &Repository,
+    commit_hash: &str,
+    mode: ResetMode,
+    force: bool,
+) -> Result<(), GitError> {
+    let commit = resolve_commit(repo, Some(commit_hash))?;
+
+    if matches!(mode, ResetMode::Hard) && !force {
+        let tree = commit.tree()?;
+        let status = get_status(repo)?;
+        let clobbered: Vec<&String> = status
+            .untracked
+            .iter()
+            .filter(|path| tree.get_path(Path::new(path.as_str())).is_ok())
+            .collect();
+
+        if !clobbered.is_empty() {
+            return Err(GitError::OperationFailed {
+                message: format!(
+                    "Hard reset would overwrite untracked files: {}",
+                    clobbered
+                        .iter()
+                        .map(|p| p.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            });
+        }
+    }
+
+    let reset_type = match mode {
+        ResetMode::Soft => git2::ResetType::Soft,
+        ResetMode::Mixed => git2::ResetType::Mixed,
+        ResetMode::Hard => git2::ResetType::Hard,
+    };
+
+    let mut checkout_opts = CheckoutBuilder::new();
+    checkout_opts.force();
+    repo.reset(commit.as_object(), reset_type, Some(&mut checkout_opts))?;
+
+    Ok(())
+}
+
+/// Resolve the commit identity: the user config when it has both name and
+/// email, otherwise git config, with the placeholder filling any gaps
+pub fn get_identity(repo: &Repository, config: &UserGitConfig) -> Result<IdentityStatus, GitError> {
     // Try user-provided config first
     if let (Some(name), Some(email)) = (&config.user_name, &config.user_email) {
-        return Signature::now(name, email).map_err(|e| GitError::OperationFailed {
-            message: format!("Invalid signature: {}", e),
+        return Ok(IdentityStatus {
+            configured: true,
+            name: name.clone(),
+            email: email.clone(),
+            source: IdentitySource::UserConfig,
         });
     }
 
     // Fall back to git config
     let git_config = repo.config()?;
+    let name = git_config.get_string("user.name").ok();
+    let email = git_config.get_string("user.email").ok();
+    let configured = name.is_some() && email.is_some();
+
+    Ok(IdentityStatus {
+        configured,
+        name: name.unwrap_or_else(|| PLACEHOLDER_NAME.to_string()),
+        email: email.unwrap_or_else(|| PLACEHOLDER_EMAIL.to_string()),
+        source: if configured {
+            IdentitySource::GitConfig
+        } else {
+            IdentitySource::Placeholder
+        },
+    })
+}
 
-    let name = git_config
-        .get_string("user.name")
-        .unwrap_or_else(|_| "Kairo User".to_string());
-
-    let email = git_config
-        .get_string("user.email")
-        .unwrap_or_else(|_| "user@kairo.local".to_string());
+/// Get the signature for commits
+fn get_signature(
+    repo: &Repository,
+    config: &UserGitConfig,
+) -> Result<Signature<'static>, GitError> {
+    let identity = get_identity(repo, config)?;
 
-    Signature::now(&name, &email).map_err(|e| GitError::OperationFailed {
+    Signature::now(&identity.name, &identity.email).map_err(|e| GitError::OperationFailed {
         message: format!("Invalid signature: {}", e),
     })
 }
 
-/// Get the history of a note file
+/// Get the history of a note file, following it across renames.
+///
+/// Walks first-parent history from HEAD. Whenever the note appears in a commit
+/// without existing at the same path in its parent, the commit is diffed with
+/// rename detection and the walk continues under the old path.
 pub fn get_note_history(repo: &Repository, note_path: &str) -> Result<Vec<NoteVersion>, GitError> {
     let mut revwalk = repo.revwalk()?;
     revwalk.push_head()?;
     revwalk.set_sorting(git2::Sort::TIME)?;
+    revwalk.simplify_first_parent()?;
 
+    let mut lookups = TreeLookupCache::default();
     let mut versions = Vec::new();
+    let mut path = note_path.to_string();
 
     for oid in revwalk {
         let oid = oid?;
         let commit = repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+
+        let current = lookups.blob_id(&tree, &path);
+        let previous = parent_tree
+            .as_ref()
+            .and_then(|parent| lookups.blob_id(parent, &path));
+
+        if current == previous {
+            continue;
+        }
 
-        // Check if this commit affected the note file
-        let dominated_by_note = commit_affects_path(repo, &commit, note_path)?;
-
-        if dominated_by_note {
-            let time = commit.time();
-            versions.push(NoteVersion {
-                commit_hash: oid.to_string(),
-                short_hash: oid.to_string()[..7].to_string(),
-                date: time.seconds(),
-                message: commit.message().unwrap_or("").to_string(),
-                author: commit.author().name().unwrap_or("Unknown").to_string(),
-            });
+        let time = commit.time();
+        versions.push(NoteVersion {
+            commit_hash: oid.to_string(),
+            short_hash: oid.to_string()[..7].to_string(),
+            date: time.seconds(),
+            message: commit.message().unwrap_or("").to_string(),
+            author: commit.author().name().unwrap_or("Unknown").to_string(),
+            path: path.clone(),
+        });
+
+        // Added here; if it was renamed from another path, keep following that
+        if current.is_some() && previous.is_none() {
+            match parent_tree {
+                Some(parent_tree) => match renamed_from(repo, &parent_tree, &tree, &path)? {
+                    Some(old_path) => path = old_path,
+                    None => break,
+                },
+                None => break,
+            }
         }
     }
 
     Ok(versions)
 }
 
-/// Check if a commit affects a specific path
-fn commit_affects_path(
-    _repo: &Repository,
-    commit: &git2::Commit,
-    path: &str,
-) -> Result<bool, GitError> {
-    let tree = commit.tree()?;
-
-    // Check if path exists in this commit
-    let exists_in_commit = tree.get_path(Path::new(path)).is_ok();
-
-    // Check parent
-    if commit.parent_count() == 0 {
-        // Initial commit - check if file exists
-        return Ok(exists_in_commit);
-    }
-
-    let parent = commit.parent(0)?;
-    let parent_tree = parent.tree()?;
-    let exists_in_parent = parent_tree.get_path(Path::new(path)).is_ok();
-
-    // File was added, modified, or deleted
-    if exists_in_commit != exists_in_parent {
-        return Ok(true);
-    }
+/// Memoized `tree path -> blob id` lookups. In linear history each tree is
+/// looked up once as a commit and again as the next commit's parent.
+#[derive(Default)]
+struct TreeLookupCache {
+    entries: HashMap<(git2::Oid, String), Option<git2::Oid>>,
+}
 
-    // Check if content changed
-    if exists_in_commit && exists_in_parent {
-        let current_entry = tree.get_path(Path::new(path))?;
-        let parent_entry = parent_tree.get_path(Path::new(path))?;
-        return Ok(current_entry.id() != parent_entry.id());
+impl TreeLookupCache {
+    fn blob_id(&mut self, tree: &git2::Tree, path: &str) -> Option<git2::Oid> {
+        *self
+            .entries
+            .entry((tree.id(), path.to_string()))
+            .or_insert_with(|| tree.get_path(Path::new(path)).ok().map(|e| e.id()))
     }
+}
 
-    Ok(false)
+/// If `path` was renamed between `old_tree` and `new_tree`, return its previous path
+fn renamed_from(
+    repo: &Repository,
+    old_tree: &git2::Tree,
+    new_tree: &git2::Tree,
+    path: &str,
+) -> Result<Option<String>, GitError> {
+    let mut diff = repo.diff_tree_to_tree(Some(old_tree), Some(new_tree), None)?;
+    let mut find_opts = git2::DiffFindOptions::new();
+    find_opts.renames(true);
+    diff.find_similar(Some(&mut find_opts))?;
+
+    let old_path = diff
+        .deltas()
+        .filter(|delta| delta.status() == git2::Delta::Renamed)
+        .find(|delta| delta.new_file().path() == Some(Path::new(path)))
+        .and_then(|delta| delta.old_file().path())
+        .map(|p| p.to_string_lossy().replace('\\', "/"));
+
+    Ok(old_path)
 }
 
 /// Get the content of a note at a specific commit
@@ -446,3 +1103,47 @@ pub fn get_note_at_commit(
 
     Ok(content.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text of a file's staged or unstaged diff
+    fn patch_text(repo: &Repository, path: &str, staged: bool) -> String {
+        let diff = file_diff(repo, path, staged, false).unwrap();
+        let mut patch = git2::Patch::from_diff(&diff, 0).unwrap().unwrap();
+        patch.to_buf().unwrap().as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn stage_hunk_stages_only_that_hunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let lines: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        std::fs::write(dir.path().join("note.md"), lines.join("\n") + "\n").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("note.md")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])
+            .unwrap();
+
+        // Two edits far enough apart to land in separate hunks
+        let mut edited = lines.clone();
+        edited[1] = "line 2 changed".to_string();
+        edited[17] = "line 18 changed".to_string();
+        std::fs::write(dir.path().join("note.md"), edited.join("\n") + "\n").unwrap();
+
+        stage_hunk(&repo, "note.md", 0).unwrap();
+
+        let staged = patch_text(&repo, "note.md", true);
+        assert!(staged.contains("+line 2 changed"));
+        assert!(!staged.contains("line 18 changed"));
+
+        let unstaged = patch_text(&repo, "note.md", false);
+        assert!(unstaged.contains("+line 18 changed"));
+        assert!(!unstaged.contains("line 2 changed"));
+    }
+}
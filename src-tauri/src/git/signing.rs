@@ -0,0 +1,174 @@
+//! SSH commit signing via `ssh-keygen -Y`, the same mechanism git uses for
+//! `gpg.format=ssh`
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::config::UserGitConfig;
+use super::error::GitError;
+
+/// Namespace git signs commits under, so signatures can't be replayed elsewhere
+const SIGNATURE_NAMESPACE: &str = "git";
+
+/// Outcome of checking a commit's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    Unsigned,
+    Valid,
+    Invalid,
+}
+
+/// Result of `verify_commit`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitVerification {
+    pub status: SignatureStatus,
+    /// Committer identity (`Name <email>`) that a valid signature vouches for
+    pub signer: Option<String>,
+    /// Why the signature didn't verify
+    pub message: Option<String>,
+}
+
+impl CommitVerification {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: SignatureStatus::Invalid,
+            signer: None,
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// Sign a commit buffer with an SSH key, returning the armored signature.
+/// Passphrase-protected keys are signed through ssh-agent, which must hold them.
+pub fn sign_buffer(key_path: &Path, data: &str) -> Result<String, GitError> {
+    // Given a public key, ssh-keygen asks the agent for the matching private key
+    let signing_key = if UserGitConfig::key_is_encrypted(key_path) {
+        public_key_path(key_path)
+    } else {
+        key_path.to_path_buf()
+    };
+
+    let mut child = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+        .arg(&signing_key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitError::OperationFailed {
+            message: format!("Failed to run ssh-keygen: {}", e),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::OperationFailed {
+            message: format!(
+                "Failed to sign commit with {} (passphrase-protected keys must be added to ssh-agent): {}",
+                key_path.display(),
+                stderr.trim()
+            ),
+        });
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| GitError::OperationFailed {
+        message: "ssh-keygen produced an invalid signature".to_string(),
+    })
+}
+
+/// Verify an SSH signature over `data` against `public_key`, accepting it for `principal`
+pub fn verify_buffer(
+    public_key: &str,
+    principal: &str,
+    signature: &str,
+    data: &[u8],
+) -> Result<CommitVerification, GitError> {
+    if !signature.contains("BEGIN SSH SIGNATURE") {
+        return Ok(CommitVerification::invalid("Not an SSH signature"));
+    }
+
+    // ssh-keygen reads the signature and allowed signers from files
+    let dir = std::env::temp_dir().join(format!("kairo-verify-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir)?;
+    let result = (|| -> Result<CommitVerification, GitError> {
+        let signature_path = dir.join("commit.sig");
+        let signers_path = dir.join("allowed_signers");
+        fs::write(&signature_path, signature)?;
+        fs::write(
+            &signers_path,
+            format!("{} {}\n", principal, public_key.trim()),
+        )?;
+
+        let mut child = Command::new("ssh-keygen")
+            .args([
+                "-Y",
+                "verify",
+                "-n",
+                SIGNATURE_NAMESPACE,
+                "-I",
+                principal,
+                "-f",
+            ])
+            .arg(&signers_path)
+            .arg("-s")
+            .arg(&signature_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GitError::OperationFailed {
+                message: format!("Failed to run ssh-keygen: {}", e),
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(data)?;
+        }
+        let output = child.wait_with_output()?;
+
+        if output.status.success() {
+            Ok(CommitVerification {
+                status: SignatureStatus::Valid,
+                signer: None,
+                message: None,
+            })
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Ok(CommitVerification::invalid(stderr.trim()))
+        }
+    })();
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Read the public half of an SSH key: `<key>.pub`, or derived from an unencrypted private key
+pub fn read_public_key(key_path: &Path) -> Result<String, GitError> {
+    if let Ok(key) = fs::read_to_string(public_key_path(key_path)) {
+        return Ok(key);
+    }
+
+    let output = Command::new("ssh-keygen")
+        .args(["-y", "-P", "", "-f"])
+        .arg(key_path)
+        .output()?;
+    if !output.status.success() {
+        return Err(GitError::OperationFailed {
+            message: format!("No public key found for {}", key_path.display()),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn public_key_path(key_path: &Path) -> PathBuf {
+    let mut path = key_path.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
@@ -1,7 +1,10 @@
 mod commands;
+mod crypto;
 mod db;
+mod error;
 mod fs;
 mod git;
+mod reminders;
 
 use tauri::Manager;
 
@@ -34,6 +37,26 @@ pub fn run() {
                 commands::vault::WatcherState::default(),
             ));
 
+            // Initialize due-date reminder state
+            app.manage(std::sync::Mutex::new(
+                commands::vault::ReminderState::default(),
+            ));
+
+            // Initialize vault passphrase session cache
+            app.manage(std::sync::Mutex::new(
+                commands::vault::VaultPassphraseState::default(),
+            ));
+
+            // Initialize vault lock state
+            app.manage(std::sync::Mutex::new(
+                commands::vault::VaultLockState::default(),
+            ));
+
+            // Initialize chunked attachment upload state
+            app.manage(std::sync::Mutex::new(
+                commands::vault::AttachmentUploadState::default(),
+            ));
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -44,17 +67,53 @@ pub fn run() {
             commands::vault::close_vault,
             commands::vault::get_vault_path,
             commands::vault::save_attachment,
+            commands::vault::list_attachments,
+            commands::vault::get_attachment_thumbnail,
+            commands::vault::begin_attachment,
+            commands::vault::append_attachment,
+            commands::vault::finish_attachment,
+            commands::vault::cancel_attachment,
+            commands::vault::resolve_attachment_path,
+            commands::vault::set_vault_passphrase,
+            commands::vault::vault_has_passphrase,
             commands::vault::get_vault_user,
             commands::vault::set_vault_user,
+            commands::vault::get_current_user_context,
             // Note commands
             commands::notes::list_notes,
+            commands::notes::list_notes_paginated,
             commands::notes::read_note,
+            commands::notes::get_note_preview,
+            commands::notes::get_note_detail_stats,
             commands::notes::write_note,
             commands::notes::delete_note,
             commands::notes::rename_note,
             commands::notes::create_folder,
+            commands::notes::list_folders,
+            commands::notes::delete_folder,
+            commands::notes::rename_folder,
             commands::notes::set_note_archived,
             commands::notes::set_note_starred,
+            commands::notes::touch_note_opened,
+            commands::notes::get_recent_notes,
+            commands::notes::pin_note,
+            commands::notes::unpin_note,
+            commands::notes::reorder_pinned,
+            commands::notes::get_pinned_notes,
+            commands::notes::set_note_label,
+            commands::notes::remove_note_label,
+            commands::notes::get_note_labels,
+            commands::notes::get_note_labels_batch,
+            commands::notes::format_link,
+            commands::notes::create_note_from_template,
+            commands::notes::get_or_create_periodic_note,
+            commands::notes::diff_notes,
+            commands::notes::normalize_frontmatter,
+            commands::notes::bulk_set_frontmatter,
+            commands::notes::toggle_task,
+            commands::notes::split_note,
+            commands::notes::encrypt_note,
+            commands::notes::decrypt_note,
             // Transclusion commands
             commands::notes::get_note_content_for_transclusion,
             commands::notes::get_block_content,
@@ -80,16 +139,32 @@ pub fn run() {
             commands::search::search_entities,
             commands::search::save_search,
             commands::search::get_saved_searches,
+            commands::search::search_cards,
+            commands::search::search_code,
+            commands::search::quick_open,
+            commands::search::get_note_keywords,
+            commands::search::suggest_tags,
             // Database commands
             commands::db::reindex_vault,
+            commands::db::cancel_indexing,
+            commands::db::rebuild_index,
+            commands::db::check_index_integrity,
+            commands::db::backfill_note_ids,
             commands::db::get_backlinks,
             commands::db::get_graph_data,
+            commands::db::get_graph_positions,
+            commands::db::save_graph_positions,
+            commands::db::export_graph,
+            commands::db::export_note_iocs,
             commands::db::get_all_tags,
             commands::db::get_tag_notes,
+            commands::db::get_code_languages,
             commands::db::get_all_mentions,
+            commands::db::get_notes_mentioning_member,
             // Vault health commands
             commands::db::get_orphan_notes,
             commands::db::get_broken_links,
+            commands::db::get_self_referencing_notes,
             commands::db::get_vault_health,
             // Organization helper commands
             commands::db::get_unlinked_mentions,
@@ -98,15 +173,27 @@ pub fn run() {
             commands::db::get_notes_by_folder,
             // Git commands
             git::git_status,
+            git::git_fetch,
             git::git_pull,
             git::git_push,
             git::git_stage_all,
             git::git_stage_file,
             git::git_unstage_file,
+            git::git_diff_file,
+            git::git_stage_hunk,
+            git::git_unstage_hunk,
             git::git_commit,
+            git::git_commit_detail,
+            git::git_verify_commit,
+            git::git_reset_to_commit,
+            // Git tag commands
+            git::git_create_tag,
+            git::git_list_tags,
+            git::git_delete_tag,
             // Git user config commands
             git::git_get_user_config,
             git::git_set_user_config,
+            git::git_identity_status,
             git::git_set_session_passphrase,
             git::git_clear_session_credentials,
             git::git_check_ssh_key,
@@ -123,9 +210,11 @@ pub fn run() {
             commands::kanban::kanban_remove_column,
             commands::kanban::kanban_update_column,
             commands::kanban::kanban_get_cards,
+            commands::kanban::kanban_get_archived_cards,
             commands::kanban::kanban_get_card,
             commands::kanban::kanban_add_card,
             commands::kanban::kanban_update_card,
+            commands::kanban::kanban_get_card_history,
             commands::kanban::kanban_move_card,
             commands::kanban::kanban_delete_card,
             commands::kanban::kanban_archive_card,
@@ -136,10 +225,16 @@ pub fn run() {
             commands::kanban::kanban_get_board_members,
             commands::kanban::kanban_add_board_member,
             commands::kanban::kanban_remove_board_member,
+            commands::kanban::kanban_rename_member,
             commands::kanban::kanban_get_assignee_suggestions,
             commands::kanban::kanban_get_card_backlinks,
             commands::kanban::kanban_get_all_cards,
+            commands::kanban::kanban_get_cards_for_assignee,
             commands::kanban::kanban_find_card_by_title,
+            commands::kanban::kanban_convert_card_to_note,
+            commands::kanban::kanban_create_card_from_note,
+            commands::kanban::kanban_export_markdown,
+            commands::kanban::kanban_import_markdown,
             // Diagram commands
             commands::diagram::diagram_list_boards,
             commands::diagram::diagram_get_board,
@@ -153,11 +248,23 @@ pub fn run() {
             commands::diagram::diagram_bulk_update_nodes,
             commands::diagram::diagram_add_edge,
             commands::diagram::diagram_update_edge,
+            commands::diagram::diagram_bulk_update_edges,
             commands::diagram::diagram_delete_edge,
+            commands::diagram::diagram_simplify_edge,
+            commands::diagram::diagram_simplify_all_edges,
+            commands::diagram::diagram_render_thumbnail,
+            commands::diagram::diagram_get_layers,
+            commands::diagram::diagram_create_layer,
+            commands::diagram::diagram_update_layer,
+            commands::diagram::diagram_reorder_layers,
+            commands::diagram::diagram_delete_layer,
+            commands::diagram::diagram_find_nodes,
+            commands::diagram::diagram_find_nodes_global,
             commands::diagram::diagram_link_note,
             commands::diagram::diagram_add_note_link,
             commands::diagram::diagram_remove_note_link,
             commands::diagram::diagram_remove_all_note_links,
+            commands::diagram::diagram_get_boards_for_note,
             // Plugin data commands
             commands::plugin::read_plugin_data,
             commands::plugin::write_plugin_data,
@@ -183,9 +290,17 @@ pub fn run() {
             commands::settings::set_app_setting,
             commands::settings::get_app_setting,
             commands::settings::remove_recent_vault,
+            commands::settings::get_vault_settings,
+            commands::settings::set_vault_settings,
             // Dataview commands
             commands::dataview::execute_dataview_query,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Drop the vault lock so the next launch doesn't find a lock from us
+            if let tauri::RunEvent::Exit = event {
+                commands::vault::release_vault_lock(app);
+            }
+        });
 }
@@ -0,0 +1,149 @@
+//! Due-date reminders for kanban cards
+//!
+//! While a vault is open, a background thread periodically checks card due dates
+//! and emits `card-due-soon` or `card-overdue` with a card summary. Sent reminders
+//! are recorded in `kanban_card_reminders`, so each card fires once per threshold
+//! crossed (moving the due date re-arms it).
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::settings::read_vault_settings;
+use crate::db::{self, DbError};
+
+/// Never poll more often than this, whatever the settings say
+const MIN_CHECK_INTERVAL_SECS: u64 = 10;
+
+const STAGE_DUE_SOON: i64 = 1;
+const STAGE_OVERDUE: i64 = 2;
+
+/// Card summary sent with reminder events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardReminder {
+    pub card_id: String,
+    pub title: String,
+    pub board_id: String,
+    pub board_name: String,
+    pub column_id: String,
+    pub due_date: i64,
+    pub priority: Option<String>,
+}
+
+/// Background reminder loop for the open vault; stops when stopped or dropped
+pub struct ReminderScheduler {
+    stop: Sender<()>,
+}
+
+impl ReminderScheduler {
+    /// Start checking due dates for the vault at `vault_path`
+    pub fn start(app: AppHandle, vault_path: PathBuf) -> Self {
+        let (stop, stop_rx) = channel::<()>();
+
+        std::thread::spawn(move || loop {
+            // Re-read settings each round so changes apply without reopening the vault
+            let settings = read_vault_settings(&vault_path)
+                .unwrap_or_default()
+                .reminders;
+
+            if settings.enabled {
+                // A failed round is retried on the next tick
+                let _ = emit_due_reminders(&app, settings.lead_time_minutes * 60);
+            }
+
+            let interval = settings.check_interval_seconds.max(MIN_CHECK_INTERVAL_SECS);
+            match stop_rx.recv_timeout(Duration::from_secs(interval)) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                // Stop requested, or the scheduler was dropped
+                _ => break,
+            }
+        });
+
+        Self { stop }
+    }
+
+    /// Stop the reminder loop
+    pub fn stop(self) {
+        let _ = self.stop.send(());
+    }
+}
+
+/// Emit reminders for cards that have crossed a threshold since the last check
+fn emit_due_reminders(app: &AppHandle, lead_time_secs: i64) -> Result<(), DbError> {
+    let now = chrono::Utc::now().timestamp();
+    let due = db::with_db(app, |conn| collect_due_reminders(conn, now, lead_time_secs))?;
+
+    for (stage, reminder) in due {
+        let event = if stage == STAGE_OVERDUE {
+            "card-overdue"
+        } else {
+            "card-due-soon"
+        };
+        let _ = app.emit(event, reminder);
+    }
+
+    Ok(())
+}
+
+/// Cards due within the lead time whose current stage hasn't been sent yet.
+/// Marks them as sent before returning them.
+fn collect_due_reminders(
+    conn: &Connection,
+    now: i64,
+    lead_time_secs: i64,
+) -> Result<Vec<(i64, CardReminder)>, DbError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT c.id, c.title, c.board_id, b.name, c.column_id, c.due_date, c.priority,
+               CASE WHEN r.due_date = c.due_date THEN r.stage ELSE 0 END
+        FROM kanban_cards c
+        JOIN kanban_boards b ON c.board_id = b.id
+        LEFT JOIN kanban_card_reminders r ON r.card_id = c.id
+        WHERE c.due_date IS NOT NULL
+          AND c.due_date <= ?1
+          AND COALESCE(c.archived, 0) = 0
+          AND COALESCE(c.is_complete, 0) = 0
+        "#,
+    )?;
+
+    let cards = stmt
+        .query_map(params![now + lead_time_secs.max(0)], |row| {
+            Ok((
+                CardReminder {
+                    card_id: row.get(0)?,
+                    title: row.get(1)?,
+                    board_id: row.get(2)?,
+                    board_name: row.get(3)?,
+                    column_id: row.get(4)?,
+                    due_date: row.get(5)?,
+                    priority: row.get(6)?,
+                },
+                row.get::<_, i64>(7)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut due = Vec::new();
+    for (reminder, sent_stage) in cards {
+        let stage = if reminder.due_date <= now {
+            STAGE_OVERDUE
+        } else {
+            STAGE_DUE_SOON
+        };
+        if stage <= sent_stage {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO kanban_card_reminders (card_id, due_date, stage) VALUES (?1, ?2, ?3)",
+            params![reminder.card_id, reminder.due_date, stage],
+        )?;
+        due.push((stage, reminder));
+    }
+
+    Ok(due)
+}
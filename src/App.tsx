@@ -12,6 +12,7 @@ import { PluginManager } from "./components/common/PluginManager";
 import { ConfirmDialog } from "./components/common/ConfirmDialog";
 import { DebugConsole } from "./components/common/DebugConsole";
 import { CreateNoteModal } from "./components/modals/CreateNoteModal";
+import { VaultPassphraseModal } from "./components/vault/VaultPassphraseModal";
 import { useVaultStore } from "./stores/vaultStore";
 import { useUIStore } from "./stores/uiStore";
 import { useNoteStore } from "./stores/noteStore";
@@ -548,6 +549,8 @@ function App() {
       <div className="h-screen flex flex-col bg-dark-950">
         <TitleBar />
         <WelcomeScreen />
+        <VaultPassphraseModal />
+        <ConfirmDialog />
       </div>
     );
   }
@@ -612,6 +615,7 @@ function App() {
 
       {/* Global passphrase modal - renders on top of everything */}
       <GitPassphraseModal />
+      <VaultPassphraseModal />
 
       {/* Toast notifications */}
       <ToastContainer />
@@ -1,4 +1,5 @@
 import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import { useDataviewStore } from "@/stores/dataviewStore";
 import { useNoteStore } from "@/stores/noteStore";
 import type { DataviewResult } from "@/lib/dataview";
@@ -20,7 +21,7 @@ const ErrorDisplay = ({ error }: { error: string }) => (
 );
 
 export function DataviewRenderer({ query }: DataviewRendererProps) {
-  const { executeQuery } = useDataviewStore();
+  const { executeQuery, invalidateCacheForPath } = useDataviewStore();
   const { openNote } = useNoteStore();
   const [result, setResult] = useState<DataviewResult | null>(null);
   const [isLoading, setIsLoading] = useState(true);
@@ -81,6 +82,33 @@ export function DataviewRenderer({ query }: DataviewRendererProps) {
     openNote(path);
   };
 
+  const handleTaskToggle = async (path: string, lineNumber: number) => {
+    try {
+      const completed = await invoke<boolean>("toggle_task", {
+        notePath: path,
+        lineNumber,
+      });
+      invalidateCacheForPath(path);
+      setResult((prev) =>
+        prev && {
+          ...prev,
+          tasks: prev.tasks?.map((group) =>
+            group.path !== path
+              ? group
+              : {
+                  ...group,
+                  tasks: group.tasks.map((task) =>
+                    task.lineNumber === lineNumber ? { ...task, completed } : task
+                  ),
+                }
+          ),
+        }
+      );
+    } catch (error) {
+      console.error("Failed to toggle task:", error);
+    }
+  };
+
   // Render based on query type
   if (result.type === "TABLE" && result.columns) {
     return (
@@ -92,6 +120,16 @@ export function DataviewRenderer({ query }: DataviewRendererProps) {
     );
   }
 
+  if (result.type === "TASK" && result.tasks) {
+    return (
+      <DataviewTaskList
+        groups={result.tasks}
+        onNoteClick={handleNoteClick}
+        onTaskToggle={handleTaskToggle}
+      />
+    );
+  }
+
   return (
     <DataviewList rows={result.rows} onNoteClick={handleNoteClick} />
   );
@@ -206,3 +244,50 @@ function DataviewList({ rows, onNoteClick }: DataviewListProps) {
     </div>
   );
 }
+
+interface DataviewTaskListProps {
+  groups: NonNullable<DataviewResult["tasks"]>;
+  onNoteClick: (path: string) => void;
+  onTaskToggle: (path: string, lineNumber: number) => void;
+}
+
+function DataviewTaskList({ groups, onNoteClick, onTaskToggle }: DataviewTaskListProps) {
+  const total = groups.reduce((sum, group) => sum + group.tasks.length, 0);
+
+  return (
+    <div className="space-y-3">
+      {groups.map((group) => (
+        <div key={group.path}>
+          <button
+            className="text-accent-primary hover:underline text-left text-sm font-medium"
+            onClick={() => onNoteClick(group.path)}
+          >
+            {group.title}
+          </button>
+          <ul className="mt-1 space-y-1">
+            {group.tasks.map((task) => (
+              <li
+                key={task.lineNumber}
+                className="flex items-start gap-2 text-sm"
+                style={{ paddingLeft: `${task.indent * 0.5}rem` }}
+              >
+                <input
+                  type="checkbox"
+                  checked={task.completed}
+                  onChange={() => onTaskToggle(group.path, task.lineNumber)}
+                  className="mt-1"
+                />
+                <span className={task.completed ? "line-through text-dark-500" : ""}>
+                  {task.text}
+                </span>
+              </li>
+            ))}
+          </ul>
+        </div>
+      ))}
+      <div className="text-xs text-dark-500">
+        {total} task{total !== 1 ? "s" : ""}
+      </div>
+    </div>
+  );
+}
@@ -33,6 +33,12 @@ const SplitViewIcon = () => (
   </svg>
 );
 
+const LockIcon = () => (
+  <svg className="w-8 h-8" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+    <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z" />
+  </svg>
+);
+
 const ImageIcon = () => (
   <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
     <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 16l4.586-4.586a2 2 0 012.828 0L16 16m-2-2l1.586-1.586a2 2 0 012.828 0L20 14m-6-6h.01M6 20h12a2 2 0 002-2V6a2 2 0 00-2-2H6a2 2 0 00-2 2v12a2 2 0 002 2z" />
@@ -538,6 +544,17 @@ export function Editor({ paneId }: EditorProps) {
   }, []);
 
   const renderContent = () => {
+    // The editor never receives an encrypted note's content, so there is nothing to edit
+    if (currentNote?.encrypted) {
+      return (
+        <div className="h-full flex flex-col items-center justify-center gap-2 text-dark-400">
+          <LockIcon />
+          <p className="text-sm">This note is encrypted.</p>
+          <p className="text-xs text-dark-500">Decrypt it to view or edit its content.</p>
+        </div>
+      );
+    }
+
     switch (editorViewMode) {
       case "editor":
         return <MarkdownPane paneId={paneId} content={editorContent} onContentChange={handleSetContent} />;
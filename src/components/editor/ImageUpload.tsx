@@ -1,6 +1,7 @@
 import { useState, useRef, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { CloseIcon } from "@/components/common/Icons";
+import { useNoteStore } from "@/stores/noteStore";
 
 interface AttachmentResult {
   relative_path: string;
@@ -67,10 +68,19 @@ export function ImageUpload({ onClose, className = "" }: ImageUploadProps) {
 
         const altText = file.name.replace(/\.[^/.]+$/, "");
 
+        // Address the attachment the way the vault's embed style expects
+        const notePath = useNoteStore.getState().currentNote?.path;
+        const path = notePath
+          ? await invoke<string>("resolve_attachment_path", {
+              notePath,
+              attachmentRel: result.relative_path,
+            })
+          : result.relative_path;
+
         setUploadedImages((prev) => [
           ...prev,
           {
-            path: result.relative_path,
+            path,
             altText,
             renamed: result.renamed,
             originalName: result.original_name,
@@ -10,6 +10,8 @@ interface NoteVersion {
   date: number;
   message: string;
   author: string;
+  /** Path of the note in this commit; differs from the current path before a rename */
+  path: string;
 }
 
 const HistoryIcon = () => (
@@ -96,7 +98,7 @@ export function NoteHistory({ onClose }: NoteHistoryProps) {
 
     try {
       const content = await invoke<string>("git_note_at_commit", {
-        notePath: currentNote.path,
+        notePath: version.path,
         commitHash: version.commitHash,
       });
       setVersionContent(content);
@@ -115,6 +117,7 @@ export function NoteHistory({ onClose }: NoteHistoryProps) {
       await invoke("git_restore_note_version", {
         notePath: currentNote.path,
         commitHash: version.commitHash,
+        sourcePath: version.path,
       });
 
       // Refresh the note and notes list
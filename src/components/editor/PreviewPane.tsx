@@ -364,8 +364,30 @@ interface KanbanCardResult {
   title: string;
 }
 
+
+/**
+ * Vault-relative path of an embed in the note at `notePath`. Paths starting
+ * with `./` or `../` are relative to the note's folder; anything else is from
+ * the vault root, so both attachment embed styles render.
+ */
+function resolveEmbedPath(notePath: string | undefined, src: string): string {
+  const isNoteRelative = src.startsWith("./") || src.startsWith("../");
+  const parts = isNoteRelative && notePath ? notePath.split("/").slice(0, -1) : [];
+
+  for (const component of src.replace(/^\/+/, "").split("/")) {
+    if (component === "" || component === ".") continue;
+    if (component === "..") {
+      parts.pop();
+    } else {
+      parts.push(component);
+    }
+  }
+
+  return parts.join("/");
+}
+
 export const PreviewPane = memo(function PreviewPane({ paneId: _paneId, content }: PreviewPaneProps) {
-  const { editorContent, openNoteByReference, resolveNoteReference, setEditorContent } = useNoteStore();
+  const { editorContent, openNoteByReference, resolveNoteReference, setEditorContent, currentNote } = useNoteStore();
   const { openNoteInNewPane } = usePaneStore();
   const { loadBoard } = useKanbanStore();
   const { boards: diagramBoards, loadBoards: loadDiagramBoards, loadBoard: loadDiagramBoard, setShowView: setDiagramShowView } = useDiagramStore();
@@ -422,12 +444,12 @@ export const PreviewPane = memo(function PreviewPane({ paneId: _paneId, content
       }
       // Relative path - resolve against vault
       if (vaultPath) {
-        const fullPath = `${vaultPath}/${src}`;
+        const fullPath = `${vaultPath}/${resolveEmbedPath(currentNote?.path, src)}`;
         return convertFileSrc(fullPath);
       }
       return src;
     },
-    [vaultPath]
+    [vaultPath, currentNote?.path]
   );
 
   // Preprocess content to convert wiki links
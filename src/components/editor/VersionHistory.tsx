@@ -75,6 +75,8 @@ function getTriggerLabel(trigger: string): { label: string; color: string } {
       return { label: "Snapshot", color: "text-green-400" };
     case "auto":
       return { label: "Before restore", color: "text-yellow-400" };
+    case "split":
+      return { label: "Before split", color: "text-yellow-400" };
     default:
       return { label: trigger, color: "text-dark-400" };
   }
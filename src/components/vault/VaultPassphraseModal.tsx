@@ -0,0 +1,106 @@
+import { useState } from "react";
+import { useVaultStore } from "@/stores/vaultStore";
+
+const LockIcon = () => (
+  <svg className="w-6 h-6" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+    <path
+      strokeLinecap="round"
+      strokeLinejoin="round"
+      strokeWidth={2}
+      d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z"
+    />
+  </svg>
+);
+
+/**
+ * Asks for the passphrase of a protected vault after `open_vault` reports
+ * `passphraseRequired` or `passphraseIncorrect`.
+ */
+export function VaultPassphraseModal() {
+  const { pendingUnlock, unlockVault, cancelUnlock } = useVaultStore();
+
+  const [passphrase, setPassphrase] = useState("");
+  const [isSubmitting, setIsSubmitting] = useState(false);
+
+  if (!pendingUnlock) {
+    return null;
+  }
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!passphrase) return;
+
+    setIsSubmitting(true);
+    try {
+      await unlockVault(passphrase);
+    } catch (err) {
+      console.error("Failed to unlock vault:", err);
+    } finally {
+      setPassphrase("");
+      setIsSubmitting(false);
+    }
+  };
+
+  const handleCancel = () => {
+    setPassphrase("");
+    cancelUnlock();
+  };
+
+  const vaultName = pendingUnlock.path.split(/[/\\]/).pop() || pendingUnlock.path;
+
+  return (
+    <div
+      className="fixed inset-0 bg-black/60 z-[200] flex items-center justify-center"
+      onClick={handleCancel}
+    >
+      <div
+        className="bg-dark-900 rounded-lg p-6 w-full max-w-sm border border-dark-700 shadow-xl"
+        onClick={(e) => e.stopPropagation()}
+      >
+        <div className="flex items-center gap-3 mb-4">
+          <div className="p-2 bg-accent-primary/20 rounded-lg text-accent-primary">
+            <LockIcon />
+          </div>
+          <div>
+            <h2 className="text-lg font-semibold text-dark-100">Vault Passphrase</h2>
+            <p className="text-xs text-dark-500">{vaultName}</p>
+          </div>
+        </div>
+
+        <form onSubmit={handleSubmit}>
+          <input
+            type="password"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.target.value)}
+            placeholder="Enter passphrase..."
+            className="input w-full mb-2"
+            autoFocus
+            disabled={isSubmitting}
+          />
+
+          {pendingUnlock.incorrect && (
+            <p className="text-sm text-red-400 mb-2">Incorrect passphrase, try again.</p>
+          )}
+
+          <div className="flex gap-2 mt-4">
+            <button
+              type="button"
+              className="btn-secondary flex-1"
+              onClick={handleCancel}
+              disabled={isSubmitting}
+            >
+              Cancel
+            </button>
+            <button
+              type="submit"
+              className="btn-primary flex-1"
+              disabled={!passphrase || isSubmitting}
+            >
+              {isSubmitting ? "Unlocking..." : "Unlock"}
+            </button>
+          </div>
+        </form>
+      </div>
+    </div>
+  );
+}
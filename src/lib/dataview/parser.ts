@@ -11,8 +11,10 @@ import type {
   LogicalCondition,
   SortClause,
   SortDirection,
+  SortType,
   ComparisonOperator,
   ValueExpression,
+  DateValue,
 } from "./types";
 
 class ParseError extends Error {
@@ -22,6 +24,23 @@ class ParseError extends Error {
   }
 }
 
+const DURATION_UNITS: Record<string, string> = {
+  d: "d", day: "d", days: "d",
+  w: "w", week: "w", weeks: "w",
+  m: "m", month: "m", months: "m",
+  y: "y", year: "y", years: "y",
+};
+
+// Convert a dur() argument like "7 days" to the backend's "7d"
+function parseDuration(text: string, position: number): string {
+  const match = text.trim().toLowerCase().match(/^(\d+)\s*([a-z]+)$/);
+  const unit = match && DURATION_UNITS[match[2]];
+  if (!match || !unit) {
+    throw new ParseError(`Unsupported duration: ${text}`, position);
+  }
+  return match[1] + unit;
+}
+
 interface Token {
   type: "keyword" | "identifier" | "string" | "number" | "operator" | "punctuation" | "eof";
   value: string;
@@ -35,7 +54,7 @@ class Lexer {
   private keywords = new Set([
     "TABLE", "LIST", "TASK", "FROM", "WHERE", "SORT", "GROUP", "BY",
     "LIMIT", "ASC", "DESC", "AND", "OR", "NOT", "CONTAINS", "STARTSWITH", "ENDSWITH",
-    "FLATTEN", "true", "false", "null"
+    "FLATTEN", "AS", "true", "false", "null"
   ]);
 
   private operators = ["!=", ">=", "<=", "=", ">", "<"];
@@ -119,7 +138,7 @@ class Lexer {
     }
 
     // Punctuation
-    if (",()[]#".includes(char)) {
+    if (",()[]#!".includes(char)) {
       this.pos++;
       return { type: "punctuation", value: char, position: startPos };
     }
@@ -137,6 +156,32 @@ class Lexer {
     throw new ParseError(`Unexpected character: ${char}`, startPos);
   }
 
+  // Read the raw text of a call's parenthesized argument, e.g. `(7 days)`
+  readCallArgument(): string {
+    this.skipWhitespace();
+    const start = this.pos;
+    if (this.input[this.pos] !== "(") {
+      throw new ParseError("Expected '('", start);
+    }
+    const close = this.input.indexOf(")", this.pos);
+    if (close === -1) {
+      throw new ParseError("Unterminated '('", start);
+    }
+    this.pos = close + 1;
+    return this.input.slice(start + 1, close).trim().replace(/^["']|["']$/g, "");
+  }
+
+  // Consume a `+` or `-` if one comes next
+  readSign(): "+" | "-" | null {
+    this.skipWhitespace();
+    const char = this.input[this.pos];
+    if (char === "+" || char === "-") {
+      this.pos++;
+      return char;
+    }
+    return null;
+  }
+
   peek(): Token {
     const savedPos = this.pos;
     const token = this.nextToken();
@@ -346,8 +391,8 @@ export class DataviewParser {
   }
 
   private parsePrimaryCondition(): Condition {
-    // NOT condition
-    if (this.match("keyword", "NOT")) {
+    // NOT condition, or the `!field` shorthand
+    if (this.match("keyword", "NOT") || this.match("punctuation", "!")) {
       this.advance();
       return {
         type: "negation",
@@ -384,6 +429,18 @@ export class DataviewParser {
     } else if (this.match("operator")) {
       operator = this.currentToken.value as ComparisonOperator;
       this.advance();
+    } else if (
+      this.match("eof") ||
+      this.match("keyword") ||
+      this.match("punctuation", ")")
+    ) {
+      // A bare field is a boolean test, e.g. `WHERE completed`
+      return {
+        type: "comparison",
+        left,
+        operator: "=",
+        right: { type: "literal", value: true },
+      };
     } else {
       throw new ParseError(
         `Expected comparison operator, got '${this.currentToken.value}'`,
@@ -430,6 +487,11 @@ export class DataviewParser {
       return { type: "literal", value: null };
     }
 
+    // Date expression: date(today), date(2024-01-01) - dur(7 days)
+    if (this.match("identifier", "date") && this.lexer.peek().value === "(") {
+      return this.parseDateValue();
+    }
+
     // Field reference
     if (this.match("identifier")) {
       return this.parseFieldReference();
@@ -441,6 +503,29 @@ export class DataviewParser {
     );
   }
 
+  // The current token is `date`; the lexer sits just after it, so the
+  // argument and any `± dur(...)` are read as raw text
+  private parseDateValue(): DateValue {
+    const position = this.currentToken.position;
+    let expression = this.lexer.readCallArgument().replace(/\s+/g, "");
+    if (!expression) {
+      throw new ParseError("Expected a date in date()", position);
+    }
+    const sign = this.lexer.readSign();
+    if (sign) {
+      this.advance();
+      if (!this.match("identifier", "dur")) {
+        throw new ParseError(
+          `Expected dur() after '${sign}', got '${this.currentToken.value}'`,
+          this.currentToken.position
+        );
+      }
+      expression += sign + parseDuration(this.lexer.readCallArgument(), position);
+    }
+    this.advance();
+    return { type: "date", expression };
+  }
+
   private parseSortClauses(): SortClause[] {
     const clauses: SortClause[] = [];
     clauses.push(this.parseSortClause());
@@ -465,7 +550,18 @@ export class DataviewParser {
       direction = "DESC";
     }
 
-    return { field, direction };
+    let sortAs: SortType | undefined;
+    if (this.match("keyword", "AS")) {
+      this.advance();
+      const token = this.expect("identifier");
+      const type = token.value.toLowerCase();
+      if (type !== "auto" && type !== "number" && type !== "text") {
+        throw new ParseError(`Expected number, text, or auto, got '${token.value}'`, token.position);
+      }
+      sortAs = type as SortType;
+    }
+
+    return { field, direction, sortAs };
   }
 }
 
@@ -21,7 +21,13 @@ export interface LiteralValue {
   value: string | number | boolean | null;
 }
 
-export type ValueExpression = FieldReference | LiteralValue;
+// Date expression (e.g., date(today) - dur(7 days) becomes "today-7d")
+export interface DateValue {
+  type: "date";
+  expression: string;
+}
+
+export type ValueExpression = FieldReference | LiteralValue | DateValue;
 
 // Comparison condition (e.g., status = "active")
 export interface ComparisonCondition {
@@ -52,10 +58,13 @@ export interface FromSource {
   value: string; // folder path, tag name, or note reference
 }
 
+export type SortType = "auto" | "number" | "text";
+
 // SORT clause
 export interface SortClause {
   field: FieldReference;
   direction: SortDirection;
+  sortAs?: SortType; // e.g. SORT priority DESC AS number
 }
 
 // GROUP BY clause
@@ -80,6 +89,8 @@ export interface DataviewResult {
   type: QueryType;
   columns?: string[]; // For TABLE
   rows: DataviewRow[];
+  table?: DataviewTable; // For TABLE; cells align with headers, missing fields are null
+  tasks?: DataviewTaskGroup[]; // For TASK; one group per note, tasks in line order
   error?: string;
   executionTime?: number;
 }
@@ -90,13 +101,32 @@ export interface DataviewRow {
   values: Record<string, unknown>;
 }
 
+export interface DataviewTable {
+  headers: string[];
+  rows: Array<{ path: string; title: string; cells: unknown[] }>;
+}
+
+export interface DataviewTask {
+  lineNumber: number;
+  text: string;
+  completed: boolean;
+  indent: number;
+}
+
+export interface DataviewTaskGroup {
+  noteId: string;
+  path: string;
+  title: string;
+  tasks: DataviewTask[];
+}
+
 // Serialized query for backend
 export interface SerializedQuery {
   query_type: string;
   fields: string[];
   from_sources: Array<{ source_type: string; value: string }>;
   where_clause?: SerializedCondition;
-  sort_clauses: Array<{ field: string; direction: string }>;
+  sort_clauses: Array<{ field: string; direction: string; sort_as?: string }>;
   group_by?: string;
   limit?: number;
 }
@@ -106,6 +136,7 @@ export interface SerializedCondition {
   field?: string;
   operator?: string;
   value?: string | number | boolean | null;
+  value_type?: "date";
   conditions?: SerializedCondition[];
 }
 
@@ -122,6 +153,7 @@ export function serializeQuery(query: DataviewQuery): SerializedQuery {
     sort_clauses: (query.sort || []).map(s => ({
       field: s.field.path.join("."),
       direction: s.direction,
+      sort_as: s.sortAs,
     })),
     group_by: query.groupBy?.field.path.join("."),
     limit: query.limit,
@@ -131,6 +163,15 @@ export function serializeQuery(query: DataviewQuery): SerializedQuery {
 function serializeCondition(condition: Condition): SerializedCondition {
   switch (condition.type) {
     case "comparison":
+      if (condition.right.type === "date") {
+        return {
+          condition_type: "comparison",
+          field: condition.left.path.join("."),
+          operator: condition.operator,
+          value: condition.right.expression,
+          value_type: "date",
+        };
+      }
       return {
         condition_type: "comparison",
         field: condition.left.path.join("."),
@@ -0,0 +1,43 @@
+/**
+ * Errors returned by the notes, vault and search commands.
+ * Mirrors `CommandError` in src-tauri/src/error.rs.
+ */
+export type CommandErrorCode =
+  | "noVault"
+  | "notFound"
+  | "pathTraversal"
+  | "conflict"
+  | "invalidInput"
+  | "passphraseRequired"
+  | "passphraseIncorrect"
+  | "vaultInUse"
+  | "db"
+  | "io"
+  | "other";
+
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+  /** Set for notFound, pathTraversal and conflict */
+  path?: string;
+  /** Set for vaultInUse: the instance holding the vault lock */
+  pid?: number;
+  /** Set for vaultInUse: when that instance took the lock (unix seconds) */
+  since?: number;
+}
+
+/** The structured error behind a failed `invoke`, or null for plain string errors */
+export function asCommandError(error: unknown): CommandError | null {
+  if (error && typeof error === "object" && "code" in error && "message" in error) {
+    return error as CommandError;
+  }
+  return null;
+}
+
+/** Text to show for a failed `invoke`, whether it failed with a `CommandError` or a string */
+export function errorMessage(error: unknown): string {
+  const commandError = asCommandError(error);
+  if (commandError) return commandError.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
@@ -9,6 +9,7 @@ import type {
   EdgeData,
   Viewport,
   NodePositionUpdate,
+  EdgeUpdate,
   HistoryAction,
   DiagramLayer,
   ClipboardData,
@@ -105,6 +106,7 @@ interface DiagramState {
     edgeType?: DiagramEdge["edgeType"],
     data?: EdgeData
   ) => Promise<void>;
+  bulkUpdateEdges: (boardId: string, updates: EdgeUpdate[]) => Promise<void>;
   deleteEdge: (edgeId: string) => Promise<void>;
 
   // Undo/Redo Actions
@@ -178,7 +180,8 @@ export const useDiagramStore = create<DiagramState>((set, get) => ({
   loadBoards: async () => {
     set({ isLoading: true, error: null });
     try {
-      const boards = await invoke<DiagramBoard[]>("diagram_list_boards");
+      // The sidebar filters archived boards itself, behind its toggle
+      const boards = await invoke<DiagramBoard[]>("diagram_list_boards", { includeArchived: true });
       set({ boards, isLoading: false });
     } catch (e) {
       set({ error: String(e), isLoading: false });
@@ -428,6 +431,18 @@ export const useDiagramStore = create<DiagramState>((set, get) => ({
     }
   },
 
+  bulkUpdateEdges: async (boardId: string, updates: EdgeUpdate[]) => {
+    try {
+      const updated = await invoke<DiagramEdge[]>("diagram_bulk_update_edges", { boardId, updates });
+      const byId = new Map(updated.map((edge) => [edge.id, edge]));
+      set((state) => ({
+        edges: state.edges.map((e) => byId.get(e.id) ?? e),
+      }));
+    } catch (e) {
+      set({ error: String(e) });
+    }
+  },
+
   deleteEdge: async (edgeId: string) => {
     try {
       await invoke("diagram_delete_edge", { edgeId });
@@ -111,6 +111,15 @@ export interface NodePositionUpdate {
   positionY: number;
 }
 
+// Bulk edge update; unset fields keep their current value
+export interface EdgeUpdate {
+  id: string;
+  sourceHandle?: string;
+  targetHandle?: string;
+  data?: EdgeData;
+  waypoints?: EdgeData["waypoints"];
+}
+
 // React Flow compatible types for the editor
 export interface ReactFlowNodeData extends NodeData {
   nodeType: 'shape' | 'icon' | 'text' | 'group';
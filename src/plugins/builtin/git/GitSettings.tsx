@@ -52,6 +52,9 @@ export interface UserGitConfig {
   rememberPassphrase: boolean;
   userName: string | null;
   userEmail: string | null;
+  signCommits?: boolean;
+  pullMode?: "merge" | "rebase";
+  strictIdentity?: boolean;
 }
 
 interface SshKeyInfo {
@@ -134,6 +137,7 @@ export function GitSettings() {
       setSuccessMessage(null);
 
       const newConfig: UserGitConfig = {
+        ...config,
         sshKeyPath: sshKeyPath || null,
         sshKeyType: keyInfo?.keyType || null,
         rememberPassphrase,
@@ -18,6 +18,7 @@ interface GitError {
   type: string;
   keyPath?: string;
   message?: string;
+  paths?: string[];
 }
 
 export type GitModalTab = "changes" | "settings";
@@ -38,6 +39,7 @@ interface GitState {
 
   // Actions
   checkStatus: () => Promise<void>;
+  fetch: (passphrase?: string) => Promise<void>;
   pull: (passphrase?: string) => Promise<void>;
   push: (passphrase?: string) => Promise<void>;
   stageAll: () => Promise<void>;
@@ -72,6 +74,9 @@ function parseGitError(error: unknown): GitError | null {
 function getErrorMessage(error: unknown): string {
   const gitError = parseGitError(error);
   if (gitError) {
+    if (gitError.type === "mergeConflict" && gitError.paths) {
+      return `Conflicts in ${gitError.paths.join(", ")}`;
+    }
     return gitError.message || gitError.type;
   }
   return String(error);
@@ -100,6 +105,31 @@ export const useGitStore = create<GitState>((set, get) => ({
     }
   },
 
+  fetch: async (passphrase?: string) => {
+    set({ isLoading: true, error: null });
+    try {
+      const status = await invoke<GitStatus>("git_fetch", { passphrase });
+      set({ status, isLoading: false });
+    } catch (error) {
+      const gitError = parseGitError(error);
+
+      // Check if passphrase is required
+      if (gitError?.type === "passphraseRequired" && gitError.keyPath) {
+        set({
+          passphraseRequired: true,
+          pendingKeyPath: gitError.keyPath,
+          pendingOperation: (p: string) => get().fetch(p),
+          isLoading: false,
+        });
+        return;
+      }
+
+      const errorMsg = getErrorMessage(error);
+      set({ error: errorMsg, isLoading: false });
+      toast.error("Fetch failed", errorMsg);
+    }
+  },
+
   pull: async (passphrase?: string) => {
     set({ isLoading: true, error: null });
     try {
@@ -21,6 +21,12 @@ export interface GraphLink {
   context?: string;
 }
 
+export interface GraphPosition {
+  noteId: string;
+  x: number;
+  y: number;
+}
+
 export interface GraphData {
   nodes: GraphNode[];
   links: GraphLink[];
@@ -54,6 +60,7 @@ interface GraphState {
 
   // Actions
   loadGraphData: () => Promise<void>;
+  saveNodePositions: (positions: GraphPosition[]) => Promise<void>;
   toggleView: () => void;
   setViewMode: (mode: "global" | "local" | "search") => void;
   setSelectedNode: (nodeId: string | null) => void;
@@ -96,6 +103,14 @@ export const useGraphStore = create<GraphState>((set, get) => ({
     }
   },
 
+  saveNodePositions: async (positions) => {
+    try {
+      await invoke("save_graph_positions", { positions });
+    } catch (error) {
+      console.error("Failed to save graph positions:", error);
+    }
+  },
+
   toggleView: () => {
     const { showView, graphData } = get();
     if (!showView && !graphData) {
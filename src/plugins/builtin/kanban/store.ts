@@ -244,7 +244,11 @@ export const useKanbanStore = create<KanbanState>((set, get) => ({
     set({ isLoading: true, error: null });
     try {
       const board = await invoke<KanbanBoard>("kanban_get_board", { boardId: id });
-      const cards = await invoke<KanbanCard[]>("kanban_get_cards", { boardId: id });
+      // The board filters archived cards itself, behind its toggle
+      const cards = await invoke<KanbanCard[]>("kanban_get_cards", {
+        boardId: id,
+        includeArchived: true,
+      });
       set({ currentBoard: board, cards, isLoading: false });
     } catch (error) {
       set({ error: String(error), isLoading: false });
@@ -497,6 +501,13 @@ export const useKanbanStore = create<KanbanState>((set, get) => ({
         cards: state.cards.map((c) => (c.id === cardId ? card : c)),
         selectedCard: state.selectedCard?.id === cardId ? card : state.selectedCard,
       }));
+      // A title change rewrites [[card:...]] links; report notes left pointing at the old title
+      const { failedLinks } = card as KanbanCard & { failedLinks?: { path: string }[] };
+      if (failedLinks?.length) {
+        set({
+          error: `Card links could not be updated in: ${failedLinks.map((f) => f.path).join(", ")}`,
+        });
+      }
     } catch (error) {
       set({ error: String(error) });
     }
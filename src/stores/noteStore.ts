@@ -2,6 +2,7 @@ import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { triggerHook } from "@/plugins/api/hooks";
 import { useUIStore } from "@/stores/uiStore";
+import { errorMessage } from "@/lib/errors";
 
 export interface NoteMetadata {
   id: string;
@@ -11,6 +12,14 @@ export interface NoteMetadata {
   created_at: number;
   archived: boolean;
   starred: boolean;
+  outgoing_count?: number;
+  incoming_count?: number;
+}
+
+/** Returned by `write_note`: metadata plus the content as saved, which may
+ * have gained frontmatter fields (`id`, `created`) the editor should adopt */
+export interface SavedNote extends NoteMetadata {
+  content: string;
 }
 
 export interface Note {
@@ -20,6 +29,8 @@ export interface Note {
   content: string;
   modified_at: number;
   created_at: number;
+  /** Encrypted on disk; `content` is empty and the note must not be saved */
+  encrypted?: boolean;
 }
 
 export interface TrashItem {
@@ -146,7 +157,7 @@ export const useNoteStore = create<NoteState>((set, get) => ({
       const notes = await invoke<NoteMetadata[]>("list_notes");
       set({ notes, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -181,7 +192,7 @@ export const useNoteStore = create<NoteState>((set, get) => ({
       // Trigger hook for extensions
       triggerHook("onNoteOpen", { note, path });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -268,11 +279,12 @@ export const useNoteStore = create<NoteState>((set, get) => ({
 
   saveNote: async () => {
     const { currentNote, editorContent, draftCache } = get();
-    if (!currentNote) return;
+    // Saving would replace the ciphertext with the empty placeholder content
+    if (!currentNote || currentNote.encrypted) return;
 
     set({ isSaving: true, error: null });
     try {
-      const metadata = await invoke<NoteMetadata>("write_note", {
+      const saved = await invoke<SavedNote>("write_note", {
         path: currentNote.path,
         content: editorContent,
         createIfMissing: false,
@@ -281,11 +293,14 @@ export const useNoteStore = create<NoteState>((set, get) => ({
       // Clear draft for this note since we've saved it
       draftCache.delete(currentNote.path);
 
-      // Update current note with new metadata
-      const updatedNote = { ...currentNote, content: editorContent, ...metadata };
+      // Update current note with new metadata and the content as saved. Edits
+      // made while the save was in flight are kept and stay unsaved.
+      const updatedNote = { ...currentNote, ...saved };
+      const editedDuringSave = get().editorContent !== editorContent;
       set({
         currentNote: updatedNote,
-        hasUnsavedChanges: false,
+        editorContent: editedDuringSave ? get().editorContent : saved.content,
+        hasUnsavedChanges: editedDuringSave,
         isSaving: false,
         draftCache: new Map(draftCache), // Trigger state update
       });
@@ -296,7 +311,7 @@ export const useNoteStore = create<NoteState>((set, get) => ({
       // Refresh notes list
       get().loadNotes();
     } catch (error) {
-      set({ error: String(error), isSaving: false });
+      set({ error: errorMessage(error), isSaving: false });
     }
   },
 
@@ -313,7 +328,7 @@ tags: []
     const noteContent = content ?? defaultContent;
     set({ isLoading: true, error: null });
     try {
-      await invoke<NoteMetadata>("write_note", {
+      await invoke<SavedNote>("write_note", {
         path,
         content: noteContent,
         createIfMissing: true,
@@ -326,7 +341,7 @@ tags: []
       await get().loadNotes();
       await get().openNote(path);
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
     }
   },
 
@@ -357,7 +372,7 @@ tags: []
       // Refresh notes list
       get().loadNotes();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -384,7 +399,7 @@ tags: []
         }
       }
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -402,7 +417,7 @@ tags: []
       );
       set({ notes: updatedNotes });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -415,7 +430,7 @@ tags: []
     try {
       await invoke("create_folder", { path });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -505,7 +520,7 @@ tags: []
         isSecondaryLoading: false,
       });
     } catch (error) {
-      set({ error: String(error), isSecondaryLoading: false });
+      set({ error: errorMessage(error), isSecondaryLoading: false });
     }
   },
 
@@ -527,25 +542,27 @@ tags: []
 
   saveSecondaryNote: async () => {
     const { secondaryNote, secondaryEditorContent } = get();
-    if (!secondaryNote) return;
+    if (!secondaryNote || secondaryNote.encrypted) return;
 
     set({ isSaving: true, error: null });
     try {
-      const metadata = await invoke<NoteMetadata>("write_note", {
+      const saved = await invoke<SavedNote>("write_note", {
         path: secondaryNote.path,
         content: secondaryEditorContent,
         createIfMissing: false,
       });
 
+      const editedDuringSave = get().secondaryEditorContent !== secondaryEditorContent;
       set({
-        secondaryNote: { ...secondaryNote, content: secondaryEditorContent, ...metadata },
-        hasSecondaryUnsavedChanges: false,
+        secondaryNote: { ...secondaryNote, ...saved },
+        secondaryEditorContent: editedDuringSave ? get().secondaryEditorContent : saved.content,
+        hasSecondaryUnsavedChanges: editedDuringSave,
         isSaving: false,
       });
 
       get().loadNotes();
     } catch (error) {
-      set({ error: String(error), isSaving: false });
+      set({ error: errorMessage(error), isSaving: false });
     }
   },
 
@@ -593,7 +610,7 @@ tags: []
       const trashItems = await invoke<TrashItem[]>("list_trash");
       set({ trashItems, isTrashLoading: false });
     } catch (error) {
-      set({ error: String(error), isTrashLoading: false });
+      set({ error: errorMessage(error), isTrashLoading: false });
     }
   },
 
@@ -622,7 +639,7 @@ tags: []
       get().loadNotes();
       get().loadTrash();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -634,7 +651,7 @@ tags: []
       get().loadNotes();
       get().loadTrash();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -645,7 +662,7 @@ tags: []
       // Refresh trash list
       get().loadTrash();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -655,7 +672,7 @@ tags: []
       set({ trashItems: [] });
       return count;
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
       return 0;
     }
   },
@@ -204,6 +204,8 @@ export interface Note {
   content: string;
   modified_at: number;
   created_at: number;
+  /** Encrypted on disk; `content` is empty and the note must not be saved */
+  encrypted?: boolean;
 }
 
 export interface PaneLeaf {
@@ -744,13 +746,14 @@ export const usePaneStore = create<PaneState>((set, get) => ({
     if (!root) return;
 
     const pane = findPaneInTree(root, paneId);
-    if (!pane || !pane.notePath) return;
+    // Saving would replace an encrypted note's ciphertext with its empty placeholder
+    if (!pane || !pane.notePath || pane.note?.encrypted) return;
 
     const notePath = pane.notePath;
     const savedContent = pane.editorContent;
 
     try {
-      await invoke("write_note", {
+      const saved = await invoke<{ content: string }>("write_note", {
         path: notePath,
         content: savedContent,
         createIfMissing: false,
@@ -765,11 +768,17 @@ export const usePaneStore = create<PaneState>((set, get) => ({
         const allPanes = collectLeafPanes(newRoot);
         for (const p of allPanes) {
           if (p.notePath === notePath) {
-            newRoot = updatePaneInTree(newRoot, p.id, (paneToUpdate) => ({
-              ...paneToUpdate,
-              originalContent: savedContent,
-              hasUnsavedChanges: false,
-            }));
+            // Adopt frontmatter fields added on save, unless the pane was edited since
+            newRoot = updatePaneInTree(newRoot, p.id, (paneToUpdate) => {
+              const unchanged = paneToUpdate.editorContent === savedContent;
+              return {
+                ...paneToUpdate,
+                editorContent: unchanged ? saved.content : paneToUpdate.editorContent,
+                originalContent: saved.content,
+                hasUnsavedChanges: !unchanged,
+                note: paneToUpdate.note ? { ...paneToUpdate.note, content: saved.content } : paneToUpdate.note,
+              };
+            });
           }
         }
       }
@@ -781,7 +790,7 @@ export const usePaneStore = create<PaneState>((set, get) => ({
 
       // Trigger hook for extensions
       if (pane.note) {
-        triggerHook("onNoteSave", { note: { ...pane.note, content: savedContent }, path: notePath });
+        triggerHook("onNoteSave", { note: { ...pane.note, content: saved.content }, path: notePath });
       }
     } catch (error) {
       console.error("Failed to save note:", error);
@@ -1,6 +1,7 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { triggerHook, applyFilter } from "@/plugins/api/hooks";
+import { errorMessage } from "@/lib/errors";
 
 export interface SearchResult {
   id: string;
@@ -24,6 +25,7 @@ export interface EntityResult {
   note_path: string;
   note_title: string;
   context: string;
+  subtype: string | null;
 }
 
 export interface SavedSearch {
@@ -38,9 +40,10 @@ export interface SearchFilters {
   tags?: string[];
   folders?: string[];
   entity_types?: string[];
-  date_from?: number;
-  date_to?: number;
+  date_from?: number | string;
+  date_to?: number | string;
   code_only?: boolean;
+  language?: string;
   include_archived?: boolean;
 }
 
@@ -101,7 +104,7 @@ export const useSearchStore = create<SearchState>((set, get) => ({
       // Trigger hook with results
       triggerHook("onSearchResult", { query, results, filters });
     } catch (error) {
-      set({ error: String(error), isSearching: false, results: [] });
+      set({ error: errorMessage(error), isSearching: false, results: [] });
     }
   },
 
@@ -115,7 +118,7 @@ export const useSearchStore = create<SearchState>((set, get) => ({
       });
       set({ entityResults: results, isSearching: false });
     } catch (error) {
-      set({ error: String(error), isSearching: false, entityResults: [] });
+      set({ error: errorMessage(error), isSearching: false, entityResults: [] });
     }
   },
 
@@ -129,7 +132,7 @@ export const useSearchStore = create<SearchState>((set, get) => ({
       });
       get().loadSavedSearches();
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -138,7 +141,7 @@ export const useSearchStore = create<SearchState>((set, get) => ({
       const savedSearches = await invoke<SavedSearch[]>("get_saved_searches");
       set({ savedSearches });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -1,5 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "@/lib/errors";
 
 // Types matching Rust structs
 interface TranscludedNote {
@@ -7,12 +8,16 @@ interface TranscludedNote {
   title: string;
   path: string;
   exists: boolean;
+  /** Encrypted notes come back with empty content */
+  encrypted: boolean;
 }
 
 interface BlockContent {
   content: string;
   line_number: number;
   exists: boolean;
+  /** Encrypted notes come back with empty content */
+  encrypted: boolean;
 }
 
 export interface BlockInfo {
@@ -137,7 +142,17 @@ export const useTransclusionStore = create<TransclusionState>((set, get) => ({
           blockId,
         });
 
-        if (blockContent.exists) {
+        if (blockContent.encrypted) {
+          result = {
+            content: "",
+            title: reference,
+            sourcePath: reference,
+            isBlock: true,
+            blockId,
+            fetchedAt: Date.now(),
+            error: `"${reference}" is encrypted; decrypt it to show its blocks`,
+          };
+        } else if (blockContent.exists) {
           result = {
             content: blockContent.content,
             title: reference,
@@ -164,7 +179,16 @@ export const useTransclusionStore = create<TransclusionState>((set, get) => ({
           { path: reference }
         );
 
-        if (note.exists) {
+        if (note.encrypted) {
+          result = {
+            content: "",
+            title: note.title,
+            sourcePath: note.path,
+            isBlock: false,
+            fetchedAt: Date.now(),
+            error: `"${note.title}" is encrypted; decrypt it to show its content`,
+          };
+        } else if (note.exists) {
           result = {
             content: note.content,
             title: note.title,
@@ -214,7 +238,7 @@ export const useTransclusionStore = create<TransclusionState>((set, get) => ({
         isBlock: !!blockId,
         blockId,
         fetchedAt: Date.now(),
-        error: `Failed to fetch: ${error}`,
+        error: `Failed to fetch: ${errorMessage(error)}`,
       };
 
       set((state) => {
@@ -1,12 +1,30 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { triggerHook } from "@/plugins/api/hooks";
+import { toast } from "@/components/common/Toast";
+import { useUIStore } from "@/stores/uiStore";
+import { asCommandError, errorMessage } from "@/lib/errors";
 
 export interface VaultInfo {
   path: string;
   name: string;
   note_count: number;
   created_at: number | null;
+  warnings?: string[];
+}
+
+export interface OpenVaultOptions {
+  passphrase?: string;
+  /** Take over the vault lock held by another instance */
+  force?: boolean;
+}
+
+/** A passphrase-protected vault waiting for its passphrase */
+export interface PendingUnlock {
+  path: string;
+  force: boolean;
+  /** The last passphrase tried was wrong */
+  incorrect: boolean;
 }
 
 export interface RecentVault {
@@ -20,9 +38,12 @@ interface VaultState {
   recentVaults: RecentVault[];
   isLoading: boolean;
   error: string | null;
+  pendingUnlock: PendingUnlock | null;
 
   // Actions
-  openVault: (path: string) => Promise<void>;
+  openVault: (path: string, options?: OpenVaultOptions) => Promise<void>;
+  unlockVault: (passphrase: string) => Promise<void>;
+  cancelUnlock: () => void;
   createVault: (path: string, name: string) => Promise<void>;
   closeVault: () => Promise<void>;
   refreshVaultInfo: () => Promise<void>;
@@ -35,6 +56,7 @@ export const useVaultStore = create<VaultState>((set, get) => ({
   recentVaults: [],
   isLoading: false,
   error: null,
+  pendingUnlock: null,
 
   loadRecentVaults: async () => {
     try {
@@ -59,11 +81,16 @@ export const useVaultStore = create<VaultState>((set, get) => ({
     }
   },
 
-  openVault: async (path: string) => {
+  openVault: async (path: string, options: OpenVaultOptions = {}) => {
     set({ isLoading: true, error: null });
     try {
-      const vault = await invoke<VaultInfo>("open_vault", { path });
-      set({ vault, isLoading: false });
+      const vault = await invoke<VaultInfo>("open_vault", {
+        path,
+        passphrase: options.passphrase ?? null,
+        force: options.force ?? null,
+      });
+      set({ vault, isLoading: false, pendingUnlock: null });
+      vault.warnings?.forEach((warning) => toast.warning("Vault opened with warnings", warning));
 
       // Add to recent vaults (stored in ~/.kairo/settings.json)
       const recentVaults = await invoke<RecentVault[]>("add_recent_vault", {
@@ -75,11 +102,53 @@ export const useVaultStore = create<VaultState>((set, get) => ({
       // Trigger hook for extensions
       triggerHook("onVaultOpen", { vault, path });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      const commandError = asCommandError(error);
+      switch (commandError?.code) {
+        case "passphraseRequired":
+        case "passphraseIncorrect":
+          // Ask for the passphrase, then retry through unlockVault
+          set({
+            isLoading: false,
+            pendingUnlock: {
+              path,
+              force: options.force ?? false,
+              incorrect: commandError.code === "passphraseIncorrect",
+            },
+          });
+          return;
+        case "vaultInUse": {
+          set({ isLoading: false });
+          const since = commandError.since
+            ? ` since ${new Date(commandError.since * 1000).toLocaleString()}`
+            : "";
+          useUIStore.getState().showConfirmDialog({
+            title: "Vault is already open",
+            message: `Another Kairo window (pid ${commandError.pid}) has had this vault open${since}. Opening it here as well can overwrite changes made there.`,
+            confirmText: "Open anyway",
+            variant: "warning",
+            onConfirm: () => {
+              get().openVault(path, { ...options, force: true }).catch(() => {});
+            },
+          });
+          return;
+        }
+      }
+      set({ error: errorMessage(error), isLoading: false });
       throw error;
     }
   },
 
+  unlockVault: async (passphrase: string) => {
+    const { pendingUnlock } = get();
+    if (!pendingUnlock) return;
+    await get().openVault(pendingUnlock.path, {
+      passphrase,
+      force: pendingUnlock.force,
+    });
+  },
+
+  cancelUnlock: () => set({ pendingUnlock: null }),
+
   createVault: async (path: string, name: string) => {
     set({ isLoading: true, error: null });
     try {
@@ -96,7 +165,7 @@ export const useVaultStore = create<VaultState>((set, get) => ({
       // Trigger hook for extensions
       triggerHook("onVaultOpen", { vault, path });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: errorMessage(error), isLoading: false });
       throw error;
     }
   },
@@ -112,7 +181,7 @@ export const useVaultStore = create<VaultState>((set, get) => ({
       set({ vault: null });
       // Don't remove from recent - just close
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 
@@ -123,7 +192,7 @@ export const useVaultStore = create<VaultState>((set, get) => ({
         set({ vault });
       }
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: errorMessage(error) });
     }
   },
 }));